@@ -0,0 +1,10 @@
+DROP INDEX security_findings_project_fingerprint_idx;
+
+ALTER TABLE security_findings
+    DROP COLUMN scanner,
+    DROP COLUMN rule_id,
+    DROP COLUMN location,
+    DROP COLUMN package,
+    DROP COLUMN fingerprint,
+    DROP COLUMN last_seen_at,
+    DROP COLUMN fixed_at;
@@ -0,0 +1,19 @@
+ALTER TABLE security_findings
+    ADD COLUMN scanner VARCHAR(100) NOT NULL DEFAULT 'legacy',
+    ADD COLUMN rule_id VARCHAR(255) NOT NULL DEFAULT 'legacy',
+    ADD COLUMN location TEXT,
+    ADD COLUMN package VARCHAR(255),
+    ADD COLUMN fingerprint VARCHAR(64),
+    ADD COLUMN last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN fixed_at TIMESTAMPTZ;
+
+-- Rows written before fingerprinting cannot be matched to scanner output,
+-- so each keeps a unique fingerprint and is closed by the next scan.
+UPDATE security_findings
+SET fingerprint = 'legacy-' || replace(id::text, '-', ''),
+    last_seen_at = created_at;
+
+ALTER TABLE security_findings ALTER COLUMN fingerprint SET NOT NULL;
+
+CREATE UNIQUE INDEX security_findings_project_fingerprint_idx
+    ON security_findings (project_id, fingerprint);
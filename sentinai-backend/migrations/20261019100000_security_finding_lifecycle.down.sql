@@ -0,0 +1,9 @@
+DROP TABLE finding_events;
+
+ALTER TABLE security_findings ADD COLUMN resolved BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE security_findings SET resolved = TRUE WHERE status <> 'open' AND status <> 'acknowledged';
+
+ALTER TABLE security_findings
+    DROP COLUMN status,
+    DROP COLUMN status_expires_at;
@@ -0,0 +1,20 @@
+ALTER TABLE security_findings
+    ADD COLUMN status VARCHAR(50) NOT NULL DEFAULT 'open', -- 'open', 'acknowledged', 'resolved', 'false_positive', 'accepted_risk'
+    ADD COLUMN status_expires_at TIMESTAMPTZ;
+
+UPDATE security_findings SET status = 'resolved' WHERE resolved;
+
+ALTER TABLE security_findings DROP COLUMN resolved;
+
+CREATE TABLE finding_events (
+    id UUID PRIMARY KEY,
+    finding_id UUID NOT NULL REFERENCES security_findings(id) ON DELETE CASCADE,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL, -- NULL for transitions made by the scanner
+    from_status VARCHAR(50) NOT NULL,
+    to_status VARCHAR(50) NOT NULL,
+    justification TEXT NOT NULL,
+    expires_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX finding_events_finding_id_idx ON finding_events (finding_id, created_at);
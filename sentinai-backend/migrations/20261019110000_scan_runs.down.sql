@@ -0,0 +1,5 @@
+ALTER TABLE security_findings
+    DROP COLUMN scan_run_id,
+    DROP COLUMN last_scan_run_id;
+
+DROP TABLE scan_runs;
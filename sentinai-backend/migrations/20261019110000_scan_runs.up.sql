@@ -0,0 +1,25 @@
+CREATE TABLE scan_runs (
+    id UUID PRIMARY KEY,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    status VARCHAR(50) NOT NULL, -- 'queued', 'running', 'succeeded', 'failed'
+    scanners TEXT[] NOT NULL DEFAULT '{}',
+    commit_sha VARCHAR(64),
+    critical_count INTEGER NOT NULL DEFAULT 0,
+    high_count INTEGER NOT NULL DEFAULT 0,
+    medium_count INTEGER NOT NULL DEFAULT 0,
+    low_count INTEGER NOT NULL DEFAULT 0,
+    new_count INTEGER NOT NULL DEFAULT 0,
+    existing_count INTEGER NOT NULL DEFAULT 0,
+    fixed_count INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+    duration_ms BIGINT
+);
+
+CREATE INDEX scan_runs_project_id_idx ON scan_runs (project_id, created_at DESC);
+
+ALTER TABLE security_findings
+    ADD COLUMN scan_run_id UUID REFERENCES scan_runs(id) ON DELETE SET NULL,
+    ADD COLUMN last_scan_run_id UUID REFERENCES scan_runs(id) ON DELETE SET NULL;
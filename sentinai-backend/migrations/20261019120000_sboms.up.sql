@@ -0,0 +1,10 @@
+CREATE TABLE sboms (
+    id UUID PRIMARY KEY,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    scan_run_id UUID NOT NULL UNIQUE REFERENCES scan_runs(id) ON DELETE CASCADE,
+    commit_sha VARCHAR(64),
+    contents JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX sboms_project_id_idx ON sboms (project_id, created_at DESC);
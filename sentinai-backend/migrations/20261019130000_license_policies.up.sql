@@ -0,0 +1,7 @@
+CREATE TABLE license_policies (
+    project_id UUID PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    allowed TEXT[] NOT NULL DEFAULT '{}',
+    denied TEXT[] NOT NULL DEFAULT '{}',
+    flag_unknown BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
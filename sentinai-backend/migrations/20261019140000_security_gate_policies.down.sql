@@ -0,0 +1 @@
+DROP TABLE security_gate_policies;
@@ -0,0 +1,11 @@
+CREATE TABLE security_gate_policies (
+    id UUID PRIMARY KEY,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    project_id UUID REFERENCES projects(id) ON DELETE CASCADE,
+    rules JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((user_id IS NULL) <> (project_id IS NULL))
+);
+
+CREATE UNIQUE INDEX security_gate_policies_user_id_idx ON security_gate_policies (user_id);
+CREATE UNIQUE INDEX security_gate_policies_project_id_idx ON security_gate_policies (project_id);
@@ -0,0 +1 @@
+ALTER TABLE security_findings ADD COLUMN remediation TEXT;
@@ -0,0 +1,3 @@
+ALTER TABLE security_findings
+    DROP COLUMN start_line,
+    DROP COLUMN start_column;
@@ -0,0 +1,3 @@
+ALTER TABLE security_findings
+    ADD COLUMN start_line INTEGER,
+    ADD COLUMN start_column INTEGER;
@@ -0,0 +1,15 @@
+CREATE TABLE scan_schedules (
+    id UUID PRIMARY KEY,
+    project_id UUID NOT NULL UNIQUE REFERENCES projects(id) ON DELETE CASCADE,
+    cron_expression TEXT,
+    interval_seconds INTEGER,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ,
+    last_scan_run_id UUID REFERENCES scan_runs(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((cron_expression IS NULL) <> (interval_seconds IS NULL))
+);
+
+CREATE INDEX scan_schedules_due_idx ON scan_schedules (next_run_at) WHERE enabled;
@@ -0,0 +1 @@
+ALTER TABLE security_findings DROP COLUMN fixed_versions;
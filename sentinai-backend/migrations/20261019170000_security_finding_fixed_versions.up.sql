@@ -0,0 +1 @@
+ALTER TABLE security_findings ADD COLUMN fixed_versions TEXT[] NOT NULL DEFAULT '{}';
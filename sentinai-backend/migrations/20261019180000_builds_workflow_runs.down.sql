@@ -0,0 +1,15 @@
+DROP INDEX builds_project_id_idx;
+DROP INDEX builds_external_id_idx;
+
+DELETE FROM builds WHERE pipeline_id IS NULL;
+
+ALTER TABLE builds
+    ALTER COLUMN pipeline_id SET NOT NULL,
+    DROP COLUMN html_url,
+    DROP COLUMN conclusion,
+    DROP COLUMN head_branch,
+    DROP COLUMN head_sha,
+    DROP COLUMN name,
+    DROP COLUMN external_id,
+    DROP COLUMN source,
+    DROP COLUMN project_id;
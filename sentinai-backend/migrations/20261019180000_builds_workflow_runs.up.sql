@@ -0,0 +1,21 @@
+ALTER TABLE builds
+    ADD COLUMN project_id UUID REFERENCES projects(id) ON DELETE CASCADE,
+    ADD COLUMN source VARCHAR(50) NOT NULL DEFAULT 'sentinai', -- 'sentinai', 'workflow_run'
+    ADD COLUMN external_id BIGINT,
+    ADD COLUMN name VARCHAR(255),
+    ADD COLUMN head_sha VARCHAR(64),
+    ADD COLUMN head_branch VARCHAR(255),
+    ADD COLUMN conclusion VARCHAR(50),
+    ADD COLUMN html_url TEXT,
+    ALTER COLUMN pipeline_id DROP NOT NULL;
+
+UPDATE builds
+SET project_id = pipelines.project_id
+FROM pipelines
+WHERE pipelines.id = builds.pipeline_id;
+
+ALTER TABLE builds ALTER COLUMN project_id SET NOT NULL;
+
+CREATE UNIQUE INDEX builds_external_id_idx ON builds (project_id, source, external_id)
+    WHERE external_id IS NOT NULL;
+CREATE INDEX builds_project_id_idx ON builds (project_id, started_at DESC);
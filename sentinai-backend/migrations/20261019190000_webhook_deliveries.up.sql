@@ -0,0 +1,18 @@
+CREATE TABLE webhook_deliveries (
+    id UUID PRIMARY KEY,
+    provider VARCHAR(50) NOT NULL, -- 'github'
+    delivery_id VARCHAR(255) NOT NULL,
+    event VARCHAR(100) NOT NULL,
+    repository VARCHAR(255),
+    headers JSONB NOT NULL, -- secrets and signatures are left out
+    body BYTEA NOT NULL,
+    status VARCHAR(50) NOT NULL, -- 'received', 'processed', 'failed'
+    action VARCHAR(50),
+    error TEXT,
+    replay_count INTEGER NOT NULL DEFAULT 0,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ,
+    UNIQUE (provider, delivery_id)
+);
+
+CREATE INDEX webhook_deliveries_repository_idx ON webhook_deliveries (repository, received_at DESC);
@@ -0,0 +1,14 @@
+ALTER TABLE builds ADD COLUMN logs TEXT;
+
+-- Compressed chunks cannot be restored in SQL and are dropped.
+UPDATE builds
+SET logs = chunks.logs
+FROM (
+    SELECT build_id, string_agg(content, E'\n' ORDER BY seq) AS logs
+    FROM build_log_chunks
+    WHERE content IS NOT NULL
+    GROUP BY build_id
+) AS chunks
+WHERE chunks.build_id = builds.id;
+
+DROP TABLE build_log_chunks;
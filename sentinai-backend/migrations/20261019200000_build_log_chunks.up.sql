@@ -0,0 +1,24 @@
+CREATE TABLE build_log_chunks (
+    id UUID PRIMARY KEY,
+    build_id UUID NOT NULL REFERENCES builds(id) ON DELETE CASCADE,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    seq INTEGER NOT NULL,
+    first_line INTEGER NOT NULL, -- 1-based number of the chunk's first line
+    line_count INTEGER NOT NULL,
+    content TEXT, -- set for small chunks
+    compressed BYTEA, -- gzipped text, set for large chunks
+    search TSVECTOR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (build_id, seq)
+);
+
+CREATE INDEX build_log_chunks_search_idx ON build_log_chunks USING GIN (search);
+CREATE INDEX build_log_chunks_project_id_idx ON build_log_chunks (project_id);
+
+INSERT INTO build_log_chunks (id, build_id, project_id, seq, first_line, line_count, content, search)
+SELECT gen_random_uuid(), id, project_id, 0, 1,
+       array_length(regexp_split_to_array(logs, E'\n'), 1), logs, to_tsvector('simple', logs)
+FROM builds
+WHERE logs IS NOT NULL AND logs <> '';
+
+ALTER TABLE builds DROP COLUMN logs;
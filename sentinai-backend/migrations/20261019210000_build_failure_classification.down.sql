@@ -0,0 +1,8 @@
+DROP INDEX IF EXISTS builds_failure_category_idx;
+
+ALTER TABLE builds
+    DROP COLUMN IF EXISTS classified_at,
+    DROP COLUMN IF EXISTS failing_tests,
+    DROP COLUMN IF EXISTS failure_excerpt,
+    DROP COLUMN IF EXISTS failure_line,
+    DROP COLUMN IF EXISTS failure_category;
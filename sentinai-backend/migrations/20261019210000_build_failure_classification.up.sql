@@ -0,0 +1,8 @@
+ALTER TABLE builds
+    ADD COLUMN failure_category VARCHAR(50),
+    ADD COLUMN failure_line INTEGER,
+    ADD COLUMN failure_excerpt TEXT,
+    ADD COLUMN failing_tests TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN classified_at TIMESTAMPTZ;
+
+CREATE INDEX builds_failure_category_idx ON builds (project_id, failure_category);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS llm_interactions;
+DROP TABLE IF EXISTS llm_budgets;
@@ -0,0 +1,25 @@
+CREATE TABLE llm_budgets (
+    project_id UUID PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    daily_token_limit BIGINT NOT NULL CHECK (daily_token_limit >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE llm_interactions (
+    id UUID PRIMARY KEY,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    purpose VARCHAR(50) NOT NULL, -- 'build_summary', 'finding_explanation', 'fix_draft'
+    subject_id UUID, -- the build or finding asked about
+    provider VARCHAR(50) NOT NULL, -- 'openai', 'stub'
+    model VARCHAR(255) NOT NULL,
+    messages JSONB NOT NULL,
+    response TEXT,
+    status VARCHAR(20) NOT NULL, -- 'succeeded', 'failed', 'rejected'
+    error TEXT,
+    prompt_tokens INTEGER NOT NULL DEFAULT 0,
+    completion_tokens INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX llm_interactions_project_id_created_at_idx
+    ON llm_interactions (project_id, created_at DESC);
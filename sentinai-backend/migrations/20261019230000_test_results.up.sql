@@ -0,0 +1,14 @@
+CREATE TABLE test_results (
+    id UUID PRIMARY KEY,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    build_id UUID NOT NULL REFERENCES builds(id) ON DELETE CASCADE,
+    test_id TEXT NOT NULL,
+    file TEXT,
+    outcome VARCHAR(20) NOT NULL,
+    duration_ms INTEGER,
+    message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (build_id, test_id)
+);
+
+CREATE INDEX test_results_project_id_created_at_idx ON test_results (project_id, created_at DESC);
@@ -0,0 +1,12 @@
+DROP TABLE IF EXISTS build_retries;
+DROP TABLE IF EXISTS build_retry_policies;
+
+DROP INDEX IF EXISTS builds_retry_of_idx;
+DROP INDEX builds_external_id_idx;
+DELETE FROM builds WHERE attempt > 1;
+CREATE UNIQUE INDEX builds_external_id_idx ON builds (project_id, source, external_id)
+    WHERE external_id IS NOT NULL;
+
+ALTER TABLE builds
+    DROP COLUMN retry_of,
+    DROP COLUMN attempt;
@@ -0,0 +1,35 @@
+ALTER TABLE builds
+    ADD COLUMN attempt INTEGER NOT NULL DEFAULT 1,
+    ADD COLUMN retry_of UUID REFERENCES builds(id) ON DELETE SET NULL;
+
+DROP INDEX builds_external_id_idx;
+CREATE UNIQUE INDEX builds_external_id_idx ON builds (project_id, source, external_id, attempt)
+    WHERE external_id IS NOT NULL;
+CREATE INDEX builds_retry_of_idx ON builds (retry_of) WHERE retry_of IS NOT NULL;
+
+CREATE TABLE build_retry_policies (
+    project_id UUID PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL,
+    max_attempts INTEGER NOT NULL,
+    backoff_seconds INTEGER NOT NULL,
+    retry_on VARCHAR(50)[] NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE build_retries (
+    id UUID PRIMARY KEY,
+    project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    build_id UUID NOT NULL UNIQUE REFERENCES builds(id) ON DELETE CASCADE,
+    original_build_id UUID NOT NULL REFERENCES builds(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    failure_category VARCHAR(50) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    scheduled_for TIMESTAMPTZ NOT NULL,
+    requested_at TIMESTAMPTZ,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX build_retries_scheduled_for_idx ON build_retries (scheduled_for)
+    WHERE status = 'scheduled';
+CREATE INDEX build_retries_original_build_id_idx ON build_retries (original_build_id);
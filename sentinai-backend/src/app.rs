@@ -9,10 +9,13 @@ use tracing::{info_span, Span};
 use crate::config::Config;
 use crate::error::AppError;
 use crate::routes;
+use crate::services::{build_retry_service, scan_scheduler};
 use crate::state::AppState;
 
 pub async fn create_app(config: Config) -> Result<Router, AppError> {
     let state = AppState::new(config).await?;
+    scan_scheduler::spawn(state.clone());
+    build_retry_service::spawn(state.clone());
 
     let app = Router::new()
         .merge(routes::router())
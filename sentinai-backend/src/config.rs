@@ -1,4 +1,5 @@
 use crate::error::AppError;
+use std::collections::HashMap;
 use std::env;
 
 #[derive(Clone, Debug)]
@@ -6,6 +7,31 @@ pub struct Config {
     pub database_url: String,
     pub jwt_secret: String,
     pub port: u16,
+    pub github_token: Option<String>,
+    /// Secret GitHub signs webhook deliveries with, used for repositories
+    /// without a secret of their own.
+    pub github_webhook_secret: Option<String>,
+    /// Per-repository webhook secrets keyed by lowercase `owner/repo`.
+    pub github_webhook_secrets: HashMap<String, String>,
+    /// Token GitLab sends in `X-Gitlab-Token`, used for projects without a
+    /// token of their own.
+    pub gitlab_webhook_token: Option<String>,
+    /// Per-project GitLab tokens keyed by lowercase `group/project`.
+    pub gitlab_webhook_tokens: HashMap<String, String>,
+    /// Secret Bitbucket signs webhook deliveries with, used for repositories
+    /// without a secret of their own.
+    pub bitbucket_webhook_secret: Option<String>,
+    /// Per-repository Bitbucket secrets keyed by lowercase `workspace/repo`.
+    pub bitbucket_webhook_secrets: HashMap<String, String>,
+    /// `openai` or `stub`; AI features are off when unset.
+    pub llm_provider: Option<String>,
+    /// Base URL of an OpenAI-compatible API.
+    pub llm_base_url: String,
+    pub llm_api_key: Option<String>,
+    pub llm_model: String,
+    /// Tokens a project may spend on LLM calls in any 24 hours, unless it
+    /// sets its own budget.
+    pub llm_daily_token_budget: i64,
 }
 
 impl Config {
@@ -21,10 +47,113 @@ impl Config {
             .parse()
             .map_err(|_| AppError::InternalServerError("PORT must be a valid u16".into()))?;
 
+        let github_token = env::var("GITHUB_TOKEN").ok().filter(|t| !t.is_empty());
+
+        let github_webhook_secret = env::var("GITHUB_WEBHOOK_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let github_webhook_secrets = env::var("GITHUB_WEBHOOK_SECRETS")
+            .map(|s| parse_webhook_secrets(&s))
+            .unwrap_or_default();
+
+        let gitlab_webhook_token = env::var("GITLAB_WEBHOOK_TOKEN")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let gitlab_webhook_tokens = env::var("GITLAB_WEBHOOK_TOKENS")
+            .map(|s| parse_webhook_secrets(&s))
+            .unwrap_or_default();
+
+        let bitbucket_webhook_secret = env::var("BITBUCKET_WEBHOOK_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let bitbucket_webhook_secrets = env::var("BITBUCKET_WEBHOOK_SECRETS")
+            .map(|s| parse_webhook_secrets(&s))
+            .unwrap_or_default();
+
+        let llm_provider = env::var("LLM_PROVIDER")
+            .ok()
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty());
+        let llm_base_url =
+            env::var("LLM_BASE_URL").unwrap_or_else(|_| "https://api.openai.com/v1".to_string());
+        let llm_api_key = env::var("LLM_API_KEY").ok().filter(|k| !k.is_empty());
+        let llm_model = env::var("LLM_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+        let llm_daily_token_budget: i64 = env::var("LLM_DAILY_TOKEN_BUDGET")
+            .unwrap_or_else(|_| "100000".to_string())
+            .parse()
+            .ok()
+            .filter(|budget| *budget >= 0)
+            .ok_or_else(|| {
+                AppError::InternalServerError(
+                    "LLM_DAILY_TOKEN_BUDGET must be a non-negative integer".into(),
+                )
+            })?;
+
         Ok(Self {
             database_url,
             jwt_secret,
             port,
+            github_token,
+            github_webhook_secret,
+            github_webhook_secrets,
+            gitlab_webhook_token,
+            gitlab_webhook_tokens,
+            bitbucket_webhook_secret,
+            bitbucket_webhook_secrets,
+            llm_provider,
+            llm_base_url,
+            llm_api_key,
+            llm_model,
+            llm_daily_token_budget,
         })
     }
+
+    /// The secret webhooks for `repository` (`owner/repo`) are signed with.
+    pub fn github_webhook_secret_for(&self, repository: Option<&str>) -> Option<&str> {
+        secret_for(
+            &self.github_webhook_secrets,
+            self.github_webhook_secret.as_ref(),
+            repository,
+        )
+    }
+
+    /// The token GitLab sends with webhooks for `project` (`group/project`).
+    pub fn gitlab_webhook_token_for(&self, project: Option<&str>) -> Option<&str> {
+        secret_for(
+            &self.gitlab_webhook_tokens,
+            self.gitlab_webhook_token.as_ref(),
+            project,
+        )
+    }
+
+    /// The secret Bitbucket webhooks for `repository` (`workspace/repo`) are
+    /// signed with.
+    pub fn bitbucket_webhook_secret_for(&self, repository: Option<&str>) -> Option<&str> {
+        secret_for(
+            &self.bitbucket_webhook_secrets,
+            self.bitbucket_webhook_secret.as_ref(),
+            repository,
+        )
+    }
+}
+
+fn secret_for<'a>(
+    secrets: &'a HashMap<String, String>,
+    default: Option<&'a String>,
+    repository: Option<&str>,
+) -> Option<&'a str> {
+    repository
+        .and_then(|r| secrets.get(&r.to_lowercase()))
+        .or(default)
+        .map(String::as_str)
+}
+
+/// Parses `owner/repo=secret` pairs separated by commas.
+fn parse_webhook_secrets(value: &str) -> HashMap<String, String> {
+    value
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(repository, secret)| (repository.trim().to_lowercase(), secret.trim().to_string()))
+        .filter(|(repository, secret)| !repository.is_empty() && !secret.is_empty())
+        .collect()
 }
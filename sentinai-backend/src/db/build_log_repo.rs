@@ -0,0 +1,239 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::build_log::{BuildLogChunk, LogSearchChunk, NewLogChunk};
+use crate::error::AppError;
+
+/// Stores chunks after the build's existing log, or in place of it when
+/// `replace` is set. Returns the number of the first stored line and the
+/// build's new line count. The build row is locked so concurrent appends
+/// keep their order.
+pub async fn append_chunks(
+    pool: &PgPool,
+    build_id: Uuid,
+    project_id: Uuid,
+    chunks: &[NewLogChunk],
+    replace: bool,
+) -> Result<(i32, i32), AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT id FROM builds WHERE id = $1 FOR UPDATE")
+        .bind(build_id)
+        .execute(&mut *tx)
+        .await?;
+    if replace {
+        sqlx::query("DELETE FROM build_log_chunks WHERE build_id = $1")
+            .bind(build_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let (mut seq, mut next_line): (i32, i32) = sqlx::query_as(
+        r#"
+        SELECT COALESCE(MAX(seq) + 1, 0), COALESCE(MAX(first_line + line_count), 1)
+        FROM build_log_chunks
+        WHERE build_id = $1
+        "#,
+    )
+    .bind(build_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let first_line = next_line;
+
+    for chunk in chunks {
+        sqlx::query(
+            r#"
+            INSERT INTO build_log_chunks (id, build_id, project_id, seq, first_line, line_count,
+                                          content, compressed, search)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, to_tsvector('simple', $9))
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(build_id)
+        .bind(project_id)
+        .bind(seq)
+        .bind(next_line)
+        .bind(chunk.line_count)
+        .bind(&chunk.content)
+        .bind(&chunk.compressed)
+        .bind(&chunk.text)
+        .execute(&mut *tx)
+        .await?;
+        seq += 1;
+        next_line += chunk.line_count;
+    }
+
+    tx.commit().await?;
+    Ok((first_line, next_line - 1))
+}
+
+pub async fn count_lines(pool: &PgPool, build_id: Uuid) -> Result<i32, AppError> {
+    let (count,): (i32,) = sqlx::query_as(
+        r#"
+        SELECT COALESCE(MAX(first_line + line_count) - 1, 0)
+        FROM build_log_chunks
+        WHERE build_id = $1
+        "#,
+    )
+    .bind(build_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
+
+/// Chunks holding any of the lines `from..=to`.
+pub async fn get_chunks(
+    pool: &PgPool,
+    build_id: Uuid,
+    from: i32,
+    to: i32,
+) -> Result<Vec<BuildLogChunk>, AppError> {
+    let chunks = sqlx::query_as::<_, BuildLogChunk>(
+        r#"
+        SELECT first_line, content, compressed
+        FROM build_log_chunks
+        WHERE build_id = $1 AND first_line <= $3 AND first_line + line_count > $2
+        ORDER BY seq
+        "#,
+    )
+    .bind(build_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(chunks)
+}
+
+/// Chunks of the project's build logs matching a web-search style query,
+/// newest builds first.
+pub async fn search_chunks(
+    pool: &PgPool,
+    project_id: Uuid,
+    query: &str,
+    limit: i64,
+) -> Result<Vec<LogSearchChunk>, AppError> {
+    let chunks = sqlx::query_as::<_, LogSearchChunk>(
+        r#"
+        SELECT c.build_id, b.name AS build_name, b.head_sha, b.started_at, c.first_line,
+               c.content, c.compressed
+        FROM build_log_chunks c
+        JOIN builds b ON b.id = c.build_id
+        WHERE c.project_id = $1 AND c.search @@ websearch_to_tsquery('simple', $2)
+        ORDER BY b.started_at DESC NULLS LAST, c.seq
+        LIMIT $3
+        "#,
+    )
+    .bind(project_id)
+    .bind(query)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{build_repo, project_repo, user_repo};
+    use crate::domain::build::NewBuild;
+    use crate::services::build_log::{clean_log, decode_chunk, encode_chunk, split_chunks};
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    fn encode(raw: &str) -> Vec<NewLogChunk> {
+        let lines = clean_log(raw);
+        split_chunks(&lines)
+            .into_iter()
+            .map(|chunk| encode_chunk(chunk).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_build_log_chunks() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_log_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_log_proj", "https://url")
+            .await
+            .unwrap();
+        let build = build_repo::create_build(
+            &pool,
+            proj.id,
+            &NewBuild {
+                pipeline_id: None,
+                name: Some("ci".into()),
+                head_sha: None,
+                head_branch: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let big: String = (1..=1500)
+            .map(|i| format!("test case {} ... ok\n", i))
+            .collect();
+        let (first, total) = append_chunks(&pool, build.id, proj.id, &encode(&big), false)
+            .await
+            .unwrap();
+        assert_eq!((first, total), (1, 1500));
+        let (first, total) = append_chunks(
+            &pool,
+            build.id,
+            proj.id,
+            &encode("thread 'main' panicked at src/lib.rs:10:5\n"),
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!((first, total), (1501, 1501));
+        assert_eq!(count_lines(&pool, build.id).await.unwrap(), 1501);
+
+        let chunks = get_chunks(&pool, build.id, 999, 1001).await.unwrap();
+        assert_eq!(
+            chunks.iter().map(|c| c.first_line).collect::<Vec<_>>(),
+            vec![1, 1001]
+        );
+        assert!(chunks[0].compressed.is_some());
+        let text =
+            decode_chunk(chunks[1].content.clone(), chunks[1].compressed.as_deref()).unwrap();
+        assert!(text.starts_with("test case 1001 ... ok"));
+
+        let hits = search_chunks(&pool, proj.id, "panicked", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].first_line, 1501);
+        assert_eq!(hits[0].build_name.as_deref(), Some("ci"));
+
+        let (first, total) = append_chunks(&pool, build.id, proj.id, &encode("fresh\n"), true)
+            .await
+            .unwrap();
+        assert_eq!((first, total), (1, 1));
+        assert!(search_chunks(&pool, proj.id, "panicked", 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,354 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::build::{Build, BuildStatus, ExternalBuild, FailureClassification, NewBuild};
+use crate::error::AppError;
+
+pub async fn create_build(
+    pool: &PgPool,
+    project_id: Uuid,
+    build: &NewBuild,
+) -> Result<Build, AppError> {
+    let id = Uuid::new_v4();
+    let build = sqlx::query_as::<_, Build>(
+        r#"
+        INSERT INTO builds (id, project_id, pipeline_id, name, status, head_sha, head_branch)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, project_id, pipeline_id, source, external_id, name, status, conclusion,
+            head_sha, head_branch, html_url, started_at, finished_at, failure_category,
+            failure_line, failure_excerpt, failing_tests, classified_at, attempt, retry_of
+        "#,
+    )
+    .bind(id)
+    .bind(project_id)
+    .bind(build.pipeline_id)
+    .bind(&build.name)
+    .bind(BuildStatus::Pending)
+    .bind(&build.head_sha)
+    .bind(&build.head_branch)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(build)
+}
+
+/// Moves a build to `status`, stamping `finished_at` once it has finished.
+pub async fn update_build_status(
+    pool: &PgPool,
+    id: Uuid,
+    status: BuildStatus,
+    conclusion: Option<&str>,
+) -> Result<Build, AppError> {
+    let build = sqlx::query_as::<_, Build>(
+        r#"
+        UPDATE builds
+        SET status = $2,
+            conclusion = COALESCE($3, conclusion),
+            finished_at = CASE WHEN $4 THEN COALESCE(finished_at, NOW()) ELSE NULL END,
+            failure_category = CASE WHEN $5 THEN failure_category END,
+            failure_line = CASE WHEN $5 THEN failure_line END,
+            failure_excerpt = CASE WHEN $5 THEN failure_excerpt END,
+            failing_tests = CASE WHEN $5 THEN failing_tests ELSE '{}' END,
+            classified_at = CASE WHEN $5 THEN classified_at END
+        WHERE id = $1
+        RETURNING id, project_id, pipeline_id, source, external_id, name, status, conclusion,
+            head_sha, head_branch, html_url, started_at, finished_at, failure_category,
+            failure_line, failure_excerpt, failing_tests, classified_at, attempt, retry_of
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .bind(conclusion)
+    .bind(status.is_finished())
+    .bind(status == BuildStatus::Failed)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(build)
+}
+
+pub async fn get_build(pool: &PgPool, id: Uuid) -> Result<Option<Build>, AppError> {
+    let build = sqlx::query_as::<_, Build>(
+        r#"
+        SELECT id, project_id, pipeline_id, source, external_id, name, status, conclusion,
+               head_sha, head_branch, html_url, started_at, finished_at, failure_category,
+               failure_line, failure_excerpt, failing_tests, classified_at, attempt, retry_of
+        FROM builds
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(build)
+}
+
+/// The project's most recent builds, optionally only those in `status`.
+pub async fn list_builds(
+    pool: &PgPool,
+    project_id: Uuid,
+    status: Option<BuildStatus>,
+    limit: i64,
+) -> Result<Vec<Build>, AppError> {
+    let builds = sqlx::query_as::<_, Build>(
+        r#"
+        SELECT id, project_id, pipeline_id, source, external_id, name, status, conclusion,
+               head_sha, head_branch, html_url, started_at, finished_at, failure_category,
+               failure_line, failure_excerpt, failing_tests, classified_at, attempt, retry_of
+        FROM builds
+        WHERE project_id = $1 AND ($2::varchar IS NULL OR status = $2)
+        ORDER BY started_at DESC NULLS LAST
+        LIMIT $3
+        "#,
+    )
+    .bind(project_id)
+    .bind(status)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(builds)
+}
+
+/// Records a build reported by a webhook, updating the existing row when the
+/// same run is reported again. Returns `None` when the report is older than
+/// the recorded state, i.e. an unfinished report arriving after the build
+/// finished.
+pub async fn upsert_external_build(
+    pool: &PgPool,
+    project_id: Uuid,
+    build: &ExternalBuild,
+) -> Result<Option<Build>, AppError> {
+    let id = Uuid::new_v4();
+    let build = sqlx::query_as::<_, Build>(
+        r#"
+        INSERT INTO builds (id, project_id, source, external_id, name, status, conclusion,
+                            head_sha, head_branch, html_url, started_at, finished_at, attempt,
+                            retry_of)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, COALESCE($11, NOW()), $12, $14,
+                (SELECT id FROM builds
+                 WHERE project_id = $2 AND source = $3 AND external_id = $4 AND attempt = 1
+                   AND $14 > 1))
+        ON CONFLICT (project_id, source, external_id, attempt) WHERE external_id IS NOT NULL
+        DO UPDATE
+        SET name = EXCLUDED.name,
+            status = EXCLUDED.status,
+            conclusion = EXCLUDED.conclusion,
+            head_sha = EXCLUDED.head_sha,
+            head_branch = EXCLUDED.head_branch,
+            html_url = EXCLUDED.html_url,
+            started_at = COALESCE(builds.started_at, EXCLUDED.started_at),
+            finished_at = EXCLUDED.finished_at,
+            failure_category = CASE WHEN $13 THEN builds.failure_category END,
+            failure_line = CASE WHEN $13 THEN builds.failure_line END,
+            failure_excerpt = CASE WHEN $13 THEN builds.failure_excerpt END,
+            failing_tests = CASE WHEN $13 THEN builds.failing_tests ELSE '{}' END,
+            classified_at = CASE WHEN $13 THEN builds.classified_at END
+        WHERE builds.finished_at IS NULL OR EXCLUDED.finished_at IS NOT NULL
+        RETURNING id, project_id, pipeline_id, source, external_id, name, status, conclusion,
+            head_sha, head_branch, html_url, started_at, finished_at, failure_category,
+            failure_line, failure_excerpt, failing_tests, classified_at, attempt, retry_of
+        "#,
+    )
+    .bind(id)
+    .bind(project_id)
+    .bind(&build.source)
+    .bind(build.external_id)
+    .bind(&build.name)
+    .bind(build.status)
+    .bind(&build.conclusion)
+    .bind(&build.head_sha)
+    .bind(&build.head_branch)
+    .bind(&build.html_url)
+    .bind(build.started_at)
+    .bind(build.finished_at)
+    .bind(build.status == BuildStatus::Failed)
+    .bind(build.attempt)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(build)
+}
+
+/// Stores why a build failed. Returns `None` if the build is no longer
+/// failed, e.g. because it was rerun while its log was being classified.
+pub async fn set_failure_classification(
+    pool: &PgPool,
+    id: Uuid,
+    classification: &FailureClassification,
+) -> Result<Option<Build>, AppError> {
+    let build = sqlx::query_as::<_, Build>(
+        r#"
+        UPDATE builds
+        SET failure_category = $2,
+            failure_line = $3,
+            failure_excerpt = $4,
+            failing_tests = $5,
+            classified_at = NOW()
+        WHERE id = $1 AND status = $6
+        RETURNING id, project_id, pipeline_id, source, external_id, name, status, conclusion,
+            head_sha, head_branch, html_url, started_at, finished_at, failure_category,
+            failure_line, failure_excerpt, failing_tests, classified_at, attempt, retry_of
+        "#,
+    )
+    .bind(id)
+    .bind(classification.category)
+    .bind(classification.line)
+    .bind(&classification.excerpt)
+    .bind(&classification.failing_tests)
+    .bind(BuildStatus::Failed)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(build)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{project_repo, user_repo};
+    use crate::domain::build::FailureCategory;
+    use chrono::Utc;
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_build_repository_crud() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_build_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_build_proj", "https://url")
+            .await
+            .unwrap();
+
+        let mut run = ExternalBuild {
+            source: "workflow_run".into(),
+            external_id: 30433642,
+            attempt: 1,
+            name: Some("CI".into()),
+            status: BuildStatus::Running,
+            conclusion: None,
+            head_sha: "acb5820ced9479c074f688cc328bf03f341a511d".into(),
+            head_branch: Some("main".into()),
+            html_url: "https://github.com/org/repo/actions/runs/30433642".into(),
+            started_at: None,
+            finished_at: None,
+        };
+        let first = upsert_external_build(&pool, proj.id, &run)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(first.started_at.is_some());
+
+        let mut finished = run.clone();
+        finished.status = BuildStatus::Failed;
+        finished.conclusion = Some("failure".into());
+        finished.finished_at = Some(Utc::now());
+        let second = upsert_external_build(&pool, proj.id, &finished)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.id, first.id);
+        assert_eq!(second.started_at, first.started_at);
+        assert_eq!(second.status, BuildStatus::Failed);
+        assert_eq!(second.conclusion.as_deref(), Some("failure"));
+
+        // A late in-progress report does not reopen the finished build.
+        run.name = Some("CI (late)".into());
+        assert!(upsert_external_build(&pool, proj.id, &run)
+            .await
+            .unwrap()
+            .is_none());
+        let stored = get_build(&pool, second.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, BuildStatus::Failed);
+        assert_eq!(stored.name.as_deref(), Some("CI"));
+
+        let internal = create_build(
+            &pool,
+            proj.id,
+            &NewBuild {
+                pipeline_id: None,
+                name: Some("sentinai".into()),
+                head_sha: None,
+                head_branch: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(internal.status, BuildStatus::Pending);
+        assert_eq!(internal.source, "sentinai");
+        let running = update_build_status(&pool, internal.id, BuildStatus::Running, None)
+            .await
+            .unwrap();
+        assert!(running.finished_at.is_none());
+        let succeeded =
+            update_build_status(&pool, internal.id, BuildStatus::Success, Some("success"))
+                .await
+                .unwrap();
+        assert!(succeeded.finished_at.is_some());
+
+        let failed = list_builds(&pool, proj.id, Some(BuildStatus::Failed), 50)
+            .await
+            .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].id, second.id);
+        assert_eq!(
+            list_builds(&pool, proj.id, None, 50).await.unwrap().len(),
+            2
+        );
+        assert_eq!(list_builds(&pool, proj.id, None, 1).await.unwrap().len(), 1);
+
+        let classification = FailureClassification {
+            category: FailureCategory::TestFailure,
+            line: Some(42),
+            excerpt: Some("test parser::tests::empty ... FAILED".into()),
+            failing_tests: vec!["parser::tests::empty".into()],
+        };
+        let classified = set_failure_classification(&pool, second.id, &classification)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            classified.failure_category,
+            Some(FailureCategory::TestFailure)
+        );
+        assert_eq!(classified.failing_tests, vec!["parser::tests::empty"]);
+        assert!(classified.classified_at.is_some());
+        assert!(
+            set_failure_classification(&pool, internal.id, &classification)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Leaving the failed state drops the classification.
+        let reopened = update_build_status(&pool, second.id, BuildStatus::Running, None)
+            .await
+            .unwrap();
+        assert!(reopened.failure_category.is_none());
+        assert!(reopened.failing_tests.is_empty());
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
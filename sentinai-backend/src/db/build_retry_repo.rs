@@ -0,0 +1,297 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::build::FailureCategory;
+use crate::domain::build_retry::{BuildRetry, BuildRetryPolicy, BuildRetryStatus};
+use crate::error::AppError;
+
+pub async fn get_policy(
+    pool: &PgPool,
+    project_id: Uuid,
+) -> Result<Option<BuildRetryPolicy>, AppError> {
+    let policy = sqlx::query_as::<_, BuildRetryPolicy>(
+        r#"
+        SELECT project_id, enabled, max_attempts, backoff_seconds, retry_on, updated_at
+        FROM build_retry_policies
+        WHERE project_id = $1
+        "#,
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(policy)
+}
+
+pub async fn upsert_policy(
+    pool: &PgPool,
+    project_id: Uuid,
+    enabled: bool,
+    max_attempts: i32,
+    backoff_seconds: i32,
+    retry_on: &[FailureCategory],
+) -> Result<BuildRetryPolicy, AppError> {
+    let policy = sqlx::query_as::<_, BuildRetryPolicy>(
+        r#"
+        INSERT INTO build_retry_policies (project_id, enabled, max_attempts, backoff_seconds,
+                                          retry_on)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (project_id) DO UPDATE
+        SET enabled = EXCLUDED.enabled,
+            max_attempts = EXCLUDED.max_attempts,
+            backoff_seconds = EXCLUDED.backoff_seconds,
+            retry_on = EXCLUDED.retry_on,
+            updated_at = NOW()
+        RETURNING project_id, enabled, max_attempts, backoff_seconds, retry_on, updated_at
+        "#,
+    )
+    .bind(project_id)
+    .bind(enabled)
+    .bind(max_attempts)
+    .bind(backoff_seconds)
+    .bind(retry_on)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(policy)
+}
+
+/// Schedules a re-run of a failed attempt. Returns `None` if that attempt
+/// already has a retry, e.g. because it was classified again.
+pub async fn schedule_retry(
+    pool: &PgPool,
+    project_id: Uuid,
+    build_id: Uuid,
+    original_build_id: Uuid,
+    attempt: i32,
+    failure_category: FailureCategory,
+    scheduled_for: DateTime<Utc>,
+) -> Result<Option<BuildRetry>, AppError> {
+    let retry = sqlx::query_as::<_, BuildRetry>(
+        r#"
+        INSERT INTO build_retries AS r (id, project_id, build_id, original_build_id, attempt,
+                                        failure_category, status, scheduled_for)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (build_id) DO NOTHING
+        RETURNING id, project_id, build_id, original_build_id, attempt, failure_category,
+            status, scheduled_for, requested_at, error,
+            (SELECT b.id FROM builds b
+             WHERE b.retry_of = r.original_build_id AND b.attempt = r.attempt) AS retry_build_id,
+            created_at
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(project_id)
+    .bind(build_id)
+    .bind(original_build_id)
+    .bind(attempt)
+    .bind(failure_category)
+    .bind(BuildRetryStatus::Scheduled)
+    .bind(scheduled_for)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(retry)
+}
+
+pub async fn list_due_retries(pool: &PgPool, limit: i64) -> Result<Vec<BuildRetry>, AppError> {
+    let retries = sqlx::query_as::<_, BuildRetry>(
+        r#"
+        SELECT id, project_id, build_id, original_build_id, attempt, failure_category, status,
+               scheduled_for, requested_at, error,
+               (SELECT b.id FROM builds b
+                WHERE b.retry_of = r.original_build_id AND b.attempt = r.attempt) AS retry_build_id,
+               created_at
+        FROM build_retries r
+        WHERE status = 'scheduled' AND scheduled_for <= NOW()
+        ORDER BY scheduled_for
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(retries)
+}
+
+/// Marks a due retry as requested. Returns `None` if another instance
+/// already claimed it.
+pub async fn claim_retry(pool: &PgPool, id: Uuid) -> Result<Option<BuildRetry>, AppError> {
+    let retry = sqlx::query_as::<_, BuildRetry>(
+        r#"
+        UPDATE build_retries r
+        SET status = 'requested', requested_at = NOW()
+        WHERE id = $1 AND status = 'scheduled'
+        RETURNING id, project_id, build_id, original_build_id, attempt, failure_category,
+            status, scheduled_for, requested_at, error,
+            (SELECT b.id FROM builds b
+             WHERE b.retry_of = r.original_build_id AND b.attempt = r.attempt) AS retry_build_id,
+            created_at
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(retry)
+}
+
+pub async fn set_retry_status(
+    pool: &PgPool,
+    id: Uuid,
+    status: BuildRetryStatus,
+    error: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query("UPDATE build_retries SET status = $2, error = $3 WHERE id = $1")
+        .bind(id)
+        .bind(status)
+        .bind(error)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// The retries of every attempt of a build, oldest first.
+pub async fn list_retries(
+    pool: &PgPool,
+    original_build_id: Uuid,
+) -> Result<Vec<BuildRetry>, AppError> {
+    let retries = sqlx::query_as::<_, BuildRetry>(
+        r#"
+        SELECT id, project_id, build_id, original_build_id, attempt, failure_category, status,
+               scheduled_for, requested_at, error,
+               (SELECT b.id FROM builds b
+                WHERE b.retry_of = r.original_build_id AND b.attempt = r.attempt) AS retry_build_id,
+               created_at
+        FROM build_retries r
+        WHERE original_build_id = $1
+        ORDER BY attempt
+        "#,
+    )
+    .bind(original_build_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(retries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{build_repo, project_repo, user_repo};
+    use crate::domain::build::{BuildStatus, ExternalBuild};
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_schedule_and_claim_retry() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_retry_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_retry_proj", "https://url")
+            .await
+            .unwrap();
+
+        assert!(get_policy(&pool, proj.id).await.unwrap().is_none());
+        let policy = upsert_policy(
+            &pool,
+            proj.id,
+            true,
+            2,
+            30,
+            &[FailureCategory::InfraFlaky, FailureCategory::Timeout],
+        )
+        .await
+        .unwrap();
+        assert_eq!(policy.retry_on.len(), 2);
+        assert!(policy.updated_at.is_some());
+
+        let run = |attempt: i32| ExternalBuild {
+            source: "workflow_run".into(),
+            external_id: 42,
+            attempt,
+            name: Some("CI".into()),
+            status: BuildStatus::Failed,
+            conclusion: Some("failure".into()),
+            head_sha: "abc123".into(),
+            head_branch: Some("main".into()),
+            html_url: "https://github.com/o/r/actions/runs/42".into(),
+            started_at: None,
+            finished_at: None,
+        };
+        let first = build_repo::upsert_external_build(&pool, proj.id, &run(1))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let retry = schedule_retry(
+            &pool,
+            proj.id,
+            first.id,
+            first.id,
+            2,
+            FailureCategory::InfraFlaky,
+            Utc::now(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(retry.status, BuildRetryStatus::Scheduled);
+        assert!(retry.retry_build_id.is_none());
+        assert!(schedule_retry(
+            &pool,
+            proj.id,
+            first.id,
+            first.id,
+            2,
+            FailureCategory::InfraFlaky,
+            Utc::now(),
+        )
+        .await
+        .unwrap()
+        .is_none());
+
+        let due = list_due_retries(&pool, 1000).await.unwrap();
+        assert!(due.iter().any(|r| r.id == retry.id));
+        let claimed = claim_retry(&pool, retry.id).await.unwrap().unwrap();
+        assert_eq!(claimed.status, BuildRetryStatus::Requested);
+        assert!(claimed.requested_at.is_some());
+        assert!(claim_retry(&pool, retry.id).await.unwrap().is_none());
+
+        let second = build_repo::upsert_external_build(&pool, proj.id, &run(2))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.attempt, 2);
+        assert_eq!(second.retry_of, Some(first.id));
+
+        let retries = list_retries(&pool, first.id).await.unwrap();
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0].retry_build_id, Some(second.id));
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::license::LicensePolicy;
+use crate::error::AppError;
+
+pub async fn get_license_policy(
+    pool: &PgPool,
+    project_id: Uuid,
+) -> Result<Option<LicensePolicy>, AppError> {
+    let policy = sqlx::query_as::<_, LicensePolicy>(
+        r#"
+        SELECT project_id, allowed, denied, flag_unknown, updated_at
+        FROM license_policies
+        WHERE project_id = $1
+        "#,
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(policy)
+}
+
+pub async fn upsert_license_policy(
+    pool: &PgPool,
+    project_id: Uuid,
+    allowed: &[String],
+    denied: &[String],
+    flag_unknown: bool,
+) -> Result<LicensePolicy, AppError> {
+    let policy = sqlx::query_as::<_, LicensePolicy>(
+        r#"
+        INSERT INTO license_policies (project_id, allowed, denied, flag_unknown)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (project_id) DO UPDATE
+        SET allowed = EXCLUDED.allowed,
+            denied = EXCLUDED.denied,
+            flag_unknown = EXCLUDED.flag_unknown,
+            updated_at = NOW()
+        RETURNING project_id, allowed, denied, flag_unknown, updated_at
+        "#,
+    )
+    .bind(project_id)
+    .bind(allowed)
+    .bind(denied)
+    .bind(flag_unknown)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{project_repo, user_repo};
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_license_policy_upsert() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_license_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_license_proj", "https://url")
+            .await
+            .unwrap();
+
+        assert!(get_license_policy(&pool, proj.id).await.unwrap().is_none());
+
+        let denied = vec!["AGPL-3.0-only".to_string()];
+        upsert_license_policy(&pool, proj.id, &[], &denied, true)
+            .await
+            .unwrap();
+        let allowed = vec!["MIT".to_string()];
+        let policy = upsert_license_policy(&pool, proj.id, &allowed, &denied, false)
+            .await
+            .unwrap();
+        assert_eq!(policy.allowed, allowed);
+        assert!(!policy.flag_unknown);
+
+        let fetched = get_license_policy(&pool, proj.id).await.unwrap().unwrap();
+        assert_eq!(fetched.denied, denied);
+        assert_eq!(fetched.allowed, allowed);
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,313 @@
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::llm::{LlmCallStatus, LlmInteraction, NewLlmInteraction};
+use crate::error::AppError;
+
+/// The project's own daily token limit, if it has one.
+pub async fn get_budget(pool: &PgPool, project_id: Uuid) -> Result<Option<i64>, AppError> {
+    let limit = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT daily_token_limit
+        FROM llm_budgets
+        WHERE project_id = $1
+        "#,
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(limit)
+}
+
+pub async fn upsert_budget(
+    pool: &PgPool,
+    project_id: Uuid,
+    daily_token_limit: i64,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO llm_budgets (project_id, daily_token_limit)
+        VALUES ($1, $2)
+        ON CONFLICT (project_id) DO UPDATE
+        SET daily_token_limit = EXCLUDED.daily_token_limit,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(project_id)
+    .bind(daily_token_limit)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+const TOKENS_USED_TODAY: &str = r#"
+    SELECT COALESCE(SUM(prompt_tokens + completion_tokens), 0)::BIGINT
+    FROM llm_interactions
+    WHERE project_id = $1 AND created_at > NOW() - INTERVAL '24 hours'
+"#;
+
+/// Tokens the project's LLM calls used or reserved in the last 24 hours.
+pub async fn tokens_used_today(pool: &PgPool, project_id: Uuid) -> Result<i64, AppError> {
+    let used = sqlx::query_scalar::<_, i64>(TOKENS_USED_TODAY)
+        .bind(project_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(used)
+}
+
+/// Records `interaction` as pending, reserving its prompt tokens and as many
+/// of its completion tokens as `daily_token_limit` leaves room for. Returns
+/// `None` without recording anything when fewer than `min_completion_tokens`
+/// are left. The project row is locked so concurrent calls cannot both spend
+/// the same tokens.
+pub async fn reserve_interaction(
+    pool: &PgPool,
+    interaction: &NewLlmInteraction<'_>,
+    daily_token_limit: i64,
+    min_completion_tokens: i64,
+) -> Result<Option<LlmInteraction>, AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT id FROM projects WHERE id = $1 FOR NO KEY UPDATE")
+        .bind(interaction.project_id)
+        .execute(&mut *tx)
+        .await?;
+    let used = sqlx::query_scalar::<_, i64>(TOKENS_USED_TODAY)
+        .bind(interaction.project_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let room = daily_token_limit - used - interaction.prompt_tokens as i64;
+    if room < min_completion_tokens {
+        return Ok(None);
+    }
+
+    let reserved = insert_interaction(
+        &mut *tx,
+        &NewLlmInteraction {
+            status: LlmCallStatus::Pending,
+            completion_tokens: room.min(interaction.completion_tokens as i64) as i32,
+            ..*interaction
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Some(reserved))
+}
+
+/// Settles a pending interaction with the provider's answer or error,
+/// replacing the reserved token counts with the ones actually used.
+pub async fn finish_interaction(
+    pool: &PgPool,
+    id: Uuid,
+    status: LlmCallStatus,
+    response: Option<&str>,
+    error: Option<&str>,
+    prompt_tokens: i32,
+    completion_tokens: i32,
+) -> Result<LlmInteraction, AppError> {
+    let interaction = sqlx::query_as::<_, LlmInteraction>(
+        r#"
+        UPDATE llm_interactions
+        SET status = $2, response = $3, error = $4, prompt_tokens = $5, completion_tokens = $6
+        WHERE id = $1
+        RETURNING id, project_id, user_id, purpose, subject_id, provider, model, messages,
+            response, status, error, prompt_tokens, completion_tokens, created_at
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .bind(response)
+    .bind(error)
+    .bind(prompt_tokens)
+    .bind(completion_tokens)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(interaction)
+}
+
+pub async fn record_interaction(
+    pool: &PgPool,
+    interaction: &NewLlmInteraction<'_>,
+) -> Result<LlmInteraction, AppError> {
+    insert_interaction(pool, interaction).await
+}
+
+async fn insert_interaction<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    interaction: &NewLlmInteraction<'_>,
+) -> Result<LlmInteraction, AppError> {
+    let id = Uuid::new_v4();
+    let interaction = sqlx::query_as::<_, LlmInteraction>(
+        r#"
+        INSERT INTO llm_interactions (id, project_id, user_id, purpose, subject_id, provider,
+                                      model, messages, response, status, error, prompt_tokens,
+                                      completion_tokens)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        RETURNING id, project_id, user_id, purpose, subject_id, provider, model, messages,
+            response, status, error, prompt_tokens, completion_tokens, created_at
+        "#,
+    )
+    .bind(id)
+    .bind(interaction.project_id)
+    .bind(interaction.user_id)
+    .bind(interaction.purpose)
+    .bind(interaction.subject_id)
+    .bind(interaction.provider)
+    .bind(interaction.model)
+    .bind(Json(interaction.messages))
+    .bind(interaction.response)
+    .bind(interaction.status)
+    .bind(interaction.error)
+    .bind(interaction.prompt_tokens)
+    .bind(interaction.completion_tokens)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(interaction)
+}
+
+/// The project's most recent LLM interactions.
+pub async fn list_interactions(
+    pool: &PgPool,
+    project_id: Uuid,
+    limit: i64,
+) -> Result<Vec<LlmInteraction>, AppError> {
+    let interactions = sqlx::query_as::<_, LlmInteraction>(
+        r#"
+        SELECT id, project_id, user_id, purpose, subject_id, provider, model, messages,
+               response, status, error, prompt_tokens, completion_tokens, created_at
+        FROM llm_interactions
+        WHERE project_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(project_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(interactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{project_repo, user_repo};
+    use crate::domain::llm::{LlmCallStatus, LlmMessage, LlmPurpose};
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_llm_budget_and_interactions() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_llm_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_llm_proj", "https://url")
+            .await
+            .unwrap();
+
+        assert!(get_budget(&pool, proj.id).await.unwrap().is_none());
+        upsert_budget(&pool, proj.id, 1000).await.unwrap();
+        upsert_budget(&pool, proj.id, 5000).await.unwrap();
+        assert_eq!(get_budget(&pool, proj.id).await.unwrap(), Some(5000));
+
+        let messages = vec![LlmMessage::user("Why did the build fail?")];
+        let interaction = NewLlmInteraction {
+            project_id: proj.id,
+            user_id: Some(user.id),
+            purpose: LlmPurpose::BuildSummary,
+            subject_id: None,
+            provider: "stub",
+            model: "stub",
+            messages: &messages,
+            response: Some("A test failed."),
+            status: LlmCallStatus::Succeeded,
+            error: None,
+            prompt_tokens: 120,
+            completion_tokens: 30,
+        };
+        record_interaction(&pool, &interaction).await.unwrap();
+        let recorded = record_interaction(
+            &pool,
+            &NewLlmInteraction {
+                response: None,
+                status: LlmCallStatus::Rejected,
+                error: Some("Token budget spent"),
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                ..interaction
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(recorded.messages.0, messages);
+
+        assert_eq!(tokens_used_today(&pool, proj.id).await.unwrap(), 150);
+        let listed = list_interactions(&pool, proj.id, 10).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, recorded.id);
+        assert_eq!(listed[0].status, LlmCallStatus::Rejected);
+
+        let call = NewLlmInteraction {
+            response: None,
+            status: LlmCallStatus::Pending,
+            prompt_tokens: 100,
+            completion_tokens: 1024,
+            ..interaction
+        };
+        let reserved = reserve_interaction(&pool, &call, 1000, 64)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reserved.status, LlmCallStatus::Pending);
+        assert_eq!(reserved.completion_tokens, 750);
+        assert_eq!(tokens_used_today(&pool, proj.id).await.unwrap(), 1000);
+        assert!(reserve_interaction(&pool, &call, 1000, 64)
+            .await
+            .unwrap()
+            .is_none());
+
+        let finished = finish_interaction(
+            &pool,
+            reserved.id,
+            LlmCallStatus::Succeeded,
+            Some("Bump the pin."),
+            None,
+            90,
+            20,
+        )
+        .await
+        .unwrap();
+        assert_eq!(finished.response.as_deref(), Some("Bump the pin."));
+        assert_eq!(tokens_used_today(&pool, proj.id).await.unwrap(), 260);
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
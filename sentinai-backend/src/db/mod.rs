@@ -1,5 +1,17 @@
+pub mod build_log_repo;
+pub mod build_repo;
+pub mod build_retry_repo;
+pub mod license_policy_repo;
+pub mod llm_repo;
 pub mod pipeline_repo;
 pub mod pool;
 pub mod project_repo;
+pub mod sbom_repo;
+pub mod scan_run_repo;
+pub mod scan_schedule_repo;
+pub mod security_gate_repo;
+pub mod security_metrics_repo;
 pub mod security_repo;
+pub mod test_result_repo;
 pub mod user_repo;
+pub mod webhook_delivery_repo;
@@ -64,6 +64,47 @@ pub async fn get_project(
     Ok(project)
 }
 
+/// Looks a project up without checking ownership, for background jobs acting
+/// on behalf of its owner.
+pub async fn get_project_by_id(
+    pool: &PgPool,
+    project_id: Uuid,
+) -> Result<Option<Project>, AppError> {
+    let project = sqlx::query_as::<_, Project>(
+        r#"
+        SELECT id, user_id, name, repository_url, created_at
+        FROM projects
+        WHERE id = $1
+        "#,
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(project)
+}
+
+/// Projects whose repository URL mentions `full_name` (`owner/repo`). The
+/// match is loose; callers compare the parsed URL before acting on a project.
+pub async fn list_projects_by_repository(
+    pool: &PgPool,
+    full_name: &str,
+) -> Result<Vec<Project>, AppError> {
+    let projects = sqlx::query_as::<_, Project>(
+        r#"
+        SELECT id, user_id, name, repository_url, created_at
+        FROM projects
+        WHERE repository_url ILIKE '%' || $1 || '%'
+        ORDER BY created_at
+        "#,
+    )
+    .bind(full_name)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(projects)
+}
+
 pub async fn delete_project(
     pool: &PgPool,
     project_id: Uuid,
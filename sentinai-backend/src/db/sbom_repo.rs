@@ -0,0 +1,153 @@
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::sbom::{Sbom, SbomContents};
+use crate::error::AppError;
+
+pub async fn create_sbom(
+    pool: &PgPool,
+    project_id: Uuid,
+    scan_run_id: Uuid,
+    commit_sha: Option<&str>,
+    contents: &SbomContents,
+) -> Result<Sbom, AppError> {
+    let id = Uuid::new_v4();
+    let sbom = sqlx::query_as::<_, Sbom>(
+        r#"
+        INSERT INTO sboms (id, project_id, scan_run_id, commit_sha, contents)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, project_id, scan_run_id, commit_sha, contents, created_at
+        "#,
+    )
+    .bind(id)
+    .bind(project_id)
+    .bind(scan_run_id)
+    .bind(commit_sha)
+    .bind(Json(contents))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(sbom)
+}
+
+pub async fn get_sbom_for_scan_run(
+    pool: &PgPool,
+    project_id: Uuid,
+    scan_run_id: Uuid,
+) -> Result<Option<Sbom>, AppError> {
+    let sbom = sqlx::query_as::<_, Sbom>(
+        r#"
+        SELECT id, project_id, scan_run_id, commit_sha, contents, created_at
+        FROM sboms
+        WHERE project_id = $1 AND scan_run_id = $2
+        "#,
+    )
+    .bind(project_id)
+    .bind(scan_run_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(sbom)
+}
+
+pub async fn get_latest_sbom(pool: &PgPool, project_id: Uuid) -> Result<Option<Sbom>, AppError> {
+    let sbom = sqlx::query_as::<_, Sbom>(
+        r#"
+        SELECT id, project_id, scan_run_id, commit_sha, contents, created_at
+        FROM sboms
+        WHERE project_id = $1
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(sbom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{project_repo, scan_run_repo, user_repo};
+    use crate::domain::sbom::{Ecosystem, Package};
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_sbom_repository_history() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_sbom_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_sbom_proj", "https://url")
+            .await
+            .unwrap();
+
+        assert!(get_latest_sbom(&pool, proj.id).await.unwrap().is_none());
+
+        let contents = SbomContents {
+            packages: vec![Package {
+                ecosystem: Ecosystem::Cargo,
+                name: "serde".into(),
+                version: "1.0.0".into(),
+                license: Some("MIT OR Apache-2.0".into()),
+                hashes: Vec::new(),
+                dependencies: Vec::new(),
+                source_file: "Cargo.lock".into(),
+            }],
+            root_dependencies: vec!["pkg:cargo/serde@1.0.0".into()],
+        };
+        let first_run = scan_run_repo::create_scan_run(&pool, proj.id)
+            .await
+            .unwrap();
+        let first = create_sbom(&pool, proj.id, first_run.id, Some("abc"), &contents)
+            .await
+            .unwrap();
+        let second_run = scan_run_repo::create_scan_run(&pool, proj.id)
+            .await
+            .unwrap();
+        let second = create_sbom(
+            &pool,
+            proj.id,
+            second_run.id,
+            None,
+            &SbomContents::default(),
+        )
+        .await
+        .unwrap();
+
+        let fetched = get_sbom_for_scan_run(&pool, proj.id, first_run.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fetched.id, first.id);
+        assert_eq!(fetched.contents.0, contents);
+
+        let latest = get_latest_sbom(&pool, proj.id).await.unwrap().unwrap();
+        assert_eq!(latest.id, second.id);
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,179 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::scan_run::{ScanRun, ScanRunCounts, ScanRunStatus};
+use crate::error::AppError;
+
+pub async fn create_scan_run(pool: &PgPool, project_id: Uuid) -> Result<ScanRun, AppError> {
+    let id = Uuid::new_v4();
+    let run = sqlx::query_as::<_, ScanRun>(
+        r#"
+        INSERT INTO scan_runs (id, project_id, status)
+        VALUES ($1, $2, 'queued')
+        RETURNING id, project_id, status, scanners, commit_sha, critical_count, high_count,
+            medium_count, low_count, new_count, existing_count, fixed_count, error, created_at,
+            started_at, finished_at, duration_ms
+        "#,
+    )
+    .bind(id)
+    .bind(project_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(run)
+}
+
+pub async fn mark_running(
+    pool: &PgPool,
+    id: Uuid,
+    scanners: &[String],
+    commit_sha: Option<&str>,
+) -> Result<ScanRun, AppError> {
+    let run = sqlx::query_as::<_, ScanRun>(
+        r#"
+        UPDATE scan_runs
+        SET status = 'running', scanners = $2, commit_sha = $3, started_at = NOW()
+        WHERE id = $1
+        RETURNING id, project_id, status, scanners, commit_sha, critical_count, high_count,
+            medium_count, low_count, new_count, existing_count, fixed_count, error, created_at,
+            started_at, finished_at, duration_ms
+        "#,
+    )
+    .bind(id)
+    .bind(scanners)
+    .bind(commit_sha)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(run)
+}
+
+pub async fn complete_scan_run(
+    pool: &PgPool,
+    id: Uuid,
+    status: ScanRunStatus,
+    counts: &ScanRunCounts,
+    error: Option<&str>,
+) -> Result<ScanRun, AppError> {
+    let run = sqlx::query_as::<_, ScanRun>(
+        r#"
+        UPDATE scan_runs
+        SET status = $2,
+            critical_count = $3,
+            high_count = $4,
+            medium_count = $5,
+            low_count = $6,
+            new_count = $7,
+            existing_count = $8,
+            fixed_count = $9,
+            error = $10,
+            finished_at = NOW(),
+            duration_ms = (EXTRACT(EPOCH FROM NOW() - COALESCE(started_at, created_at)) * 1000)::BIGINT
+        WHERE id = $1
+        RETURNING id, project_id, status, scanners, commit_sha, critical_count, high_count,
+            medium_count, low_count, new_count, existing_count, fixed_count, error, created_at,
+            started_at, finished_at, duration_ms
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .bind(counts.critical)
+    .bind(counts.high)
+    .bind(counts.medium)
+    .bind(counts.low)
+    .bind(counts.new)
+    .bind(counts.existing)
+    .bind(counts.fixed)
+    .bind(error)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(run)
+}
+
+pub async fn list_scan_runs(pool: &PgPool, project_id: Uuid) -> Result<Vec<ScanRun>, AppError> {
+    let runs = sqlx::query_as::<_, ScanRun>(
+        r#"
+        SELECT id, project_id, status, scanners, commit_sha, critical_count, high_count,
+               medium_count, low_count, new_count, existing_count, fixed_count, error, created_at,
+               started_at, finished_at, duration_ms
+        FROM scan_runs
+        WHERE project_id = $1
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{project_repo, user_repo};
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_scan_run_repository_lifecycle() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_scan_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_scan_proj", "https://url")
+            .await
+            .unwrap();
+
+        let run = create_scan_run(&pool, proj.id).await.unwrap();
+        assert_eq!(run.status, ScanRunStatus::Queued);
+        assert!(run.started_at.is_none());
+
+        let scanners = vec!["dependency-audit".to_string()];
+        let run = mark_running(&pool, run.id, &scanners, Some("abc123"))
+            .await
+            .unwrap();
+        assert_eq!(run.status, ScanRunStatus::Running);
+        assert_eq!(run.scanners, scanners);
+        assert_eq!(run.commit_sha.as_deref(), Some("abc123"));
+
+        let counts = ScanRunCounts {
+            high: 1,
+            new: 1,
+            ..Default::default()
+        };
+        let run = complete_scan_run(&pool, run.id, ScanRunStatus::Succeeded, &counts, None)
+            .await
+            .unwrap();
+        assert_eq!(run.status, ScanRunStatus::Succeeded);
+        assert_eq!(run.high_count, 1);
+        assert!(run.finished_at.is_some());
+        assert!(run.duration_ms.is_some());
+
+        let runs = list_scan_runs(&pool, proj.id).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].id, run.id);
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::scan_schedule::ScanSchedule;
+use crate::error::AppError;
+
+pub async fn get_schedule(
+    pool: &PgPool,
+    project_id: Uuid,
+) -> Result<Option<ScanSchedule>, AppError> {
+    let schedule = sqlx::query_as::<_, ScanSchedule>(
+        r#"
+        SELECT id, project_id, cron_expression, interval_seconds, enabled, next_run_at,
+               last_run_at, last_scan_run_id, created_at, updated_at
+        FROM scan_schedules
+        WHERE project_id = $1
+        "#,
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(schedule)
+}
+
+pub async fn upsert_schedule(
+    pool: &PgPool,
+    project_id: Uuid,
+    cron_expression: Option<&str>,
+    interval_seconds: Option<i32>,
+    enabled: bool,
+    next_run_at: DateTime<Utc>,
+) -> Result<ScanSchedule, AppError> {
+    let id = Uuid::new_v4();
+    let schedule = sqlx::query_as::<_, ScanSchedule>(
+        r#"
+        INSERT INTO scan_schedules (id, project_id, cron_expression, interval_seconds, enabled, next_run_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (project_id) DO UPDATE
+        SET cron_expression = EXCLUDED.cron_expression,
+            interval_seconds = EXCLUDED.interval_seconds,
+            enabled = EXCLUDED.enabled,
+            next_run_at = EXCLUDED.next_run_at,
+            updated_at = NOW()
+        RETURNING id, project_id, cron_expression, interval_seconds, enabled, next_run_at,
+            last_run_at, last_scan_run_id, created_at, updated_at
+        "#,
+    )
+    .bind(id)
+    .bind(project_id)
+    .bind(cron_expression)
+    .bind(interval_seconds)
+    .bind(enabled)
+    .bind(next_run_at)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(schedule)
+}
+
+pub async fn delete_schedule(pool: &PgPool, project_id: Uuid) -> Result<u64, AppError> {
+    let result = sqlx::query("DELETE FROM scan_schedules WHERE project_id = $1")
+        .bind(project_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn list_due_schedules(pool: &PgPool, limit: i64) -> Result<Vec<ScanSchedule>, AppError> {
+    let schedules = sqlx::query_as::<_, ScanSchedule>(
+        r#"
+        SELECT id, project_id, cron_expression, interval_seconds, enabled, next_run_at,
+               last_run_at, last_scan_run_id, created_at, updated_at
+        FROM scan_schedules
+        WHERE enabled AND next_run_at <= NOW()
+        ORDER BY next_run_at
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(schedules)
+}
+
+/// Moves a due schedule on to its next run. Returns `None` if the schedule is
+/// no longer due because another instance already claimed this run.
+pub async fn claim_schedule(
+    pool: &PgPool,
+    id: Uuid,
+    next_run_at: DateTime<Utc>,
+) -> Result<Option<ScanSchedule>, AppError> {
+    let schedule = sqlx::query_as::<_, ScanSchedule>(
+        r#"
+        UPDATE scan_schedules
+        SET next_run_at = $2, last_run_at = NOW()
+        WHERE id = $1 AND enabled AND next_run_at <= NOW()
+        RETURNING id, project_id, cron_expression, interval_seconds, enabled, next_run_at,
+            last_run_at, last_scan_run_id, created_at, updated_at
+        "#,
+    )
+    .bind(id)
+    .bind(next_run_at)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(schedule)
+}
+
+pub async fn set_last_scan_run(pool: &PgPool, id: Uuid, scan_run_id: Uuid) -> Result<(), AppError> {
+    sqlx::query("UPDATE scan_schedules SET last_scan_run_id = $2 WHERE id = $1")
+        .bind(id)
+        .bind(scan_run_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{project_repo, user_repo};
+    use chrono::Duration;
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_scan_schedule_claim() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_schedule_user", None)
+            .await
+            .unwrap();
+        let proj =
+            project_repo::create_project(&pool, user.id, "test_schedule_proj", "https://url")
+                .await
+                .unwrap();
+
+        let past = Utc::now() - Duration::minutes(5);
+        upsert_schedule(&pool, proj.id, Some("0 3 * * *"), None, true, past)
+            .await
+            .unwrap();
+        let schedule = upsert_schedule(&pool, proj.id, None, Some(3600), true, past)
+            .await
+            .unwrap();
+        assert_eq!(schedule.cron_expression, None);
+        assert_eq!(schedule.interval_seconds, Some(3600));
+
+        let due = list_due_schedules(&pool, 1000).await.unwrap();
+        assert!(due.iter().any(|s| s.id == schedule.id));
+
+        let next = Utc::now() + Duration::hours(1);
+        let claimed = claim_schedule(&pool, schedule.id, next).await.unwrap();
+        assert!(claimed.unwrap().last_run_at.is_some());
+        assert!(claim_schedule(&pool, schedule.id, next)
+            .await
+            .unwrap()
+            .is_none());
+
+        assert_eq!(delete_schedule(&pool, proj.id).await.unwrap(), 1);
+        assert!(get_schedule(&pool, proj.id).await.unwrap().is_none());
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::security_gate::{GatePolicy, GateRule};
+use crate::error::AppError;
+
+pub async fn get_project_policy(
+    pool: &PgPool,
+    project_id: Uuid,
+) -> Result<Option<GatePolicy>, AppError> {
+    let policy = sqlx::query_as::<_, GatePolicy>(
+        r#"
+        SELECT id, user_id, project_id, rules, updated_at
+        FROM security_gate_policies
+        WHERE project_id = $1
+        "#,
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(policy)
+}
+
+pub async fn get_organization_policy(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Option<GatePolicy>, AppError> {
+    let policy = sqlx::query_as::<_, GatePolicy>(
+        r#"
+        SELECT id, user_id, project_id, rules, updated_at
+        FROM security_gate_policies
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(policy)
+}
+
+pub async fn upsert_project_policy(
+    pool: &PgPool,
+    project_id: Uuid,
+    rules: &[GateRule],
+) -> Result<GatePolicy, AppError> {
+    let id = Uuid::new_v4();
+    let policy = sqlx::query_as::<_, GatePolicy>(
+        r#"
+        INSERT INTO security_gate_policies (id, project_id, rules)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (project_id) DO UPDATE
+        SET rules = EXCLUDED.rules, updated_at = NOW()
+        RETURNING id, user_id, project_id, rules, updated_at
+        "#,
+    )
+    .bind(id)
+    .bind(project_id)
+    .bind(Json(rules))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(policy)
+}
+
+pub async fn upsert_organization_policy(
+    pool: &PgPool,
+    user_id: Uuid,
+    rules: &[GateRule],
+) -> Result<GatePolicy, AppError> {
+    let id = Uuid::new_v4();
+    let policy = sqlx::query_as::<_, GatePolicy>(
+        r#"
+        INSERT INTO security_gate_policies (id, user_id, rules)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id) DO UPDATE
+        SET rules = EXCLUDED.rules, updated_at = NOW()
+        RETURNING id, user_id, project_id, rules, updated_at
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(Json(rules))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(policy)
+}
+
+pub async fn delete_project_policy(pool: &PgPool, project_id: Uuid) -> Result<u64, AppError> {
+    let result = sqlx::query("DELETE FROM security_gate_policies WHERE project_id = $1")
+        .bind(project_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{project_repo, user_repo};
+    use crate::domain::security_gate::{default_gate_rules, Severity};
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_gate_policy_scopes() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_gate_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_gate_proj", "https://url")
+            .await
+            .unwrap();
+
+        let org = upsert_organization_policy(&pool, user.id, &default_gate_rules())
+            .await
+            .unwrap();
+        assert_eq!(org.user_id, Some(user.id));
+
+        let rules = vec![GateRule::NoOpenFindings {
+            min_severity: Severity::High,
+        }];
+        upsert_project_policy(&pool, proj.id, &default_gate_rules())
+            .await
+            .unwrap();
+        let project = upsert_project_policy(&pool, proj.id, &rules).await.unwrap();
+        assert_eq!(project.rules.0, rules);
+
+        let fetched = get_project_policy(&pool, proj.id).await.unwrap().unwrap();
+        assert_eq!(fetched.id, project.id);
+        let fetched = get_organization_policy(&pool, user.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fetched.rules.0, default_gate_rules());
+
+        assert_eq!(delete_project_policy(&pool, proj.id).await.unwrap(), 1);
+        assert!(get_project_policy(&pool, proj.id).await.unwrap().is_none());
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,270 @@
+//! Aggregate queries behind the security metrics endpoints. Every function
+//! takes a set of project IDs so the same SQL serves a single project and the
+//! rollup across all of a user's projects.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::security_metrics::{
+    AgeDistribution, OpenFindingsPoint, RemediationTime, ScanRunTrend,
+};
+use crate::error::AppError;
+
+/// Open finding counts at the end of each of the last `days` UTC days. A
+/// closed finding counts as open until it was resolved or, for suppressions,
+/// until its latest move into its current status.
+pub async fn open_findings_over_time(
+    pool: &PgPool,
+    project_ids: &[Uuid],
+    days: i32,
+) -> Result<Vec<OpenFindingsPoint>, AppError> {
+    let points = sqlx::query_as::<_, OpenFindingsPoint>(
+        r#"
+        WITH days AS (
+            SELECT d::date AS date
+            FROM generate_series(
+                (NOW() AT TIME ZONE 'UTC')::date - ($2::int - 1),
+                (NOW() AT TIME ZONE 'UTC')::date,
+                INTERVAL '1 day'
+            ) AS d
+        ),
+        findings AS (
+            SELECT f.severity, f.created_at,
+                   CASE WHEN f.status IN ('open', 'acknowledged') THEN NULL
+                        ELSE COALESCE(
+                            f.fixed_at,
+                            (SELECT MAX(e.created_at) FROM finding_events e
+                             WHERE e.finding_id = f.id AND e.to_status = f.status),
+                            f.last_seen_at
+                        )
+                   END AS closed_at
+            FROM security_findings f
+            WHERE f.project_id = ANY($1)
+        )
+        SELECT days.date,
+               COUNT(f.created_at) FILTER (WHERE f.severity = 'critical') AS critical,
+               COUNT(f.created_at) FILTER (WHERE f.severity = 'high') AS high,
+               COUNT(f.created_at) FILTER (WHERE f.severity = 'medium') AS medium,
+               COUNT(f.created_at) FILTER (
+                   WHERE f.severity NOT IN ('critical', 'high', 'medium')
+               ) AS low
+        FROM days
+        LEFT JOIN findings f
+            ON f.created_at < ((days.date + 1)::timestamp AT TIME ZONE 'UTC')
+           AND (f.closed_at IS NULL
+                OR f.closed_at >= ((days.date + 1)::timestamp AT TIME ZONE 'UTC'))
+        GROUP BY days.date
+        ORDER BY days.date
+        "#,
+    )
+    .bind(project_ids)
+    .bind(days)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(points)
+}
+
+/// Mean time to remediate findings resolved in the last `days` days, by
+/// severity from critical down to low.
+pub async fn remediation_times(
+    pool: &PgPool,
+    project_ids: &[Uuid],
+    days: i32,
+) -> Result<Vec<RemediationTime>, AppError> {
+    let times = sqlx::query_as::<_, RemediationTime>(
+        r#"
+        SELECT severity, COUNT(*) AS resolved_count,
+               AVG(EXTRACT(EPOCH FROM (fixed_at - created_at)))::float8 AS mean_seconds
+        FROM (
+            SELECT CASE WHEN severity IN ('critical', 'high', 'medium') THEN severity
+                        ELSE 'low' END AS severity,
+                   created_at, fixed_at
+            FROM security_findings
+            WHERE project_id = ANY($1)
+              AND status = 'resolved'
+              AND fixed_at >= NOW() - make_interval(days => $2)
+        ) resolved
+        GROUP BY severity
+        ORDER BY CASE severity
+            WHEN 'critical' THEN 0 WHEN 'high' THEN 1 WHEN 'medium' THEN 2 ELSE 3 END
+        "#,
+    )
+    .bind(project_ids)
+    .bind(days)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(times)
+}
+
+pub async fn open_finding_ages(
+    pool: &PgPool,
+    project_ids: &[Uuid],
+) -> Result<AgeDistribution, AppError> {
+    let ages = sqlx::query_as::<_, AgeDistribution>(
+        r#"
+        SELECT COUNT(*) FILTER (WHERE age < INTERVAL '7 days') AS under_7_days,
+               COUNT(*) FILTER (
+                   WHERE age >= INTERVAL '7 days' AND age < INTERVAL '30 days'
+               ) AS days_7_to_30,
+               COUNT(*) FILTER (
+                   WHERE age >= INTERVAL '30 days' AND age < INTERVAL '90 days'
+               ) AS days_30_to_90,
+               COUNT(*) FILTER (WHERE age >= INTERVAL '90 days') AS over_90_days
+        FROM (
+            SELECT NOW() - created_at AS age
+            FROM security_findings
+            WHERE project_id = ANY($1) AND status IN ('open', 'acknowledged')
+        ) open_findings
+        "#,
+    )
+    .bind(project_ids)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(ages)
+}
+
+/// New and fixed counts of the most recent finished scan runs in the window,
+/// oldest first.
+pub async fn scan_run_trends(
+    pool: &PgPool,
+    project_ids: &[Uuid],
+    days: i32,
+    limit: i64,
+) -> Result<Vec<ScanRunTrend>, AppError> {
+    let runs = sqlx::query_as::<_, ScanRunTrend>(
+        r#"
+        SELECT scan_run_id, project_id, status, new_count, fixed_count, created_at
+        FROM (
+            SELECT id AS scan_run_id, project_id, status, new_count, fixed_count, created_at
+            FROM scan_runs
+            WHERE project_id = ANY($1)
+              AND status IN ('succeeded', 'failed')
+              AND created_at >= NOW() - make_interval(days => $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+        ) recent
+        ORDER BY created_at
+        "#,
+    )
+    .bind(project_ids)
+    .bind(days)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{project_repo, scan_run_repo, security_repo, user_repo};
+    use crate::domain::scan_run::{ScanRunCounts, ScanRunStatus};
+    use crate::domain::security::NewFinding;
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_security_metrics_aggregates() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_metrics_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_metrics_proj", "https://url")
+            .await
+            .unwrap();
+        let run = scan_run_repo::create_scan_run(&pool, proj.id)
+            .await
+            .unwrap();
+
+        let finding = |package: &str, severity: &str| NewFinding {
+            scanner: "dependency-audit".into(),
+            rule_id: "vulnerable-dependency".into(),
+            severity: severity.into(),
+            description: "vulnerable".into(),
+            location: None,
+            start_line: None,
+            start_column: None,
+            package: Some(package.into()),
+            remediation: None,
+            fixed_versions: Vec::new(),
+            partial_fingerprint: None,
+        };
+        for (package, severity) in [("a", "critical"), ("b", "high"), ("c", "high")] {
+            security_repo::upsert_finding(&pool, proj.id, run.id, &finding(package, severity))
+                .await
+                .unwrap();
+        }
+        // "a" was first reported ten days ago and fixed two days later.
+        sqlx::query(
+            "UPDATE security_findings SET created_at = NOW() - INTERVAL '10 days', status = 'resolved', fixed_at = NOW() - INTERVAL '8 days' WHERE project_id = $1 AND package = 'a'",
+        )
+        .bind(proj.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "UPDATE security_findings SET created_at = NOW() - INTERVAL '40 days' WHERE project_id = $1 AND package = 'b'",
+        )
+        .bind(proj.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let counts = ScanRunCounts {
+            new: 3,
+            fixed: 1,
+            ..Default::default()
+        };
+        scan_run_repo::complete_scan_run(&pool, run.id, ScanRunStatus::Succeeded, &counts, None)
+            .await
+            .unwrap();
+
+        let points = open_findings_over_time(&pool, &[proj.id], 14)
+            .await
+            .unwrap();
+        assert_eq!(points.len(), 14);
+        let nine_days_ago = &points[4];
+        assert_eq!((nine_days_ago.critical, nine_days_ago.high), (1, 1));
+        let today = points.last().unwrap();
+        assert_eq!((today.critical, today.high), (0, 2));
+
+        let times = remediation_times(&pool, &[proj.id], 30).await.unwrap();
+        assert_eq!(times.len(), 1);
+        assert_eq!(times[0].severity, "critical");
+        assert!((times[0].mean_seconds - 2.0 * 86400.0).abs() < 1.0);
+
+        let ages = open_finding_ages(&pool, &[proj.id]).await.unwrap();
+        assert_eq!(
+            (ages.under_7_days, ages.days_30_to_90, ages.over_90_days),
+            (1, 1, 0)
+        );
+
+        let runs = scan_run_trends(&pool, &[proj.id], 30, 10).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!((runs[0].new_count, runs[0].fixed_count), (3, 1));
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -1,31 +1,208 @@
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::domain::security::SecurityFinding;
+use crate::domain::security::{FindingEvent, FindingStatus, NewFinding, SecurityFinding};
 use crate::error::AppError;
 
-pub async fn create_finding(
+#[derive(sqlx::FromRow)]
+struct UpsertedFinding {
+    #[sqlx(flatten)]
+    finding: SecurityFinding,
+    previous_status: Option<FindingStatus>,
+}
+
+#[derive(sqlx::FromRow)]
+struct TransitionedFinding {
+    #[sqlx(flatten)]
+    finding: SecurityFinding,
+    previous_status: FindingStatus,
+}
+
+/// Inserts a finding produced by `scan_run_id` or, when one with the same
+/// fingerprint already exists for the project, bumps its `last_seen_at`.
+/// Resolved findings that are reported again are reopened; suppressed ones
+/// keep their status. The returned status is the one the finding had before
+/// this call, or `None` for a new row.
+pub async fn upsert_finding(
     pool: &PgPool,
     project_id: Uuid,
-    severity: &str,
-    description: &str,
-) -> Result<SecurityFinding, AppError> {
+    scan_run_id: Uuid,
+    finding: &NewFinding,
+) -> Result<(SecurityFinding, Option<FindingStatus>), AppError> {
     let id = Uuid::new_v4();
-    let finding = sqlx::query_as::<_, SecurityFinding>(
+    let row = sqlx::query_as::<_, UpsertedFinding>(
         r#"
-        INSERT INTO security_findings (id, project_id, severity, description)
-        VALUES ($1, $2, $3, $4)
-        RETURNING id, project_id, severity, description, resolved, created_at
+        WITH previous AS (
+            SELECT status FROM security_findings WHERE project_id = $2 AND fingerprint = $13
+        )
+        INSERT INTO security_findings (
+            id,
+            project_id,
+            scanner,
+            rule_id,
+            severity,
+            description,
+            location,
+            start_line,
+            start_column,
+            package,
+            remediation,
+            fixed_versions,
+            fingerprint,
+            scan_run_id,
+            last_scan_run_id
+        )
+        VALUES (
+            $1,
+            $2,
+            $3,
+            $4,
+            $5,
+            $6,
+            $7,
+            $8,
+            $9,
+            $10,
+            $11,
+            $12,
+            $13,
+            $14,
+            $14
+        )
+        ON CONFLICT (project_id, fingerprint) DO UPDATE
+        SET severity = EXCLUDED.severity,
+            description = EXCLUDED.description,
+            start_line = EXCLUDED.start_line,
+            start_column = EXCLUDED.start_column,
+            remediation = EXCLUDED.remediation,
+            fixed_versions = EXCLUDED.fixed_versions,
+            last_seen_at = NOW(),
+            last_scan_run_id = EXCLUDED.last_scan_run_id,
+            status = CASE WHEN security_findings.status = 'resolved'
+                THEN 'open' ELSE security_findings.status END,
+            fixed_at = CASE WHEN security_findings.status = 'resolved'
+                THEN NULL ELSE security_findings.fixed_at END
+        RETURNING
+            id,
+            project_id,
+            scanner,
+            rule_id,
+            severity,
+            description,
+            location,
+            start_line,
+            start_column,
+            package,
+            remediation,
+            fixed_versions,
+            fingerprint,
+            status,
+            status_expires_at,
+            created_at,
+            last_seen_at,
+            fixed_at,
+            scan_run_id,
+            last_scan_run_id,
+            (SELECT status FROM previous) AS previous_status
         "#,
     )
     .bind(id)
     .bind(project_id)
-    .bind(severity)
-    .bind(description)
+    .bind(&finding.scanner)
+    .bind(&finding.rule_id)
+    .bind(&finding.severity)
+    .bind(&finding.description)
+    .bind(&finding.location)
+    .bind(finding.start_line)
+    .bind(finding.start_column)
+    .bind(&finding.package)
+    .bind(&finding.remediation)
+    .bind(&finding.fixed_versions)
+    .bind(finding.fingerprint())
+    .bind(scan_run_id)
     .fetch_one(pool)
     .await?;
 
-    Ok(finding)
+    Ok((row.finding, row.previous_status))
+}
+
+/// Resolves open findings from the given scanners when their fingerprint was
+/// not reported by the latest scan, along with findings from before
+/// fingerprinting when `include_legacy` is set. Returns each closed finding
+/// together with the status it had before.
+pub async fn close_missing_findings(
+    pool: &PgPool,
+    project_id: Uuid,
+    scanners: &[String],
+    seen_fingerprints: &[String],
+    include_legacy: bool,
+) -> Result<Vec<(SecurityFinding, FindingStatus)>, AppError> {
+    let rows = sqlx::query_as::<_, TransitionedFinding>(
+        r#"
+        UPDATE security_findings AS f
+        SET status = 'resolved', fixed_at = NOW()
+        FROM (
+            SELECT id, status
+            FROM security_findings
+            WHERE project_id = $1
+              AND status IN ('open', 'acknowledged')
+              AND (scanner = ANY($2) OR ($4 AND scanner = 'legacy'))
+              AND fingerprint <> ALL($3)
+            FOR UPDATE
+        ) AS previous
+        WHERE f.id = previous.id
+        RETURNING f.id, f.project_id, f.scanner, f.rule_id, f.severity, f.description,
+            f.location, f.start_line, f.start_column, f.package, f.remediation,
+            f.fixed_versions, f.fingerprint, f.status, f.status_expires_at, f.created_at,
+            f.last_seen_at, f.fixed_at, f.scan_run_id, f.last_scan_run_id,
+            previous.status AS previous_status
+        "#,
+    )
+    .bind(project_id)
+    .bind(scanners)
+    .bind(seen_fingerprints)
+    .bind(include_legacy)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.finding, r.previous_status))
+        .collect())
+}
+
+/// Reopens suppressed findings whose expiry date has passed.
+pub async fn reopen_expired_findings(
+    pool: &PgPool,
+    project_id: Uuid,
+) -> Result<Vec<(SecurityFinding, FindingStatus)>, AppError> {
+    let rows = sqlx::query_as::<_, TransitionedFinding>(
+        r#"
+        UPDATE security_findings AS f
+        SET status = 'open', status_expires_at = NULL
+        FROM (
+            SELECT id, status
+            FROM security_findings
+            WHERE project_id = $1 AND status_expires_at <= NOW()
+            FOR UPDATE
+        ) AS previous
+        WHERE f.id = previous.id
+        RETURNING f.id, f.project_id, f.scanner, f.rule_id, f.severity, f.description,
+            f.location, f.start_line, f.start_column, f.package, f.remediation,
+            f.fixed_versions, f.fingerprint, f.status, f.status_expires_at, f.created_at,
+            f.last_seen_at, f.fixed_at, f.scan_run_id, f.last_scan_run_id,
+            previous.status AS previous_status
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.finding, r.previous_status))
+        .collect())
 }
 
 pub async fn get_findings_for_project(
@@ -34,7 +211,10 @@ pub async fn get_findings_for_project(
 ) -> Result<Vec<SecurityFinding>, AppError> {
     let findings = sqlx::query_as::<_, SecurityFinding>(
         r#"
-        SELECT id, project_id, severity, description, resolved, created_at
+        SELECT id, project_id, scanner, rule_id, severity, description, location,
+               start_line, start_column, package, remediation, fixed_versions, fingerprint,
+               status, status_expires_at, created_at, last_seen_at, fixed_at, scan_run_id,
+               last_scan_run_id
         FROM security_findings
         WHERE project_id = $1
         ORDER BY created_at DESC
@@ -47,10 +227,117 @@ pub async fn get_findings_for_project(
     Ok(findings)
 }
 
+pub async fn get_finding(
+    pool: &PgPool,
+    project_id: Uuid,
+    finding_id: Uuid,
+) -> Result<Option<SecurityFinding>, AppError> {
+    let finding = sqlx::query_as::<_, SecurityFinding>(
+        r#"
+        SELECT id, project_id, scanner, rule_id, severity, description, location,
+               start_line, start_column, package, remediation, fixed_versions, fingerprint,
+               status, status_expires_at, created_at, last_seen_at, fixed_at, scan_run_id,
+               last_scan_run_id
+        FROM security_findings
+        WHERE id = $1 AND project_id = $2
+        "#,
+    )
+    .bind(finding_id)
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(finding)
+}
+
+/// Moves a finding to `to`, provided it is still in `from`. Returns `None`
+/// when the finding's status changed in the meantime.
+pub async fn update_finding_status(
+    pool: &PgPool,
+    finding_id: Uuid,
+    from: FindingStatus,
+    to: FindingStatus,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<Option<SecurityFinding>, AppError> {
+    let finding = sqlx::query_as::<_, SecurityFinding>(
+        r#"
+        UPDATE security_findings
+        SET status = $3,
+            status_expires_at = $4,
+            fixed_at = CASE WHEN $3 = 'resolved' THEN NOW() ELSE NULL END
+        WHERE id = $1 AND status = $2
+        RETURNING id, project_id, scanner, rule_id, severity, description, location,
+            start_line, start_column, package, remediation, fixed_versions, fingerprint,
+            status, status_expires_at, created_at, last_seen_at, fixed_at, scan_run_id,
+            last_scan_run_id
+        "#,
+    )
+    .bind(finding_id)
+    .bind(from)
+    .bind(to)
+    .bind(expires_at)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(finding)
+}
+
+pub async fn create_finding_event(
+    pool: &PgPool,
+    finding_id: Uuid,
+    actor_id: Option<Uuid>,
+    from_status: FindingStatus,
+    to_status: FindingStatus,
+    justification: &str,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<FindingEvent, AppError> {
+    let id = Uuid::new_v4();
+    let event = sqlx::query_as::<_, FindingEvent>(
+        r#"
+        INSERT INTO finding_events
+            (id, finding_id, actor_id, from_status, to_status, justification, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, finding_id, actor_id, from_status, to_status, justification, expires_at,
+            created_at
+        "#,
+    )
+    .bind(id)
+    .bind(finding_id)
+    .bind(actor_id)
+    .bind(from_status)
+    .bind(to_status)
+    .bind(justification)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(event)
+}
+
+pub async fn get_events_for_finding(
+    pool: &PgPool,
+    finding_id: Uuid,
+) -> Result<Vec<FindingEvent>, AppError> {
+    let events = sqlx::query_as::<_, FindingEvent>(
+        r#"
+        SELECT id, finding_id, actor_id, from_status, to_status, justification, expires_at,
+               created_at
+        FROM finding_events
+        WHERE finding_id = $1
+        ORDER BY created_at ASC
+        "#,
+    )
+    .bind(finding_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(events)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::{project_repo, user_repo};
+    use crate::db::{project_repo, scan_run_repo, user_repo};
     use sqlx::postgres::PgPoolOptions;
     use std::env;
 
@@ -79,17 +366,127 @@ mod tests {
             .await
             .unwrap();
 
-        let finding = create_finding(&pool, proj.id, "high", "Found exposed token")
+        let run = scan_run_repo::create_scan_run(&pool, proj.id)
+            .await
+            .unwrap();
+
+        let candidate = NewFinding {
+            scanner: "dependency-audit".into(),
+            rule_id: "outdated-dependency".into(),
+            severity: "high".into(),
+            description: "Found exposed token".into(),
+            location: Some("Cargo.lock".into()),
+            start_line: None,
+            start_column: None,
+            package: Some("openssl".into()),
+            remediation: None,
+            fixed_versions: Vec::new(),
+            partial_fingerprint: None,
+        };
+        let (finding, previous) = upsert_finding(&pool, proj.id, run.id, &candidate)
             .await
             .unwrap();
+        assert!(previous.is_none());
         assert_eq!(finding.severity, "high");
         assert_eq!(finding.project_id, proj.id);
-        assert_eq!(finding.resolved, false);
+        assert_eq!(finding.fingerprint, candidate.fingerprint());
+        assert_eq!(finding.status, FindingStatus::Open);
+        assert_eq!(finding.scan_run_id, Some(run.id));
+
+        let (again, previous) = upsert_finding(&pool, proj.id, run.id, &candidate)
+            .await
+            .unwrap();
+        assert_eq!(previous, Some(FindingStatus::Open));
+        assert_eq!(again.id, finding.id);
+        assert!(again.last_seen_at >= finding.last_seen_at);
 
         let findings = get_findings_for_project(&pool, proj.id).await.unwrap();
         assert_eq!(findings.len(), 1);
         assert_eq!(findings[0].id, finding.id);
 
+        let fixed = close_missing_findings(
+            &pool,
+            proj.id,
+            std::slice::from_ref(&candidate.scanner),
+            &[],
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(fixed.len(), 1);
+        assert_eq!(fixed[0].0.status, FindingStatus::Resolved);
+        assert_eq!(fixed[0].1, FindingStatus::Open);
+        assert!(fixed[0].0.fixed_at.is_some());
+
+        let (reopened, previous) = upsert_finding(&pool, proj.id, run.id, &candidate)
+            .await
+            .unwrap();
+        assert_eq!(previous, Some(FindingStatus::Resolved));
+        assert_eq!(reopened.status, FindingStatus::Open);
+        assert!(reopened.fixed_at.is_none());
+
+        let expired = Utc::now() - chrono::Duration::minutes(1);
+        let suppressed = update_finding_status(
+            &pool,
+            finding.id,
+            FindingStatus::Open,
+            FindingStatus::AcceptedRisk,
+            Some(expired),
+        )
+        .await
+        .unwrap()
+        .expect("Finding should be open");
+        assert_eq!(suppressed.status, FindingStatus::AcceptedRisk);
+
+        let stale = update_finding_status(
+            &pool,
+            finding.id,
+            FindingStatus::Open,
+            FindingStatus::Resolved,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(stale.is_none());
+
+        create_finding_event(
+            &pool,
+            finding.id,
+            Some(user.id),
+            FindingStatus::Open,
+            FindingStatus::AcceptedRisk,
+            "Mitigated by network policy",
+            Some(expired),
+        )
+        .await
+        .unwrap();
+        let events = get_events_for_finding(&pool, finding.id).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].to_status, FindingStatus::AcceptedRisk);
+
+        let reopened = reopen_expired_findings(&pool, proj.id).await.unwrap();
+        assert_eq!(reopened.len(), 1);
+        assert_eq!(reopened[0].0.status, FindingStatus::Open);
+        assert_eq!(reopened[0].1, FindingStatus::AcceptedRisk);
+
+        let legacy = NewFinding {
+            scanner: "legacy".into(),
+            ..candidate
+        };
+        upsert_finding(&pool, proj.id, run.id, &legacy)
+            .await
+            .unwrap();
+        let imported = ["sarif:semgrep".to_string()];
+        let fixed = close_missing_findings(&pool, proj.id, &imported, &[], false)
+            .await
+            .unwrap();
+        assert!(fixed.is_empty());
+        let fixed = close_missing_findings(&pool, proj.id, &imported, &[], true)
+            .await
+            .unwrap();
+        assert_eq!(fixed.len(), 1);
+        assert_eq!(fixed[0].0.scanner, "legacy");
+
         sqlx::query("DELETE FROM users WHERE id = $1")
             .bind(user.id)
             .execute(&pool)
@@ -0,0 +1,173 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::test_result::{ParsedTest, TestRun};
+use crate::error::AppError;
+
+/// Stores a build's test results. A test reported again for the same build,
+/// e.g. by a second report upload, replaces its earlier result.
+pub async fn upsert_results(
+    pool: &PgPool,
+    project_id: Uuid,
+    build_id: Uuid,
+    tests: &[ParsedTest],
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    for test in tests {
+        sqlx::query(
+            r#"
+            INSERT INTO test_results (id, project_id, build_id, test_id, file, outcome,
+                                      duration_ms, message)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (build_id, test_id) DO UPDATE
+            SET file = EXCLUDED.file,
+                outcome = EXCLUDED.outcome,
+                duration_ms = EXCLUDED.duration_ms,
+                message = EXCLUDED.message,
+                created_at = NOW()
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(project_id)
+        .bind(build_id)
+        .bind(&test.test_id)
+        .bind(&test.file)
+        .bind(test.outcome)
+        .bind(test.duration_ms)
+        .bind(&test.message)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Passed and failed test results of the last `days` days, oldest first.
+pub async fn list_runs(
+    pool: &PgPool,
+    project_id: Uuid,
+    branch: Option<&str>,
+    days: i32,
+) -> Result<Vec<TestRun>, AppError> {
+    let runs = sqlx::query_as::<_, TestRun>(
+        r#"
+        SELECT r.test_id, r.file, r.outcome, r.message, r.build_id, b.head_sha, b.head_branch,
+               COALESCE(b.started_at, r.created_at) AS ran_at
+        FROM test_results r
+        JOIN builds b ON b.id = r.build_id
+        WHERE r.project_id = $1
+          AND r.outcome <> 'skipped'
+          AND r.created_at >= NOW() - make_interval(days => $2)
+          AND ($3::text IS NULL OR b.head_branch = $3)
+        ORDER BY ran_at, r.created_at
+        "#,
+    )
+    .bind(project_id)
+    .bind(days)
+    .bind(branch)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{build_repo, project_repo, user_repo};
+    use crate::domain::build::NewBuild;
+    use crate::domain::test_result::TestOutcome;
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    fn result(test_id: &str, outcome: TestOutcome) -> ParsedTest {
+        ParsedTest {
+            test_id: test_id.to_string(),
+            file: None,
+            outcome,
+            duration_ms: Some(12),
+            message: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_upsert_and_list_runs() {
+        let pool = get_pool().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_results_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_results_proj", "https://url")
+            .await
+            .unwrap();
+        let new_build = |branch: &str| NewBuild {
+            pipeline_id: None,
+            name: Some("test".into()),
+            head_sha: Some("abc123".into()),
+            head_branch: Some(branch.into()),
+        };
+        let main = build_repo::create_build(&pool, proj.id, &new_build("main"))
+            .await
+            .unwrap();
+        let feature = build_repo::create_build(&pool, proj.id, &new_build("feature"))
+            .await
+            .unwrap();
+
+        upsert_results(
+            &pool,
+            proj.id,
+            main.id,
+            &[
+                result("tests::a", TestOutcome::Passed),
+                result("tests::b", TestOutcome::Skipped),
+            ],
+        )
+        .await
+        .unwrap();
+        upsert_results(
+            &pool,
+            proj.id,
+            main.id,
+            &[result("tests::a", TestOutcome::Failed)],
+        )
+        .await
+        .unwrap();
+        upsert_results(
+            &pool,
+            proj.id,
+            feature.id,
+            &[result("tests::a", TestOutcome::Passed)],
+        )
+        .await
+        .unwrap();
+
+        let runs = list_runs(&pool, proj.id, None, 14).await.unwrap();
+        assert_eq!(runs.len(), 2);
+        let on_main = list_runs(&pool, proj.id, Some("main"), 14).await.unwrap();
+        assert_eq!(on_main.len(), 1);
+        assert_eq!(on_main[0].outcome, TestOutcome::Failed);
+        assert_eq!(on_main[0].head_sha.as_deref(), Some("abc123"));
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
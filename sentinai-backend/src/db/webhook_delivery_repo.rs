@@ -0,0 +1,174 @@
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::github_event::WebhookAction;
+use crate::domain::webhook_delivery::{DeliveryStatus, NewWebhookDelivery, WebhookDelivery};
+use crate::error::AppError;
+
+/// Stores a delivery. Returns `None` if a delivery with the same provider and
+/// delivery ID was stored before.
+pub async fn create_delivery(
+    pool: &PgPool,
+    delivery: &NewWebhookDelivery,
+) -> Result<Option<WebhookDelivery>, AppError> {
+    let id = Uuid::new_v4();
+    let delivery = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        INSERT INTO webhook_deliveries (id, provider, delivery_id, event, repository, headers, body, status)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'received')
+        ON CONFLICT (provider, delivery_id) DO NOTHING
+        RETURNING id, provider, delivery_id, event, repository, body, status, action,
+            error, replay_count, received_at, processed_at
+        "#,
+    )
+    .bind(id)
+    .bind(&delivery.provider)
+    .bind(&delivery.delivery_id)
+    .bind(&delivery.event)
+    .bind(&delivery.repository)
+    .bind(Json(&delivery.headers))
+    .bind(&delivery.body)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(delivery)
+}
+
+pub async fn get_delivery(
+    pool: &PgPool,
+    provider: &str,
+    delivery_id: &str,
+) -> Result<Option<WebhookDelivery>, AppError> {
+    let delivery = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT id, provider, delivery_id, event, repository, body, status, action,
+               error, replay_count, received_at, processed_at
+        FROM webhook_deliveries
+        WHERE provider = $1 AND delivery_id = $2
+        "#,
+    )
+    .bind(provider)
+    .bind(delivery_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(delivery)
+}
+
+/// Records how processing a delivery ended.
+pub async fn finish_delivery(
+    pool: &PgPool,
+    id: Uuid,
+    status: DeliveryStatus,
+    action: Option<WebhookAction>,
+    error: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = $2, action = $3, error = $4, processed_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(status)
+    .bind(action)
+    .bind(error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Puts a delivery back into `received` ahead of processing it again.
+pub async fn start_replay(pool: &PgPool, id: Uuid) -> Result<WebhookDelivery, AppError> {
+    let delivery = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = 'received', action = NULL, error = NULL, processed_at = NULL,
+            replay_count = replay_count + 1
+        WHERE id = $1
+        RETURNING id, provider, delivery_id, event, repository, body, status, action,
+            error, replay_count, received_at, processed_at
+        "#,
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(delivery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::postgres::PgPoolOptions;
+    use std::collections::BTreeMap;
+    use std::env;
+
+    async fn get_pool() -> PgPool {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_webhook_delivery_dedupe_and_replay() {
+        let pool = get_pool().await;
+        let delivery = NewWebhookDelivery {
+            provider: "github".into(),
+            delivery_id: Uuid::new_v4().to_string(),
+            event: "push".into(),
+            repository: Some("org/repo".into()),
+            headers: BTreeMap::from([("x-github-event".to_string(), "push".to_string())]),
+            body: br#"{"ref":"refs/heads/main"}"#.to_vec(),
+        };
+
+        let stored = create_delivery(&pool, &delivery).await.unwrap().unwrap();
+        assert_eq!(stored.status, DeliveryStatus::Received);
+        assert_eq!(stored.body, delivery.body);
+        assert!(create_delivery(&pool, &delivery).await.unwrap().is_none());
+
+        finish_delivery(&pool, stored.id, DeliveryStatus::Failed, None, Some("boom"))
+            .await
+            .unwrap();
+        let failed = get_delivery(&pool, "github", &delivery.delivery_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(failed.status, DeliveryStatus::Failed);
+        assert_eq!(failed.error.as_deref(), Some("boom"));
+        assert!(failed.processed_at.is_some());
+
+        let replayed = start_replay(&pool, stored.id).await.unwrap();
+        assert_eq!(replayed.status, DeliveryStatus::Received);
+        assert_eq!(replayed.replay_count, 1);
+        assert_eq!(replayed.error, None);
+        finish_delivery(
+            &pool,
+            stored.id,
+            DeliveryStatus::Processed,
+            Some(WebhookAction::Ignored),
+            None,
+        )
+        .await
+        .unwrap();
+        let processed = get_delivery(&pool, "github", &delivery.delivery_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(processed.action, Some(WebhookAction::Ignored));
+
+        sqlx::query("DELETE FROM webhook_deliveries WHERE id = $1")
+            .bind(stored.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,341 @@
+//! Bitbucket Cloud webhook payloads, normalized into the GitHub event types
+//! the webhook handlers dispatch on.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::domain::github_event::{
+    GithubEvent, PullRequest, PullRequestEvent, PullRequestHead, PushEvent, Repository,
+    WorkflowRun, WorkflowRunEvent,
+};
+
+/// Branches treated as the main branch when a payload does not name one.
+const FALLBACK_MAIN_BRANCHES: [&str; 2] = ["main", "master"];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Link {
+    pub href: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepositoryLinks {
+    pub html: Option<Link>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Branch {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BitbucketRepository {
+    /// `workspace/repo`.
+    pub full_name: String,
+    #[serde(default)]
+    pub mainbranch: Option<Branch>,
+    #[serde(default)]
+    pub links: Option<RepositoryLinks>,
+}
+
+impl From<BitbucketRepository> for Repository {
+    fn from(repository: BitbucketRepository) -> Self {
+        Repository {
+            full_name: repository.full_name,
+            default_branch: repository.mainbranch.map(|branch| branch.name),
+            html_url: repository
+                .links
+                .and_then(|links| links.html)
+                .map(|link| link.href),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Commit {
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RefState {
+    /// `branch`, `tag`, ...
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Change {
+    /// `None` when the ref was deleted.
+    pub new: Option<RefState>,
+    pub old: Option<RefState>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Push {
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepoPush {
+    pub push: Push,
+    pub repository: BitbucketRepository,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PullRequestSource {
+    pub commit: Commit,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BitbucketPullRequest {
+    pub id: i64,
+    pub source: PullRequestSource,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PullRequestPayload {
+    pub pullrequest: BitbucketPullRequest,
+    pub repository: BitbucketRepository,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitStatus {
+    /// Identifies the build within the commit, e.g. a pipeline UUID.
+    pub key: String,
+    pub name: Option<String>,
+    /// `INPROGRESS`, `SUCCESSFUL`, `FAILED` or `STOPPED`.
+    pub state: String,
+    pub url: String,
+    #[serde(default)]
+    pub refname: Option<String>,
+    pub commit: Commit,
+    pub created_on: Option<DateTime<Utc>>,
+    pub updated_on: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitStatusPayload {
+    pub commit_status: CommitStatus,
+    pub repository: BitbucketRepository,
+}
+
+/// Picks the change a push is about: the one to the main branch if there is
+/// one, otherwise the first.
+fn push_event(payload: RepoPush) -> PushEvent {
+    let mut repository: Repository = payload.repository.into();
+    let ref_name = |change: &Change| change.new.as_ref().or(change.old.as_ref()).cloned();
+    let is_main = |name: &str| match &repository.default_branch {
+        Some(branch) => branch == name,
+        None => FALLBACK_MAIN_BRANCHES.contains(&name),
+    };
+    let change = payload
+        .push
+        .changes
+        .iter()
+        .find(|change| ref_name(change).is_some_and(|r| r.kind == "branch" && is_main(&r.name)))
+        .or(payload.push.changes.first());
+
+    let (git_ref, deleted) = match change.and_then(|change| Some((ref_name(change)?, change))) {
+        Some((state, change)) => {
+            let prefix = if state.kind == "tag" { "tags" } else { "heads" };
+            (
+                format!("refs/{}/{}", prefix, state.name),
+                change.new.is_none(),
+            )
+        }
+        None => (String::new(), false),
+    };
+    if repository.default_branch.is_none() {
+        repository.default_branch = git_ref
+            .strip_prefix("refs/heads/")
+            .filter(|name| FALLBACK_MAIN_BRANCHES.contains(name))
+            .map(String::from);
+    }
+
+    PushEvent {
+        git_ref,
+        deleted,
+        repository,
+    }
+}
+
+/// Commit statuses carry no numeric ID, so the build is identified by a stable
+/// hash of its commit and key.
+fn commit_status_id(status: &CommitStatus) -> i64 {
+    let digest = Sha256::new()
+        .chain_update(status.commit.hash.as_bytes())
+        .chain_update(b":")
+        .chain_update(status.key.as_bytes())
+        .finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    i64::from_be_bytes(bytes) & i64::MAX
+}
+
+impl From<CommitStatusPayload> for WorkflowRunEvent {
+    fn from(payload: CommitStatusPayload) -> Self {
+        let status = payload.commit_status;
+        let (run_status, conclusion) = match status.state.as_str() {
+            "SUCCESSFUL" => ("completed", Some("success")),
+            "FAILED" => ("completed", Some("failure")),
+            "STOPPED" => ("completed", Some("cancelled")),
+            _ => ("in_progress", None),
+        };
+        WorkflowRunEvent {
+            action: run_status.into(),
+            workflow_run: WorkflowRun {
+                id: commit_status_id(&status),
+                run_attempt: 1,
+                name: status.name,
+                head_branch: status.refname,
+                head_sha: status.commit.hash,
+                status: run_status.into(),
+                conclusion: conclusion.map(Into::into),
+                html_url: status.url,
+                run_started_at: status.created_on,
+                updated_at: status.updated_on,
+            },
+            repository: payload.repository.into(),
+            source: "bitbucket_commit_status".into(),
+        }
+    }
+}
+
+/// Deserializes a delivery according to its `X-Event-Key` header.
+pub fn parse(event: &str, body: &[u8]) -> Result<GithubEvent, serde_json::Error> {
+    Ok(match event {
+        "repo:push" => GithubEvent::Push(push_event(serde_json::from_slice(body)?)),
+        "pullrequest:created"
+        | "pullrequest:updated"
+        | "pullrequest:fulfilled"
+        | "pullrequest:rejected" => {
+            let payload: PullRequestPayload = serde_json::from_slice(body)?;
+            let action = match event {
+                "pullrequest:created" => "opened",
+                "pullrequest:updated" => "synchronize",
+                "pullrequest:fulfilled" => "merged",
+                _ => "closed",
+            };
+            GithubEvent::PullRequest(PullRequestEvent {
+                action: action.into(),
+                number: payload.pullrequest.id,
+                pull_request: PullRequest {
+                    head: PullRequestHead {
+                        sha: payload.pullrequest.source.commit.hash,
+                    },
+                },
+                repository: payload.repository.into(),
+            })
+        }
+        "repo:commit_status_created" | "repo:commit_status_updated" => {
+            GithubEvent::WorkflowRun(serde_json::from_slice::<CommitStatusPayload>(body)?.into())
+        }
+        other => GithubEvent::Other(other.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPOSITORY: &str = r#"{
+        "type": "repository",
+        "full_name": "team/repo",
+        "links": { "html": { "href": "https://bitbucket.org/team/repo" } }
+    }"#;
+
+    #[test]
+    fn test_parse_push_prefers_main_branch_change() {
+        let body = format!(
+            r#"{{
+                "push": {{ "changes": [
+                    {{ "new": {{ "type": "branch", "name": "feature" }}, "old": null }},
+                    {{ "new": {{ "type": "branch", "name": "main" }},
+                       "old": {{ "type": "branch", "name": "main" }} }}
+                ] }},
+                "repository": {REPOSITORY}
+            }}"#
+        );
+        let GithubEvent::Push(push) = parse("repo:push", body.as_bytes()).unwrap() else {
+            panic!("expected a push event");
+        };
+        assert_eq!(push.git_ref, "refs/heads/main");
+        assert!(!push.deleted);
+        assert_eq!(push.repository.default_branch.as_deref(), Some("main"));
+        assert_eq!(
+            push.repository.html_url.as_deref(),
+            Some("https://bitbucket.org/team/repo")
+        );
+
+        let deleted = format!(
+            r#"{{
+                "push": {{ "changes": [
+                    {{ "new": null, "old": {{ "type": "branch", "name": "feature" }} }}
+                ] }},
+                "repository": {REPOSITORY}
+            }}"#
+        );
+        let GithubEvent::Push(push) = parse("repo:push", deleted.as_bytes()).unwrap() else {
+            panic!("expected a push event");
+        };
+        assert_eq!(push.git_ref, "refs/heads/feature");
+        assert!(push.deleted);
+        assert_eq!(push.repository.default_branch, None);
+    }
+
+    #[test]
+    fn test_parse_pull_request() {
+        let body = format!(
+            r#"{{
+                "pullrequest": {{
+                    "id": 12,
+                    "source": {{ "commit": {{ "hash": "d3022fc0ca3d" }} }}
+                }},
+                "repository": {REPOSITORY}
+            }}"#
+        );
+        let GithubEvent::PullRequest(event) =
+            parse("pullrequest:updated", body.as_bytes()).unwrap()
+        else {
+            panic!("expected a pull request event");
+        };
+        assert_eq!(event.action, "synchronize");
+        assert_eq!(event.number, 12);
+        assert_eq!(event.pull_request.head.sha, "d3022fc0ca3d");
+    }
+
+    #[test]
+    fn test_parse_commit_status() {
+        let body = format!(
+            r#"{{
+                "commit_status": {{
+                    "key": "{{8b2d1c3e}}",
+                    "name": "Pipeline #42 for main",
+                    "state": "FAILED",
+                    "url": "https://bitbucket.org/team/repo/pipelines/results/42",
+                    "refname": "main",
+                    "commit": {{ "hash": "d3022fc0ca3d" }},
+                    "created_on": "2026-08-12T15:23:28.000000+00:00",
+                    "updated_on": "2026-08-12T15:26:29.000000+00:00"
+                }},
+                "repository": {REPOSITORY}
+            }}"#
+        );
+        let event = parse("repo:commit_status_updated", body.as_bytes()).unwrap();
+        let GithubEvent::WorkflowRun(event) = event else {
+            panic!("expected a workflow_run event");
+        };
+        assert_eq!(event.action, "completed");
+        assert_eq!(event.source, "bitbucket_commit_status");
+        assert_eq!(event.workflow_run.conclusion.as_deref(), Some("failure"));
+        assert!(event.workflow_run.id >= 0);
+
+        // The same build reported again maps to the same run.
+        let again = parse("repo:commit_status_created", body.as_bytes()).unwrap();
+        let GithubEvent::WorkflowRun(again) = again else {
+            panic!("expected a workflow_run event");
+        };
+        assert_eq!(again.workflow_run.id, event.workflow_run.id);
+    }
+}
@@ -2,12 +2,121 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum BuildStatus {
+    Pending,
+    Running,
+    Success,
+    Failed,
+}
+
+impl BuildStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, BuildStatus::Success | BuildStatus::Failed)
+    }
+}
+
+/// Why a build failed, as read from its log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum FailureCategory {
+    CompileError,
+    TestFailure,
+    DependencyResolution,
+    OutOfMemory,
+    Timeout,
+    InfraFlaky,
+    Lint,
+    MissingSecret,
+    Unknown,
+}
+
+/// The result of classifying a failed build's log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailureClassification {
+    pub category: FailureCategory,
+    /// The log line the category was read from.
+    pub line: Option<i32>,
+    pub excerpt: Option<String>,
+    pub failing_tests: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Build {
     pub id: Uuid,
-    pub pipeline_id: Uuid,
-    pub status: String,
-    pub logs: Option<String>,
+    pub project_id: Uuid,
+    pub pipeline_id: Option<Uuid>,
+    /// Where the build ran: `sentinai`, `workflow_run` or `check_run` for
+    /// GitHub, `gitlab_pipeline`, or `bitbucket_commit_status`.
+    pub source: String,
+    /// The run's ID in its source system.
+    pub external_id: Option<i64>,
+    pub name: Option<String>,
+    pub status: BuildStatus,
+    pub conclusion: Option<String>,
+    pub head_sha: Option<String>,
+    pub head_branch: Option<String>,
+    pub html_url: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub failure_category: Option<FailureCategory>,
+    pub failure_line: Option<i32>,
+    pub failure_excerpt: Option<String>,
+    pub failing_tests: Vec<String>,
+    pub classified_at: Option<DateTime<Utc>>,
+    /// Which run of the build this is, counting from 1.
+    pub attempt: i32,
+    /// The first attempt, for builds that re-run an earlier one.
+    pub retry_of: Option<Uuid>,
+}
+
+/// A build that ran outside Sentinai and was reported by a webhook.
+#[derive(Debug, Clone)]
+pub struct ExternalBuild {
+    pub source: String,
+    pub external_id: i64,
+    /// GitHub's `run_attempt`; 1 for sources that do not re-run in place.
+    pub attempt: i32,
+    pub name: Option<String>,
+    pub status: BuildStatus,
+    pub conclusion: Option<String>,
+    pub head_sha: String,
+    pub head_branch: Option<String>,
+    pub html_url: String,
     pub started_at: Option<DateTime<Utc>>,
     pub finished_at: Option<DateTime<Utc>>,
 }
+
+/// A build reported through the API rather than a webhook, e.g. by a CI step.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewBuild {
+    #[serde(default)]
+    pub pipeline_id: Option<Uuid>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub head_sha: Option<String>,
+    #[serde(default)]
+    pub head_branch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateBuildRequest {
+    pub status: BuildStatus,
+    #[serde(default)]
+    pub conclusion: Option<String>,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BuildListQuery {
+    pub status: Option<BuildStatus>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
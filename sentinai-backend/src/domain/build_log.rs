@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::build::BuildStatus;
+
+/// A stored slice of a build's log. Small chunks keep their text in
+/// `content`; large ones are gzipped into `compressed`.
+#[derive(Debug, sqlx::FromRow)]
+pub struct BuildLogChunk {
+    pub first_line: i32,
+    pub content: Option<String>,
+    pub compressed: Option<Vec<u8>>,
+}
+
+/// A chunk ready to be stored: the text indexed for search, and the text as
+/// stored, gzipped when large.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewLogChunk {
+    pub text: String,
+    pub line_count: i32,
+    pub content: Option<String>,
+    pub compressed: Option<Vec<u8>>,
+}
+
+/// A chunk matching a search, with the build it belongs to.
+#[derive(Debug, sqlx::FromRow)]
+pub struct LogSearchChunk {
+    pub build_id: Uuid,
+    pub build_name: Option<String>,
+    pub head_sha: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub first_line: i32,
+    pub content: Option<String>,
+    pub compressed: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogLine {
+    pub number: i32,
+    pub text: String,
+}
+
+/// What a log stream sends: each new line, then the build's final status.
+/// `Reset` means the log was replaced and is sent again from line 1.
+#[derive(Debug)]
+pub enum LogStreamEvent {
+    Line(LogLine),
+    Reset,
+    Finished(BuildStatus),
+}
+
+#[derive(Debug, Serialize)]
+pub struct BuildLogPage {
+    pub build_id: Uuid,
+    pub from: i32,
+    pub to: i32,
+    pub total_lines: i32,
+    pub lines: Vec<LogLine>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogAppendResult {
+    pub build_id: Uuid,
+    /// Number of the first appended line; lines are numbered from 1.
+    pub first_line: i32,
+    pub line_count: i32,
+    pub total_lines: i32,
+}
+
+fn default_from() -> i32 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BuildLogQuery {
+    #[serde(default = "default_from")]
+    pub from: i32,
+    /// Last line to return, inclusive.
+    pub to: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogSearchHit {
+    pub build_id: Uuid,
+    pub build_name: Option<String>,
+    pub head_sha: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub line_number: i32,
+    pub text: String,
+}
+
+fn default_search_limit() -> i64 {
+    50
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogSearchQuery {
+    pub q: String,
+    #[serde(default = "default_search_limit")]
+    pub limit: i64,
+}
@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::build::{Build, FailureCategory};
+
+/// When a project's failed builds are re-run automatically. Projects opt in;
+/// without a stored policy nothing is re-run.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BuildRetryPolicy {
+    pub project_id: Uuid,
+    pub enabled: bool,
+    /// Runs of a build including the first, so 3 allows two retries.
+    pub max_attempts: i32,
+    /// Delay before the first retry; it doubles for each later one.
+    pub backoff_seconds: i32,
+    /// Failure categories worth re-running.
+    pub retry_on: Vec<FailureCategory>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl BuildRetryPolicy {
+    pub fn default_for(project_id: Uuid) -> Self {
+        Self {
+            project_id,
+            enabled: false,
+            max_attempts: 3,
+            backoff_seconds: 60,
+            retry_on: vec![FailureCategory::InfraFlaky],
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateBuildRetryPolicyRequest {
+    pub enabled: bool,
+    pub max_attempts: i32,
+    pub backoff_seconds: i32,
+    pub retry_on: Vec<FailureCategory>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum BuildRetryStatus {
+    /// Waiting for its backoff to pass.
+    Scheduled,
+    /// The re-run was started in the build's CI system.
+    Requested,
+    /// Not re-run because the build was re-run some other way first.
+    Skipped,
+    /// The CI system refused the re-run.
+    Failed,
+}
+
+/// An automatic re-run of a failed build.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BuildRetry {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    /// The failed attempt being re-run.
+    pub build_id: Uuid,
+    /// The first attempt of the build.
+    pub original_build_id: Uuid,
+    /// The attempt the retry starts.
+    pub attempt: i32,
+    pub failure_category: FailureCategory,
+    pub status: BuildRetryStatus,
+    pub scheduled_for: DateTime<Utc>,
+    pub requested_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+    /// The build recorded for the new attempt, once its webhook has arrived.
+    pub retry_build_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A build together with the automatic retries of its run.
+#[derive(Debug, Serialize)]
+pub struct BuildDetail {
+    #[serde(flatten)]
+    pub build: Build,
+    pub retries: Vec<BuildRetry>,
+}
@@ -0,0 +1,282 @@
+//! Typed GitHub webhook payloads, limited to the fields Sentinai acts on.
+//! GitLab and Bitbucket deliveries are normalized into the same types (see
+//! [`super::gitlab_event`] and [`super::bitbucket_event`]).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Repository {
+    /// `owner/repo`.
+    pub full_name: String,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    /// Web address of the repository, used to tell forges apart.
+    #[serde(default)]
+    pub html_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushEvent {
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    #[serde(default)]
+    pub deleted: bool,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PullRequestHead {
+    pub sha: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PullRequest {
+    pub head: PullRequestHead,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PullRequestEvent {
+    pub action: String,
+    pub number: i64,
+    pub pull_request: PullRequest,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkflowRun {
+    pub id: i64,
+    /// Counts re-runs of the same run, starting at 1.
+    #[serde(default = "WorkflowRun::first_attempt")]
+    pub run_attempt: i32,
+    pub name: Option<String>,
+    pub head_branch: Option<String>,
+    pub head_sha: String,
+    /// `queued`, `in_progress`, `completed`, ...
+    pub status: String,
+    /// Set once the run has completed: `success`, `failure`, `cancelled`, ...
+    pub conclusion: Option<String>,
+    pub html_url: String,
+    pub run_started_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkflowRunEvent {
+    pub action: String,
+    pub workflow_run: WorkflowRun,
+    pub repository: Repository,
+    /// The `source` builds recorded from the run are stored under.
+    #[serde(skip_deserializing, default = "WorkflowRunEvent::github_source")]
+    pub source: String,
+}
+
+impl WorkflowRun {
+    fn first_attempt() -> i32 {
+        1
+    }
+}
+
+impl WorkflowRunEvent {
+    fn github_source() -> String {
+        "workflow_run".into()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRunSuite {
+    pub head_branch: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRun {
+    pub id: i64,
+    pub name: String,
+    pub head_sha: String,
+    /// `queued`, `in_progress`, `completed`, ...
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub html_url: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub check_suite: Option<CheckRunSuite>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRunEvent {
+    pub action: String,
+    pub check_run: CheckRun,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckSuite {
+    pub head_sha: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckSuiteEvent {
+    pub action: String,
+    pub check_suite: CheckSuite,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Clone)]
+pub enum GithubEvent {
+    Ping,
+    Push(PushEvent),
+    PullRequest(PullRequestEvent),
+    WorkflowRun(WorkflowRunEvent),
+    CheckRun(CheckRunEvent),
+    CheckSuite(CheckSuiteEvent),
+    /// An event type Sentinai does not act on.
+    Other(String),
+}
+
+impl GithubEvent {
+    /// Deserializes a delivery according to its `X-GitHub-Event` header.
+    pub fn parse(event: &str, body: &[u8]) -> Result<GithubEvent, serde_json::Error> {
+        Ok(match event {
+            "ping" => GithubEvent::Ping,
+            "push" => GithubEvent::Push(serde_json::from_slice(body)?),
+            "pull_request" => GithubEvent::PullRequest(serde_json::from_slice(body)?),
+            "workflow_run" => GithubEvent::WorkflowRun(serde_json::from_slice(body)?),
+            "check_run" => GithubEvent::CheckRun(serde_json::from_slice(body)?),
+            "check_suite" => GithubEvent::CheckSuite(serde_json::from_slice(body)?),
+            other => GithubEvent::Other(other.to_string()),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            GithubEvent::Ping => "ping",
+            GithubEvent::Push(_) => "push",
+            GithubEvent::PullRequest(_) => "pull_request",
+            GithubEvent::WorkflowRun(_) => "workflow_run",
+            GithubEvent::CheckRun(_) => "check_run",
+            GithubEvent::CheckSuite(_) => "check_suite",
+            GithubEvent::Other(name) => name,
+        }
+    }
+
+    pub fn repository(&self) -> Option<&Repository> {
+        match self {
+            GithubEvent::Push(event) => Some(&event.repository),
+            GithubEvent::PullRequest(event) => Some(&event.repository),
+            GithubEvent::WorkflowRun(event) => Some(&event.repository),
+            GithubEvent::CheckRun(event) => Some(&event.repository),
+            GithubEvent::CheckSuite(event) => Some(&event.repository),
+            GithubEvent::Ping | GithubEvent::Other(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum WebhookAction {
+    ScanStarted,
+    GateEvaluationStarted,
+    BuildRecorded,
+    Ignored,
+    /// The delivery ID was already received; nothing was done.
+    Duplicate,
+}
+
+/// What Sentinai did with a delivery, returned to the sender.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookOutcome {
+    pub event: String,
+    pub action: WebhookAction,
+    pub project_ids: Vec<Uuid>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_push_event() {
+        let body = br#"{
+            "ref": "refs/heads/main",
+            "before": "0000000000000000000000000000000000000000",
+            "after": "3f1c2b7e9d0a4c5b6e7f8091a2b3c4d5e6f70819",
+            "repository": {
+                "full_name": "org/repo",
+                "html_url": "https://github.com/org/repo",
+                "default_branch": "main"
+            },
+            "commits": []
+        }"#;
+        let GithubEvent::Push(push) = GithubEvent::parse("push", body).unwrap() else {
+            panic!("expected a push event");
+        };
+        assert_eq!(push.git_ref, "refs/heads/main");
+        assert!(!push.deleted);
+        assert_eq!(push.repository.default_branch.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_parse_workflow_run_event() {
+        let body = br#"{
+            "action": "completed",
+            "workflow_run": {
+                "id": 30433642,
+                "name": "CI",
+                "head_branch": "main",
+                "head_sha": "acb5820ced9479c074f688cc328bf03f341a511d",
+                "status": "completed",
+                "conclusion": "failure",
+                "html_url": "https://github.com/org/repo/actions/runs/30433642",
+                "run_started_at": "2026-02-22T10:20:00Z",
+                "updated_at": "2026-02-22T10:24:10Z"
+            },
+            "repository": { "full_name": "org/repo", "html_url": "https://github.com/org/repo" }
+        }"#;
+        let event = GithubEvent::parse("workflow_run", body).unwrap();
+        assert_eq!(event.name(), "workflow_run");
+        assert_eq!(event.repository().unwrap().full_name, "org/repo");
+        let GithubEvent::WorkflowRun(run) = event else {
+            panic!("expected a workflow_run event");
+        };
+        assert_eq!(run.workflow_run.conclusion.as_deref(), Some("failure"));
+        assert_eq!(run.source, "workflow_run");
+    }
+
+    #[test]
+    fn test_parse_check_run_event() {
+        let body = br#"{
+            "action": "created",
+            "check_run": {
+                "id": 4,
+                "name": "clippy",
+                "head_sha": "ce587453ced02b1526dfb4cb910479d431683101",
+                "status": "in_progress",
+                "conclusion": null,
+                "html_url": "https://github.com/org/repo/runs/4",
+                "started_at": "2026-02-22T10:20:00Z",
+                "completed_at": null,
+                "check_suite": { "id": 5, "head_branch": "feature" }
+            },
+            "repository": { "full_name": "org/repo" }
+        }"#;
+        let GithubEvent::CheckRun(event) = GithubEvent::parse("check_run", body).unwrap() else {
+            panic!("expected a check_run event");
+        };
+        assert_eq!(event.check_run.name, "clippy");
+        assert_eq!(
+            event.check_run.check_suite.unwrap().head_branch.as_deref(),
+            Some("feature")
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_and_ignores_unknown_events() {
+        assert!(GithubEvent::parse("pull_request", br#"{"action": "opened"}"#).is_err());
+        assert!(matches!(
+            GithubEvent::parse("star", b"{}").unwrap(),
+            GithubEvent::Other(name) if name == "star"
+        ));
+    }
+}
@@ -0,0 +1,300 @@
+//! GitLab webhook payloads, normalized into the GitHub event types the
+//! webhook handlers dispatch on.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer};
+
+use crate::domain::github_event::{
+    GithubEvent, PullRequest, PullRequestEvent, PullRequestHead, PushEvent, Repository,
+    WorkflowRun, WorkflowRunEvent,
+};
+
+const NULL_SHA: &str = "0000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Project {
+    /// `group/project`, possibly with subgroups.
+    pub path_with_namespace: String,
+    pub web_url: String,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+}
+
+impl From<Project> for Repository {
+    fn from(project: Project) -> Self {
+        Repository {
+            full_name: project.path_with_namespace,
+            default_branch: project.default_branch,
+            html_url: Some(project.web_url),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushHook {
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    /// All zeros when the branch was deleted.
+    pub after: String,
+    pub project: Project,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LastCommit {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeRequestAttributes {
+    pub iid: i64,
+    /// `open`, `reopen`, `update`, `close`, `merge`, ...
+    #[serde(default)]
+    pub action: Option<String>,
+    /// The previous head commit, present when an update pushed new commits.
+    #[serde(default)]
+    pub oldrev: Option<String>,
+    pub last_commit: LastCommit,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeRequestHook {
+    pub object_attributes: MergeRequestAttributes,
+    pub project: Project,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PipelineAttributes {
+    pub id: i64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    pub sha: String,
+    /// `created`, `pending`, `running`, `success`, `failed`, `canceled`, ...
+    pub status: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default, deserialize_with = "gitlab_time")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "gitlab_time")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PipelineHook {
+    pub object_attributes: PipelineAttributes,
+    pub project: Project,
+}
+
+/// GitLab writes most webhook timestamps as `2016-08-12 15:23:28 UTC` rather
+/// than RFC 3339.
+fn gitlab_time<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(value) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    DateTime::parse_from_rfc3339(&value)
+        .or_else(|_| DateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S %z"))
+        .map(|time| time.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S UTC")
+                .map(|time| time.and_utc())
+        })
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("invalid GitLab timestamp: {}", value)))
+}
+
+/// GitLab's merge request actions, named after their GitHub equivalents.
+fn pull_request_action(attributes: &MergeRequestAttributes) -> String {
+    match attributes.action.as_deref() {
+        Some("open") => "opened".into(),
+        Some("reopen") => "reopened".into(),
+        Some("update") if attributes.oldrev.is_some() => "synchronize".into(),
+        Some("update") => "edited".into(),
+        Some("close") => "closed".into(),
+        Some("merge") => "merged".into(),
+        Some(other) => other.into(),
+        None => "unknown".into(),
+    }
+}
+
+/// Maps a pipeline status onto a GitHub run's `(status, conclusion)`.
+fn run_status(status: &str) -> (&'static str, Option<&'static str>) {
+    match status {
+        "success" => ("completed", Some("success")),
+        "failed" => ("completed", Some("failure")),
+        "canceled" => ("completed", Some("cancelled")),
+        "skipped" => ("completed", Some("skipped")),
+        "running" => ("in_progress", None),
+        _ => ("queued", None),
+    }
+}
+
+impl From<PipelineHook> for WorkflowRunEvent {
+    fn from(hook: PipelineHook) -> Self {
+        let pipeline = hook.object_attributes;
+        let (status, conclusion) = run_status(&pipeline.status);
+        let html_url = pipeline
+            .url
+            .unwrap_or_else(|| format!("{}/-/pipelines/{}", hook.project.web_url, pipeline.id));
+        WorkflowRunEvent {
+            action: status.into(),
+            workflow_run: WorkflowRun {
+                id: pipeline.id,
+                run_attempt: 1,
+                name: pipeline.name,
+                head_branch: pipeline.git_ref,
+                head_sha: pipeline.sha,
+                status: status.into(),
+                conclusion: conclusion.map(Into::into),
+                html_url,
+                run_started_at: pipeline.created_at,
+                updated_at: pipeline
+                    .finished_at
+                    .or(pipeline.created_at)
+                    .unwrap_or_else(Utc::now),
+            },
+            repository: hook.project.into(),
+            source: "gitlab_pipeline".into(),
+        }
+    }
+}
+
+/// Deserializes a delivery according to its `X-Gitlab-Event` header.
+pub fn parse(event: &str, body: &[u8]) -> Result<GithubEvent, serde_json::Error> {
+    Ok(match event {
+        "Push Hook" => {
+            let hook: PushHook = serde_json::from_slice(body)?;
+            GithubEvent::Push(PushEvent {
+                git_ref: hook.git_ref,
+                deleted: hook.after == NULL_SHA,
+                repository: hook.project.into(),
+            })
+        }
+        "Merge Request Hook" => {
+            let hook: MergeRequestHook = serde_json::from_slice(body)?;
+            GithubEvent::PullRequest(PullRequestEvent {
+                action: pull_request_action(&hook.object_attributes),
+                number: hook.object_attributes.iid,
+                pull_request: PullRequest {
+                    head: PullRequestHead {
+                        sha: hook.object_attributes.last_commit.id,
+                    },
+                },
+                repository: hook.project.into(),
+            })
+        }
+        "Pipeline Hook" => {
+            GithubEvent::WorkflowRun(serde_json::from_slice::<PipelineHook>(body)?.into())
+        }
+        other => GithubEvent::Other(other.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT: &str = r#"{
+        "id": 15,
+        "path_with_namespace": "group/sub/repo",
+        "web_url": "https://gitlab.example.com/group/sub/repo",
+        "default_branch": "main"
+    }"#;
+
+    #[test]
+    fn test_parse_push_hook() {
+        let body = format!(
+            r#"{{
+                "object_kind": "push",
+                "ref": "refs/heads/main",
+                "before": "95790bf891e76fee5e1747ab589903a6a1f80f22",
+                "after": "da1560886d4f094c3e6c9ef40349f7d38b5d27d7",
+                "project": {PROJECT}
+            }}"#
+        );
+        let GithubEvent::Push(push) = parse("Push Hook", body.as_bytes()).unwrap() else {
+            panic!("expected a push event");
+        };
+        assert_eq!(push.git_ref, "refs/heads/main");
+        assert!(!push.deleted);
+        assert_eq!(push.repository.full_name, "group/sub/repo");
+        assert_eq!(push.repository.default_branch.as_deref(), Some("main"));
+
+        let deleted = body.replace("da1560886d4f094c3e6c9ef40349f7d38b5d27d7", NULL_SHA);
+        let GithubEvent::Push(push) = parse("Push Hook", deleted.as_bytes()).unwrap() else {
+            panic!("expected a push event");
+        };
+        assert!(push.deleted);
+    }
+
+    #[test]
+    fn test_parse_merge_request_hook() {
+        let body = format!(
+            r#"{{
+                "object_kind": "merge_request",
+                "object_attributes": {{
+                    "iid": 7,
+                    "action": "update",
+                    "oldrev": "95790bf891e76fee5e1747ab589903a6a1f80f22",
+                    "last_commit": {{ "id": "da1560886d4f094c3e6c9ef40349f7d38b5d27d7" }}
+                }},
+                "project": {PROJECT}
+            }}"#
+        );
+        let GithubEvent::PullRequest(event) = parse("Merge Request Hook", body.as_bytes()).unwrap()
+        else {
+            panic!("expected a pull request event");
+        };
+        assert_eq!(event.action, "synchronize");
+        assert_eq!(event.number, 7);
+        assert_eq!(
+            event.pull_request.head.sha,
+            "da1560886d4f094c3e6c9ef40349f7d38b5d27d7"
+        );
+    }
+
+    #[test]
+    fn test_parse_pipeline_hook() {
+        let body = format!(
+            r#"{{
+                "object_kind": "pipeline",
+                "object_attributes": {{
+                    "id": 31,
+                    "ref": "main",
+                    "sha": "bcbb5ec396a2c0f828686f14fac9b80b780504f2",
+                    "status": "failed",
+                    "created_at": "2026-08-12 15:23:28 UTC",
+                    "finished_at": "2026-08-12 15:26:29 UTC"
+                }},
+                "project": {PROJECT}
+            }}"#
+        );
+        let GithubEvent::WorkflowRun(event) = parse("Pipeline Hook", body.as_bytes()).unwrap()
+        else {
+            panic!("expected a workflow_run event");
+        };
+        assert_eq!(event.action, "completed");
+        assert_eq!(event.source, "gitlab_pipeline");
+        let run = event.workflow_run;
+        assert_eq!(run.conclusion.as_deref(), Some("failure"));
+        assert_eq!(
+            run.html_url,
+            "https://gitlab.example.com/group/sub/repo/-/pipelines/31"
+        );
+        assert_eq!(
+            run.updated_at,
+            "2026-08-12T15:26:29Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_ignores_unknown_hooks() {
+        assert!(matches!(
+            parse("Tag Push Hook", b"{}").unwrap(),
+            GithubEvent::Other(name) if name == "Tag Push Hook"
+        ));
+    }
+}
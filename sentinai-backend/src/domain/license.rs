@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Licenses denied for projects that have not configured a policy.
+pub const DEFAULT_DENIED_LICENSES: [&str; 2] = ["AGPL-3.0-only", "AGPL-3.0-or-later"];
+
+/// Which dependency licenses a project may ship. An empty allow list permits
+/// every license that is not explicitly denied.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct LicensePolicy {
+    pub project_id: Uuid,
+    pub allowed: Vec<String>,
+    pub denied: Vec<String>,
+    pub flag_unknown: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl LicensePolicy {
+    pub fn default_for(project_id: Uuid) -> Self {
+        Self {
+            project_id,
+            allowed: Vec::new(),
+            denied: DEFAULT_DENIED_LICENSES
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            flag_unknown: true,
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateLicensePolicyRequest {
+    #[serde(default)]
+    pub allowed: Vec<String>,
+    #[serde(default)]
+    pub denied: Vec<String>,
+    #[serde(default = "default_flag_unknown")]
+    pub flag_unknown: bool,
+}
+
+fn default_flag_unknown() -> bool {
+    true
+}
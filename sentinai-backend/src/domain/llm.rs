@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+/// What an LLM was asked to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum LlmPurpose {
+    BuildSummary,
+    FindingExplanation,
+    FixDraft,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum LlmCallStatus {
+    /// Sent and awaiting an answer; its estimated tokens are reserved.
+    Pending,
+    Succeeded,
+    Failed,
+    /// Not sent because the project's token budget was spent.
+    Rejected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmMessage {
+    /// `system`, `user` or `assistant`.
+    pub role: String,
+    pub content: String,
+}
+
+impl LlmMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: "system".into(),
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: "user".into(),
+            content: content.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmRequest {
+    pub messages: Vec<LlmMessage>,
+    pub max_tokens: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmResponse {
+    pub content: String,
+    pub prompt_tokens: i32,
+    pub completion_tokens: i32,
+}
+
+/// A prompt sent to an LLM and what came back, kept for audit.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct LlmInteraction {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub purpose: LlmPurpose,
+    pub subject_id: Option<Uuid>,
+    pub provider: String,
+    pub model: String,
+    pub messages: Json<Vec<LlmMessage>>,
+    pub response: Option<String>,
+    pub status: LlmCallStatus,
+    pub error: Option<String>,
+    pub prompt_tokens: i32,
+    pub completion_tokens: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct NewLlmInteraction<'a> {
+    pub project_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub purpose: LlmPurpose,
+    pub subject_id: Option<Uuid>,
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub messages: &'a [LlmMessage],
+    pub response: Option<&'a str>,
+    pub status: LlmCallStatus,
+    pub error: Option<&'a str>,
+    pub prompt_tokens: i32,
+    pub completion_tokens: i32,
+}
+
+/// How many tokens a project may spend on LLM calls in any 24 hours.
+#[derive(Debug, Serialize)]
+pub struct LlmBudget {
+    pub project_id: Uuid,
+    pub daily_token_limit: i64,
+    /// Tokens spent in the last 24 hours.
+    pub used_tokens: i64,
+    pub remaining_tokens: i64,
+    /// False while the project uses the server-wide default.
+    pub custom: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateLlmBudgetRequest {
+    pub daily_token_limit: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LlmInteractionQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+/// An LLM's answer about a build or finding.
+#[derive(Debug, Serialize)]
+pub struct LlmAnswer {
+    pub interaction_id: Uuid,
+    pub purpose: LlmPurpose,
+    pub subject_id: Uuid,
+    pub content: String,
+    /// The unified diff in the answer, for fix drafts.
+    pub patch: Option<String>,
+    pub prompt_tokens: i32,
+    pub completion_tokens: i32,
+}
@@ -1,5 +1,21 @@
+pub mod bitbucket_event;
+pub mod build;
+pub mod build_log;
+pub mod build_retry;
+pub mod github_event;
+pub mod gitlab_event;
+pub mod license;
+pub mod llm;
 pub mod pipeline;
 pub mod project;
 pub mod realtime;
+pub mod remediation;
+pub mod sbom;
+pub mod scan_run;
+pub mod scan_schedule;
 pub mod security;
+pub mod security_gate;
+pub mod security_metrics;
+pub mod test_result;
 pub mod user;
+pub mod webhook_delivery;
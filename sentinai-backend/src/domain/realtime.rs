@@ -1,11 +1,16 @@
 use serde::Serialize;
 use uuid::Uuid;
 
+use crate::domain::build::{BuildStatus, FailureCategory};
+
 #[derive(Clone, Serialize, Debug)]
 #[serde(tag = "type", content = "payload")]
 pub enum RealtimeEvent {
     PipelineCreated(PipelineEvent),
     SecurityFindingCreated(SecurityEvent),
+    BuildUpdated(BuildEvent),
+    BuildLogAppended(BuildLogEvent),
+    BuildClassified(BuildFailureEvent),
 }
 
 #[derive(Clone, Serialize, Debug)]
@@ -21,3 +26,25 @@ pub struct SecurityEvent {
     pub severity: String,
     pub description: String,
 }
+
+#[derive(Clone, Serialize, Debug)]
+pub struct BuildEvent {
+    pub project_id: Uuid,
+    pub build_id: Uuid,
+    pub status: BuildStatus,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct BuildLogEvent {
+    pub project_id: Uuid,
+    pub build_id: Uuid,
+    pub total_lines: i32,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct BuildFailureEvent {
+    pub project_id: Uuid,
+    pub build_id: Uuid,
+    pub category: FailureCategory,
+    pub failing_tests: Vec<String>,
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::build::FailureCategory;
+use crate::domain::sbom::Ecosystem;
+
+fn default_dry_run() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DependencyBumpRequest {
+    /// Return the patch without creating a branch or pull request.
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+    /// Limit the upgrade to these findings; all open findings with fixed
+    /// versions are considered when empty.
+    #[serde(default)]
+    pub finding_ids: Vec<Uuid>,
+}
+
+/// One package moved to the smallest version that fixes its findings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackageUpgrade {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub finding_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedFinding {
+    pub finding_id: Uuid,
+    pub package: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDiff {
+    pub path: String,
+    pub diff: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullRequestRef {
+    pub number: i64,
+    pub url: String,
+    pub branch: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyBumpResult {
+    pub project_id: Uuid,
+    pub dry_run: bool,
+    pub base_branch: String,
+    pub base_sha: String,
+    pub upgrades: Vec<PackageUpgrade>,
+    pub skipped: Vec<SkippedFinding>,
+    pub files: Vec<FileDiff>,
+    pub warnings: Vec<String>,
+    /// Every file diff concatenated, ready for `git apply`.
+    pub patch: String,
+    /// The pull request opened for the upgrade; absent on dry runs.
+    pub pull_request: Option<PullRequestRef>,
+}
+
+/// A way of repairing a CI failure with a patch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixStrategy {
+    /// Check formatting with `cargo fmt` before building.
+    AddFmtStep,
+    /// Move GitHub Actions to their current major version.
+    BumpActions,
+    /// Raise `timeout-minutes` on the workflow's jobs.
+    RaiseTimeout,
+    /// Cache Cargo dependencies and build artifacts between runs.
+    AddCacheStep,
+    /// Keep a dependency below a release that broke the build.
+    PinDependency,
+}
+
+/// A file's contents before and after an automated edit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChange {
+    pub path: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// A fix computed from a repository snapshot, with each file it changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedFix {
+    pub strategy: FixStrategy,
+    pub title: String,
+    pub description: String,
+    pub files: Vec<FileChange>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BuildFixRequest {
+    /// Return the proposals without creating a branch or pull request.
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+    /// Only these strategies; those suited to the build's failure category
+    /// when empty.
+    #[serde(default)]
+    pub strategies: Vec<FixStrategy>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FixProposal {
+    pub strategy: FixStrategy,
+    pub title: String,
+    pub description: String,
+    pub files: Vec<FileDiff>,
+    /// The proposal's diffs, on top of the proposals listed before it.
+    pub patch: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildFixResult {
+    pub build_id: Uuid,
+    pub failure_category: FailureCategory,
+    pub dry_run: bool,
+    pub base_branch: String,
+    pub base_sha: String,
+    pub proposals: Vec<FixProposal>,
+    /// Every proposal combined, ready for `git apply`.
+    pub patch: String,
+    /// The pull request opened for the proposals; absent on dry runs.
+    pub pull_request: Option<PullRequestRef>,
+}
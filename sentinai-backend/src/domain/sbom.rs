@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    Pypi,
+}
+
+impl Ecosystem {
+    pub fn purl_type(self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "cargo",
+            Ecosystem::Npm => "npm",
+            Ecosystem::Pypi => "pypi",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageHash {
+    pub algorithm: HashAlgorithm,
+    /// Lowercase hex digest.
+    pub value: String,
+}
+
+/// A third-party package pinned by one of the project's lockfiles.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Package {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub version: String,
+    pub license: Option<String>,
+    pub hashes: Vec<PackageHash>,
+    /// Package URLs of the packages this one depends on.
+    pub dependencies: Vec<String>,
+    /// Lockfile the package was resolved from.
+    pub source_file: String,
+}
+
+impl Package {
+    pub fn purl(&self) -> String {
+        let name = match self.ecosystem {
+            Ecosystem::Npm => self.name.replace('@', "%40"),
+            Ecosystem::Pypi => self.name.to_lowercase().replace(['_', '.'], "-"),
+            Ecosystem::Cargo => self.name.clone(),
+        };
+        format!(
+            "pkg:{}/{}@{}",
+            self.ecosystem.purl_type(),
+            name,
+            self.version
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SbomContents {
+    pub packages: Vec<Package>,
+    /// Package URLs the project itself depends on directly.
+    pub root_dependencies: Vec<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Sbom {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub scan_run_id: Uuid,
+    pub commit_sha: Option<String>,
+    pub contents: Json<SbomContents>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum SbomFormat {
+    #[default]
+    #[serde(rename = "cyclonedx-json")]
+    CycloneDxJson,
+    #[serde(rename = "spdx-json")]
+    SpdxJson,
+}
+
+impl SbomFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            SbomFormat::CycloneDxJson => "application/vnd.cyclonedx+json",
+            SbomFormat::SpdxJson => "application/spdx+json",
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum ScanRunStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ScanRun {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub status: ScanRunStatus,
+    pub scanners: Vec<String>,
+    pub commit_sha: Option<String>,
+    pub critical_count: i32,
+    pub high_count: i32,
+    pub medium_count: i32,
+    pub low_count: i32,
+    pub new_count: i32,
+    pub existing_count: i32,
+    pub fixed_count: i32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+}
+
+/// Totals recorded on a scan run once all scanners have finished.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScanRunCounts {
+    pub critical: i32,
+    pub high: i32,
+    pub medium: i32,
+    pub low: i32,
+    pub new: i32,
+    pub existing: i32,
+    pub fixed: i32,
+}
+
+impl ScanRunCounts {
+    pub fn record_severity(&mut self, severity: &str) {
+        match severity {
+            "critical" => self.critical += 1,
+            "high" => self.high += 1,
+            "medium" => self.medium += 1,
+            _ => self.low += 1,
+        }
+    }
+}
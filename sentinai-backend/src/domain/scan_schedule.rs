@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A recurring security scan for a project, run either on a cron expression
+/// or every `interval_seconds`.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ScanSchedule {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub cron_expression: Option<String>,
+    pub interval_seconds: Option<i32>,
+    pub enabled: bool,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_scan_run_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateScanScheduleRequest {
+    pub cron_expression: Option<String>,
+    pub interval_seconds: Option<i32>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
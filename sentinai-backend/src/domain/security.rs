@@ -1,13 +1,219 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+use crate::domain::scan_run::ScanRun;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct SecurityFinding {
     pub id: Uuid,
     pub project_id: Uuid,
+    pub scanner: String,
+    pub rule_id: String,
     pub severity: String,
     pub description: String,
-    pub resolved: bool,
+    pub location: Option<String>,
+    /// Where in the file the finding was last reported, for display. Unlike
+    /// `location` it is not part of the fingerprint, so edits that move the
+    /// code do not reopen the finding.
+    pub start_line: Option<i32>,
+    pub start_column: Option<i32>,
+    pub package: Option<String>,
+    /// How to fix the finding, when the scanner knows.
+    pub remediation: Option<String>,
+    /// Versions of `package` that fix the finding, when known.
+    pub fixed_versions: Vec<String>,
+    pub fingerprint: String,
+    pub status: FindingStatus,
+    pub status_expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub fixed_at: Option<DateTime<Utc>>,
+    pub scan_run_id: Option<Uuid>,
+    pub last_scan_run_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum FindingStatus {
+    Open,
+    Acknowledged,
+    Resolved,
+    FalsePositive,
+    AcceptedRisk,
+}
+
+impl FindingStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FindingStatus::Open => "open",
+            FindingStatus::Acknowledged => "acknowledged",
+            FindingStatus::Resolved => "resolved",
+            FindingStatus::FalsePositive => "false_positive",
+            FindingStatus::AcceptedRisk => "accepted_risk",
+        }
+    }
+
+    /// Suppressed findings are closed by a human decision rather than a fix,
+    /// and are the only ones that may carry an expiry date.
+    pub fn is_suppression(self) -> bool {
+        matches!(
+            self,
+            FindingStatus::FalsePositive | FindingStatus::AcceptedRisk
+        )
+    }
+
+    pub fn is_closed(self) -> bool {
+        !matches!(self, FindingStatus::Open | FindingStatus::Acknowledged)
+    }
+
+    /// Open and acknowledged findings may move anywhere; closed findings can
+    /// only be reopened.
+    pub fn can_transition_to(self, next: FindingStatus) -> bool {
+        if self == next {
+            return false;
+        }
+        !self.is_closed() || next == FindingStatus::Open
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FindingEvent {
+    pub id: Uuid,
+    pub finding_id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub from_status: FindingStatus,
+    pub to_status: FindingStatus,
+    pub justification: String,
+    pub expires_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
+
+#[derive(Debug, Serialize)]
+pub struct FindingDetail {
+    #[serde(flatten)]
+    pub finding: SecurityFinding,
+    pub events: Vec<FindingEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateFindingStatusRequest {
+    pub status: FindingStatus,
+    pub justification: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A finding as reported by a scanner, before it is matched against the
+/// findings already stored for the project.
+#[derive(Debug, Clone)]
+pub struct NewFinding {
+    pub scanner: String,
+    pub rule_id: String,
+    pub severity: String,
+    pub description: String,
+    pub location: Option<String>,
+    pub start_line: Option<i32>,
+    pub start_column: Option<i32>,
+    pub package: Option<String>,
+    pub remediation: Option<String>,
+    pub fixed_versions: Vec<String>,
+    /// The identity an external tool gave the finding, such as a SARIF
+    /// partial fingerprint. Tells apart results the location alone can't.
+    pub partial_fingerprint: Option<String>,
+}
+
+impl NewFinding {
+    /// Stable identity of the finding across scans. Severity and description
+    /// are deliberately left out so that rewording a rule does not reopen it.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            Some(self.scanner.as_str()),
+            Some(self.rule_id.as_str()),
+            Some(self.location.as_deref().unwrap_or_default()),
+            Some(self.package.as_deref().unwrap_or_default()),
+            self.partial_fingerprint.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            hasher.update(part.as_bytes());
+            hasher.update([0x1f]);
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanSummary {
+    pub scan_run: ScanRun,
+    pub new: Vec<SecurityFinding>,
+    pub existing: Vec<SecurityFinding>,
+    pub fixed: Vec<SecurityFinding>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding() -> NewFinding {
+        NewFinding {
+            scanner: "dependency-audit".into(),
+            rule_id: "outdated-dependency".into(),
+            severity: "high".into(),
+            description: "Detected outdated OpenSSL dependency".into(),
+            location: Some("Cargo.lock".into()),
+            start_line: None,
+            start_column: None,
+            package: Some("openssl".into()),
+            remediation: None,
+            fixed_versions: Vec::new(),
+            partial_fingerprint: None,
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_severity_and_description() {
+        let a = finding();
+        let mut b = finding();
+        b.severity = "critical".into();
+        b.description = "Reworded".into();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_eq!(a.fingerprint().len(), 64);
+    }
+
+    #[test]
+    fn test_fingerprint_distinguishes_identity_fields() {
+        let a = finding();
+        let mut b = finding();
+        b.package = Some("openssl-sys".into());
+        assert_ne!(a.fingerprint(), b.fingerprint());
+
+        let mut c = finding();
+        c.location = None;
+        c.package = Some("Cargo.lockopenssl".into());
+        assert_ne!(a.fingerprint(), c.fingerprint());
+
+        let mut d = finding();
+        d.partial_fingerprint = Some("primaryLocationLineHash=3f2a".into());
+        assert_ne!(a.fingerprint(), d.fingerprint());
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        use FindingStatus::*;
+
+        assert!(Open.can_transition_to(Acknowledged));
+        assert!(Open.can_transition_to(FalsePositive));
+        assert!(Acknowledged.can_transition_to(Resolved));
+        assert!(Acknowledged.can_transition_to(Open));
+        assert!(Resolved.can_transition_to(Open));
+        assert!(AcceptedRisk.can_transition_to(Open));
+
+        assert!(!Open.can_transition_to(Open));
+        assert!(!Resolved.can_transition_to(AcceptedRisk));
+        assert!(!FalsePositive.can_transition_to(Acknowledged));
+    }
+}
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::domain::security::SecurityFinding;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Findings store severity as free text; anything unrecognized ranks as
+    /// low, matching how scan runs count it.
+    pub fn from_finding(severity: &str) -> Severity {
+        match severity {
+            "critical" => Severity::Critical,
+            "high" => Severity::High,
+            "medium" => Severity::Medium,
+            _ => Severity::Low,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+/// A condition every open finding of a project must satisfy for its gate to
+/// pass.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GateRule {
+    /// No open findings at or above `min_severity`.
+    NoOpenFindings { min_severity: Severity },
+    /// No open findings at or above `min_severity` first reported more than
+    /// `max_age_days` ago.
+    MaxFindingAge {
+        min_severity: Severity,
+        max_age_days: u32,
+    },
+    /// No open findings from the license scanner for denied licenses.
+    NoDeniedLicenses,
+}
+
+impl GateRule {
+    pub fn describe(&self) -> String {
+        let at_least = |severity: &Severity| match severity {
+            Severity::Critical => "critical".to_string(),
+            other => format!("{} or higher", other.as_str()),
+        };
+        match self {
+            GateRule::NoOpenFindings { min_severity } => {
+                format!("No open {} findings", at_least(min_severity))
+            }
+            GateRule::MaxFindingAge {
+                min_severity,
+                max_age_days,
+            } => format!(
+                "No open {} findings older than {} days",
+                at_least(min_severity),
+                max_age_days
+            ),
+            GateRule::NoDeniedLicenses => "No dependencies with denied licenses".to_string(),
+        }
+    }
+}
+
+/// Rules applied when neither the project nor its organization defines a
+/// policy.
+pub fn default_gate_rules() -> Vec<GateRule> {
+    vec![
+        GateRule::NoOpenFindings {
+            min_severity: Severity::Critical,
+        },
+        GateRule::MaxFindingAge {
+            min_severity: Severity::High,
+            max_age_days: 14,
+        },
+        GateRule::NoDeniedLicenses,
+    ]
+}
+
+/// Gate rules stored either for a single project or, with `project_id` unset,
+/// for every project owned by `user_id`.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct GatePolicy {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub rules: Json<Vec<GateRule>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateGatePolicyRequest {
+    pub rules: Vec<GateRule>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GatePolicyScope {
+    Project,
+    Organization,
+    Default,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GateRuleResult {
+    pub rule: GateRule,
+    pub description: String,
+    pub passed: bool,
+    pub violations: Vec<SecurityFinding>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GateEvaluation {
+    pub project_id: Uuid,
+    pub passed: bool,
+    pub policy_scope: GatePolicyScope,
+    pub rules: Vec<GateRuleResult>,
+    pub evaluated_at: DateTime<Utc>,
+}
@@ -0,0 +1,67 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::scan_run::ScanRunStatus;
+
+/// Findings open at the end of a UTC day, by severity.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct OpenFindingsPoint {
+    pub date: NaiveDate,
+    pub critical: i64,
+    pub high: i64,
+    pub medium: i64,
+    pub low: i64,
+}
+
+/// Mean time from first report to resolution for findings of one severity.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct RemediationTime {
+    pub severity: String,
+    pub resolved_count: i64,
+    pub mean_seconds: f64,
+}
+
+/// Currently open findings bucketed by how long ago they were first reported.
+#[derive(Debug, Clone, Default, Serialize, sqlx::FromRow)]
+pub struct AgeDistribution {
+    pub under_7_days: i64,
+    pub days_7_to_30: i64,
+    pub days_30_to_90: i64,
+    pub over_90_days: i64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ScanRunTrend {
+    pub scan_run_id: Uuid,
+    pub project_id: Uuid,
+    pub status: ScanRunStatus,
+    pub new_count: i32,
+    pub fixed_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Security trends for one project or, when `project_id` is absent, for every
+/// project the user owns.
+#[derive(Debug, Serialize)]
+pub struct SecurityMetrics {
+    pub project_id: Option<Uuid>,
+    pub project_count: usize,
+    pub days: i32,
+    pub open_over_time: Vec<OpenFindingsPoint>,
+    /// Mean time to remediate findings resolved within the window.
+    pub mean_time_to_remediate_seconds: Option<f64>,
+    pub mean_time_to_remediate_by_severity: Vec<RemediationTime>,
+    pub age_distribution: AgeDistribution,
+    pub scan_runs: Vec<ScanRunTrend>,
+}
+
+fn default_days() -> i32 {
+    30
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SecurityMetricsQuery {
+    #[serde(default = "default_days")]
+    pub days: i32,
+}
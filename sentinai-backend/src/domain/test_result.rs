@@ -0,0 +1,142 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::remediation::{FileDiff, PullRequestRef};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// How a test report is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestReportFormat {
+    /// JUnit XML, as written by most test runners and `cargo nextest`.
+    Junit,
+    /// libtest JSON lines from `cargo test -- -Z unstable-options --format json`.
+    Cargo,
+    /// libtest JSON lines from `cargo nextest run --message-format libtest-json`.
+    Nextest,
+}
+
+/// One test's result in a report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedTest {
+    /// `suite::name` for JUnit, the test path for libtest.
+    pub test_id: String,
+    pub file: Option<String>,
+    pub outcome: TestOutcome,
+    pub duration_ms: Option<i32>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TestReportQuery {
+    /// Detected from the report when absent.
+    pub format: Option<TestReportFormat>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestIngestResult {
+    pub build_id: Uuid,
+    pub format: TestReportFormat,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+/// A test's result in a build, with the commit and branch the build ran on.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TestRun {
+    pub test_id: String,
+    pub file: Option<String>,
+    pub outcome: TestOutcome,
+    pub message: Option<String>,
+    pub build_id: Uuid,
+    pub head_sha: Option<String>,
+    pub head_branch: Option<String>,
+    pub ran_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlakyTest {
+    pub test_id: String,
+    pub file: Option<String>,
+    pub runs: usize,
+    pub passes: usize,
+    pub failures: usize,
+    /// Times the outcome changed between consecutive runs on a branch.
+    pub flips: usize,
+    /// Commits the test both passed and failed on.
+    pub mixed_commits: usize,
+    /// Between 0 and 1.
+    pub score: f64,
+    pub last_failed_at: Option<DateTime<Utc>>,
+    pub last_failed_build_id: Option<Uuid>,
+    pub last_message: Option<String>,
+}
+
+fn default_days() -> i32 {
+    14
+}
+
+fn default_min_score() -> f64 {
+    0.3
+}
+
+fn default_limit() -> usize {
+    50
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FlakyTestQuery {
+    /// Only builds of this branch.
+    pub branch: Option<String>,
+    #[serde(default = "default_days")]
+    pub days: i32,
+    #[serde(default = "default_min_score")]
+    pub min_score: f64,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuarantineRequest {
+    /// Return the patch without creating a branch or pull request.
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+    /// Only these tests; every currently flaky test when empty.
+    #[serde(default)]
+    pub test_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarantinedTest {
+    pub test_id: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuarantineResult {
+    pub project_id: Uuid,
+    pub dry_run: bool,
+    pub base_branch: String,
+    pub base_sha: String,
+    pub quarantined: Vec<QuarantinedTest>,
+    /// Tests whose definition could not be found or is already skipped.
+    pub unresolved: Vec<String>,
+    pub files: Vec<FileDiff>,
+    pub patch: String,
+    pub pull_request: Option<PullRequestRef>,
+}
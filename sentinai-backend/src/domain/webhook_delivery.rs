@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::domain::github_event::{WebhookAction, WebhookOutcome};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Received,
+    Processed,
+    Failed,
+}
+
+/// A verified webhook delivery, stored as received so it can be replayed.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub provider: String,
+    /// The sender's delivery ID, e.g. GitHub's `X-GitHub-Delivery`.
+    pub delivery_id: String,
+    pub event: String,
+    /// `owner/repo` of the repository the delivery is about.
+    pub repository: Option<String>,
+    #[serde(skip_serializing)]
+    pub body: Vec<u8>,
+    pub status: DeliveryStatus,
+    pub action: Option<WebhookAction>,
+    pub error: Option<String>,
+    pub replay_count: i32,
+    pub received_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewWebhookDelivery {
+    pub provider: String,
+    pub delivery_id: String,
+    pub event: String,
+    pub repository: Option<String>,
+    /// Request headers without secrets or signatures. They are stored for
+    /// debugging but never returned by the API.
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// The result of replaying a delivery: the delivery as recorded afterwards,
+/// and what was done with it unless processing failed.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookReplay {
+    pub delivery: WebhookDelivery,
+    pub outcome: Option<WebhookOutcome>,
+}
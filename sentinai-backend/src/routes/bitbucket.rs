@@ -0,0 +1,69 @@
+use axum::{body::Bytes, extract::State, http::HeaderMap, routing::post, Json, Router};
+use serde::Deserialize;
+
+use crate::domain::github_event::WebhookOutcome;
+use crate::domain::webhook_delivery::NewWebhookDelivery;
+use crate::error::AppError;
+use crate::routes::github::{delivery_headers, required_header};
+use crate::services::{github_webhook_service, webhook};
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+struct DeliveryRepository {
+    full_name: String,
+}
+
+/// Just enough of a delivery to pick the secret it should be signed with.
+#[derive(Deserialize)]
+struct DeliveryTarget {
+    repository: Option<DeliveryRepository>,
+}
+
+async fn bitbucket_webhook_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<WebhookOutcome>, AppError> {
+    let repository = serde_json::from_slice::<DeliveryTarget>(&body)
+        .ok()
+        .and_then(|target| target.repository)
+        .map(|repository| repository.full_name);
+    let signature = headers
+        .get("x-hub-signature")
+        .and_then(|value| value.to_str().ok());
+
+    let Some(secret) = state
+        .config
+        .bitbucket_webhook_secret_for(repository.as_deref())
+    else {
+        tracing::warn!(target: "webhook", ?repository, "No Bitbucket webhook secret configured");
+        return Err(AppError::AuthError("Invalid webhook signature".into()));
+    };
+    if !webhook::verify_signature(secret, &body, signature) {
+        tracing::warn!(target: "webhook", ?repository, "Bitbucket webhook signature check failed");
+        return Err(AppError::AuthError("Invalid webhook signature".into()));
+    }
+
+    let delivery = NewWebhookDelivery {
+        provider: github_webhook_service::BITBUCKET.into(),
+        delivery_id: required_header(&headers, "x-request-uuid")?,
+        event: required_header(&headers, "x-event-key")?,
+        repository,
+        headers: delivery_headers(&headers),
+        body: body.to_vec(),
+    };
+    tracing::info!(
+        repository = ?delivery.repository,
+        event = %delivery.event,
+        delivery_id = %delivery.delivery_id,
+        "Received Bitbucket webhook"
+    );
+
+    let outcome = github_webhook_service::receive(&state, delivery).await?;
+
+    Ok(Json(outcome))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/webhooks/bitbucket", post(bitbucket_webhook_handler))
+}
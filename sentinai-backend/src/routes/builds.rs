@@ -0,0 +1,330 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+    Json, Router,
+};
+use std::convert::Infallible;
+use tokio_stream::{Stream, StreamExt};
+use uuid::Uuid;
+
+use crate::db::project_repo;
+use crate::domain::build::{Build, BuildListQuery, NewBuild, UpdateBuildRequest};
+use crate::domain::build_log::{
+    BuildLogPage, BuildLogQuery, LogAppendResult, LogSearchHit, LogSearchQuery, LogStreamEvent,
+};
+use crate::domain::build_retry::{BuildDetail, BuildRetryPolicy, UpdateBuildRetryPolicyRequest};
+use crate::domain::remediation::{BuildFixRequest, BuildFixResult};
+use crate::domain::test_result::{TestIngestResult, TestReportQuery};
+use crate::error::AppError;
+use crate::routes::auth::AuthedUser;
+use crate::services::{
+    build_failure_service, build_fix_service, build_log_service, build_retry_service,
+    build_service, test_result_service,
+};
+use crate::state::AppState;
+
+async fn list_builds(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<BuildListQuery>,
+) -> Result<Json<Vec<Build>>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let builds = build_service::list_builds(&state, project_id, query.status, query.limit).await?;
+
+    Ok(Json(builds))
+}
+
+async fn create_build(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Json(payload): Json<NewBuild>,
+) -> Result<Json<Build>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let build = build_service::create_build(&state, project_id, &payload).await?;
+
+    Ok(Json(build))
+}
+
+async fn get_build(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+) -> Result<Json<BuildDetail>, AppError> {
+    let build = build_service::get_build_detail(&state, user.id, build_id).await?;
+    Ok(Json(build))
+}
+
+async fn update_build(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+    Json(payload): Json<UpdateBuildRequest>,
+) -> Result<Json<Build>, AppError> {
+    let build = build_service::update_build_status(
+        &state,
+        user.id,
+        build_id,
+        payload.status,
+        payload.conclusion.as_deref(),
+    )
+    .await?;
+    Ok(Json(build))
+}
+
+async fn classify_build(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+) -> Result<Json<Build>, AppError> {
+    let build = build_service::get_build(&state, user.id, build_id).await?;
+    let build = build_failure_service::classify_build(&state, build.id).await?;
+    Ok(Json(build))
+}
+
+async fn propose_fixes(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+    Json(payload): Json<BuildFixRequest>,
+) -> Result<Json<BuildFixResult>, AppError> {
+    let result = build_fix_service::propose_fixes(&state, user.id, build_id, payload).await?;
+    Ok(Json(result))
+}
+
+async fn append_logs(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+    body: String,
+) -> Result<Json<LogAppendResult>, AppError> {
+    let result = build_log_service::append_logs(&state, user.id, build_id, &body).await?;
+    Ok(Json(result))
+}
+
+async fn get_logs(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+    Query(query): Query<BuildLogQuery>,
+) -> Result<Json<BuildLogPage>, AppError> {
+    let page =
+        build_log_service::get_log_lines(&state, user.id, build_id, query.from, query.to).await?;
+    Ok(Json(page))
+}
+
+/// Tails a build's log. Each line is a `line` event whose ID is its line
+/// number, so a reconnecting client resumes after the last line it saw. A
+/// `reset` event with ID 0 means the log was replaced by its archive and
+/// starts over.
+async fn stream_logs(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    let after = match headers.get("last-event-id") {
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(|value| value.trim().parse::<i32>().ok())
+            .ok_or_else(|| AppError::BadRequest("Last-Event-ID must be a line number".into()))?,
+        None => 0,
+    };
+
+    let events = build_log_service::tail_logs(&state, user.id, build_id, after).await?;
+    let stream = events.map(|event| {
+        Ok(match event {
+            LogStreamEvent::Line(line) => Event::default()
+                .id(line.number.to_string())
+                .event("line")
+                .data(line.text),
+            LogStreamEvent::Reset => Event::default().id("0").event("reset").data(""),
+            LogStreamEvent::Finished(status) => Event::default()
+                .event("finished")
+                .data(serde_json::json!({ "status": status }).to_string()),
+        })
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new()))
+}
+
+async fn fetch_log_archive(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+) -> Result<Json<LogAppendResult>, AppError> {
+    let result = build_log_service::fetch_log_archive(&state, user.id, build_id).await?;
+    Ok(Json(result))
+}
+
+async fn ingest_test_report(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+    Query(query): Query<TestReportQuery>,
+    body: String,
+) -> Result<Json<TestIngestResult>, AppError> {
+    let result =
+        test_result_service::ingest_report(&state, user.id, build_id, &body, query.format).await?;
+    Ok(Json(result))
+}
+
+async fn search_logs(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<LogSearchQuery>,
+) -> Result<Json<Vec<LogSearchHit>>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let hits = build_log_service::search_logs(&state, project_id, &query.q, query.limit).await?;
+
+    Ok(Json(hits))
+}
+
+async fn get_retry_policy(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<BuildRetryPolicy>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let policy = build_retry_service::get_policy(&state, project_id).await?;
+
+    Ok(Json(policy))
+}
+
+async fn update_retry_policy(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Json(payload): Json<UpdateBuildRetryPolicyRequest>,
+) -> Result<Json<BuildRetryPolicy>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let policy = build_retry_service::update_policy(&state, project_id, payload).await?;
+
+    Ok(Json(policy))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/builds", get(list_builds).post(create_build))
+        .route("/projects/:id/builds/logs/search", get(search_logs))
+        .route(
+            "/projects/:id/builds/retry-policy",
+            get(get_retry_policy).put(update_retry_policy),
+        )
+        .route("/builds/:id", get(get_build).patch(update_build))
+        .route("/builds/:id/classify", post(classify_build))
+        .route("/builds/:id/fixes", post(propose_fixes))
+        .route("/builds/:id/logs", get(get_logs).post(append_logs))
+        .route("/builds/:id/logs/archive", post(fetch_log_archive))
+        .route("/builds/:id/logs/stream", get(stream_logs))
+        .route("/builds/:id/tests", post(ingest_test_report))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Request, StatusCode},
+    };
+    use sqlx::postgres::PgPoolOptions;
+    use tower::ServiceExt;
+
+    use crate::app;
+    use crate::config::Config;
+    use crate::db::{build_repo, project_repo, user_repo};
+    use crate::domain::build::{BuildStatus, NewBuild};
+    use crate::utils::jwt;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_stream_logs_resumes_and_closes() {
+        dotenvy::dotenv().ok();
+        let config = Config::from_env().expect("Config should load from env");
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&config.database_url)
+            .await
+            .unwrap();
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&pool, github_id, "test_stream_user", None)
+            .await
+            .unwrap();
+        let proj = project_repo::create_project(&pool, user.id, "test_stream_proj", "https://url")
+            .await
+            .unwrap();
+        let build = build_repo::create_build(
+            &pool,
+            proj.id,
+            &NewBuild {
+                pipeline_id: None,
+                name: Some("ci".into()),
+                head_sha: None,
+                head_branch: None,
+            },
+        )
+        .await
+        .unwrap();
+        let token = jwt::create_token(user.id, &config.jwt_secret).unwrap();
+        let app = app::create_app(config)
+            .await
+            .expect("App should be created");
+
+        let append = Request::builder()
+            .method("POST")
+            .uri(format!("/builds/{}/logs", build.id))
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::from("compiling\ntesting\ndone\n"))
+            .unwrap();
+        let response = app.clone().oneshot(append).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        build_repo::update_build_status(&pool, build.id, BuildStatus::Success, None)
+            .await
+            .unwrap();
+
+        // The build has finished, so the stream sends what is left and ends.
+        let stream = Request::builder()
+            .uri(format!("/builds/{}/logs/stream", build.id))
+            .header("authorization", format!("Bearer {}", token))
+            .header("last-event-id", "1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(stream).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(!body.contains("compiling"));
+        assert!(body.contains("data: testing\n"));
+        assert!(body.contains("id: 2\n"));
+        assert!(body.contains("id: 3\n"));
+        assert!(body.contains("event: finished\ndata: {\"status\":\"success\"}"));
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -1,18 +1,199 @@
-use axum::{routing::post, Json, Router};
-use serde_json::Value;
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
 
+use crate::domain::github_event::WebhookOutcome;
+use crate::domain::webhook_delivery::{NewWebhookDelivery, WebhookReplay};
+use crate::error::AppError;
+use crate::routes::auth::AuthedUser;
+use crate::services::{github_webhook_service, webhook};
 use crate::state::AppState;
 
-async fn github_webhook_handler(Json(payload): Json<Value>) -> Json<&'static str> {
-    tracing::info!("Received GitHub webhook payload");
+#[derive(Deserialize)]
+struct DeliveryRepository {
+    full_name: String,
+}
+
+/// Just enough of a delivery to pick the secret it should be signed with.
+#[derive(Deserialize)]
+struct DeliveryTarget {
+    repository: Option<DeliveryRepository>,
+}
+
+/// Headers carrying webhook secrets or credentials, which are never stored.
+const SECRET_HEADERS: [&str; 5] = [
+    "authorization",
+    "cookie",
+    "x-gitlab-token",
+    "x-hub-signature",
+    "x-hub-signature-256",
+];
+
+/// The request headers with readable values, keyed by lowercase name,
+/// leaving out secrets and signatures.
+pub(crate) fn delivery_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| !SECRET_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+/// The value of a header the sender must include.
+pub(crate) fn required_header(headers: &HeaderMap, name: &str) -> Result<String, AppError> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .ok_or_else(|| AppError::BadRequest(format!("Missing {} header", name)))
+}
+
+async fn github_webhook_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<WebhookOutcome>, AppError> {
+    let repository = serde_json::from_slice::<DeliveryTarget>(&body)
+        .ok()
+        .and_then(|target| target.repository)
+        .map(|repository| repository.full_name);
+    let signature = headers
+        .get("x-hub-signature-256")
+        .and_then(|value| value.to_str().ok());
+
+    let Some(secret) = state
+        .config
+        .github_webhook_secret_for(repository.as_deref())
+    else {
+        tracing::warn!(target: "webhook", ?repository, "No webhook secret configured");
+        return Err(AppError::AuthError("Invalid webhook signature".into()));
+    };
+    if !webhook::verify_signature(secret, &body, signature) {
+        tracing::warn!(target: "webhook", ?repository, "Webhook signature verification failed");
+        return Err(AppError::AuthError("Invalid webhook signature".into()));
+    }
+
+    let delivery = NewWebhookDelivery {
+        provider: github_webhook_service::GITHUB.into(),
+        delivery_id: required_header(&headers, "x-github-delivery")?,
+        event: required_header(&headers, "x-github-event")?,
+        repository,
+        headers: delivery_headers(&headers),
+        body: body.to_vec(),
+    };
+    tracing::info!(
+        repository = ?delivery.repository,
+        event = %delivery.event,
+        delivery_id = %delivery.delivery_id,
+        "Received GitHub webhook"
+    );
+
+    let outcome = github_webhook_service::receive(&state, delivery).await?;
+
+    Ok(Json(outcome))
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplayQuery {
+    #[serde(default = "ReplayQuery::default_provider")]
+    provider: String,
+}
 
-    if payload.get("action").is_none() {
-        tracing::error!(target: "webhook", "signature verification failed");
+impl ReplayQuery {
+    fn default_provider() -> String {
+        github_webhook_service::GITHUB.into()
     }
+}
 
-    Json("Webhook received")
+async fn replay_delivery(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(delivery_id): Path<String>,
+    Query(query): Query<ReplayQuery>,
+) -> Result<Json<WebhookReplay>, AppError> {
+    let replay =
+        github_webhook_service::replay(&state, user.id, &query.provider, &delivery_id).await?;
+    Ok(Json(replay))
 }
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/webhooks/github", post(github_webhook_handler))
+    Router::new()
+        .route("/webhooks/github", post(github_webhook_handler))
+        .route("/webhooks/deliveries/:id/replay", post(replay_delivery))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{HeaderMap, HeaderValue, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use super::delivery_headers;
+    use crate::app;
+    use crate::config::Config;
+
+    #[test]
+    fn test_delivery_headers_leave_out_secrets() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-gitlab-event", HeaderValue::from_static("Push Hook"));
+        headers.insert("x-gitlab-token", HeaderValue::from_static("shared-secret"));
+        headers.insert(
+            "x-hub-signature-256",
+            HeaderValue::from_static("sha256=abc"),
+        );
+        headers.insert("Authorization", HeaderValue::from_static("Bearer token"));
+
+        let stored = delivery_headers(&headers);
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored["x-gitlab-event"], "Push Hook");
+    }
+
+    const BODY: &str =
+        r#"{"zen":"Keep it logically awesome.","repository":{"full_name":"org/repo"}}"#;
+
+    fn delivery(signature: &str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/webhooks/github")
+            .header("content-type", "application/json")
+            .header("x-github-event", "ping")
+            .header("x-github-delivery", uuid::Uuid::new_v4().to_string())
+            .header("x-hub-signature-256", signature)
+            .body(Body::from(BODY))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_github_webhook_signature() {
+        dotenvy::dotenv().ok();
+        let mut config = Config::from_env().expect("Config should load from env");
+        config.github_webhook_secret = Some("global-secret".into());
+        config
+            .github_webhook_secrets
+            .insert("org/repo".into(), "webhook-secret".into());
+        let app = app::create_app(config)
+            .await
+            .expect("App should be created");
+
+        let signed = "sha256=34b17eb208bcecda9634610d6c4ad29a6b40d46ed11211512c8d0bff87b66750";
+        let response = app.clone().oneshot(delivery(signed)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Signed with the global secret, which this repository overrides.
+        let global = "sha256=f2334b219b485fa7c13510a1f5c34d876790962007e3aa908e9e635df3586d6c";
+        let response = app.clone().oneshot(delivery(global)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.oneshot(delivery("sha256=invalidhash")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
 }
@@ -0,0 +1,119 @@
+use axum::{body::Bytes, extract::State, http::HeaderMap, routing::post, Json, Router};
+use serde::Deserialize;
+
+use crate::domain::github_event::WebhookOutcome;
+use crate::domain::webhook_delivery::NewWebhookDelivery;
+use crate::error::AppError;
+use crate::routes::github::{delivery_headers, required_header};
+use crate::services::{github_webhook_service, webhook};
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+struct DeliveryProject {
+    path_with_namespace: String,
+}
+
+/// Just enough of a delivery to pick the token it should carry.
+#[derive(Deserialize)]
+struct DeliveryTarget {
+    project: Option<DeliveryProject>,
+}
+
+async fn gitlab_webhook_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<WebhookOutcome>, AppError> {
+    let repository = serde_json::from_slice::<DeliveryTarget>(&body)
+        .ok()
+        .and_then(|target| target.project)
+        .map(|project| project.path_with_namespace);
+    let token = headers
+        .get("x-gitlab-token")
+        .and_then(|value| value.to_str().ok());
+
+    let Some(expected) = state.config.gitlab_webhook_token_for(repository.as_deref()) else {
+        tracing::warn!(target: "webhook", ?repository, "No GitLab webhook token configured");
+        return Err(AppError::AuthError("Invalid webhook token".into()));
+    };
+    if !webhook::verify_token(expected, token) {
+        tracing::warn!(target: "webhook", ?repository, "GitLab webhook token verification failed");
+        return Err(AppError::AuthError("Invalid webhook token".into()));
+    }
+
+    // GitLab keeps the Idempotency-Key across retries of the same event.
+    let delivery_id = required_header(&headers, "idempotency-key")
+        .or_else(|_| required_header(&headers, "x-gitlab-event-uuid"))?;
+    let delivery = NewWebhookDelivery {
+        provider: github_webhook_service::GITLAB.into(),
+        delivery_id,
+        event: required_header(&headers, "x-gitlab-event")?,
+        repository,
+        headers: delivery_headers(&headers),
+        body: body.to_vec(),
+    };
+    tracing::info!(
+        repository = ?delivery.repository,
+        event = %delivery.event,
+        delivery_id = %delivery.delivery_id,
+        "Received GitLab webhook"
+    );
+
+    let outcome = github_webhook_service::receive(&state, delivery).await?;
+
+    Ok(Json(outcome))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/webhooks/gitlab", post(gitlab_webhook_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use crate::app;
+    use crate::config::Config;
+
+    fn delivery(token: &str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/webhooks/gitlab")
+            .header("content-type", "application/json")
+            .header("x-gitlab-event", "Tag Push Hook")
+            .header("x-gitlab-event-uuid", uuid::Uuid::new_v4().to_string())
+            .header("x-gitlab-token", token)
+            .body(Body::from(
+                r#"{"object_kind":"tag_push","project":{"path_with_namespace":"group/repo"}}"#,
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_gitlab_webhook_token() {
+        dotenvy::dotenv().ok();
+        let mut config = Config::from_env().expect("Config should load from env");
+        config.gitlab_webhook_token = Some("global-token".into());
+        config
+            .gitlab_webhook_tokens
+            .insert("group/repo".into(), "project-token".into());
+        let app = app::create_app(config)
+            .await
+            .expect("App should be created");
+
+        let response = app
+            .clone()
+            .oneshot(delivery("project-token"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.oneshot(delivery("global-token")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::{get, post},
+    Json, Router,
+};
+use uuid::Uuid;
+
+use crate::db::project_repo;
+use crate::domain::llm::{
+    LlmAnswer, LlmBudget, LlmInteraction, LlmInteractionQuery, UpdateLlmBudgetRequest,
+};
+use crate::error::AppError;
+use crate::routes::auth::AuthedUser;
+use crate::services::llm_service;
+use crate::state::AppState;
+
+async fn summarize_build(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+) -> Result<Json<LlmAnswer>, AppError> {
+    let answer = llm_service::summarize_build(&state, user.id, build_id).await?;
+    Ok(Json(answer))
+}
+
+async fn draft_fix(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(build_id): Path<Uuid>,
+) -> Result<Json<LlmAnswer>, AppError> {
+    let answer = llm_service::draft_fix(&state, user.id, build_id).await?;
+    Ok(Json(answer))
+}
+
+async fn explain_finding(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path((project_id, finding_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<LlmAnswer>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let answer = llm_service::explain_finding(&state, user.id, project_id, finding_id).await?;
+
+    Ok(Json(answer))
+}
+
+async fn list_interactions(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<LlmInteractionQuery>,
+) -> Result<Json<Vec<LlmInteraction>>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let interactions = llm_service::list_interactions(&state, project_id, query.limit).await?;
+
+    Ok(Json(interactions))
+}
+
+async fn get_budget(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<LlmBudget>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let budget = llm_service::get_budget(&state, project_id).await?;
+
+    Ok(Json(budget))
+}
+
+async fn update_budget(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Json(payload): Json<UpdateLlmBudgetRequest>,
+) -> Result<Json<LlmBudget>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let budget = llm_service::update_budget(&state, project_id, payload.daily_token_limit).await?;
+
+    Ok(Json(budget))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/builds/:id/ai/summary", post(summarize_build))
+        .route("/builds/:id/ai/fix", post(draft_fix))
+        .route(
+            "/projects/:id/security/:finding_id/explanation",
+            post(explain_finding),
+        )
+        .route("/projects/:id/llm/interactions", get(list_interactions))
+        .route(
+            "/projects/:id/llm/budget",
+            get(get_budget).put(update_budget),
+        )
+}
@@ -2,11 +2,17 @@ use crate::state::AppState;
 use axum::Router;
 
 pub mod auth;
+pub mod bitbucket;
+pub mod builds;
 pub mod github;
+pub mod gitlab;
 pub mod health;
+pub mod llm;
 pub mod pipelines;
 pub mod projects;
+pub mod sbom;
 pub mod security;
+pub mod test_results;
 
 pub fn router() -> Router<AppState> {
     Router::new()
@@ -14,6 +20,12 @@ pub fn router() -> Router<AppState> {
         .merge(auth::router())
         .merge(projects::router())
         .merge(pipelines::router())
+        .merge(builds::router())
         .merge(security::router())
+        .merge(sbom::router())
+        .merge(llm::router())
+        .merge(test_results::router())
         .merge(github::router())
+        .merge(gitlab::router())
+        .merge(bitbucket::router())
 }
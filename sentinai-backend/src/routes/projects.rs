@@ -76,6 +76,9 @@ async fn project_events(
                 crate::domain::realtime::RealtimeEvent::SecurityFindingCreated(e) => {
                     e.project_id == id
                 }
+                crate::domain::realtime::RealtimeEvent::BuildUpdated(e) => e.project_id == id,
+                crate::domain::realtime::RealtimeEvent::BuildLogAppended(e) => e.project_id == id,
+                crate::domain::realtime::RealtimeEvent::BuildClassified(e) => e.project_id == id,
             };
             if matches {
                 let data = serde_json::to_string(&event).unwrap_or_default();
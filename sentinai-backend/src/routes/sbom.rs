@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::db::project_repo;
+use crate::domain::sbom::SbomFormat;
+use crate::error::AppError;
+use crate::routes::auth::AuthedUser;
+use crate::services::sbom_service;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+struct SbomQuery {
+    #[serde(default)]
+    format: SbomFormat,
+    scan_run_id: Option<Uuid>,
+}
+
+async fn get_sbom(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<SbomQuery>,
+) -> Result<Response, AppError> {
+    let project = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let document =
+        sbom_service::get_sbom_document(&state, &project, query.scan_run_id, query.format).await?;
+
+    Ok((
+        [(header::CONTENT_TYPE, query.format.content_type())],
+        Json(document),
+    )
+        .into_response())
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/projects/:id/sbom", get(get_sbom))
+}
@@ -1,47 +1,370 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
+use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::db::project_repo;
-use crate::domain::security::SecurityFinding;
+use crate::domain::license::{LicensePolicy, UpdateLicensePolicyRequest};
+use crate::domain::remediation::{DependencyBumpRequest, DependencyBumpResult};
+use crate::domain::scan_run::ScanRun;
+use crate::domain::scan_schedule::{ScanSchedule, UpdateScanScheduleRequest};
+use crate::domain::security::{
+    FindingDetail, ScanSummary, SecurityFinding, UpdateFindingStatusRequest,
+};
+use crate::domain::security_gate::{GateEvaluation, GatePolicy, UpdateGatePolicyRequest};
+use crate::domain::security_metrics::{SecurityMetrics, SecurityMetricsQuery};
 use crate::error::AppError;
 use crate::routes::auth::AuthedUser;
-use crate::services::security_service;
+use crate::services::sarif::SarifLog;
+use crate::services::{
+    remediation_service, scan_scheduler, security_gate_service, security_metrics_service,
+    security_service,
+};
 use crate::state::AppState;
 
 async fn run_scan(
     State(state): State<AppState>,
     AuthedUser(user): AuthedUser,
     Path(project_id): Path<Uuid>,
-) -> Result<Json<Vec<SecurityFinding>>, AppError> {
-    let _ = project_repo::get_project(&state.db, project_id, user.id)
+) -> Result<Json<ScanSummary>, AppError> {
+    let project = project_repo::get_project(&state.db, project_id, user.id)
         .await?
         .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
 
-    let findings = security_service::run_security_scan(&state, project_id).await?;
+    let summary = security_service::run_security_scan(&state, &project).await?;
 
-    Ok(Json(findings))
+    Ok(Json(summary))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FindingsFormat {
+    #[default]
+    Json,
+    Sarif,
+}
+
+#[derive(Debug, Deserialize)]
+struct FindingsQuery {
+    #[serde(default)]
+    format: FindingsFormat,
 }
 
 async fn get_findings(
     State(state): State<AppState>,
     AuthedUser(user): AuthedUser,
     Path(project_id): Path<Uuid>,
-) -> Result<Json<Vec<SecurityFinding>>, AppError> {
+    Query(query): Query<FindingsQuery>,
+) -> Result<Response, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    match query.format {
+        FindingsFormat::Json => {
+            let findings: Vec<SecurityFinding> =
+                security_service::get_security_findings(&state, project_id).await?;
+            Ok(Json(findings).into_response())
+        }
+        FindingsFormat::Sarif => {
+            let log = security_service::export_sarif(&state, project_id).await?;
+            Ok((
+                [(header::CONTENT_TYPE, "application/sarif+json")],
+                Json(log),
+            )
+                .into_response())
+        }
+    }
+}
+
+async fn import_sarif(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Json(log): Json<SarifLog>,
+) -> Result<Json<ScanSummary>, AppError> {
+    let project = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let summary = security_service::import_sarif(&state, &project, log).await?;
+
+    Ok(Json(summary))
+}
+
+async fn list_scans(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<Vec<ScanRun>>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let runs = security_service::list_scan_runs(&state, project_id).await?;
+
+    Ok(Json(runs))
+}
+
+async fn get_finding(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path((project_id, finding_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<FindingDetail>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let detail = security_service::get_finding_detail(&state, project_id, finding_id).await?;
+
+    Ok(Json(detail))
+}
+
+async fn update_finding(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path((project_id, finding_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<UpdateFindingStatusRequest>,
+) -> Result<Json<FindingDetail>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let detail =
+        security_service::update_finding_status(&state, project_id, finding_id, user.id, payload)
+            .await?;
+
+    Ok(Json(detail))
+}
+
+async fn get_license_policy(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<LicensePolicy>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let policy = security_service::get_license_policy(&state, project_id).await?;
+
+    Ok(Json(policy))
+}
+
+async fn update_license_policy(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Json(payload): Json<UpdateLicensePolicyRequest>,
+) -> Result<Json<LicensePolicy>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let policy = security_service::update_license_policy(&state, project_id, payload).await?;
+
+    Ok(Json(policy))
+}
+
+async fn get_gate(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<GateEvaluation>, AppError> {
+    let project = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let evaluation = security_gate_service::evaluate_gate(&state, &project).await?;
+
+    Ok(Json(evaluation))
+}
+
+async fn get_project_gate_policy(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<GatePolicy>, AppError> {
     let _ = project_repo::get_project(&state.db, project_id, user.id)
         .await?
         .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
 
-    let findings = security_service::get_security_findings(&state, project_id).await?;
+    let policy = security_gate_service::get_project_policy(&state, project_id).await?;
+
+    Ok(Json(policy))
+}
+
+async fn update_project_gate_policy(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Json(payload): Json<UpdateGatePolicyRequest>,
+) -> Result<Json<GatePolicy>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let policy = security_gate_service::update_project_policy(&state, project_id, payload).await?;
+
+    Ok(Json(policy))
+}
+
+async fn delete_project_gate_policy(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<()>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    security_gate_service::delete_project_policy(&state, project_id).await?;
+
+    Ok(Json(()))
+}
+
+async fn get_organization_gate_policy(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+) -> Result<Json<GatePolicy>, AppError> {
+    let policy = security_gate_service::get_organization_policy(&state, user.id).await?;
+    Ok(Json(policy))
+}
+
+async fn update_organization_gate_policy(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Json(payload): Json<UpdateGatePolicyRequest>,
+) -> Result<Json<GatePolicy>, AppError> {
+    let policy =
+        security_gate_service::update_organization_policy(&state, user.id, payload).await?;
+    Ok(Json(policy))
+}
+
+async fn get_schedule(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<ScanSchedule>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let schedule = scan_scheduler::get_schedule(&state, project_id).await?;
+
+    Ok(Json(schedule))
+}
+
+async fn update_schedule(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Json(payload): Json<UpdateScanScheduleRequest>,
+) -> Result<Json<ScanSchedule>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let schedule = scan_scheduler::update_schedule(&state, project_id, payload).await?;
+
+    Ok(Json(schedule))
+}
+
+async fn delete_schedule(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<()>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    scan_scheduler::delete_schedule(&state, project_id).await?;
+
+    Ok(Json(()))
+}
+
+async fn get_metrics(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<SecurityMetricsQuery>,
+) -> Result<Json<SecurityMetrics>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let metrics =
+        security_metrics_service::get_project_metrics(&state, project_id, query.days).await?;
+
+    Ok(Json(metrics))
+}
+
+async fn get_organization_metrics(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Query(query): Query<SecurityMetricsQuery>,
+) -> Result<Json<SecurityMetrics>, AppError> {
+    let metrics =
+        security_metrics_service::get_organization_metrics(&state, user.id, query.days).await?;
+    Ok(Json(metrics))
+}
+
+async fn bump_dependencies(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Json(payload): Json<DependencyBumpRequest>,
+) -> Result<Json<DependencyBumpResult>, AppError> {
+    let project = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let result = remediation_service::bump_dependencies(&state, &project, payload).await?;
 
-    Ok(Json(findings))
+    Ok(Json(result))
 }
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/projects/:id/security", get(get_findings))
         .route("/projects/:id/security/scan", post(run_scan))
+        .route("/projects/:id/security/scans", get(list_scans))
+        .route("/projects/:id/security/metrics", get(get_metrics))
+        .route("/security/metrics", get(get_organization_metrics))
+        .route("/projects/:id/security/sarif", post(import_sarif))
+        .route(
+            "/projects/:id/security/remediation",
+            post(bump_dependencies),
+        )
+        .route(
+            "/projects/:id/security/schedule",
+            get(get_schedule)
+                .put(update_schedule)
+                .delete(delete_schedule),
+        )
+        .route("/projects/:id/security/gate", get(get_gate))
+        .route(
+            "/projects/:id/security/gate/policy",
+            get(get_project_gate_policy)
+                .put(update_project_gate_policy)
+                .delete(delete_project_gate_policy),
+        )
+        .route(
+            "/security/gate/policy",
+            get(get_organization_gate_policy).put(update_organization_gate_policy),
+        )
+        .route(
+            "/projects/:id/security/license-policy",
+            get(get_license_policy).put(update_license_policy),
+        )
+        .route(
+            "/projects/:id/security/:finding_id",
+            get(get_finding).patch(update_finding),
+        )
 }
@@ -0,0 +1,48 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::{get, post},
+    Json, Router,
+};
+use uuid::Uuid;
+
+use crate::db::project_repo;
+use crate::domain::test_result::{FlakyTest, FlakyTestQuery, QuarantineRequest, QuarantineResult};
+use crate::error::AppError;
+use crate::routes::auth::AuthedUser;
+use crate::services::test_result_service;
+use crate::state::AppState;
+
+async fn list_flaky_tests(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Query(query): Query<FlakyTestQuery>,
+) -> Result<Json<Vec<FlakyTest>>, AppError> {
+    let _ = project_repo::get_project(&state.db, project_id, user.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let tests = test_result_service::list_flaky_tests(&state, project_id, &query).await?;
+
+    Ok(Json(tests))
+}
+
+async fn quarantine_flaky_tests(
+    State(state): State<AppState>,
+    AuthedUser(user): AuthedUser,
+    Path(project_id): Path<Uuid>,
+    Json(payload): Json<QuarantineRequest>,
+) -> Result<Json<QuarantineResult>, AppError> {
+    let result =
+        test_result_service::propose_quarantine(&state, user.id, project_id, payload).await?;
+    Ok(Json(result))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/projects/:id/tests/flaky", get(list_flaky_tests))
+        .route(
+            "/projects/:id/tests/flaky/quarantine",
+            post(quarantine_flaky_tests),
+        )
+}
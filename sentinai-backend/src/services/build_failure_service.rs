@@ -0,0 +1,58 @@
+use uuid::Uuid;
+
+use crate::db::build_repo;
+use crate::domain::build::{Build, BuildStatus};
+use crate::domain::realtime::{BuildFailureEvent, RealtimeEvent};
+use crate::error::AppError;
+use crate::services::{build_log_service, build_retry_service, failure_classifier};
+use crate::state::AppState;
+
+/// Classifies a failed build from its log and stores the result on the build,
+/// scheduling a retry if the project's retry policy covers the failure.
+#[tracing::instrument(name = "build_failure_service", skip(state), fields(build_id = %build_id))]
+pub async fn classify_build(state: &AppState, build_id: Uuid) -> Result<Build, AppError> {
+    let build = build_repo::get_build(&state.db, build_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Build not found".into()))?;
+    if build.status != BuildStatus::Failed {
+        return Err(AppError::BadRequest(
+            "Only failed builds can be classified".into(),
+        ));
+    }
+
+    let log = build_log_service::read_log(state, build.id).await?;
+    let classification = failure_classifier::classify(&log, build.conclusion.as_deref());
+    let build = build_repo::set_failure_classification(&state.db, build.id, &classification)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Build is no longer failed".into()))?;
+
+    tracing::info!(
+        category = ?classification.category,
+        failing_tests = classification.failing_tests.len(),
+        "Classified build failure"
+    );
+    let _ = state
+        .tx
+        .send(RealtimeEvent::BuildClassified(BuildFailureEvent {
+            project_id: build.project_id,
+            build_id: build.id,
+            category: classification.category,
+            failing_tests: classification.failing_tests,
+        }));
+    if let Err(e) = build_retry_service::maybe_schedule_retry(state, &build).await {
+        tracing::warn!(error = %e, "Could not schedule build retry");
+    }
+
+    Ok(build)
+}
+
+/// Classifies a failed build in the background, e.g. once it has failed or
+/// more of its log has arrived.
+pub fn spawn_classification(state: &AppState, build_id: Uuid) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = classify_build(&state, build_id).await {
+            tracing::warn!(build_id = %build_id, error = %e, "Could not classify build failure");
+        }
+    });
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use uuid::Uuid;
+
+use crate::db::project_repo;
+use crate::domain::build::BuildStatus;
+use crate::domain::remediation::{
+    BuildFixRequest, BuildFixResult, FileChange, FileDiff, FixProposal,
+};
+use crate::error::AppError;
+use crate::services::remediation_service::{self, PullRequestDraft};
+use crate::services::{
+    build_log_service, build_service, ci_fix, diff, failure_classifier, github_service, repository,
+};
+use crate::state::AppState;
+
+/// Proposes patches for a failed build from its failure category. On a dry
+/// run only the proposals are returned; otherwise they are committed to a new
+/// branch and opened as a pull request.
+#[tracing::instrument(name = "build_fix", skip(state, request), fields(build_id = %build_id))]
+pub async fn propose_fixes(
+    state: &AppState,
+    user_id: Uuid,
+    build_id: Uuid,
+    request: BuildFixRequest,
+) -> Result<BuildFixResult, AppError> {
+    let build = build_service::get_build(state, user_id, build_id).await?;
+    if build.status != BuildStatus::Failed {
+        return Err(AppError::BadRequest("Only failed builds have fixes".into()));
+    }
+    remediation_service::require_pull_requests(state, request.dry_run)?;
+    let project = project_repo::get_project(&state.db, build.project_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Build not found".into()))?;
+
+    let log = build_log_service::read_log(state, build.id).await?;
+    let category = build.failure_category.unwrap_or_else(|| {
+        failure_classifier::classify(&log, build.conclusion.as_deref()).category
+    });
+    let strategies = if request.strategies.is_empty() {
+        ci_fix::strategies_for(category).to_vec()
+    } else {
+        request.strategies
+    };
+    // A workflow run's name is the workflow's; other builds may be any job.
+    let workflow = build
+        .name
+        .as_deref()
+        .filter(|_| build.source == "workflow_run");
+
+    let base_branch = github_service::get_default_branch(state, &project.repository_url).await?;
+    let base_sha =
+        github_service::get_commit_sha(state, &project.repository_url, &base_branch).await?;
+    let snapshot = repository::fetch_snapshot(state, &project.repository_url, &base_sha).await?;
+    let fixes = ci_fix::plan_fixes(&snapshot, &strategies, workflow, &log);
+
+    let mut originals: BTreeMap<String, String> = BTreeMap::new();
+    let mut changed: BTreeMap<String, String> = BTreeMap::new();
+    let proposals: Vec<FixProposal> = fixes
+        .iter()
+        .map(|fix| {
+            let files: Vec<FileDiff> = fix
+                .files
+                .iter()
+                .map(|change| {
+                    originals
+                        .entry(change.path.clone())
+                        .or_insert_with(|| change.before.clone());
+                    changed.insert(change.path.clone(), change.after.clone());
+                    FileDiff {
+                        path: change.path.clone(),
+                        diff: diff::unified_diff(&change.path, &change.before, &change.after),
+                    }
+                })
+                .collect();
+            FixProposal {
+                strategy: fix.strategy,
+                title: fix.title.clone(),
+                description: fix.description.clone(),
+                patch: files.iter().map(|f| f.diff.as_str()).collect(),
+                files,
+            }
+        })
+        .collect();
+    let changes: Vec<FileChange> = changed
+        .into_iter()
+        .map(|(path, after)| FileChange {
+            before: originals.remove(&path).unwrap_or_default(),
+            path,
+            after,
+        })
+        .collect();
+    let patch: String = changes
+        .iter()
+        .map(|change| diff::unified_diff(&change.path, &change.before, &change.after))
+        .collect();
+
+    let pull_request = if request.dry_run {
+        None
+    } else {
+        if fixes.is_empty() {
+            return Err(AppError::BadRequest("No fixes to apply".into()));
+        }
+        let (title, body) = ci_fix::describe_fixes(
+            &fixes,
+            category,
+            build.name.as_deref(),
+            build.html_url.as_deref(),
+        );
+        let pr = remediation_service::open_pull_request(
+            state,
+            &project,
+            PullRequestDraft {
+                branch_prefix: "ci-fix",
+                base_branch: &base_branch,
+                base_sha: &base_sha,
+                title: &title,
+                body: &body,
+                files: &changes,
+            },
+        )
+        .await?;
+        Some(pr)
+    };
+
+    Ok(BuildFixResult {
+        build_id: build.id,
+        failure_category: category,
+        dry_run: request.dry_run,
+        base_branch,
+        base_sha,
+        proposals,
+        patch,
+        pull_request,
+    })
+}
@@ -0,0 +1,261 @@
+//! Cleaning, chunking, compression and line matching for build logs.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Cursor, Read, Write};
+
+use crate::domain::build_log::NewLogChunk;
+use crate::error::AppError;
+
+/// Lines stored per chunk at most.
+pub const MAX_CHUNK_LINES: usize = 1000;
+/// Bytes stored per chunk at most, keeping each chunk's tsvector well under
+/// Postgres' 1 MB limit.
+pub const MAX_CHUNK_BYTES: usize = 256 * 1024;
+/// Chunks larger than this are stored gzipped.
+pub const COMPRESSION_THRESHOLD: usize = 8 * 1024;
+/// Bytes of job logs unpacked from one log archive at most.
+const MAX_LOG_ARCHIVE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Splits raw log output into lines with ANSI escapes removed. Progress output
+/// redrawn with carriage returns keeps only its final state.
+pub fn clean_log(raw: &str) -> Vec<String> {
+    let text = raw.strip_suffix('\n').unwrap_or(raw);
+    if text.is_empty() {
+        return Vec::new();
+    }
+    text.split('\n')
+        .map(|line| {
+            // Resolve redraws first: stripping escapes also drops `\r`.
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let line = line.rsplit('\r').next().unwrap_or(line);
+            strip_ansi_escapes::strip_str(line).replace('\0', "")
+        })
+        .collect()
+}
+
+/// Groups lines into chunks of at most [`MAX_CHUNK_LINES`] lines and roughly
+/// [`MAX_CHUNK_BYTES`] bytes.
+pub fn split_chunks(lines: &[String]) -> Vec<&[String]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut bytes = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if i > start && (i - start == MAX_CHUNK_LINES || bytes + line.len() > MAX_CHUNK_BYTES) {
+            chunks.push(&lines[start..i]);
+            start = i;
+            bytes = 0;
+        }
+        bytes += line.len() + 1;
+    }
+    if start < lines.len() {
+        chunks.push(&lines[start..]);
+    }
+    chunks
+}
+
+pub fn encode_chunk(lines: &[String]) -> Result<NewLogChunk, AppError> {
+    let text = lines.join("\n");
+    let (content, compressed) = if text.len() > COMPRESSION_THRESHOLD {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(text.as_bytes())
+            .and_then(|_| encoder.finish())
+            .map(|bytes| (None, Some(bytes)))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to compress log: {}", e)))?
+    } else {
+        (Some(text.clone()), None)
+    };
+    Ok(NewLogChunk {
+        text,
+        line_count: lines.len() as i32,
+        content,
+        compressed,
+    })
+}
+
+/// The text of a stored chunk.
+pub fn decode_chunk(
+    content: Option<String>,
+    compressed: Option<&[u8]>,
+) -> Result<String, AppError> {
+    match (content, compressed) {
+        (Some(content), _) => Ok(content),
+        (None, Some(bytes)) => {
+            let mut text = String::new();
+            GzDecoder::new(bytes)
+                .read_to_string(&mut text)
+                .map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to decompress log: {}", e))
+                })?;
+            Ok(text)
+        }
+        (None, None) => Ok(String::new()),
+    }
+}
+
+/// Lowercase words a search query looks for, leaving out excluded (`-word`)
+/// terms and `or`.
+pub fn search_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .filter(|word| !word.starts_with('-') && !word.eq_ignore_ascii_case("or"))
+        .map(|word| word.trim_matches('"').to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Numbered lines of a chunk starting at `first_line` that contain any of
+/// `terms`.
+pub fn matching_lines<'a>(text: &'a str, first_line: i32, terms: &[String]) -> Vec<(i32, &'a str)> {
+    text.split('\n')
+        .zip(first_line..)
+        .filter(|(line, _)| {
+            let line = line.to_lowercase();
+            terms.iter().any(|term| line.contains(term.as_str()))
+        })
+        .map(|(line, number)| (number, line))
+        .collect()
+}
+
+/// Concatenates the job logs in a GitHub Actions log archive. The archive
+/// holds one `<n>_<job>.txt` file per job at its root, next to per-step files
+/// in job directories; the root files are used when present.
+pub fn unpack_log_archive(archive: &[u8]) -> Result<String, AppError> {
+    unpack_log_archive_within(archive, MAX_LOG_ARCHIVE_SIZE)
+}
+
+fn unpack_log_archive_within(archive: &[u8], limit: u64) -> Result<String, AppError> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive))
+        .map_err(|e| AppError::BadRequest(format!("Invalid log archive: {}", e)))?;
+    let mut names: Vec<String> = zip
+        .file_names()
+        .filter(|name| name.ends_with(".txt"))
+        .map(String::from)
+        .collect();
+    if names.iter().any(|name| !name.contains('/')) {
+        names.retain(|name| !name.contains('/'));
+    }
+    names.sort_by_key(|name| job_order(name));
+
+    let mut log = String::new();
+    let mut total: u64 = 0;
+    for name in names {
+        let file = zip
+            .by_name(&name)
+            .map_err(|e| AppError::BadRequest(format!("Invalid log archive: {}", e)))?;
+        let mut text = String::new();
+        let read = file
+            .take(limit - total + 1)
+            .read_to_string(&mut text)
+            .map_err(|e| AppError::BadRequest(format!("Invalid log file {}: {}", name, e)))?;
+        total += read as u64;
+        if total > limit {
+            return Err(AppError::BadRequest(
+                "Log archive is too large to import".into(),
+            ));
+        }
+        log.push_str(&text);
+        if !log.ends_with('\n') {
+            log.push('\n');
+        }
+    }
+    Ok(log)
+}
+
+/// Sorts `2_test.txt` before `10_lint.txt`.
+fn job_order(name: &str) -> (Option<u32>, String) {
+    let file = name.rsplit('/').next().unwrap_or(name);
+    let number = file.split('_').next().and_then(|n| n.parse().ok());
+    (number, name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_log() {
+        let raw = "\x1b[1;32mCompiling\x1b[0m app\r\nDownloading 10%\rDownloading 100%\nok\n";
+        assert_eq!(
+            clean_log(raw),
+            vec!["Compiling app", "Downloading 100%", "ok"]
+        );
+        assert!(clean_log("").is_empty());
+        assert_eq!(clean_log("\n\n"), vec!["", ""]);
+    }
+
+    #[test]
+    fn test_split_chunks_bounds_lines_and_bytes() {
+        let lines: Vec<String> = (0..2500).map(|i| format!("line {}", i)).collect();
+        let chunks = split_chunks(&lines);
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            vec![1000, 1000, 500]
+        );
+
+        let long = vec!["x".repeat(100 * 1024); 5];
+        let chunks = split_chunks(&long);
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+    }
+
+    #[test]
+    fn test_encode_chunk_compresses_large_chunks() {
+        let small = encode_chunk(&["error: oops".to_string()]).unwrap();
+        assert_eq!(small.content.as_deref(), Some("error: oops"));
+        assert!(small.compressed.is_none());
+
+        let lines: Vec<String> = (0..1000)
+            .map(|i| format!("test case {} ... ok", i))
+            .collect();
+        let large = encode_chunk(&lines).unwrap();
+        assert_eq!(large.line_count, 1000);
+        assert!(large.content.is_none());
+        let compressed = large.compressed.unwrap();
+        assert!(compressed.len() < large.text.len() / 4);
+        assert_eq!(decode_chunk(None, Some(&compressed)).unwrap(), large.text);
+    }
+
+    #[test]
+    fn test_matching_lines() {
+        let terms = search_terms("\"Segmentation\" fault -warning or");
+        assert_eq!(terms, vec!["segmentation", "fault"]);
+        let text = "running tests\nSegmentation fault (core dumped)\nwarning: unused";
+        assert_eq!(
+            matching_lines(text, 41, &terms),
+            vec![(42, "Segmentation fault (core dumped)")]
+        );
+    }
+
+    #[test]
+    fn test_unpack_log_archive_prefers_job_logs() {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buffer);
+            let options = zip::write::SimpleFileOptions::default();
+            for (name, text) in [
+                ("10_lint.txt", "lint ok"),
+                ("2_test.txt", "test failed\n"),
+                ("test/1_Run tests.txt", "step log"),
+            ] {
+                zip.start_file(name, options).unwrap();
+                zip.write_all(text.as_bytes()).unwrap();
+            }
+            zip.finish().unwrap();
+        }
+        assert_eq!(
+            unpack_log_archive(buffer.get_ref()).unwrap(),
+            "test failed\nlint ok\n"
+        );
+        assert!(unpack_log_archive(b"not a zip").is_err());
+        assert!(unpack_log_archive_within(buffer.get_ref(), 19).is_ok());
+        assert!(matches!(
+            unpack_log_archive_within(buffer.get_ref(), 18),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,471 @@
+use std::time::Duration;
+
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio::time::Instant;
+use tokio_stream::wrappers::ReceiverStream;
+use uuid::Uuid;
+
+use crate::db::{build_log_repo, build_repo, project_repo};
+use crate::domain::build::{Build, BuildStatus};
+use crate::domain::build_log::{
+    BuildLogPage, LogAppendResult, LogLine, LogSearchHit, LogStreamEvent, NewLogChunk,
+};
+use crate::domain::project::Project;
+use crate::domain::realtime::{BuildLogEvent, RealtimeEvent};
+use crate::error::AppError;
+use crate::services::{build_failure_service, build_log, build_service, github_service};
+use crate::state::AppState;
+
+/// Lines returned per page at most.
+const MAX_PAGE_LINES: i32 = 5000;
+const MAX_SEARCH_RESULTS: i64 = 200;
+/// Lines a log stream reads from the database at a time.
+const STREAM_BATCH_LINES: i32 = 1000;
+/// How long the stream of a finished build waits for its log archive.
+const ARCHIVE_WAIT: Duration = Duration::from_secs(120);
+
+fn encode(raw: &str) -> Result<(Vec<NewLogChunk>, i32), AppError> {
+    let lines = build_log::clean_log(raw);
+    let chunks = build_log::split_chunks(&lines)
+        .into_iter()
+        .map(build_log::encode_chunk)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((chunks, lines.len() as i32))
+}
+
+async fn store(
+    state: &AppState,
+    build: &Build,
+    raw: &str,
+    replace: bool,
+) -> Result<LogAppendResult, AppError> {
+    let (chunks, line_count) = encode(raw)?;
+    let (first_line, total_lines) =
+        build_log_repo::append_chunks(&state.db, build.id, build.project_id, &chunks, replace)
+            .await?;
+    let _ = state
+        .tx
+        .send(RealtimeEvent::BuildLogAppended(BuildLogEvent {
+            project_id: build.project_id,
+            build_id: build.id,
+            total_lines,
+        }));
+    if build.status == BuildStatus::Failed {
+        build_failure_service::spawn_classification(state, build.id);
+    }
+    Ok(LogAppendResult {
+        build_id: build.id,
+        first_line,
+        line_count,
+        total_lines,
+    })
+}
+
+/// Appends whole lines of raw output to a build's log.
+#[tracing::instrument(name = "build_log_service", skip(state, raw), fields(build_id = %build_id))]
+pub async fn append_logs(
+    state: &AppState,
+    user_id: Uuid,
+    build_id: Uuid,
+    raw: &str,
+) -> Result<LogAppendResult, AppError> {
+    let build = build_service::get_build(state, user_id, build_id).await?;
+    store(state, &build, raw, false).await
+}
+
+/// Replaces a GitHub Actions build's log with the run's log archive.
+#[tracing::instrument(name = "build_log_service", skip(state, project, build), fields(build_id = %build.id))]
+pub async fn import_workflow_run_logs(
+    state: &AppState,
+    project: &Project,
+    build: &Build,
+) -> Result<LogAppendResult, AppError> {
+    let run_id = build
+        .external_id
+        .filter(|_| build.source == "workflow_run")
+        .ok_or_else(|| {
+            AppError::BadRequest("Only GitHub Actions builds have log archives".into())
+        })?;
+
+    let archive = github_service::download_workflow_run_logs(
+        state,
+        &project.repository_url,
+        run_id,
+        build.attempt,
+    )
+    .await?;
+    let log = build_log::unpack_log_archive(&archive)?;
+    let result = store(state, build, &log, true).await?;
+    tracing::info!(lines = result.total_lines, "Imported workflow run logs");
+    Ok(result)
+}
+
+#[tracing::instrument(name = "build_log_service", skip(state), fields(build_id = %build_id))]
+pub async fn fetch_log_archive(
+    state: &AppState,
+    user_id: Uuid,
+    build_id: Uuid,
+) -> Result<LogAppendResult, AppError> {
+    let build = build_service::get_build(state, user_id, build_id).await?;
+    let project = project_repo::get_project(&state.db, build.project_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Build not found".into()))?;
+    import_workflow_run_logs(state, &project, &build).await
+}
+
+async fn read_lines(
+    state: &AppState,
+    build_id: Uuid,
+    from: i32,
+    to: i32,
+) -> Result<Vec<LogLine>, AppError> {
+    let mut lines = Vec::new();
+    for chunk in build_log_repo::get_chunks(&state.db, build_id, from, to).await? {
+        let text = build_log::decode_chunk(chunk.content, chunk.compressed.as_deref())?;
+        lines.extend(
+            text.split('\n')
+                .zip(chunk.first_line..)
+                .filter(|(_, number)| (from..=to).contains(number))
+                .map(|(text, number)| LogLine {
+                    number,
+                    text: text.to_string(),
+                }),
+        );
+    }
+    Ok(lines)
+}
+
+/// Every line of a build's log.
+pub async fn read_log(state: &AppState, build_id: Uuid) -> Result<Vec<String>, AppError> {
+    let lines = read_lines(state, build_id, 1, i32::MAX).await?;
+    Ok(lines.into_iter().map(|line| line.text).collect())
+}
+
+/// Lines `from..=to` of a build's log, numbered from 1.
+#[tracing::instrument(name = "build_log_service", skip(state), fields(build_id = %build_id))]
+pub async fn get_log_lines(
+    state: &AppState,
+    user_id: Uuid,
+    build_id: Uuid,
+    from: i32,
+    to: Option<i32>,
+) -> Result<BuildLogPage, AppError> {
+    let to = to.unwrap_or(from.saturating_add(999));
+    if from < 1 || to < from || to - from >= MAX_PAGE_LINES {
+        return Err(AppError::BadRequest(format!(
+            "from must be at least 1 and to at most {} lines after it",
+            MAX_PAGE_LINES - 1
+        )));
+    }
+    let build = build_service::get_build(state, user_id, build_id).await?;
+
+    let total_lines = build_log_repo::count_lines(&state.db, build.id).await?;
+    let lines = read_lines(state, build.id, from, to).await?;
+
+    Ok(BuildLogPage {
+        build_id: build.id,
+        from,
+        to: to.min(total_lines),
+        total_lines,
+        lines,
+    })
+}
+
+/// Lines matching `query` across the project's build logs, newest builds
+/// first.
+#[tracing::instrument(name = "build_log_service", skip(state), fields(project_id = %project_id))]
+pub async fn search_logs(
+    state: &AppState,
+    project_id: Uuid,
+    query: &str,
+    limit: i64,
+) -> Result<Vec<LogSearchHit>, AppError> {
+    if query.trim().is_empty() {
+        return Err(AppError::BadRequest("q must not be empty".into()));
+    }
+    if !(1..=MAX_SEARCH_RESULTS).contains(&limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_SEARCH_RESULTS
+        )));
+    }
+
+    let terms = build_log::search_terms(query);
+    let mut hits = Vec::new();
+    for chunk in build_log_repo::search_chunks(&state.db, project_id, query, limit).await? {
+        let text = build_log::decode_chunk(chunk.content, chunk.compressed.as_deref())?;
+        for (line_number, line) in build_log::matching_lines(&text, chunk.first_line, &terms) {
+            hits.push(LogSearchHit {
+                build_id: chunk.build_id,
+                build_name: chunk.build_name.clone(),
+                head_sha: chunk.head_sha.clone(),
+                started_at: chunk.started_at,
+                line_number,
+                text: line.to_string(),
+            });
+        }
+        if hits.len() as i64 >= limit {
+            hits.truncate(limit as usize);
+            break;
+        }
+    }
+
+    Ok(hits)
+}
+
+/// Streams a build's log lines after line `after`: the stored backlog first,
+/// then lines as they are appended. Ends with the build's status once it
+/// finishes.
+#[tracing::instrument(name = "build_log_service", skip(state), fields(build_id = %build_id))]
+pub async fn tail_logs(
+    state: &AppState,
+    user_id: Uuid,
+    build_id: Uuid,
+    after: i32,
+) -> Result<ReceiverStream<LogStreamEvent>, AppError> {
+    if after < 0 {
+        return Err(AppError::BadRequest(
+            "Last-Event-ID must be a line number".into(),
+        ));
+    }
+    // Subscribe before reading the backlog so no append slips in between.
+    let rx = state.tx.subscribe();
+    let build = build_service::get_build(state, user_id, build_id).await?;
+
+    let (tx, stream) = mpsc::channel(STREAM_BATCH_LINES as usize);
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = follow(&state, &build, after, rx, &tx).await {
+            tracing::warn!(build_id = %build.id, "Log stream ended early: {:?}", e);
+        }
+    });
+
+    Ok(ReceiverStream::new(stream))
+}
+
+/// Sends new lines as they are appended until the build finishes or the
+/// client goes away. GitHub Actions logs are only imported once the run has
+/// finished, so for those builds the stream stays open until the import
+/// replaces the log, or `ARCHIVE_WAIT` passes without one. The import is
+/// announced with a reset before its lines are sent from line 1.
+async fn follow(
+    state: &AppState,
+    build: &Build,
+    mut cursor: i32,
+    mut rx: tokio::sync::broadcast::Receiver<RealtimeEvent>,
+    tx: &mpsc::Sender<LogStreamEvent>,
+) -> Result<(), AppError> {
+    let mut status = build.status;
+    // A finished run that already has a log has had its archive imported.
+    let imported =
+        status.is_finished() && build_log_repo::count_lines(&state.db, build.id).await? > 0;
+    let mut awaiting_archive =
+        build.source == "workflow_run" && state.config.github_token.is_some() && !imported;
+    loop {
+        cursor = send_lines(state, build.id, cursor, tx).await?;
+        if status.is_finished() && !awaiting_archive {
+            let _ = tx.send(LogStreamEvent::Finished(status)).await;
+            return Ok(());
+        }
+
+        let deadline = status.is_finished().then(|| Instant::now() + ARCHIVE_WAIT);
+        loop {
+            let event = tokio::select! {
+                _ = tx.closed() => return Ok(()),
+                _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)),
+                    if deadline.is_some() =>
+                {
+                    tracing::warn!(build_id = %build.id, "Log archive did not arrive in time");
+                    awaiting_archive = false;
+                    break;
+                }
+                event = rx.recv() => event,
+            };
+            match event {
+                Ok(RealtimeEvent::BuildLogAppended(e)) if e.build_id == build.id => {
+                    // The archive replaces whatever was streamed before it.
+                    if status.is_finished() && awaiting_archive {
+                        awaiting_archive = false;
+                        cursor = 0;
+                        if tx.send(LogStreamEvent::Reset).await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                    break;
+                }
+                Ok(RealtimeEvent::BuildUpdated(e)) if e.build_id == build.id => {
+                    status = e.status;
+                    if status.is_finished() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                // Missed events: catch up from the database.
+                Err(RecvError::Lagged(_)) => {
+                    status = current_status(state, build.id).await?;
+                    break;
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Sends the stored lines after `cursor` and returns the last line sent.
+async fn send_lines(
+    state: &AppState,
+    build_id: Uuid,
+    mut cursor: i32,
+    tx: &mpsc::Sender<LogStreamEvent>,
+) -> Result<i32, AppError> {
+    let total_lines = build_log_repo::count_lines(&state.db, build_id).await?;
+    while cursor < total_lines {
+        let to = total_lines.min(cursor + STREAM_BATCH_LINES);
+        for line in read_lines(state, build_id, cursor + 1, to).await? {
+            cursor = line.number;
+            if tx.send(LogStreamEvent::Line(line)).await.is_err() {
+                return Ok(cursor);
+            }
+        }
+        cursor = cursor.max(to);
+    }
+    Ok(cursor)
+}
+
+async fn current_status(state: &AppState, build_id: Uuid) -> Result<BuildStatus, AppError> {
+    build_repo::get_build(&state.db, build_id)
+        .await?
+        .map(|build| build.status)
+        .ok_or_else(|| AppError::NotFound("Build not found".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::db::user_repo;
+    use crate::domain::build::NewBuild;
+    use crate::domain::realtime::BuildEvent;
+    use sqlx::postgres::PgPoolOptions;
+    use std::env;
+    use std::sync::Arc;
+    use tokio_stream::StreamExt;
+
+    async fn get_state() -> AppState {
+        dotenvy::dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let db = PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&db_url)
+            .await
+            .unwrap();
+        let config = Config {
+            database_url: db_url,
+            jwt_secret: "secret".into(),
+            port: 8080,
+            github_token: Some("token".into()),
+            github_webhook_secret: None,
+            github_webhook_secrets: Default::default(),
+            gitlab_webhook_token: None,
+            gitlab_webhook_tokens: Default::default(),
+            bitbucket_webhook_secret: None,
+            bitbucket_webhook_secrets: Default::default(),
+            llm_provider: None,
+            llm_base_url: String::new(),
+            llm_api_key: None,
+            llm_model: String::new(),
+            llm_daily_token_budget: 0,
+        };
+        let (tx, _rx) = tokio::sync::broadcast::channel(100);
+        AppState {
+            db,
+            config: Arc::new(config),
+            http_client: reqwest::Client::new(),
+            tx,
+            llm: None,
+        }
+    }
+
+    async fn next_event(stream: &mut ReceiverStream<LogStreamEvent>) -> String {
+        let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("log stream stalled")
+            .expect("log stream closed");
+        match event {
+            LogStreamEvent::Line(line) => format!("{} {}", line.number, line.text),
+            LogStreamEvent::Reset => "reset".into(),
+            LogStreamEvent::Finished(status) => format!("finished {:?}", status),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_tail_logs_resumes_across_archive_import() {
+        let state = get_state().await;
+        let github_id: i64 = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let user = user_repo::create_user(&state.db, github_id, "test_tail_user", None)
+            .await
+            .unwrap();
+        let proj =
+            project_repo::create_project(&state.db, user.id, "test_tail_proj", "https://url")
+                .await
+                .unwrap();
+        let build = build_repo::create_build(
+            &state.db,
+            proj.id,
+            &NewBuild {
+                pipeline_id: None,
+                name: Some("ci".into()),
+                head_sha: None,
+                head_branch: None,
+            },
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE builds SET source = 'workflow_run', external_id = 1 WHERE id = $1")
+            .bind(build.id)
+            .execute(&state.db)
+            .await
+            .unwrap();
+
+        append_logs(&state, user.id, build.id, "live 1\nlive 2\n")
+            .await
+            .unwrap();
+        let mut stream = tail_logs(&state, user.id, build.id, 0).await.unwrap();
+        assert_eq!(next_event(&mut stream).await, "1 live 1");
+        assert_eq!(next_event(&mut stream).await, "2 live 2");
+
+        let build =
+            build_repo::update_build_status(&state.db, build.id, BuildStatus::Success, None)
+                .await
+                .unwrap();
+        let _ = state.tx.send(RealtimeEvent::BuildUpdated(BuildEvent {
+            project_id: proj.id,
+            build_id: build.id,
+            status: build.status,
+        }));
+        store(&state, &build, "job 1\njob 2\njob 3\n", true)
+            .await
+            .unwrap();
+        assert_eq!(next_event(&mut stream).await, "reset");
+        assert_eq!(next_event(&mut stream).await, "1 job 1");
+        assert_eq!(next_event(&mut stream).await, "2 job 2");
+        assert_eq!(next_event(&mut stream).await, "3 job 3");
+        assert_eq!(next_event(&mut stream).await, "finished Success");
+
+        // A client that saw the first imported line picks up after it.
+        let mut resumed = tail_logs(&state, user.id, build.id, 1).await.unwrap();
+        assert_eq!(next_event(&mut resumed).await, "2 job 2");
+        assert_eq!(next_event(&mut resumed).await, "3 job 3");
+        assert_eq!(next_event(&mut resumed).await, "finished Success");
+
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&state.db)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,258 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::db::{build_repo, build_retry_repo, project_repo};
+use crate::domain::build::Build;
+use crate::domain::build_retry::{
+    BuildRetry, BuildRetryPolicy, BuildRetryStatus, UpdateBuildRetryPolicyRequest,
+};
+use crate::error::AppError;
+use crate::services::github_service;
+use crate::state::AppState;
+
+/// How often each instance looks for due retries.
+const POLL_INTERVAL: StdDuration = StdDuration::from_secs(30);
+/// Upper bound on retries started by one instance per poll.
+const MAX_DUE_PER_POLL: i64 = 20;
+const MAX_ATTEMPTS: i32 = 10;
+/// Longest wait before a retry, however many attempts came before it.
+const MAX_BACKOFF_SECONDS: i32 = 6 * 60 * 60;
+/// Only GitHub Actions runs can be re-run through their source's API.
+const RETRYABLE_SOURCE: &str = "workflow_run";
+/// GitHub's conclusion for runs stopped by a user, which are never re-run
+/// even though their log reads like a lost runner.
+const CANCELLED: &str = "cancelled";
+
+/// How long to wait before starting `attempt`: the policy's backoff, doubled
+/// for every retry before it.
+pub fn backoff(backoff_seconds: i32, attempt: i32) -> Duration {
+    let doublings = (attempt - 2).clamp(0, 30) as u32;
+    let seconds = i64::from(backoff_seconds).saturating_mul(1 << doublings);
+    Duration::seconds(seconds.min(i64::from(MAX_BACKOFF_SECONDS)))
+}
+
+#[tracing::instrument(name = "build_retry_service", skip(state), fields(project_id = %project_id))]
+pub async fn get_policy(state: &AppState, project_id: Uuid) -> Result<BuildRetryPolicy, AppError> {
+    Ok(build_retry_repo::get_policy(&state.db, project_id)
+        .await?
+        .unwrap_or_else(|| BuildRetryPolicy::default_for(project_id)))
+}
+
+#[tracing::instrument(name = "build_retry_service", skip(state, request), fields(project_id = %project_id))]
+pub async fn update_policy(
+    state: &AppState,
+    project_id: Uuid,
+    request: UpdateBuildRetryPolicyRequest,
+) -> Result<BuildRetryPolicy, AppError> {
+    if !(1..=MAX_ATTEMPTS).contains(&request.max_attempts) {
+        return Err(AppError::BadRequest(format!(
+            "max_attempts must be between 1 and {}",
+            MAX_ATTEMPTS
+        )));
+    }
+    if !(0..=MAX_BACKOFF_SECONDS).contains(&request.backoff_seconds) {
+        return Err(AppError::BadRequest(format!(
+            "backoff_seconds must be between 0 and {}",
+            MAX_BACKOFF_SECONDS
+        )));
+    }
+    let mut retry_on = Vec::with_capacity(request.retry_on.len());
+    for category in request.retry_on {
+        if !retry_on.contains(&category) {
+            retry_on.push(category);
+        }
+    }
+
+    build_retry_repo::upsert_policy(
+        &state.db,
+        project_id,
+        request.enabled,
+        request.max_attempts,
+        request.backoff_seconds,
+        &retry_on,
+    )
+    .await
+}
+
+/// The automatic retries of a build's run, across all of its attempts.
+pub async fn list_retries(state: &AppState, build: &Build) -> Result<Vec<BuildRetry>, AppError> {
+    build_retry_repo::list_retries(&state.db, build.retry_of.unwrap_or(build.id)).await
+}
+
+/// Whether `policy` asks for another run of a classified failed build.
+/// Cancelled runs are left alone.
+pub fn should_retry(build: &Build, policy: &BuildRetryPolicy) -> bool {
+    build.source == RETRYABLE_SOURCE
+        && build.external_id.is_some()
+        && build.conclusion.as_deref() != Some(CANCELLED)
+        && policy.enabled
+        && build
+            .failure_category
+            .is_some_and(|category| policy.retry_on.contains(&category))
+        && build.attempt < policy.max_attempts
+}
+
+/// Schedules a re-run of a classified failed build when the project's retry
+/// policy covers its failure category and it has attempts left.
+#[tracing::instrument(name = "build_retry_service", skip(state, build), fields(build_id = %build.id))]
+pub async fn maybe_schedule_retry(
+    state: &AppState,
+    build: &Build,
+) -> Result<Option<BuildRetry>, AppError> {
+    let Some(category) = build.failure_category else {
+        return Ok(None);
+    };
+    if build.source != RETRYABLE_SOURCE || state.config.github_token.is_none() {
+        return Ok(None);
+    }
+    let policy = get_policy(state, build.project_id).await?;
+    if !should_retry(build, &policy) {
+        return Ok(None);
+    }
+
+    let attempt = build.attempt + 1;
+    let scheduled_for = Utc::now() + backoff(policy.backoff_seconds, attempt);
+    let retry = build_retry_repo::schedule_retry(
+        &state.db,
+        build.project_id,
+        build.id,
+        build.retry_of.unwrap_or(build.id),
+        attempt,
+        category,
+        scheduled_for,
+    )
+    .await?;
+
+    if retry.is_some() {
+        tracing::info!(attempt, %scheduled_for, category = ?category, "Scheduled build retry");
+    }
+    Ok(retry)
+}
+
+/// Starts the background loop that re-runs builds once their backoff has
+/// passed. Retries live in the database, so ones that came due while no
+/// instance was up start on the first poll after a restart.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = start_due_retries(&state).await {
+                tracing::warn!(error = %e, "Could not load due build retries");
+            }
+        }
+    });
+}
+
+async fn start_due_retries(state: &AppState) -> Result<(), AppError> {
+    for retry in build_retry_repo::list_due_retries(&state.db, MAX_DUE_PER_POLL).await? {
+        if let Err(e) = run_retry(state, &retry).await {
+            tracing::warn!(retry_id = %retry.id, error = %e, "Build retry failed");
+            build_retry_repo::set_retry_status(
+                &state.db,
+                retry.id,
+                BuildRetryStatus::Failed,
+                Some(&e.to_string()),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+#[tracing::instrument(name = "build_retry_service", skip(state, retry), fields(build_id = %retry.build_id, attempt = retry.attempt))]
+async fn run_retry(state: &AppState, retry: &BuildRetry) -> Result<(), AppError> {
+    // Another instance may have started this retry since it was listed.
+    let Some(retry) = build_retry_repo::claim_retry(&state.db, retry.id).await? else {
+        return Ok(());
+    };
+    if retry.retry_build_id.is_some() {
+        tracing::info!("Build was already re-run; skipping retry");
+        return build_retry_repo::set_retry_status(
+            &state.db,
+            retry.id,
+            BuildRetryStatus::Skipped,
+            None,
+        )
+        .await;
+    }
+
+    let build = build_repo::get_build(&state.db, retry.build_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Build not found".into()))?;
+    let run_id = build
+        .external_id
+        .ok_or_else(|| AppError::BadRequest("Build has no GitHub run to re-run".into()))?;
+    let project = project_repo::get_project_by_id(&state.db, retry.project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    github_service::rerun_failed_jobs(state, &project.repository_url, run_id).await?;
+    tracing::info!(run_id, "Requested build retry");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::build::{BuildStatus, FailureCategory};
+
+    fn failed_run(conclusion: &str, attempt: i32) -> Build {
+        Build {
+            id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            pipeline_id: None,
+            source: RETRYABLE_SOURCE.into(),
+            external_id: Some(42),
+            name: Some("CI".into()),
+            status: BuildStatus::Failed,
+            conclusion: Some(conclusion.into()),
+            head_sha: None,
+            head_branch: None,
+            html_url: None,
+            started_at: None,
+            finished_at: None,
+            failure_category: Some(FailureCategory::InfraFlaky),
+            failure_line: None,
+            failure_excerpt: None,
+            failing_tests: Vec::new(),
+            classified_at: None,
+            attempt,
+            retry_of: None,
+        }
+    }
+
+    #[test]
+    fn test_should_retry() {
+        let default = BuildRetryPolicy::default_for(Uuid::nil());
+        assert!(!should_retry(&failed_run("failure", 1), &default));
+
+        let policy = BuildRetryPolicy {
+            enabled: true,
+            ..default
+        };
+        assert!(should_retry(&failed_run("failure", 1), &policy));
+        assert!(should_retry(&failed_run("failure", 2), &policy));
+        assert!(!should_retry(&failed_run("failure", 3), &policy));
+        assert!(!should_retry(&failed_run("cancelled", 1), &policy));
+
+        let mut compile_error = failed_run("failure", 1);
+        compile_error.failure_category = Some(FailureCategory::CompileError);
+        assert!(!should_retry(&compile_error, &policy));
+    }
+
+    #[test]
+    fn test_backoff_doubles_per_retry() {
+        assert_eq!(backoff(60, 2), Duration::seconds(60));
+        assert_eq!(backoff(60, 3), Duration::seconds(120));
+        assert_eq!(backoff(60, 5), Duration::seconds(480));
+        assert_eq!(backoff(0, 4), Duration::zero());
+        assert_eq!(
+            backoff(3600, 10),
+            Duration::seconds(i64::from(MAX_BACKOFF_SECONDS))
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use uuid::Uuid;
+
+use crate::db::{build_repo, project_repo};
+use crate::domain::build::{Build, BuildStatus, NewBuild};
+use crate::domain::build_retry::BuildDetail;
+use crate::domain::realtime::{BuildEvent, RealtimeEvent};
+use crate::error::AppError;
+use crate::services::{build_failure_service, build_retry_service};
+use crate::state::AppState;
+
+const MAX_LIMIT: i64 = 200;
+
+pub fn publish_build_updated(state: &AppState, build: &Build) {
+    let _ = state.tx.send(RealtimeEvent::BuildUpdated(BuildEvent {
+        project_id: build.project_id,
+        build_id: build.id,
+        status: build.status,
+    }));
+}
+
+#[tracing::instrument(name = "build_service", skip(state, build), fields(project_id = %project_id))]
+pub async fn create_build(
+    state: &AppState,
+    project_id: Uuid,
+    build: &NewBuild,
+) -> Result<Build, AppError> {
+    let build = build_repo::create_build(&state.db, project_id, build).await?;
+    publish_build_updated(state, &build);
+    Ok(build)
+}
+
+#[tracing::instrument(name = "build_service", skip(state), fields(project_id = %project_id))]
+pub async fn list_builds(
+    state: &AppState,
+    project_id: Uuid,
+    status: Option<BuildStatus>,
+    limit: i64,
+) -> Result<Vec<Build>, AppError> {
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_LIMIT
+        )));
+    }
+    build_repo::list_builds(&state.db, project_id, status, limit).await
+}
+
+/// A build belonging to one of `user_id`'s projects.
+#[tracing::instrument(name = "build_service", skip(state), fields(build_id = %build_id))]
+pub async fn get_build(state: &AppState, user_id: Uuid, build_id: Uuid) -> Result<Build, AppError> {
+    let not_found = || AppError::NotFound("Build not found".into());
+    let build = build_repo::get_build(&state.db, build_id)
+        .await?
+        .ok_or_else(not_found)?;
+    project_repo::get_project(&state.db, build.project_id, user_id)
+        .await?
+        .ok_or_else(not_found)?;
+    Ok(build)
+}
+
+/// A build along with the automatic retries of its run.
+pub async fn get_build_detail(
+    state: &AppState,
+    user_id: Uuid,
+    build_id: Uuid,
+) -> Result<BuildDetail, AppError> {
+    let build = get_build(state, user_id, build_id).await?;
+    let retries = build_retry_service::list_retries(state, &build).await?;
+    Ok(BuildDetail { build, retries })
+}
+
+#[tracing::instrument(name = "build_service", skip(state, conclusion), fields(build_id = %build_id))]
+pub async fn update_build_status(
+    state: &AppState,
+    user_id: Uuid,
+    build_id: Uuid,
+    status: BuildStatus,
+    conclusion: Option<&str>,
+) -> Result<Build, AppError> {
+    let build = get_build(state, user_id, build_id).await?;
+    if build.source != "sentinai" {
+        return Err(AppError::BadRequest(format!(
+            "Builds reported by {} are updated by their webhooks",
+            build.source
+        )));
+    }
+
+    let build = build_repo::update_build_status(&state.db, build_id, status, conclusion).await?;
+    publish_build_updated(state, &build);
+    if build.status == BuildStatus::Failed {
+        build_failure_service::spawn_classification(state, build.id);
+    }
+    Ok(build)
+}
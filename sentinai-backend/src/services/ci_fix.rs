@@ -0,0 +1,611 @@
+//! Patches that repair common CI failures: workflow edits for GitHub Actions
+//! and dependency pins for Cargo manifests.
+
+use std::collections::BTreeMap;
+
+use crate::domain::build::FailureCategory;
+use crate::domain::remediation::{FileChange, FixStrategy, PlannedFix};
+use crate::services::dependency_bump;
+use crate::services::repository::RepoSnapshot;
+
+/// The current major version of widely used actions.
+const LATEST_ACTIONS: [(&str, u32); 9] = [
+    ("actions/checkout", 4),
+    ("actions/setup-node", 4),
+    ("actions/setup-python", 5),
+    ("actions/setup-java", 4),
+    ("actions/setup-go", 5),
+    ("actions/cache", 4),
+    ("actions/upload-artifact", 4),
+    ("actions/download-artifact", 4),
+    ("actions/github-script", 7),
+];
+/// GitHub-hosted runners stop jobs after six hours whatever the workflow says.
+const MAX_TIMEOUT_MINUTES: u32 = 360;
+
+/// The strategies worth trying for a failure category.
+pub fn strategies_for(category: FailureCategory) -> &'static [FixStrategy] {
+    match category {
+        FailureCategory::Lint => &[FixStrategy::AddFmtStep],
+        FailureCategory::Timeout => &[FixStrategy::RaiseTimeout, FixStrategy::AddCacheStep],
+        FailureCategory::DependencyResolution => &[
+            FixStrategy::PinDependency,
+            FixStrategy::AddCacheStep,
+            FixStrategy::BumpActions,
+        ],
+        FailureCategory::CompileError => &[FixStrategy::PinDependency],
+        FailureCategory::InfraFlaky => &[FixStrategy::AddCacheStep, FixStrategy::BumpActions],
+        FailureCategory::Unknown => &[FixStrategy::BumpActions],
+        FailureCategory::TestFailure
+        | FailureCategory::OutOfMemory
+        | FailureCategory::MissingSecret => &[],
+    }
+}
+
+fn is_workflow(path: &str) -> bool {
+    path.starts_with(".github/workflows/") && (path.ends_with(".yml") || path.ends_with(".yaml"))
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// The workflow's top-level `name`.
+fn workflow_name(contents: &str) -> Option<&str> {
+    contents.lines().find_map(|line| {
+        line.strip_prefix("name:")
+            .map(|name| name.trim().trim_matches(['"', '\'']))
+    })
+}
+
+/// The text of a step key such as `run:` or `uses:`, whether or not it opens
+/// the step with `- `.
+fn step_key<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let trimmed = line.trim_start();
+    let trimmed = trimmed.strip_prefix("- ").unwrap_or(trimmed).trim_start();
+    trimmed.strip_prefix(key).map(str::trim)
+}
+
+/// The line opening the step that contains line `index`.
+fn step_start(lines: &[&str], index: usize) -> Option<usize> {
+    if lines[index].trim_start().starts_with("- ") {
+        return Some(index);
+    }
+    let key_indent = indent(lines[index]);
+    (0..index).rev().find(|&i| {
+        let trimmed = lines[i].trim_start();
+        trimmed.starts_with("- ") && indent(lines[i]) < key_indent
+    })
+}
+
+/// The line after the last line of the step opening at `start`.
+fn step_end(lines: &[&str], start: usize) -> usize {
+    let dash = indent(lines[start]);
+    (start + 1..lines.len())
+        .find(|&i| !lines[i].trim().is_empty() && indent(lines[i]) <= dash)
+        .unwrap_or(lines.len())
+}
+
+fn runs_cargo(lines: &[&str], index: usize) -> bool {
+    match step_key(lines[index], "run:") {
+        Some("|") | Some(">") => lines[index + 1..]
+            .iter()
+            .find(|line| !line.trim().is_empty())
+            .is_some_and(|line| line.trim_start().starts_with("cargo ")),
+        Some(command) => command.starts_with("cargo "),
+        None => false,
+    }
+}
+
+fn join(lines: &[String], trailing_newline: bool) -> String {
+    let mut contents = lines.join("\n");
+    if trailing_newline {
+        contents.push('\n');
+    }
+    contents
+}
+
+/// Moves actions in [`LATEST_ACTIONS`] to their current major version.
+/// Returns the new contents and a note per change.
+pub fn bump_actions(contents: &str) -> Option<(String, Vec<String>)> {
+    let mut notes = Vec::new();
+    let lines: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            let Some(reference) = step_key(line, "uses:") else {
+                return line.to_string();
+            };
+            let reference = reference
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .trim_matches(['"', '\'']);
+            let Some((action, version)) = reference.split_once('@') else {
+                return line.to_string();
+            };
+            let Some(&(_, latest)) = LATEST_ACTIONS.iter().find(|(name, _)| *name == action) else {
+                return line.to_string();
+            };
+            let major = version
+                .strip_prefix('v')
+                .and_then(|v| v.split('.').next())
+                .and_then(|v| v.parse::<u32>().ok());
+            match major {
+                Some(major) if major < latest => {
+                    notes.push(format!("{} {} → v{}", action, version, latest));
+                    line.replacen(reference, &format!("{}@v{}", action, latest), 1)
+                }
+                _ => line.to_string(),
+            }
+        })
+        .collect();
+    (!notes.is_empty()).then(|| (join(&lines, contents.ends_with('\n')), notes))
+}
+
+/// Doubles every `timeout-minutes` below the hosted runner limit.
+pub fn raise_timeouts(contents: &str) -> Option<(String, Vec<String>)> {
+    let mut notes = Vec::new();
+    let lines: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let Some(value) = trimmed.strip_prefix("timeout-minutes:") else {
+                return line.to_string();
+            };
+            let (minutes, comment) = match value.split_once('#') {
+                Some((minutes, comment)) => (minutes, Some(comment)),
+                None => (value, None),
+            };
+            match minutes.trim().parse::<u32>() {
+                Ok(minutes) if minutes < MAX_TIMEOUT_MINUTES => {
+                    let raised = (minutes * 2).min(MAX_TIMEOUT_MINUTES);
+                    notes.push(format!("timeout-minutes {} → {}", minutes, raised));
+                    let mut line = format!("{}timeout-minutes: {}", &line[..indent(line)], raised);
+                    if let Some(comment) = comment {
+                        line.push_str(" #");
+                        line.push_str(comment);
+                    }
+                    line
+                }
+                _ => line.to_string(),
+            }
+        })
+        .collect();
+    (!notes.is_empty()).then(|| (join(&lines, contents.ends_with('\n')), notes))
+}
+
+/// Adds a `cargo fmt --check` step before the first step that runs Cargo.
+pub fn add_fmt_step(contents: &str) -> Option<String> {
+    if contents.contains("cargo fmt") {
+        return None;
+    }
+    let lines: Vec<&str> = contents.lines().collect();
+    let first = (0..lines.len()).find(|&i| runs_cargo(&lines, i))?;
+    let start = step_start(&lines, first)?;
+    let pad = " ".repeat(indent(lines[start]));
+
+    let mut updated: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    updated.splice(
+        start..start,
+        [
+            format!("{}- name: Check formatting", pad),
+            format!("{}  run: cargo fmt --all -- --check", pad),
+        ],
+    );
+    Some(join(&updated, contents.ends_with('\n')))
+}
+
+/// Adds `Swatinem/rust-cache` after the checkout step of every job that runs
+/// Cargo, unless the workflow already caches.
+pub fn add_cache_step(contents: &str) -> Option<String> {
+    if contents.contains("rust-cache") || contents.contains("actions/cache") {
+        return None;
+    }
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut inserts = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let is_checkout =
+            step_key(line, "uses:").is_some_and(|uses| uses.starts_with("actions/checkout@"));
+        let Some(start) = is_checkout.then(|| step_start(&lines, i)).flatten() else {
+            continue;
+        };
+        let dash = indent(lines[start]);
+        let steps_end = (start + 1..lines.len())
+            .find(|&j| !lines[j].trim().is_empty() && indent(lines[j]) < dash)
+            .unwrap_or(lines.len());
+        if (start..steps_end).any(|j| runs_cargo(&lines, j)) {
+            inserts.push((step_end(&lines, start), dash));
+        }
+    }
+    if inserts.is_empty() {
+        return None;
+    }
+
+    let mut updated: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    for (at, dash) in inserts.into_iter().rev() {
+        updated.insert(
+            at,
+            format!("{}- uses: Swatinem/rust-cache@v2", " ".repeat(dash)),
+        );
+    }
+    Some(join(&updated, contents.ends_with('\n')))
+}
+
+/// Splits `serde-1.0.200` into its name and version.
+fn split_package_dir(dir: &str) -> Option<(&str, &str)> {
+    let at = dir
+        .rmatch_indices('-')
+        .map(|(i, _)| i)
+        .find(|&i| dir[i + 1..].starts_with(|c: char| c.is_ascii_digit()))?;
+    Some((&dir[..at], &dir[at + 1..]))
+}
+
+/// Crate releases the log blames for the failure: those that need a newer
+/// compiler, and those in the registry that an error points into.
+pub fn broken_releases(log: &[String]) -> Vec<(String, String)> {
+    let mut releases: Vec<(String, String)> = Vec::new();
+    let mut after_error = false;
+    for line in log {
+        let trimmed = line.trim();
+        let release = if let Some(rest) = trimmed
+            .split_once("package `")
+            .filter(|_| trimmed.contains("cannot be built because it requires rustc"))
+            .map(|(_, rest)| rest)
+        {
+            rest.split_once('`')
+                .and_then(|(package, _)| package.split_once(" v"))
+        } else if let Some(path) = trimmed.strip_prefix("--> ").filter(|_| after_error) {
+            path.split_once("/registry/src/")
+                .and_then(|(_, rest)| rest.split('/').nth(1))
+                .and_then(split_package_dir)
+        } else {
+            None
+        };
+        if let Some((name, version)) = release {
+            if !releases.iter().any(|(n, v)| n == name && v == version) {
+                releases.push((name.to_string(), version.to_string()));
+            }
+        }
+        if !trimmed.is_empty() {
+            after_error = trimmed.starts_with("error");
+        }
+    }
+    releases
+}
+
+fn plan(
+    strategy: FixStrategy,
+    files: &mut BTreeMap<String, String>,
+    workflows: &[String],
+    log: &[String],
+) -> Option<PlannedFix> {
+    let mut changed = Vec::new();
+    let mut notes = Vec::new();
+    let targets: Vec<String> = match strategy {
+        FixStrategy::PinDependency => files
+            .keys()
+            .filter(|path| path.rsplit('/').next() == Some("Cargo.toml"))
+            .cloned()
+            .collect(),
+        _ => workflows.to_vec(),
+    };
+    let releases = broken_releases(log);
+    let mut capped: Vec<(String, String)> = Vec::new();
+
+    for path in targets {
+        let before = files.get(&path).cloned().unwrap_or_default();
+        let after = match strategy {
+            FixStrategy::AddFmtStep => add_fmt_step(&before),
+            FixStrategy::AddCacheStep => add_cache_step(&before),
+            FixStrategy::BumpActions | FixStrategy::RaiseTimeout => {
+                let edited = if strategy == FixStrategy::BumpActions {
+                    bump_actions(&before)
+                } else {
+                    raise_timeouts(&before)
+                };
+                edited.map(|(after, file_notes)| {
+                    notes.extend(file_notes.into_iter().map(|n| format!("{} in {}", n, path)));
+                    after
+                })
+            }
+            FixStrategy::PinDependency => {
+                let mut current = before.clone();
+                for (name, version) in &releases {
+                    if let Some(capped_manifest) =
+                        dependency_bump::cap_cargo_manifest(&current, name, version)
+                    {
+                        notes.push(format!("{} kept below {} in {}", name, version, path));
+                        current = capped_manifest;
+                        if !capped.contains(&(name.clone(), version.clone())) {
+                            capped.push((name.clone(), version.clone()));
+                        }
+                    }
+                }
+                (current != before).then_some(current)
+            }
+        };
+        if let Some(after) = after {
+            files.insert(path.clone(), after.clone());
+            changed.push(FileChange {
+                path,
+                before,
+                after,
+            });
+        }
+    }
+    if changed.is_empty() {
+        return None;
+    }
+    // The lockfile needs the version a registry query would pick, so it is
+    // left for the author to refresh.
+    for (name, version) in &capped {
+        let locked = format!("name = \"{}\"\nversion = \"{}\"\n", name, version);
+        for (path, contents) in files.iter() {
+            if path.rsplit('/').next() == Some("Cargo.lock") && contents.contains(&locked) {
+                notes.push(format!(
+                    "{} still locks {} {}; run `cargo update -p {}` on this branch before merging, or builds with `--locked` keep failing",
+                    path, name, version, name
+                ));
+            }
+        }
+    }
+
+    let (title, summary) = match strategy {
+        FixStrategy::AddFmtStep => (
+            "Check formatting in CI".to_string(),
+            "Adds a `cargo fmt --all -- --check` step before the first Cargo step, so formatting problems fail fast with a readable diff.",
+        ),
+        FixStrategy::BumpActions => (
+            "Update outdated GitHub Actions".to_string(),
+            "Moves actions to their current major version; older majors run on deprecated Node.js runtimes and are being switched off.",
+        ),
+        FixStrategy::RaiseTimeout => (
+            "Raise CI job timeouts".to_string(),
+            "Doubles the jobs' `timeout-minutes`, up to the six-hour limit of GitHub-hosted runners.",
+        ),
+        FixStrategy::AddCacheStep => (
+            "Cache Cargo builds in CI".to_string(),
+            "Adds `Swatinem/rust-cache@v2` after checkout, so dependencies and build artifacts are reused between runs instead of downloaded and rebuilt.",
+        ),
+        FixStrategy::PinDependency => (
+            match releases.as_slice() {
+                [(name, version)] => format!("Pin {} below {}", name, version),
+                _ => "Pin dependencies below broken releases".to_string(),
+            },
+            "Keeps dependencies below the releases the build failed on until they are fixed upstream.",
+        ),
+    };
+    let mut description = summary.to_string();
+    for note in notes {
+        description.push_str(&format!("\n- {}", note));
+    }
+
+    Some(PlannedFix {
+        strategy,
+        title,
+        description,
+        files: changed,
+    })
+}
+
+/// Plans a fix per strategy that changes the repository. Each fix applies on
+/// top of the ones before it. Fixes to workflows are limited to the workflow
+/// named `workflow` when one matches.
+pub fn plan_fixes(
+    snapshot: &RepoSnapshot,
+    strategies: &[FixStrategy],
+    workflow: Option<&str>,
+    log: &[String],
+) -> Vec<PlannedFix> {
+    let mut files: BTreeMap<String, String> = snapshot
+        .files()
+        .filter(|(path, _)| {
+            is_workflow(path)
+                || matches!(path.rsplit('/').next(), Some("Cargo.toml" | "Cargo.lock"))
+        })
+        .map(|(path, contents)| (path.to_string(), contents.to_string()))
+        .collect();
+
+    let all: Vec<String> = files
+        .keys()
+        .filter(|path| is_workflow(path))
+        .cloned()
+        .collect();
+    let named: Vec<String> = all
+        .iter()
+        .filter(|path| workflow.is_some() && workflow_name(&files[path.as_str()]) == workflow)
+        .cloned()
+        .collect();
+    let workflows = if named.is_empty() { all } else { named };
+
+    let mut seen = Vec::new();
+    strategies
+        .iter()
+        .filter(|strategy| {
+            let first = !seen.contains(*strategy);
+            seen.push(**strategy);
+            first
+        })
+        .filter_map(|&strategy| plan(strategy, &mut files, &workflows, log))
+        .collect()
+}
+
+/// The pull request title and body for a set of fixes.
+pub fn describe_fixes(
+    fixes: &[PlannedFix],
+    category: FailureCategory,
+    build_name: Option<&str>,
+    build_url: Option<&str>,
+) -> (String, String) {
+    let title = match fixes {
+        [fix] => fix.title.clone(),
+        _ => format!("Fix CI failure in {}", build_name.unwrap_or("build")),
+    };
+    let category = serde_json::to_value(category)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default();
+    let build = match build_url {
+        Some(url) => format!("[{}]({})", build_name.unwrap_or("The build"), url),
+        None => build_name.unwrap_or("The build").to_string(),
+    };
+    let mut body = format!("{} failed with `{}`.\n", build, category);
+    for fix in fixes {
+        body.push_str(&format!("\n### {}\n\n{}\n", fix.title, fix.description));
+    }
+    (title, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = r#"name: CI
+on: [push]
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    timeout-minutes: 30 # keep it short
+    steps:
+      - uses: actions/checkout@v3
+        with:
+          fetch-depth: 0
+      - uses: actions/cache@v2.1.0
+      - name: Test
+        run: cargo test
+  lint:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: "actions/setup-node@v4"
+      - run: |
+          cargo clippy -- -D warnings
+"#;
+
+    #[test]
+    fn test_bump_actions() {
+        let (updated, notes) = bump_actions(WORKFLOW).unwrap();
+        assert!(updated.contains("      - uses: actions/checkout@v4\n"));
+        assert!(updated.contains("      - uses: actions/cache@v4\n"));
+        assert!(updated.contains("\"actions/setup-node@v4\""));
+        assert_eq!(
+            notes,
+            vec!["actions/checkout v3 → v4", "actions/cache v2.1.0 → v4"]
+        );
+        assert!(bump_actions(&updated).is_none());
+    }
+
+    #[test]
+    fn test_raise_timeouts() {
+        let (updated, notes) = raise_timeouts(WORKFLOW).unwrap();
+        assert!(updated.contains("    timeout-minutes: 60 # keep it short\n"));
+        assert_eq!(notes, vec!["timeout-minutes 30 → 60"]);
+        assert!(raise_timeouts("jobs:\n  a:\n    timeout-minutes: 360\n").is_none());
+    }
+
+    #[test]
+    fn test_add_fmt_step() {
+        let updated = add_fmt_step(WORKFLOW).unwrap();
+        assert!(updated.contains(
+            "      - uses: actions/cache@v2.1.0\n      - name: Check formatting\n        run: cargo fmt --all -- --check\n      - name: Test\n"
+        ));
+        assert!(add_fmt_step(&updated).is_none());
+    }
+
+    #[test]
+    fn test_add_cache_step() {
+        let workflow = WORKFLOW.replace("      - uses: actions/cache@v2.1.0\n", "");
+        let updated = add_cache_step(&workflow).unwrap();
+        assert!(updated.contains(
+            "          fetch-depth: 0\n      - uses: Swatinem/rust-cache@v2\n      - name: Test\n"
+        ));
+        // The lint job has no checkout step to follow.
+        assert_eq!(updated.matches("rust-cache").count(), 1);
+        assert!(add_cache_step(WORKFLOW).is_none());
+    }
+
+    #[test]
+    fn test_broken_releases() {
+        let log: Vec<String> = [
+            "error: package `time v0.3.36` cannot be built because it requires rustc 1.67.0 or newer",
+            "warning: unused import",
+            "  --> /home/runner/.cargo/registry/src/index.crates.io-6f17d22bba15001f/log-0.4.20/src/lib.rs:1:5",
+            "error[E0282]: type annotations needed for `Box<_>`",
+            "  --> /home/runner/.cargo/registry/src/index.crates.io-6f17d22bba15001f/time-macros-impl-0.3.1/src/lib.rs:83:9",
+            "error: could not compile `app`",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+        assert_eq!(
+            broken_releases(&log),
+            vec![
+                ("time".to_string(), "0.3.36".to_string()),
+                ("time-macros-impl".to_string(), "0.3.1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_fixes_stacks_fixes() {
+        let snapshot = RepoSnapshot::from_files([
+            (".github/workflows/ci.yml", WORKFLOW),
+            (
+                ".github/workflows/release.yml",
+                "name: Release\njobs:\n  a:\n    timeout-minutes: 10\n",
+            ),
+            ("Cargo.toml", "[dependencies]\ntime = \"0.3\"\n"),
+            (
+                "Cargo.lock",
+                "[[package]]\nname = \"time\"\nversion = \"0.3.36\"\n",
+            ),
+            ("README.md", "timeout-minutes: 5\n"),
+        ]);
+        let log = vec!["error: package `time v0.3.36` cannot be built because it requires rustc 1.67.0 or newer".to_string()];
+        let fixes = plan_fixes(
+            &snapshot,
+            &[
+                FixStrategy::BumpActions,
+                FixStrategy::RaiseTimeout,
+                FixStrategy::PinDependency,
+                FixStrategy::BumpActions,
+            ],
+            Some("CI"),
+            &log,
+        );
+        assert_eq!(
+            fixes.iter().map(|f| f.strategy).collect::<Vec<_>>(),
+            vec![
+                FixStrategy::BumpActions,
+                FixStrategy::RaiseTimeout,
+                FixStrategy::PinDependency
+            ]
+        );
+        // Only the failing workflow is edited, and later fixes build on
+        // earlier ones.
+        let change = &fixes[1].files[0];
+        assert_eq!(fixes[1].files.len(), 1);
+        assert_eq!(change.path, ".github/workflows/ci.yml");
+        assert!(change.before.contains("actions/checkout@v4"));
+        assert!(change.after.contains("timeout-minutes: 60"));
+        assert_eq!(fixes[2].title, "Pin time below 0.3.36");
+        assert_eq!(
+            fixes[2].files[0].after,
+            "[dependencies]\ntime = \"0.3, <0.3.36\"\n"
+        );
+        assert!(fixes[2]
+            .description
+            .contains("Cargo.lock still locks time 0.3.36; run `cargo update -p time`"));
+
+        let (title, body) = describe_fixes(
+            &fixes[..1],
+            FailureCategory::Unknown,
+            Some("CI"),
+            Some("https://github.com/org/repo/actions/runs/1"),
+        );
+        assert_eq!(title, "Update outdated GitHub Actions");
+        assert!(body.starts_with(
+            "[CI](https://github.com/org/repo/actions/runs/1) failed with `unknown`.\n"
+        ));
+        assert!(body.contains("- actions/checkout v3 → v4 in .github/workflows/ci.yml"));
+    }
+}
@@ -0,0 +1,784 @@
+//! Picks the smallest upgrade that fixes a vulnerable dependency and rewrites
+//! manifests and lockfiles to use it.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::domain::remediation::{PackageUpgrade, SkippedFinding};
+use crate::domain::sbom::{Ecosystem, Package};
+use crate::domain::security::SecurityFinding;
+use toml_edit::{DocumentMut, Item, Value};
+
+const CARGO_DEPENDENCY_TABLES: [&str; 3] =
+    ["dependencies", "dev-dependencies", "build-dependencies"];
+const NPM_DEPENDENCY_FIELDS: [&str; 4] = [
+    "dependencies",
+    "devDependencies",
+    "optionalDependencies",
+    "peerDependencies",
+];
+
+/// A version number loose enough to compare Cargo, npm and PyPI releases:
+/// numeric release components followed by an optional pre-release tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Version {
+    release: Vec<u64>,
+    pre: Option<String>,
+}
+
+impl Version {
+    fn parse(version: &str) -> Option<Version> {
+        let version = version.trim().trim_start_matches('v');
+        let version = version.split('+').next().unwrap_or(version);
+        let end = version
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(version.len());
+        let (release, rest) = version.split_at(end);
+        let release: Vec<u64> = release
+            .trim_end_matches('.')
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<_>>()?;
+        let pre = rest.trim_start_matches(['-', '.']);
+        Some(Version {
+            release,
+            pre: (!pre.is_empty()).then(|| pre.to_string()),
+        })
+    }
+
+    fn component(&self, idx: usize) -> u64 {
+        self.release.get(idx).copied().unwrap_or(0)
+    }
+
+    /// Releases on the same line are expected to be API compatible: same
+    /// major version, or same minor version while the major is zero.
+    fn compatible_line(&self) -> (u64, u64) {
+        match self.component(0) {
+            0 => (0, self.component(1)),
+            major => (major, 0),
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.release.len().max(other.release.len());
+        (0..len)
+            .map(|idx| self.component(idx).cmp(&other.component(idx)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Upgrade {
+    /// The current version already contains a fix.
+    NotNeeded,
+    To(String),
+    /// No fixed version is newer than the current one.
+    Unavailable,
+}
+
+/// The smallest fixed version above `current`, preferring one on the same
+/// compatible release line so the upgrade is least likely to break callers.
+pub fn minimal_safe_upgrade(current: &str, fixed_versions: &[String]) -> Upgrade {
+    let Some(current_version) = Version::parse(current) else {
+        return Upgrade::Unavailable;
+    };
+    let fixed: Vec<(Version, &String)> = fixed_versions
+        .iter()
+        .filter_map(|raw| Version::parse(raw).map(|version| (version, raw)))
+        .collect();
+    if fixed.is_empty() {
+        return Upgrade::Unavailable;
+    }
+
+    let line = current_version.compatible_line();
+    let fixed_on_line = fixed
+        .iter()
+        .any(|(version, _)| version.compatible_line() == line && *version <= current_version);
+    let past_every_fix = fixed.iter().all(|(version, _)| *version <= current_version);
+    if fixed_on_line || past_every_fix {
+        return Upgrade::NotNeeded;
+    }
+
+    let newer = fixed
+        .iter()
+        .filter(|(version, _)| *version > current_version);
+    let same_line = newer
+        .clone()
+        .filter(|(version, _)| version.compatible_line() == line)
+        .min_by(|a, b| a.0.cmp(&b.0));
+    match same_line.or_else(|| newer.min_by(|a, b| a.0.cmp(&b.0))) {
+        Some((_, raw)) => Upgrade::To(raw.to_string()),
+        None => Upgrade::Unavailable,
+    }
+}
+
+fn normalize_name(ecosystem: Ecosystem, name: &str) -> String {
+    match ecosystem {
+        Ecosystem::Pypi => name.to_lowercase().replace(['_', '.'], "-"),
+        _ => name.to_string(),
+    }
+}
+
+/// Matches open findings that carry fixed versions to the resolved packages
+/// they affect and decides on one upgrade per package version.
+pub fn plan_upgrades(
+    packages: &[Package],
+    findings: &[SecurityFinding],
+) -> (Vec<PackageUpgrade>, Vec<SkippedFinding>) {
+    let mut planned: BTreeMap<(Ecosystem, String, String), PackageUpgrade> = BTreeMap::new();
+    let mut skipped = Vec::new();
+
+    for finding in findings {
+        let Some(package_name) = finding.package.as_deref() else {
+            continue;
+        };
+        let skip = |reason: String| SkippedFinding {
+            finding_id: finding.id,
+            package: package_name.to_string(),
+            reason,
+        };
+
+        let affected: Vec<&Package> = packages
+            .iter()
+            .filter(|p| {
+                normalize_name(p.ecosystem, &p.name) == normalize_name(p.ecosystem, package_name)
+            })
+            .collect();
+        if affected.is_empty() {
+            skipped.push(skip("Package not found in any lockfile".into()));
+            continue;
+        }
+
+        let mut upgraded = false;
+        let mut unavailable = Vec::new();
+        for package in affected {
+            match minimal_safe_upgrade(&package.version, &finding.fixed_versions) {
+                Upgrade::NotNeeded => {}
+                Upgrade::Unavailable => unavailable.push(package.version.as_str()),
+                Upgrade::To(target) => {
+                    upgraded = true;
+                    let key = (
+                        package.ecosystem,
+                        package.name.clone(),
+                        package.version.clone(),
+                    );
+                    let entry = planned.entry(key).or_insert_with(|| PackageUpgrade {
+                        ecosystem: package.ecosystem,
+                        name: package.name.clone(),
+                        from_version: package.version.clone(),
+                        to_version: target.clone(),
+                        finding_ids: Vec::new(),
+                    });
+                    if Version::parse(&target) > Version::parse(&entry.to_version) {
+                        entry.to_version = target;
+                    }
+                    if !entry.finding_ids.contains(&finding.id) {
+                        entry.finding_ids.push(finding.id);
+                    }
+                }
+            }
+        }
+        if !unavailable.is_empty() {
+            skipped.push(skip(format!(
+                "No fixed version newer than {}",
+                unavailable.join(", ")
+            )));
+        } else if !upgraded {
+            skipped.push(skip("Already at a fixed version".into()));
+        }
+    }
+
+    (planned.into_values().collect(), skipped)
+}
+
+/// Points a simple version requirement such as `^1.2.3`, `~1.2` or `1.2` at
+/// `version`, keeping its operator. Ranges, wildcards and non-registry
+/// sources are left alone.
+fn rewrite_requirement(requirement: &str, version: &str) -> Option<String> {
+    let trimmed = requirement.trim();
+    let operator_end = trimmed
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (operator, rest) = trimmed.split_at(operator_end);
+    if !matches!(operator, "" | "^" | "~" | "=" | ">=" | "v")
+        || rest.is_empty()
+        || rest.contains([' ', ',', '*', '|', '<', '>'])
+        || rest.contains(".x")
+    {
+        return None;
+    }
+    Some(format!("{}{}", operator, version))
+}
+
+fn set_requirement(value: &mut Value, rewrite: &dyn Fn(&str) -> Option<String>) -> bool {
+    let Some(requirement) = value.as_str() else {
+        return false;
+    };
+    let Some(updated) = rewrite(requirement) else {
+        return false;
+    };
+    let decor = value.decor().clone();
+    *value = Value::from(updated);
+    *value.decor_mut() = decor;
+    true
+}
+
+fn edit_cargo_table(
+    table: &mut Item,
+    name: &str,
+    rewrite: &dyn Fn(&str) -> Option<String>,
+) -> bool {
+    let Some(table) = table.as_table_like_mut() else {
+        return false;
+    };
+    let mut changed = false;
+    for (key, dependency) in table.iter_mut() {
+        if let Some(value) = dependency.as_value_mut().filter(|v| v.is_str()) {
+            if key.get() == name {
+                changed |= set_requirement(value, rewrite);
+            }
+            continue;
+        }
+        let Some(detail) = dependency.as_table_like_mut() else {
+            continue;
+        };
+        let package = detail
+            .get("package")
+            .and_then(Item::as_str)
+            .unwrap_or(key.get());
+        if package != name {
+            continue;
+        }
+        if let Some(value) = detail.get_mut("version").and_then(Item::as_value_mut) {
+            changed |= set_requirement(value, rewrite);
+        }
+    }
+    changed
+}
+
+/// Rewrites the requirement on `name` in every dependency table of a
+/// `Cargo.toml`, preserving formatting and comments.
+fn edit_cargo_manifest(
+    contents: &str,
+    name: &str,
+    rewrite: &dyn Fn(&str) -> Option<String>,
+) -> Option<String> {
+    let mut document: DocumentMut = contents.parse().ok()?;
+    let mut changed = false;
+
+    for table in CARGO_DEPENDENCY_TABLES {
+        if let Some(item) = document.get_mut(table) {
+            changed |= edit_cargo_table(item, name, rewrite);
+        }
+    }
+    if let Some(targets) = document.get_mut("target").and_then(Item::as_table_like_mut) {
+        for (_, target) in targets.iter_mut() {
+            let Some(target) = target.as_table_like_mut() else {
+                continue;
+            };
+            for table in CARGO_DEPENDENCY_TABLES {
+                if let Some(item) = target.get_mut(table) {
+                    changed |= edit_cargo_table(item, name, rewrite);
+                }
+            }
+        }
+    }
+    if let Some(item) = document
+        .get_mut("workspace")
+        .and_then(|workspace| workspace.get_mut("dependencies"))
+    {
+        changed |= edit_cargo_table(item, name, rewrite);
+    }
+
+    changed.then(|| document.to_string())
+}
+
+/// Raises the requirement on `name` in every dependency table of a
+/// `Cargo.toml`.
+pub fn bump_cargo_manifest(contents: &str, name: &str, version: &str) -> Option<String> {
+    edit_cargo_manifest(contents, name, &|requirement| {
+        rewrite_requirement(requirement, version)
+    })
+}
+
+/// The lowest version a Cargo requirement admits, if it has a lower bound.
+fn requirement_floor(requirement: &semver::VersionReq) -> Option<semver::Version> {
+    requirement
+        .comparators
+        .iter()
+        .filter_map(|comparator| match comparator.op {
+            semver::Op::Exact
+            | semver::Op::GreaterEq
+            | semver::Op::Tilde
+            | semver::Op::Caret
+            | semver::Op::Wildcard => Some(semver::Version {
+                major: comparator.major,
+                minor: comparator.minor.unwrap_or(0),
+                patch: comparator.patch.unwrap_or(0),
+                pre: comparator.pre.clone(),
+                build: semver::BuildMetadata::EMPTY,
+            }),
+            _ => None,
+        })
+        .max()
+}
+
+/// Keeps `name` below a broken release, e.g. `1.2` becomes `1.2, <1.2.3`.
+/// Requirements that already have an upper bound or an exact version are
+/// left alone, as are those no release below `broken` satisfies: capping
+/// `1.80` below `1.80.0` would leave no version to build with.
+pub fn cap_cargo_manifest(contents: &str, name: &str, broken: &str) -> Option<String> {
+    let broken_version = semver::Version::parse(broken).ok()?;
+    edit_cargo_manifest(contents, name, &|requirement| {
+        let requirement = requirement.trim();
+        if requirement.is_empty()
+            || requirement.contains(['<', '*'])
+            || requirement.starts_with('=')
+        {
+            return None;
+        }
+        let parsed = semver::VersionReq::parse(requirement).ok()?;
+        let floor = requirement_floor(&parsed)?;
+        (parsed.matches(&floor) && floor < broken_version)
+            .then(|| format!("{}, <{}", requirement, broken))
+    })
+}
+
+/// Moves one `[[package]]` entry of a `Cargo.lock` to a new version,
+/// updating its checksum and any references that pin the old version. Without
+/// a checksum the stale one is dropped so that Cargo records a fresh one.
+pub fn bump_cargo_lock(
+    contents: &str,
+    name: &str,
+    from: &str,
+    to: &str,
+    checksum: Option<&str>,
+) -> Option<String> {
+    let name_line = format!("name = \"{}\"", name);
+    let version_line = format!("version = \"{}\"", from);
+    let old_reference = format!("\"{} {}", name, from);
+    let new_reference = format!("\"{} {}", name, to);
+
+    let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+    let mut out = String::with_capacity(contents.len());
+    let mut changed = false;
+    let mut in_target = false;
+
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed == "[[package]]" {
+            in_target = lines.get(idx + 1).map(|l| l.trim()) == Some(name_line.as_str())
+                && lines.get(idx + 2).map(|l| l.trim()) == Some(version_line.as_str());
+        }
+
+        if in_target && trimmed == version_line {
+            out.push_str(&line.replace(from, to));
+            changed = true;
+        } else if in_target && trimmed.starts_with("checksum = ") {
+            if let Some(checksum) = checksum {
+                out.push_str(&format!("checksum = \"{}\"\n", checksum));
+            }
+        } else if trimmed.starts_with(&old_reference)
+            && matches!(
+                trimmed[old_reference.len()..].chars().next(),
+                Some('"') | Some(' ')
+            )
+        {
+            out.push_str(&line.replacen(&old_reference, &new_reference, 1));
+            changed = true;
+        } else {
+            out.push_str(line);
+        }
+    }
+
+    changed.then_some(out)
+}
+
+/// Rewrites the requirement on `name` in a `package.json` in place, leaving
+/// the rest of the file byte-for-byte untouched.
+pub fn bump_package_json(contents: &str, name: &str, version: &str) -> Option<String> {
+    let manifest: serde_json::Value = serde_json::from_str(contents).ok()?;
+    let mut updated = contents.to_string();
+    let mut changed = false;
+
+    for field in NPM_DEPENDENCY_FIELDS {
+        let Some(requirement) = manifest
+            .get(field)
+            .and_then(|deps| deps.get(name))
+            .and_then(serde_json::Value::as_str)
+        else {
+            continue;
+        };
+        let Some(new_requirement) = rewrite_requirement(requirement, version) else {
+            continue;
+        };
+
+        let key = serde_json::to_string(name).ok()?;
+        let old_value = serde_json::to_string(requirement).ok()?;
+        let new_value = serde_json::to_string(&new_requirement).ok()?;
+        let mut search_from = 0;
+        while let Some(pos) = updated[search_from..].find(&key) {
+            let key_end = search_from + pos + key.len();
+            let after_key = updated[key_end..].trim_start();
+            let Some(after_colon) = after_key.strip_prefix(':') else {
+                search_from = key_end;
+                continue;
+            };
+            let value_start = updated.len() - after_colon.trim_start().len();
+            if updated[value_start..].starts_with(&old_value) {
+                updated.replace_range(value_start..value_start + old_value.len(), &new_value);
+                changed = true;
+            }
+            search_from = key_end;
+        }
+    }
+
+    changed.then_some(updated)
+}
+
+/// Updates the pin on `name` in a requirements file. Hash-checked entries
+/// are skipped, since their hashes would no longer match.
+pub fn bump_requirements(contents: &str, name: &str, version: &str) -> Option<String> {
+    let target = normalize_name(Ecosystem::Pypi, name);
+    let mut out = String::with_capacity(contents.len());
+    let mut changed = false;
+
+    for line in contents.split_inclusive('\n') {
+        let requirement = line.split('#').next().unwrap_or_default();
+        let name_end = requirement
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+            .unwrap_or(requirement.len());
+        let is_target = name_end > 0
+            && normalize_name(Ecosystem::Pypi, &requirement[..name_end]) == target
+            && !line.contains("--hash")
+            && !line.trim_end().ends_with('\\');
+
+        let rewritten = is_target
+            .then(|| {
+                ["==", ">=", "~="].iter().find_map(|operator| {
+                    let start = requirement.find(operator)? + operator.len();
+                    let pinned = &requirement[start..];
+                    let end = pinned
+                        .find(|c: char| c.is_whitespace() || matches!(c, ',' | ';'))
+                        .unwrap_or(pinned.len());
+                    let mut line = line.to_string();
+                    line.replace_range(start..start + end, version);
+                    Some(line)
+                })
+            })
+            .flatten();
+
+        match rewritten {
+            Some(line) => {
+                changed = true;
+                out.push_str(&line);
+            }
+            None => out.push_str(line),
+        }
+    }
+
+    changed.then_some(out)
+}
+
+/// Title and body for the pull request applying `upgrades`, listing the
+/// findings each one closes.
+pub fn describe_upgrades(
+    upgrades: &[PackageUpgrade],
+    findings: &[SecurityFinding],
+) -> (String, String) {
+    let title = match upgrades {
+        [upgrade] => format!(
+            "Bump {} from {} to {}",
+            upgrade.name, upgrade.from_version, upgrade.to_version
+        ),
+        _ => format!("Bump {} vulnerable dependencies", upgrades.len()),
+    };
+
+    let mut body = String::from(
+        "Upgrades vulnerable dependencies to the smallest release that fixes them.\n\n| Package | From | To |\n| --- | --- | --- |\n",
+    );
+    for upgrade in upgrades {
+        body.push_str(&format!(
+            "| {} | {} | {} |\n",
+            upgrade.name, upgrade.from_version, upgrade.to_version
+        ));
+    }
+
+    body.push_str("\nCloses findings:\n");
+    for finding in findings {
+        if upgrades.iter().any(|u| u.finding_ids.contains(&finding.id)) {
+            body.push_str(&format!(
+                "- **{}** `{}` {} ({})\n",
+                finding.severity, finding.rule_id, finding.description, finding.id
+            ));
+        }
+    }
+
+    (title, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::security::FindingStatus;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn fixed(versions: &[&str]) -> Vec<String> {
+        versions.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_minimal_safe_upgrade_prefers_compatible_line() {
+        let fixes = fixed(&["1.2.5", "2.0.3"]);
+        assert_eq!(
+            minimal_safe_upgrade("1.2.0", &fixes),
+            Upgrade::To("1.2.5".into())
+        );
+        assert_eq!(minimal_safe_upgrade("1.3.0", &fixes), Upgrade::NotNeeded);
+        assert_eq!(
+            minimal_safe_upgrade("2.0.0", &fixes),
+            Upgrade::To("2.0.3".into())
+        );
+        assert_eq!(minimal_safe_upgrade("2.1.0", &fixes), Upgrade::NotNeeded);
+        assert_eq!(
+            minimal_safe_upgrade("0.9.4", &fixes),
+            Upgrade::To("1.2.5".into())
+        );
+        assert_eq!(
+            minimal_safe_upgrade("0.10.55", &fixed(&["0.10.66", "0.11.0"])),
+            Upgrade::To("0.10.66".into())
+        );
+        assert_eq!(
+            minimal_safe_upgrade("1.0.0-rc.1", &fixed(&["1.0.0"])),
+            Upgrade::To("1.0.0".into())
+        );
+        assert_eq!(
+            minimal_safe_upgrade("1.0", &fixed(&[])),
+            Upgrade::Unavailable
+        );
+    }
+
+    fn package(ecosystem: Ecosystem, name: &str, version: &str, lockfile: &str) -> Package {
+        Package {
+            ecosystem,
+            name: name.into(),
+            version: version.into(),
+            license: None,
+            hashes: Vec::new(),
+            dependencies: Vec::new(),
+            source_file: lockfile.into(),
+        }
+    }
+
+    fn finding(package: &str, fixed_versions: &[&str]) -> SecurityFinding {
+        SecurityFinding {
+            id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            scanner: "dependency-audit".into(),
+            rule_id: "vulnerable-dependency".into(),
+            severity: "high".into(),
+            description: "vulnerable".into(),
+            location: None,
+            start_line: None,
+            start_column: None,
+            package: Some(package.into()),
+            remediation: None,
+            fixed_versions: fixed(fixed_versions),
+            fingerprint: "fp".into(),
+            status: FindingStatus::Open,
+            status_expires_at: None,
+            created_at: Utc::now(),
+            last_seen_at: Utc::now(),
+            fixed_at: None,
+            scan_run_id: None,
+            last_scan_run_id: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_upgrades() {
+        let packages = vec![
+            package(Ecosystem::Cargo, "openssl", "0.10.55", "Cargo.lock"),
+            package(Ecosystem::Cargo, "time", "0.3.30", "Cargo.lock"),
+            package(Ecosystem::Pypi, "Django", "4.2.1", "requirements.txt"),
+        ];
+        let openssl = finding("openssl", &["0.10.60"]);
+        let openssl_later = finding("openssl", &["0.10.66"]);
+        let django = finding("django", &["4.2.7", "5.0.1"]);
+        let time = finding("time", &["0.3.20"]);
+        let missing = finding("left-pad", &["1.3.0"]);
+
+        let (upgrades, skipped) = plan_upgrades(
+            &packages,
+            &[
+                openssl.clone(),
+                openssl_later.clone(),
+                django.clone(),
+                time.clone(),
+                missing.clone(),
+            ],
+        );
+
+        assert_eq!(upgrades.len(), 2);
+        assert_eq!(upgrades[0].name, "openssl");
+        assert_eq!(upgrades[0].to_version, "0.10.66");
+        assert_eq!(upgrades[0].finding_ids, vec![openssl.id, openssl_later.id]);
+        assert_eq!(upgrades[1].name, "Django");
+        assert_eq!(upgrades[1].to_version, "4.2.7");
+
+        let reasons: Vec<(Uuid, &str)> = skipped
+            .iter()
+            .map(|s| (s.finding_id, s.reason.as_str()))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (time.id, "Already at a fixed version"),
+                (missing.id, "Package not found in any lockfile"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bump_cargo_manifest_preserves_formatting() {
+        let manifest = r#"[package]
+name = "app"
+
+[dependencies]
+openssl = "0.10.55" # TLS
+serde = { version = "1.0", features = ["derive"] }
+ssl = { package = "openssl", version = "^0.10" }
+
+[target.'cfg(unix)'.dev-dependencies]
+openssl = { version = "=0.10.55", optional = true }
+
+[workspace.dependencies.openssl]
+version = "~0.10.50"
+"#;
+        let updated = bump_cargo_manifest(manifest, "openssl", "0.10.66").unwrap();
+        assert_eq!(
+            updated,
+            r#"[package]
+name = "app"
+
+[dependencies]
+openssl = "0.10.66" # TLS
+serde = { version = "1.0", features = ["derive"] }
+ssl = { package = "openssl", version = "^0.10.66" }
+
+[target.'cfg(unix)'.dev-dependencies]
+openssl = { version = "=0.10.66", optional = true }
+
+[workspace.dependencies.openssl]
+version = "~0.10.66"
+"#
+        );
+        assert_eq!(bump_cargo_manifest(manifest, "tokio", "1.0.0"), None);
+    }
+
+    #[test]
+    fn test_cap_cargo_manifest() {
+        let manifest = r#"[dependencies]
+time = "0.3" # dates
+chrono = { version = "=0.4.31" }
+
+[dev-dependencies]
+time = { version = ">=0.3, <0.3.35", features = ["macros"] }
+"#;
+        assert_eq!(
+            cap_cargo_manifest(manifest, "time", "0.3.36").unwrap(),
+            r#"[dependencies]
+time = "0.3, <0.3.36" # dates
+chrono = { version = "=0.4.31" }
+
+[dev-dependencies]
+time = { version = ">=0.3, <0.3.35", features = ["macros"] }
+"#
+        );
+        assert_eq!(cap_cargo_manifest(manifest, "chrono", "0.4.32"), None);
+
+        // Nothing below the broken release satisfies these requirements.
+        let floor = "[dependencies]\nfoo = \"1.80\"\nbar = \"~2.1.4\"\n";
+        assert_eq!(cap_cargo_manifest(floor, "foo", "1.80.0"), None);
+        assert_eq!(cap_cargo_manifest(floor, "bar", "2.1.4"), None);
+        assert_eq!(
+            cap_cargo_manifest(floor, "foo", "1.80.1").unwrap(),
+            "[dependencies]\nfoo = \"1.80, <1.80.1\"\nbar = \"~2.1.4\"\n"
+        );
+        assert_eq!(cap_cargo_manifest(floor, "foo", "not-a-version"), None);
+    }
+
+    #[test]
+    fn test_bump_cargo_lock() {
+        let lock = r#"[[package]]
+name = "native-tls"
+version = "0.2.11"
+dependencies = [
+ "openssl 0.10.55",
+ "openssl-sys",
+]
+
+[[package]]
+name = "openssl"
+version = "0.10.55"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "aaaa"
+
+[[package]]
+name = "openssl"
+version = "0.9.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "bbbb"
+"#;
+        let updated = bump_cargo_lock(lock, "openssl", "0.10.55", "0.10.66", Some("cccc")).unwrap();
+        assert!(updated.contains(" \"openssl 0.10.66\",\n"));
+        assert!(updated.contains("version = \"0.10.66\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\nchecksum = \"cccc\"\n"));
+        assert!(updated.contains("version = \"0.9.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\nchecksum = \"bbbb\"\n"));
+
+        let without_checksum =
+            bump_cargo_lock(lock, "openssl", "0.10.55", "0.10.66", None).unwrap();
+        assert!(!without_checksum.contains("aaaa"));
+        assert!(without_checksum.contains("bbbb"));
+    }
+
+    #[test]
+    fn test_bump_package_json() {
+        let manifest = "{\n  \"name\": \"web\",\n  \"dependencies\": {\n    \"lodash\": \"^4.17.15\",\n    \"react\": \"18.2.0\"\n  },\n  \"devDependencies\": {\n    \"lodash\" :  \"~4.17.0\"\n  }\n}\n";
+        assert_eq!(
+            bump_package_json(manifest, "lodash", "4.17.21").unwrap(),
+            "{\n  \"name\": \"web\",\n  \"dependencies\": {\n    \"lodash\": \"^4.17.21\",\n    \"react\": \"18.2.0\"\n  },\n  \"devDependencies\": {\n    \"lodash\" :  \"~4.17.21\"\n  }\n}\n"
+        );
+        let ranged = "{\"dependencies\": {\"lodash\": \">=4 <5\"}}";
+        assert_eq!(bump_package_json(ranged, "lodash", "4.17.21"), None);
+    }
+
+    #[test]
+    fn test_bump_requirements() {
+        let requirements = "# web\nDjango[argon2]==4.2.1 ; python_version >= \"3.8\"\nrequests>=2.20,<3\nflask==2.0.0 \\\n    --hash=sha256:abc\n";
+        assert_eq!(
+            bump_requirements(requirements, "django", "4.2.7").unwrap(),
+            "# web\nDjango[argon2]==4.2.7 ; python_version >= \"3.8\"\nrequests>=2.20,<3\nflask==2.0.0 \\\n    --hash=sha256:abc\n"
+        );
+        assert_eq!(
+            bump_requirements(requirements, "requests", "2.31.0").unwrap(),
+            "# web\nDjango[argon2]==4.2.1 ; python_version >= \"3.8\"\nrequests>=2.31.0,<3\nflask==2.0.0 \\\n    --hash=sha256:abc\n"
+        );
+        assert_eq!(bump_requirements(requirements, "flask", "2.3.2"), None);
+    }
+}
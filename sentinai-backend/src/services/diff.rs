@@ -0,0 +1,205 @@
+//! Unified diffs between two versions of a text file.
+
+const CONTEXT: usize = 3;
+/// Past this many edits a file is shown as fully replaced rather than
+/// searched for a minimal diff.
+const MAX_EDITS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Myers' O(ND) shortest edit script between two line sequences.
+fn edit_script(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace = Vec::new();
+
+    'search: for d in 0..=max.min(MAX_EDITS) as isize {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let idx = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+        if d as usize == MAX_EDITS {
+            let mut ops: Vec<Op> = (0..a.len()).map(Op::Delete).collect();
+            ops.extend((0..b.len()).map(Op::Insert));
+            return ops;
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let idx = (k + offset) as usize;
+        let prev_k = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push(Op::Equal(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                ops.push(Op::Insert(prev_y as usize));
+            } else {
+                ops.push(Op::Delete(prev_x as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+    ops
+}
+
+fn push_line(out: &mut String, marker: char, line: &str) {
+    out.push(marker);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Renders a unified diff with `a/` and `b/` prefixes, as produced by
+/// `git diff`. Returns an empty string when the contents are identical.
+pub fn unified_diff(path: &str, original: &str, updated: &str) -> String {
+    let a: Vec<&str> = original.split_inclusive('\n').collect();
+    let b: Vec<&str> = updated.split_inclusive('\n').collect();
+    let ops = edit_script(&a, &b);
+
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // Group changes whose context windows touch into hunks.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &i in &changed {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", path, path);
+    for (start, end) in hunks {
+        let hunk = &ops[start..end];
+        let (mut old_start, mut new_start) = (None, None);
+        let (mut old_len, mut new_len) = (0, 0);
+        let mut body = String::new();
+        for op in hunk {
+            match *op {
+                Op::Equal(i, j) => {
+                    old_start.get_or_insert(i);
+                    new_start.get_or_insert(j);
+                    old_len += 1;
+                    new_len += 1;
+                    push_line(&mut body, ' ', a[i]);
+                }
+                Op::Delete(i) => {
+                    old_start.get_or_insert(i);
+                    old_len += 1;
+                    push_line(&mut body, '-', a[i]);
+                }
+                Op::Insert(j) => {
+                    new_start.get_or_insert(j);
+                    new_len += 1;
+                    push_line(&mut body, '+', b[j]);
+                }
+            }
+        }
+        // An empty side is reported as starting at the line before it.
+        let old_start = if old_len == 0 {
+            old_start.unwrap_or(0)
+        } else {
+            old_start.unwrap_or(0) + 1
+        };
+        let new_start = if new_len == 0 {
+            new_start.unwrap_or(0)
+        } else {
+            new_start.unwrap_or(0) + 1
+        };
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_len, new_start, new_len
+        ));
+        out.push_str(&body);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff_single_change_with_context() {
+        let original = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let updated = "a\nb\nc\nd\nE\nf\ng\nh\n";
+        assert_eq!(
+            unified_diff("file.txt", original, updated),
+            "--- a/file.txt\n+++ b/file.txt\n@@ -2,7 +2,7 @@\n b\n c\n d\n-e\n+E\n f\n g\n h\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_separate_hunks_and_deletions() {
+        let original: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let updated = original
+            .replace("line 2\n", "line two\n")
+            .replace("line 18\nline 19\n", "line 18\n");
+        let diff = unified_diff("n.txt", &original, &updated);
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n line 1\n-line 2\n+line two\n"));
+        assert!(
+            diff.contains("@@ -16,5 +16,4 @@\n line 16\n line 17\n line 18\n-line 19\n line 20\n")
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_missing_trailing_newline() {
+        let diff = unified_diff("f", "x\ny", "x\nz");
+        assert_eq!(
+            diff,
+            "--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n x\n-y\n\\ No newline at end of file\n+z\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_identical() {
+        assert_eq!(unified_diff("f", "same\n", "same\n"), "");
+    }
+}
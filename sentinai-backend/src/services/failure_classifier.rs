@@ -0,0 +1,398 @@
+//! Rule-based diagnosis of failed builds from their logs.
+
+use crate::domain::build::{FailureCategory, FailureClassification};
+
+/// Lines kept before and after the line a failure was read from.
+const EXCERPT_BEFORE: usize = 5;
+const EXCERPT_AFTER: usize = 15;
+/// Lines kept from the end of the log when no rule matches.
+const EXCERPT_TAIL: usize = 20;
+const MAX_FAILING_TESTS: usize = 50;
+
+/// A category and the log markers that identify it. A marker matches a line
+/// containing all of its lowercase fragments.
+struct Rule {
+    category: FailureCategory,
+    markers: &'static [&'static [&'static str]],
+}
+
+/// Rules in priority order: the first rule with a matching line decides the
+/// category. Causes that make later steps fail, such as a missing secret or an
+/// OOM-killed compiler, come before the symptoms they produce.
+const RULES: [Rule; 8] = [
+    Rule {
+        category: FailureCategory::MissingSecret,
+        markers: &[
+            &["input required and not supplied"],
+            &["environment variable", "not set"],
+            &["environment variable", "not found"],
+            &["must be set"],
+            &["secret", "not found"],
+            &["unable to locate credentials"],
+            &["could not load credentials"],
+            &["bad credentials"],
+            &["could not read username"],
+            &["permission denied (publickey)"],
+        ],
+    },
+    Rule {
+        category: FailureCategory::OutOfMemory,
+        markers: &[
+            &["out of memory"],
+            &["outofmemoryerror"],
+            &["oomkilled"],
+            &["cannot allocate memory"],
+            &["memory allocation of", "failed"],
+            &["killed signal terminated program"],
+            &["signal: 9, sigkill"],
+            &["exit code 137"],
+        ],
+    },
+    Rule {
+        category: FailureCategory::InfraFlaky,
+        markers: &[
+            &["could not resolve host"],
+            &["temporary failure in name resolution"],
+            &["connection reset by peer"],
+            &["network is unreachable"],
+            &["econnreset"],
+            &["etimedout"],
+            &["eai_again"],
+            &["tls handshake timeout"],
+            &["502 bad gateway"],
+            &["503 service unavailable"],
+            &["504 gateway time"],
+            &["toomanyrequests"],
+            &["rate limit exceeded"],
+            &["spurious network error"],
+            &["no space left on device"],
+            &["runner has received a shutdown signal"],
+            &["lost communication with the server"],
+            &["hosted runner encountered an error"],
+        ],
+    },
+    Rule {
+        category: FailureCategory::Timeout,
+        markers: &[
+            &["exceeded the maximum execution time"],
+            &["execution took longer than"],
+            &["timed out after"],
+            &["test timed out"],
+            &["timeout of", "ms exceeded"],
+            &["has been running for over"],
+            &["deadline exceeded"],
+        ],
+    },
+    Rule {
+        category: FailureCategory::DependencyResolution,
+        markers: &[
+            &["failed to select a version"],
+            &["no matching package named"],
+            &["failed to load source for dependency"],
+            &["needs to be updated but --locked was passed"],
+            &["could not find a version that satisfies"],
+            &["no matching distribution found"],
+            &["resolutionimpossible"],
+            &["solverproblemerror"],
+            &["version solving failed"],
+            &["unable to resolve dependency tree"],
+            &["npm err!", "eresolve"],
+            &["npm err!", "404"],
+            &["could not resolve dependencies"],
+            &["could not find artifact"],
+            &["unknown revision"],
+        ],
+    },
+    Rule {
+        category: FailureCategory::Lint,
+        markers: &[
+            &["implied by `-d warnings`"],
+            &["-d clippy::"],
+            &["#[deny(clippy::"],
+            &["diff in ", " at line "],
+            &["would reformat"],
+            &["code style issues found"],
+            &["problems (", "error"],
+            &["golangci-lint"],
+            &["offenses detected"],
+            &["flake8"],
+        ],
+    },
+    Rule {
+        category: FailureCategory::CompileError,
+        markers: &[
+            &["error[e"],
+            &["could not compile"],
+            &["error ts"],
+            &["compilation failed"],
+            &["compilation failure"],
+            &["compilation error"],
+            &["syntaxerror"],
+            &["cannot find symbol"],
+            &["undefined reference to"],
+            &["fatal error:"],
+            &[": undefined: "],
+        ],
+    },
+    Rule {
+        category: FailureCategory::TestFailure,
+        markers: &[
+            &["test result: failed"],
+            &["error: test failed"],
+            &["--- fail:"],
+            &["short test summary info"],
+            &["there are test failures"],
+            &["tests failed"],
+            &["tests:", " failed"],
+            &[" failing"],
+        ],
+    },
+];
+
+/// Removes the timestamp GitHub Actions puts in front of every log line.
+fn strip_timestamp(line: &str) -> &str {
+    match line.split_once(' ') {
+        Some((stamp, rest))
+            if stamp.len() >= 20
+                && stamp.ends_with('Z')
+                && stamp.as_bytes()[10] == b'T'
+                && stamp.as_bytes()[..4].iter().all(u8::is_ascii_digit) =>
+        {
+            rest
+        }
+        _ => line,
+    }
+}
+
+fn matches(line: &str, rule: &Rule) -> bool {
+    rule.markers
+        .iter()
+        .any(|fragments| fragments.iter().all(|fragment| line.contains(fragment)))
+}
+
+/// The name of the failing test reported on `line` by cargo, nextest, go,
+/// pytest, Jest or Maven Surefire.
+fn failing_test(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if let Some(name) = line
+        .strip_prefix("test ")
+        .and_then(|rest| rest.strip_suffix(" ... FAILED"))
+    {
+        return Some(name);
+    }
+    if let Some(name) = line
+        .strip_prefix("---- ")
+        .and_then(|rest| rest.strip_suffix(" stdout ----"))
+    {
+        return Some(name);
+    }
+    if let Some(rest) = line.strip_prefix("FAIL [") {
+        return rest.split_once("] ").map(|(_, name)| name.trim());
+    }
+    if let Some(rest) = line.strip_prefix("--- FAIL: ") {
+        return rest.split_whitespace().next();
+    }
+    if let Some(rest) = line.strip_prefix("FAILED ") {
+        let name = rest.split(" - ").next().unwrap_or(rest).trim();
+        return name.contains("::").then_some(name);
+    }
+    if let Some(rest) = line.strip_prefix("● ") {
+        return rest.contains(" › ").then_some(rest);
+    }
+    if let Some(rest) = line.strip_prefix("[ERROR]") {
+        let token = rest.split_whitespace().next()?;
+        let (name, position) = token.split_once(':')?;
+        let is_test = name.contains('.') && position.bytes().all(|b| b.is_ascii_digit());
+        return is_test.then_some(name);
+    }
+    None
+}
+
+/// Names of the tests reported as failing, in log order.
+pub fn failing_tests(lines: &[&str]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in lines.iter().filter_map(|line| failing_test(line)) {
+        if !name.is_empty() && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+            if names.len() == MAX_FAILING_TESTS {
+                break;
+            }
+        }
+    }
+    names
+}
+
+/// Classifies a failed build from its log lines and CI conclusion.
+pub fn classify(log: &[String], conclusion: Option<&str>) -> FailureClassification {
+    let lines: Vec<&str> = log.iter().map(|line| strip_timestamp(line)).collect();
+    let lowered: Vec<String> = lines.iter().map(|line| line.to_lowercase()).collect();
+    let failing_tests = failing_tests(&lines);
+
+    let matched = RULES.iter().find_map(|rule| {
+        lowered
+            .iter()
+            .position(|line| matches(line, rule))
+            .map(|index| (rule.category, Some(index)))
+    });
+    let first_test = || lines.iter().position(|line| failing_test(line).is_some());
+    let (category, index) = match (matched, conclusion) {
+        // The CI system's own verdict outranks the log.
+        (_, Some("timed_out")) => (FailureCategory::Timeout, None),
+        (Some(matched), _) => matched,
+        (None, _) if !failing_tests.is_empty() => (FailureCategory::TestFailure, first_test()),
+        (None, _) => (FailureCategory::Unknown, None),
+    };
+
+    let range = match index {
+        Some(index) => {
+            index.saturating_sub(EXCERPT_BEFORE)..(index + EXCERPT_AFTER + 1).min(lines.len())
+        }
+        None => lines.len().saturating_sub(EXCERPT_TAIL)..lines.len(),
+    };
+    let excerpt = (!range.is_empty()).then(|| lines[range].join("\n"));
+
+    FailureClassification {
+        category,
+        line: index.map(|index| index as i32 + 1),
+        excerpt,
+        failing_tests,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_classify_cargo_test_failure() {
+        let log = log("2024-05-01T12:00:00.1234567Z    Compiling app v0.1.0\n\
+             running 3 tests\n\
+             test parser::tests::empty ... FAILED\n\
+             test parser::tests::nested ... ok\n\
+             \n\
+             failures:\n\
+             \n\
+             ---- parser::tests::empty stdout ----\n\
+             thread 'parser::tests::empty' panicked at src/parser.rs:10:5\n\
+             \n\
+             test result: FAILED. 2 passed; 1 failed\n\
+             error: test failed, to rerun pass `--bin app`");
+        let classification = classify(&log, Some("failure"));
+        assert_eq!(classification.category, FailureCategory::TestFailure);
+        assert_eq!(classification.failing_tests, vec!["parser::tests::empty"]);
+        assert_eq!(classification.line, Some(3));
+        let excerpt = classification.excerpt.unwrap();
+        assert!(excerpt.starts_with("   Compiling app v0.1.0\n"));
+        assert!(excerpt.ends_with("error: test failed, to rerun pass `--bin app`"));
+    }
+
+    #[test]
+    fn test_classify_categories() {
+        let cases = [
+            (
+                "error[E0308]: mismatched types\nerror: could not compile `app`",
+                FailureCategory::CompileError,
+            ),
+            (
+                "warning: unused variable: `x`\n  = note: `-D unused-variables` implied by `-D warnings`\nerror: could not compile `app`",
+                FailureCategory::Lint,
+            ),
+            (
+                "Diff in /src/main.rs at line 3:\n-fn main(){}\n+fn main() {}",
+                FailureCategory::Lint,
+            ),
+            (
+                "error: failed to select a version for the requirement `serde = \"^9\"`",
+                FailureCategory::DependencyResolution,
+            ),
+            (
+                "npm ERR! code ERESOLVE\nnpm ERR! ERESOLVE unable to resolve dependency tree",
+                FailureCategory::DependencyResolution,
+            ),
+            (
+                "error: failed to download from `https://crates.io`\nCould not resolve host: static.crates.io",
+                FailureCategory::InfraFlaky,
+            ),
+            (
+                "FATAL ERROR: Reached heap limit Allocation failed - JavaScript heap out of memory",
+                FailureCategory::OutOfMemory,
+            ),
+            (
+                "error: could not compile `app`\nprocess didn't exit successfully (signal: 9, SIGKILL: kill)",
+                FailureCategory::OutOfMemory,
+            ),
+            (
+                "##[error]The job running on runner GitHub Actions 2 has exceeded the maximum execution time of 360 minutes.",
+                FailureCategory::Timeout,
+            ),
+            (
+                "thread 'main' panicked at src/main.rs:4:5:\nDATABASE_URL must be set",
+                FailureCategory::MissingSecret,
+            ),
+            (
+                "Error: Input required and not supplied: token",
+                FailureCategory::MissingSecret,
+            ),
+            ("Process completed with exit code 1.", FailureCategory::Unknown),
+        ];
+        for (text, category) in cases {
+            assert_eq!(classify(&log(text), None).category, category, "{}", text);
+        }
+        assert_eq!(
+            classify(&log("Process completed"), Some("timed_out")).category,
+            FailureCategory::Timeout
+        );
+        assert_eq!(classify(&[], None).excerpt, None);
+    }
+
+    #[test]
+    fn test_failing_tests_across_runners() {
+        let lines = [
+            "--- FAIL: TestParse (0.00s)",
+            "FAILED tests/test_api.py::test_login - AssertionError: 401",
+            "  ● Auth › rejects bad tokens",
+            "        FAIL [   0.004s] app parser::tests::nested",
+            "[ERROR]   UserServiceTest.testCreate:42 expected:<1> but was:<2>",
+            "[ERROR] Tests run: 3, Failures: 1",
+            "--- FAIL: TestParse (0.00s)",
+        ];
+        assert_eq!(
+            failing_tests(&lines),
+            vec![
+                "TestParse",
+                "tests/test_api.py::test_login",
+                "Auth › rejects bad tokens",
+                "app parser::tests::nested",
+                "UserServiceTest.testCreate",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unmatched_log_keeps_its_tail() {
+        let log: Vec<String> = (1..=30).map(|i| format!("step {}", i)).collect();
+        let classification = classify(&log, Some("failure"));
+        assert_eq!(classification.category, FailureCategory::Unknown);
+        assert_eq!(classification.line, None);
+        let excerpt = classification.excerpt.unwrap();
+        assert!(excerpt.starts_with("step 11\n"));
+        assert!(excerpt.ends_with("step 30"));
+    }
+
+    #[test]
+    fn test_strip_timestamp() {
+        assert_eq!(
+            strip_timestamp("2024-05-01T12:00:00.1234567Z error: boom"),
+            "error: boom"
+        );
+        assert_eq!(
+            strip_timestamp("abcé12345T67890123Z foo"),
+            "abcé12345T67890123Z foo"
+        );
+        assert_eq!(strip_timestamp("plain line"), "plain line");
+    }
+}
@@ -0,0 +1,432 @@
+//! Flakiness scoring over test history, and quarantine edits for flaky tests.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::domain::remediation::FileChange;
+use crate::domain::test_result::{FlakyTest, QuarantinedTest, TestOutcome, TestRun};
+use crate::services::repository::RepoSnapshot;
+
+pub const QUARANTINE_REASON: &str = "flaky: quarantined by Sentinai";
+
+/// Scores each test that both passed and failed in `runs`, which are in the
+/// order they ran.
+///
+/// The score is the larger of two rates:
+/// - the share of re-run commits the test both passed and failed on, the
+///   clearest sign of flakiness since the code did not change;
+/// - half the rate at which its outcome flips between consecutive runs on a
+///   branch. Flips count half because a real regression and its fix flip a
+///   test too.
+pub fn score_tests(runs: &[TestRun]) -> Vec<FlakyTest> {
+    let mut by_test: BTreeMap<&str, Vec<&TestRun>> = BTreeMap::new();
+    for run in runs {
+        by_test.entry(run.test_id.as_str()).or_default().push(run);
+    }
+
+    let mut flaky: Vec<FlakyTest> = by_test
+        .into_iter()
+        .filter_map(|(test_id, runs)| score_test(test_id, &runs))
+        .collect();
+    flaky.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.failures.cmp(&a.failures))
+            .then(a.test_id.cmp(&b.test_id))
+    });
+    flaky
+}
+
+fn score_test(test_id: &str, runs: &[&TestRun]) -> Option<FlakyTest> {
+    let failures = runs
+        .iter()
+        .filter(|run| run.outcome == TestOutcome::Failed)
+        .count();
+    let passes = runs.len() - failures;
+    if failures == 0 || passes == 0 {
+        return None;
+    }
+
+    let mut last_by_branch: HashMap<&str, TestOutcome> = HashMap::new();
+    let mut commits: HashMap<&str, (usize, bool, bool)> = HashMap::new();
+    let (mut flips, mut transitions) = (0, 0);
+    for run in runs {
+        let branch = run.head_branch.as_deref().unwrap_or_default();
+        if let Some(previous) = last_by_branch.insert(branch, run.outcome) {
+            transitions += 1;
+            if previous != run.outcome {
+                flips += 1;
+            }
+        }
+        if let Some(sha) = run.head_sha.as_deref() {
+            let commit = commits.entry(sha).or_default();
+            commit.0 += 1;
+            match run.outcome {
+                TestOutcome::Failed => commit.2 = true,
+                _ => commit.1 = true,
+            }
+        }
+    }
+    let rerun_commits = commits.values().filter(|(runs, _, _)| *runs > 1).count();
+    let mixed_commits = commits
+        .values()
+        .filter(|(_, passed, failed)| *passed && *failed)
+        .count();
+
+    let commit_rate = if rerun_commits > 0 {
+        mixed_commits as f64 / rerun_commits as f64
+    } else {
+        0.0
+    };
+    let flip_rate = if transitions > 0 {
+        flips as f64 / transitions as f64 / 2.0
+    } else {
+        0.0
+    };
+    let score = (commit_rate.max(flip_rate) * 1000.0).round() / 1000.0;
+
+    let last_failure = runs
+        .iter()
+        .rev()
+        .find(|run| run.outcome == TestOutcome::Failed);
+    Some(FlakyTest {
+        test_id: test_id.to_string(),
+        file: runs.iter().rev().find_map(|run| run.file.clone()),
+        runs: runs.len(),
+        passes,
+        failures,
+        flips,
+        mixed_commits,
+        score,
+        last_failed_at: last_failure.map(|run| run.ran_at),
+        last_failed_build_id: last_failure.map(|run| run.build_id),
+        last_message: last_failure.and_then(|run| run.message.clone()),
+    })
+}
+
+/// Quarantine edits for a set of tests, with each changed file in path order.
+#[derive(Debug, Default)]
+pub struct QuarantinePlan {
+    pub quarantined: Vec<QuarantinedTest>,
+    pub unresolved: Vec<String>,
+    pub files: Vec<FileChange>,
+}
+
+/// Marks each test as skipped where it is defined: `#[ignore]` for Rust,
+/// `@pytest.mark.skip` for Python, and `it.skip`/`test.skip` for JavaScript
+/// and TypeScript. `file` narrows the search to the file a report named.
+pub fn plan_quarantine(
+    snapshot: &RepoSnapshot,
+    tests: &[(String, Option<String>)],
+) -> QuarantinePlan {
+    let mut plan = QuarantinePlan::default();
+    for (test_id, file) in tests {
+        let name = test_name(test_id);
+        let mut candidates: Vec<(&str, &str)> = snapshot
+            .files()
+            .filter(|(path, _)| match file {
+                Some(file) => path == file,
+                None => language(path).is_some(),
+            })
+            .collect();
+        // Prefer files whose path matches the test's module path.
+        let modules: Vec<&str> = test_id.split("::").collect();
+        candidates.sort_by_key(|(path, _)| {
+            std::cmp::Reverse(modules.iter().filter(|m| path.contains(*m)).count())
+        });
+
+        let edit = candidates.into_iter().find_map(|(path, original)| {
+            let current = plan
+                .files
+                .iter()
+                .find(|change| change.path == path)
+                .map(|change| change.after.as_str())
+                .unwrap_or(original);
+            quarantine_in(path, current, name).map(|edited| (path, original, edited))
+        });
+        match edit {
+            Some((path, original, edited)) => {
+                match plan.files.iter_mut().find(|change| change.path == path) {
+                    Some(change) => change.after = edited,
+                    None => plan.files.push(FileChange {
+                        path: path.to_string(),
+                        before: original.to_string(),
+                        after: edited,
+                    }),
+                }
+                plan.quarantined.push(QuarantinedTest {
+                    test_id: test_id.clone(),
+                    path: path.to_string(),
+                });
+            }
+            None => plan.unresolved.push(test_id.clone()),
+        }
+    }
+    plan.files.sort_by(|a, b| a.path.cmp(&b.path));
+    plan
+}
+
+#[derive(Clone, Copy)]
+enum Language {
+    Rust,
+    Python,
+    JavaScript,
+}
+
+fn language(path: &str) -> Option<Language> {
+    let extension = path.rsplit_once('.')?.1;
+    match extension {
+        "rs" => Some(Language::Rust),
+        "py" => Some(Language::Python),
+        "js" | "jsx" | "mjs" | "ts" | "tsx" => Some(Language::JavaScript),
+        _ => None,
+    }
+}
+
+/// The function or title a test ID ends with: `test_login` for
+/// `tests.test_api::test_login[sqlite]`.
+fn test_name(test_id: &str) -> &str {
+    let name = test_id.rsplit("::").next().unwrap_or(test_id);
+    match name.find('[') {
+        Some(i) if name.ends_with(']') => &name[..i],
+        _ => name,
+    }
+}
+
+fn indent(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// `contents` with the test named `name` skipped, or `None` when it is not
+/// defined there or is skipped already.
+fn quarantine_in(path: &str, contents: &str, name: &str) -> Option<String> {
+    let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+    match language(path)? {
+        Language::Rust => {
+            let at = lines.iter().position(|line| {
+                let line = line.trim_start();
+                line.contains(&format!("fn {}(", name)) && !line.starts_with("//")
+            })?;
+            let attributes: Vec<&str> = lines[..at]
+                .iter()
+                .rev()
+                .map(|line| line.trim_start())
+                .take_while(|line| line.starts_with("#[") || line.starts_with("///"))
+                .collect();
+            if !attributes.iter().any(|attr| attr.contains("test"))
+                || attributes.iter().any(|attr| attr.starts_with("#[ignore"))
+            {
+                return None;
+            }
+            let line = format!(
+                "{}#[ignore = \"{}\"]",
+                indent(&lines[at]),
+                QUARANTINE_REASON
+            );
+            lines.insert(at, line);
+        }
+        Language::Python => {
+            let at = lines.iter().position(|line| {
+                let line = line.trim_start();
+                line.starts_with(&format!("def {}(", name))
+                    || line.starts_with(&format!("async def {}(", name))
+            })?;
+            let skipped = lines[..at]
+                .iter()
+                .rev()
+                .map(|line| line.trim_start())
+                .take_while(|line| line.starts_with('@'))
+                .any(|line| line.starts_with("@pytest.mark.skip"));
+            if skipped {
+                return None;
+            }
+            let line = format!(
+                "{}@pytest.mark.skip(reason=\"{}\")",
+                indent(&lines[at]),
+                QUARANTINE_REASON
+            );
+            lines.insert(at, line);
+            if !lines.iter().any(|line| line.trim() == "import pytest") {
+                let at = lines
+                    .iter()
+                    .position(|line| {
+                        (line.starts_with("import ") || line.starts_with("from "))
+                            && !line.starts_with("from __future__")
+                    })
+                    .unwrap_or(0);
+                lines.insert(at, "import pytest".to_string());
+            }
+        }
+        Language::JavaScript => {
+            let at = lines.iter().position(|line| {
+                js_test_title(line).is_some_and(|(_, title)| {
+                    title == name || name.ends_with(&format!(" {}", title))
+                })
+            })?;
+            let (call, _) = js_test_title(&lines[at])?;
+            let line = &lines[at];
+            let start = indent(line).len();
+            lines[at] = format!(
+                "{}{}.skip({}",
+                &line[..start],
+                &call[..call.len() - 1],
+                &line[start + call.len()..]
+            );
+        }
+    }
+
+    let mut edited = lines.join("\n");
+    if contents.ends_with('\n') {
+        edited.push('\n');
+    }
+    Some(edited)
+}
+
+/// The `it(`/`test(` call on a line and the test title it is given.
+fn js_test_title(line: &str) -> Option<(&'static str, &str)> {
+    let trimmed = line.trim_start();
+    let call = ["it(", "test("]
+        .into_iter()
+        .find(|call| trimmed.starts_with(call))?;
+    let rest = &trimmed[call.len()..];
+    let quote = rest
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '\'' | '"' | '`'))?;
+    let title = &rest[1..];
+    let end = title.find(quote)?;
+    Some((call, &title[..end]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    fn runs(history: &[(&str, &str, &str, TestOutcome)]) -> Vec<TestRun> {
+        let start = Utc::now() - Duration::hours(history.len() as i64);
+        history
+            .iter()
+            .enumerate()
+            .map(|(i, (test_id, sha, branch, outcome))| TestRun {
+                test_id: test_id.to_string(),
+                file: None,
+                outcome: *outcome,
+                message: (*outcome == TestOutcome::Failed).then(|| format!("run {}", i)),
+                build_id: Uuid::new_v4(),
+                head_sha: Some(sha.to_string()),
+                head_branch: Some(branch.to_string()),
+                ran_at: start + Duration::hours(i as i64),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_score_tests() {
+        use TestOutcome::{Failed, Passed};
+        let history = runs(&[
+            // Fails and passes on the same commit.
+            ("flaky", "a1", "main", Failed),
+            ("flaky", "a1", "main", Passed),
+            ("flaky", "b2", "main", Passed),
+            ("flaky", "b2", "main", Passed),
+            // Broken by one commit and fixed by the next.
+            ("regressed", "a1", "main", Passed),
+            ("regressed", "b2", "main", Passed),
+            ("regressed", "c3", "main", Failed),
+            ("regressed", "c3", "main", Failed),
+            ("regressed", "d4", "main", Passed),
+            // Always passes.
+            ("stable", "a1", "main", Passed),
+            ("stable", "a1", "main", Passed),
+        ]);
+        let scored = score_tests(&history);
+        assert_eq!(scored.len(), 2);
+
+        let flaky = &scored[0];
+        assert_eq!(flaky.test_id, "flaky");
+        assert_eq!((flaky.runs, flaky.passes, flaky.failures), (4, 3, 1));
+        assert_eq!((flaky.flips, flaky.mixed_commits), (1, 1));
+        assert_eq!(flaky.score, 0.5);
+        assert_eq!(flaky.last_message.as_deref(), Some("run 0"));
+
+        let regressed = &scored[1];
+        assert_eq!(regressed.test_id, "regressed");
+        assert_eq!(regressed.mixed_commits, 0);
+        assert_eq!(regressed.flips, 2);
+        assert_eq!(regressed.score, 0.25);
+        assert_eq!(regressed.last_message.as_deref(), Some("run 7"));
+    }
+
+    #[test]
+    fn test_flips_are_counted_per_branch() {
+        use TestOutcome::{Failed, Passed};
+        let history = runs(&[
+            ("t", "a1", "main", Passed),
+            ("t", "f1", "feature", Failed),
+            ("t", "a2", "main", Passed),
+            ("t", "f2", "feature", Failed),
+        ]);
+        let scored = score_tests(&history);
+        assert_eq!(scored[0].flips, 0);
+        assert_eq!(scored[0].score, 0.0);
+    }
+
+    #[test]
+    fn test_plan_quarantine() {
+        let snapshot = RepoSnapshot::from_files([
+            (
+                "src/services/diff.rs",
+                "#[cfg(test)]\nmod tests {\n    #[test]\n    fn test_unified_diff() {}\n\n    #[tokio::test]\n    #[ignore]\n    async fn test_slow() {}\n}\n",
+            ),
+            (
+                "tests/test_api.py",
+                "from __future__ import annotations\nimport os\n\nclass TestLogin:\n    @pytest.fixture\n    def client(self):\n        pass\n\n    def test_login(self, client):\n        assert True\n",
+            ),
+            (
+                "web/checkout.test.ts",
+                "describe('Checkout', () => {\n  it('applies coupons', async () => {});\n  test(\"totals\", () => {});\n});\n",
+            ),
+        ]);
+        let tests = [
+            (
+                "app::services::diff::tests::test_unified_diff".to_string(),
+                None,
+            ),
+            ("app::services::diff::tests::test_slow".to_string(), None),
+            (
+                "tests.test_api.TestLogin::test_login[sqlite]".to_string(),
+                Some("tests/test_api.py".to_string()),
+            ),
+            ("Checkout::Checkout applies coupons".to_string(), None),
+            ("Checkout::totals".to_string(), None),
+            ("missing::test_gone".to_string(), None),
+        ];
+        let plan = plan_quarantine(&snapshot, &tests);
+
+        assert_eq!(
+            plan.unresolved,
+            vec![
+                "app::services::diff::tests::test_slow",
+                "missing::test_gone"
+            ]
+        );
+        assert_eq!(plan.quarantined.len(), 4);
+        let edited = |path: &str| {
+            let change = plan.files.iter().find(|change| change.path == path);
+            change.unwrap().after.as_str()
+        };
+        assert_eq!(
+            edited("src/services/diff.rs"),
+            "#[cfg(test)]\nmod tests {\n    #[test]\n    #[ignore = \"flaky: quarantined by Sentinai\"]\n    fn test_unified_diff() {}\n\n    #[tokio::test]\n    #[ignore]\n    async fn test_slow() {}\n}\n"
+        );
+        assert_eq!(
+            edited("tests/test_api.py"),
+            "from __future__ import annotations\nimport pytest\nimport os\n\nclass TestLogin:\n    @pytest.fixture\n    def client(self):\n        pass\n\n    @pytest.mark.skip(reason=\"flaky: quarantined by Sentinai\")\n    def test_login(self, client):\n        assert True\n"
+        );
+        assert_eq!(
+            edited("web/checkout.test.ts"),
+            "describe('Checkout', () => {\n  it.skip('applies coupons', async () => {});\n  test.skip(\"totals\", () => {});\n});\n"
+        );
+    }
+}
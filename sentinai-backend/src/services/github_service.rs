@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::error::AppError;
 use crate::state::AppState;
@@ -46,3 +46,410 @@ pub async fn get_user_profile(
 
     Ok(user)
 }
+
+/// Splits a GitHub repository URL such as `https://github.com/owner/repo.git`
+/// into its owner and repository name.
+pub fn parse_repository_url(repository_url: &str) -> Option<(String, String)> {
+    let path = repository_url
+        .trim()
+        .trim_end_matches('/')
+        .strip_prefix("https://github.com/")
+        .or_else(|| repository_url.trim().strip_prefix("git@github.com:"))?;
+    let mut parts = path.splitn(2, '/');
+    let owner = parts.next().filter(|o| !o.is_empty())?;
+    let repo = parts.next()?.trim_end_matches(".git");
+    if repo.is_empty() || repo.contains('/') {
+        return None;
+    }
+    Some((owner.to_string(), repo.to_string()))
+}
+
+fn authorize(state: &AppState, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    let request = request.header("Accept", "application/vnd.github+json");
+    match &state.config.github_token {
+        Some(token) => request.header("Authorization", format!("Bearer {}", token)),
+        None => request,
+    }
+}
+
+fn repository_coordinates(repository_url: &str) -> Result<(String, String), AppError> {
+    parse_repository_url(repository_url).ok_or_else(|| {
+        AppError::BadRequest(format!("Not a GitHub repository URL: {}", repository_url))
+    })
+}
+
+#[derive(Deserialize)]
+struct GithubCommit {
+    sha: String,
+}
+
+#[tracing::instrument(name = "github", skip(state))]
+pub async fn get_head_commit_sha(
+    state: &AppState,
+    repository_url: &str,
+) -> Result<String, AppError> {
+    get_commit_sha(state, repository_url, "HEAD").await
+}
+
+/// Resolves a branch, tag or commit reference to a commit SHA.
+#[tracing::instrument(name = "github", skip(state))]
+pub async fn get_commit_sha(
+    state: &AppState,
+    repository_url: &str,
+    git_ref: &str,
+) -> Result<String, AppError> {
+    let (owner, repo) = repository_coordinates(repository_url)?;
+
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/commits/{}",
+        owner, repo, git_ref
+    );
+    let res = authorize(state, state.http_client.get(&url))
+        .send()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("GitHub API error: {}", e)))?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalServerError(format!(
+            "GitHub API returned {} for {}/{}",
+            res.status(),
+            owner,
+            repo
+        )));
+    }
+
+    let commit: GithubCommit = res.json().await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to parse GitHub commit: {}", e))
+    })?;
+
+    Ok(commit.sha)
+}
+
+/// Sends a GitHub API request and parses its JSON response, describing
+/// failures with `action`.
+async fn send_json<T: for<'de> Deserialize<'de>>(
+    state: &AppState,
+    request: reqwest::RequestBuilder,
+    action: &str,
+) -> Result<T, AppError> {
+    let res = authorize(state, request)
+        .send()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("GitHub API error: {}", e)))?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalServerError(format!(
+            "GitHub API returned {} {}",
+            res.status(),
+            action
+        )));
+    }
+
+    res.json().await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to parse GitHub response: {}", e))
+    })
+}
+
+#[derive(Deserialize)]
+struct GithubRepository {
+    default_branch: String,
+}
+
+#[tracing::instrument(name = "github", skip(state))]
+pub async fn get_default_branch(
+    state: &AppState,
+    repository_url: &str,
+) -> Result<String, AppError> {
+    let (owner, repo) = repository_coordinates(repository_url)?;
+
+    let url = format!("https://api.github.com/repos/{}/{}", owner, repo);
+    let repository: GithubRepository = send_json(
+        state,
+        state.http_client.get(&url),
+        &format!("for {}/{}", owner, repo),
+    )
+    .await?;
+
+    Ok(repository.default_branch)
+}
+
+#[derive(Deserialize)]
+struct GitObject {
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct GitCommit {
+    tree: GitObject,
+}
+
+/// Commits `files` (path and new contents) on top of `base_sha` and points a
+/// new branch at the commit. Returns the commit SHA.
+#[tracing::instrument(name = "github", skip(state, message, files))]
+pub async fn create_branch_commit(
+    state: &AppState,
+    repository_url: &str,
+    base_sha: &str,
+    branch: &str,
+    message: &str,
+    files: &[(String, String)],
+) -> Result<String, AppError> {
+    let (owner, repo) = repository_coordinates(repository_url)?;
+    let api = format!("https://api.github.com/repos/{}/{}/git", owner, repo);
+
+    let base: GitCommit = send_json(
+        state,
+        state
+            .http_client
+            .get(format!("{}/commits/{}", api, base_sha)),
+        &format!("reading commit {}", base_sha),
+    )
+    .await?;
+
+    let tree: Vec<serde_json::Value> = files
+        .iter()
+        .map(|(path, content)| {
+            serde_json::json!({ "path": path, "mode": "100644", "type": "blob", "content": content })
+        })
+        .collect();
+    let tree: GitObject = send_json(
+        state,
+        state
+            .http_client
+            .post(format!("{}/trees", api))
+            .json(&serde_json::json!({ "base_tree": base.tree.sha, "tree": tree })),
+        "creating a tree",
+    )
+    .await?;
+
+    let commit: GitObject = send_json(
+        state,
+        state
+            .http_client
+            .post(format!("{}/commits", api))
+            .json(&serde_json::json!({
+                "message": message,
+                "tree": tree.sha,
+                "parents": [base_sha],
+            })),
+        "creating a commit",
+    )
+    .await?;
+
+    let _: serde_json::Value = send_json(
+        state,
+        state
+            .http_client
+            .post(format!("{}/refs", api))
+            .json(&serde_json::json!({
+                "ref": format!("refs/heads/{}", branch),
+                "sha": commit.sha,
+            })),
+        &format!("creating branch {}", branch),
+    )
+    .await?;
+
+    Ok(commit.sha)
+}
+
+#[derive(Deserialize)]
+pub struct PullRequest {
+    pub number: i64,
+    pub html_url: String,
+}
+
+#[tracing::instrument(name = "github", skip(state, title, body))]
+pub async fn create_pull_request(
+    state: &AppState,
+    repository_url: &str,
+    head: &str,
+    base: &str,
+    title: &str,
+    body: &str,
+) -> Result<PullRequest, AppError> {
+    let (owner, repo) = repository_coordinates(repository_url)?;
+
+    let url = format!("https://api.github.com/repos/{}/{}/pulls", owner, repo);
+    send_json(
+        state,
+        state.http_client.post(&url).json(&serde_json::json!({
+            "title": title,
+            "body": body,
+            "head": head,
+            "base": base,
+        })),
+        &format!("opening a pull request on {}/{}", owner, repo),
+    )
+    .await
+}
+
+#[derive(Serialize)]
+struct CommitStatusRequest<'a> {
+    state: &'a str,
+    description: &'a str,
+    context: &'a str,
+}
+
+/// Sets a commit status on `sha`; `status` is one of GitHub's `success`,
+/// `failure`, `error` or `pending`.
+#[tracing::instrument(name = "github", skip(state, description))]
+pub async fn create_commit_status(
+    state: &AppState,
+    repository_url: &str,
+    sha: &str,
+    status: &str,
+    context: &str,
+    description: &str,
+) -> Result<(), AppError> {
+    let (owner, repo) = repository_coordinates(repository_url)?;
+
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/statuses/{}",
+        owner, repo, sha
+    );
+    let body = CommitStatusRequest {
+        state: status,
+        description,
+        context,
+    };
+    let res = authorize(state, state.http_client.post(&url))
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("GitHub API error: {}", e)))?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalServerError(format!(
+            "GitHub API returned {} creating a commit status on {}/{}",
+            res.status(),
+            owner,
+            repo
+        )));
+    }
+
+    Ok(())
+}
+
+/// Downloads the gzipped source tarball of a repository at `git_ref`.
+#[tracing::instrument(name = "github", skip(state))]
+pub async fn download_tarball(
+    state: &AppState,
+    repository_url: &str,
+    git_ref: &str,
+) -> Result<Vec<u8>, AppError> {
+    let (owner, repo) = repository_coordinates(repository_url)?;
+
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/tarball/{}",
+        owner, repo, git_ref
+    );
+    let res = authorize(state, state.http_client.get(&url))
+        .send()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("GitHub API error: {}", e)))?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalServerError(format!(
+            "GitHub API returned {} for {}/{} tarball",
+            res.status(),
+            owner,
+            repo
+        )));
+    }
+
+    let bytes = res.bytes().await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to download repository tarball: {}", e))
+    })?;
+
+    Ok(bytes.to_vec())
+}
+
+/// Downloads the zip archive of a GitHub Actions run's job logs.
+#[tracing::instrument(name = "github", skip(state))]
+pub async fn download_workflow_run_logs(
+    state: &AppState,
+    repository_url: &str,
+    run_id: i64,
+    attempt: i32,
+) -> Result<Vec<u8>, AppError> {
+    let (owner, repo) = repository_coordinates(repository_url)?;
+
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/actions/runs/{}/attempts/{}/logs",
+        owner, repo, run_id, attempt
+    );
+    let res = authorize(state, state.http_client.get(&url))
+        .send()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("GitHub API error: {}", e)))?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalServerError(format!(
+            "GitHub API returned {} for {}/{} run {} logs",
+            res.status(),
+            owner,
+            repo,
+            run_id
+        )));
+    }
+
+    let bytes = res.bytes().await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to download run logs: {}", e))
+    })?;
+
+    Ok(bytes.to_vec())
+}
+
+/// Re-runs the failed jobs of a GitHub Actions run, starting its next attempt.
+#[tracing::instrument(name = "github", skip(state))]
+pub async fn rerun_failed_jobs(
+    state: &AppState,
+    repository_url: &str,
+    run_id: i64,
+) -> Result<(), AppError> {
+    let (owner, repo) = repository_coordinates(repository_url)?;
+
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/actions/runs/{}/rerun-failed-jobs",
+        owner, repo, run_id
+    );
+    let res = authorize(state, state.http_client.post(&url))
+        .send()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("GitHub API error: {}", e)))?;
+
+    if !res.status().is_success() {
+        return Err(AppError::InternalServerError(format!(
+            "GitHub API returned {} re-running {}/{} run {}",
+            res.status(),
+            owner,
+            repo,
+            run_id
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repository_url() {
+        assert_eq!(
+            parse_repository_url("https://github.com/krishpranav/sentinai"),
+            Some(("krishpranav".to_string(), "sentinai".to_string()))
+        );
+        assert_eq!(
+            parse_repository_url("https://github.com/org/repo.git/"),
+            Some(("org".to_string(), "repo".to_string()))
+        );
+        assert_eq!(
+            parse_repository_url("git@github.com:org/repo.git"),
+            Some(("org".to_string(), "repo".to_string()))
+        );
+        assert_eq!(parse_repository_url("https://gitlab.com/org/repo"), None);
+        assert_eq!(parse_repository_url("https://github.com/org"), None);
+    }
+}
@@ -0,0 +1,480 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::db::{build_repo, project_repo, webhook_delivery_repo};
+use crate::domain::build::{Build, BuildStatus, ExternalBuild};
+use crate::domain::github_event::{
+    CheckRunEvent, CheckSuiteEvent, GithubEvent, PullRequestEvent, PushEvent, WebhookAction,
+    WebhookOutcome, WorkflowRunEvent,
+};
+use crate::domain::project::Project;
+use crate::domain::webhook_delivery::{
+    DeliveryStatus, NewWebhookDelivery, WebhookDelivery, WebhookReplay,
+};
+use crate::domain::{bitbucket_event, gitlab_event};
+use crate::error::AppError;
+use crate::services::{
+    build_failure_service, build_log_service, build_service, security_gate_service,
+    security_service,
+};
+use crate::state::AppState;
+
+pub const GITHUB: &str = "github";
+pub const GITLAB: &str = "gitlab";
+pub const BITBUCKET: &str = "bitbucket";
+
+const GATED_PULL_REQUEST_ACTIONS: [&str; 4] =
+    ["opened", "synchronize", "reopened", "ready_for_review"];
+
+/// Maps a GitHub Actions run or check run onto Sentinai's build statuses.
+pub fn workflow_build_status(status: &str, conclusion: Option<&str>) -> BuildStatus {
+    match (status, conclusion) {
+        ("completed", Some("success" | "neutral" | "skipped")) => BuildStatus::Success,
+        ("completed", _) => BuildStatus::Failed,
+        ("in_progress", _) => BuildStatus::Running,
+        _ => BuildStatus::Pending,
+    }
+}
+
+/// Splits a clone or web URL into its lowercase host and repository path,
+/// e.g. `("gitlab.com", "group/sub/repo")`.
+pub fn repository_location(url: &str) -> Option<(String, String)> {
+    let url = url.trim().trim_end_matches('/');
+    let (host, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        None => url.split_once(':')?,
+    };
+    let host = host.rsplit('@').next()?.split(':').next()?;
+    let path = path.trim_end_matches(".git");
+    if host.is_empty() || !path.contains('/') || path.split('/').any(str::is_empty) {
+        return None;
+    }
+    Some((host.to_lowercase(), path.to_lowercase()))
+}
+
+/// Projects tracking the repository `full_name` (`owner/repo`), optionally
+/// only those owned by `owner`. When the repository's web address is known,
+/// the project must also live on the same host.
+async fn projects_for_repository(
+    state: &AppState,
+    full_name: &str,
+    html_url: Option<&str>,
+    owner_id: Option<Uuid>,
+) -> Result<Vec<Project>, AppError> {
+    let full_name = full_name.to_lowercase();
+    let host = html_url.and_then(repository_location).map(|(host, _)| host);
+    let projects = project_repo::list_projects_by_repository(&state.db, &full_name).await?;
+    Ok(projects
+        .into_iter()
+        .filter(|project| owner_id.is_none_or(|id| project.user_id == id))
+        .filter(|project| {
+            repository_location(&project.repository_url).is_some_and(|(h, path)| {
+                path == full_name && host.as_ref().is_none_or(|host| *host == h)
+            })
+        })
+        .collect())
+}
+
+fn spawn_scan(state: &AppState, project: Project) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = security_service::run_security_scan(&state, &project).await {
+            tracing::warn!(project_id = %project.id, error = %e, "Webhook-triggered scan failed");
+        }
+    });
+}
+
+fn spawn_gate_evaluation(state: &AppState, project: Project, commit_sha: String) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        // GitLab and Bitbucket projects are evaluated without a commit status.
+        let result = if security_gate_service::publishes_commit_status(&state, &project) {
+            security_gate_service::publish_commit_status(&state, &project, &commit_sha).await
+        } else {
+            security_gate_service::evaluate_gate(&state, &project).await
+        };
+        match result {
+            Ok(evaluation) => tracing::info!(
+                project_id = %project.id,
+                commit_sha = %commit_sha,
+                passed = evaluation.passed,
+                "Evaluated security gate"
+            ),
+            Err(e) => tracing::warn!(
+                project_id = %project.id,
+                error = %e,
+                "Webhook-triggered gate evaluation failed"
+            ),
+        }
+    });
+}
+
+/// Pushes to the default branch re-scan the repository.
+fn handle_push(state: &AppState, push: &PushEvent, projects: Vec<Project>) -> WebhookAction {
+    let default_ref = push
+        .repository
+        .default_branch
+        .as_ref()
+        .map(|branch| format!("refs/heads/{}", branch));
+    if push.deleted || default_ref.as_deref() != Some(push.git_ref.as_str()) {
+        return WebhookAction::Ignored;
+    }
+
+    for project in projects {
+        spawn_scan(state, project);
+    }
+    WebhookAction::ScanStarted
+}
+
+fn handle_pull_request(
+    state: &AppState,
+    event: &PullRequestEvent,
+    projects: Vec<Project>,
+) -> WebhookAction {
+    if !GATED_PULL_REQUEST_ACTIONS.contains(&event.action.as_str()) {
+        return WebhookAction::Ignored;
+    }
+
+    tracing::info!(number = event.number, action = %event.action, "Evaluating gate for pull request");
+    for project in projects {
+        spawn_gate_evaluation(state, project, event.pull_request.head.sha.clone());
+    }
+    WebhookAction::GateEvaluationStarted
+}
+
+/// GitHub asks for checks on a new or re-requested suite; the gate is
+/// Sentinai's check.
+fn handle_check_suite(
+    state: &AppState,
+    event: &CheckSuiteEvent,
+    projects: Vec<Project>,
+) -> WebhookAction {
+    if !matches!(event.action.as_str(), "requested" | "rerequested") {
+        return WebhookAction::Ignored;
+    }
+
+    for project in projects {
+        spawn_gate_evaluation(state, project, event.check_suite.head_sha.clone());
+    }
+    WebhookAction::GateEvaluationStarted
+}
+
+/// Records an externally run build on every project, creating it on the
+/// first report and updating it on later ones. Returns the builds that were
+/// written.
+async fn record_build<'a>(
+    state: &AppState,
+    build: &ExternalBuild,
+    projects: &'a [Project],
+) -> Result<Vec<(&'a Project, Build)>, AppError> {
+    let mut recorded = Vec::new();
+    for project in projects {
+        match build_repo::upsert_external_build(&state.db, project.id, build).await? {
+            Some(stored) => {
+                tracing::info!(
+                    project_id = %project.id,
+                    build_id = %stored.id,
+                    status = ?stored.status,
+                    "Recorded external build"
+                );
+                build_service::publish_build_updated(state, &stored);
+                if stored.status == BuildStatus::Failed {
+                    build_failure_service::spawn_classification(state, stored.id);
+                }
+                recorded.push((project, stored));
+            }
+            None => tracing::info!(
+                project_id = %project.id,
+                external_id = build.external_id,
+                "Ignoring stale report for finished build"
+            ),
+        }
+    }
+    Ok(recorded)
+}
+
+/// Imports a finished GitHub Actions run's log archive in the background.
+fn spawn_log_import(state: &AppState, project: Project, build: Build) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = build_log_service::import_workflow_run_logs(&state, &project, &build).await
+        {
+            tracing::warn!(build_id = %build.id, error = %e, "Could not import workflow run logs");
+        }
+    });
+}
+
+async fn handle_workflow_run(
+    state: &AppState,
+    event: &WorkflowRunEvent,
+    projects: &[Project],
+) -> Result<WebhookAction, AppError> {
+    let run = &event.workflow_run;
+    let status = workflow_build_status(&run.status, run.conclusion.as_deref());
+    tracing::info!(
+        run_id = run.id,
+        action = %event.action,
+        source = %event.source,
+        "Workflow run updated"
+    );
+    let build = ExternalBuild {
+        source: event.source.clone(),
+        external_id: run.id,
+        attempt: run.run_attempt,
+        name: run.name.clone(),
+        status,
+        conclusion: run.conclusion.clone(),
+        head_sha: run.head_sha.clone(),
+        head_branch: run.head_branch.clone(),
+        html_url: run.html_url.clone(),
+        started_at: run.run_started_at,
+        finished_at: status.is_finished().then_some(run.updated_at),
+    };
+    let recorded = record_build(state, &build, projects).await?;
+
+    if status.is_finished() && event.source == "workflow_run" && state.config.github_token.is_some()
+    {
+        for (project, build) in recorded {
+            spawn_log_import(state, project.clone(), build);
+        }
+    }
+    Ok(WebhookAction::BuildRecorded)
+}
+
+async fn handle_check_run(
+    state: &AppState,
+    event: &CheckRunEvent,
+    projects: &[Project],
+) -> Result<WebhookAction, AppError> {
+    if !matches!(
+        event.action.as_str(),
+        "created" | "completed" | "rerequested"
+    ) {
+        return Ok(WebhookAction::Ignored);
+    }
+
+    let run = &event.check_run;
+    let status = workflow_build_status(&run.status, run.conclusion.as_deref());
+    let build = ExternalBuild {
+        source: "check_run".into(),
+        external_id: run.id,
+        attempt: 1,
+        name: Some(run.name.clone()),
+        status,
+        conclusion: run.conclusion.clone(),
+        head_sha: run.head_sha.clone(),
+        head_branch: run
+            .check_suite
+            .as_ref()
+            .and_then(|suite| suite.head_branch.clone()),
+        html_url: run.html_url.clone(),
+        started_at: run.started_at,
+        finished_at: if status.is_finished() {
+            run.completed_at.or(Some(Utc::now()))
+        } else {
+            None
+        },
+    };
+    record_build(state, &build, projects).await?;
+    Ok(WebhookAction::BuildRecorded)
+}
+
+/// Routes a verified delivery to the handler for its event type, acting on
+/// every project that tracks the delivery's repository (and belongs to
+/// `owner_id`, when given). Scans and gate evaluations run in the background
+/// so GitHub gets a prompt response.
+#[tracing::instrument(name = "github_webhook", skip(state, event), fields(event = event.name()))]
+pub async fn dispatch(
+    state: &AppState,
+    event: GithubEvent,
+    owner_id: Option<Uuid>,
+) -> Result<WebhookOutcome, AppError> {
+    let projects = match event.repository() {
+        Some(repository) => {
+            projects_for_repository(
+                state,
+                &repository.full_name,
+                repository.html_url.as_deref(),
+                owner_id,
+            )
+            .await?
+        }
+        None => Vec::new(),
+    };
+    let project_ids: Vec<_> = projects.iter().map(|project| project.id).collect();
+
+    let action = if projects.is_empty() {
+        WebhookAction::Ignored
+    } else {
+        match &event {
+            GithubEvent::Push(push) => handle_push(state, push, projects),
+            GithubEvent::PullRequest(pull_request) => {
+                handle_pull_request(state, pull_request, projects)
+            }
+            GithubEvent::WorkflowRun(run) => handle_workflow_run(state, run, &projects).await?,
+            GithubEvent::CheckRun(run) => handle_check_run(state, run, &projects).await?,
+            GithubEvent::CheckSuite(suite) => handle_check_suite(state, suite, projects),
+            GithubEvent::Ping | GithubEvent::Other(_) => WebhookAction::Ignored,
+        }
+    };
+
+    tracing::info!(?action, projects = project_ids.len(), "Dispatched webhook");
+    Ok(WebhookOutcome {
+        event: event.name().to_string(),
+        action,
+        project_ids,
+    })
+}
+
+/// Parses a delivery from any supported forge into the event type the
+/// handlers act on.
+fn parse_delivery(delivery: &WebhookDelivery) -> Result<GithubEvent, AppError> {
+    let parsed = match delivery.provider.as_str() {
+        GITHUB => GithubEvent::parse(&delivery.event, &delivery.body),
+        GITLAB => gitlab_event::parse(&delivery.event, &delivery.body),
+        BITBUCKET => bitbucket_event::parse(&delivery.event, &delivery.body),
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "Unsupported webhook provider: {}",
+                other
+            )))
+        }
+    };
+    parsed.map_err(|e| AppError::BadRequest(format!("Invalid {} payload: {}", delivery.event, e)))
+}
+
+/// Parses and dispatches a stored delivery, recording the outcome on it.
+#[tracing::instrument(
+    name = "webhook_delivery",
+    skip(state, delivery),
+    fields(provider = %delivery.provider, delivery_id = %delivery.delivery_id)
+)]
+async fn process_delivery(
+    state: &AppState,
+    delivery: &WebhookDelivery,
+    owner_id: Option<Uuid>,
+) -> Result<WebhookOutcome, AppError> {
+    let result = match parse_delivery(delivery) {
+        Ok(event) => dispatch(state, event, owner_id).await,
+        Err(e) => Err(e),
+    };
+
+    match &result {
+        Ok(outcome) => {
+            webhook_delivery_repo::finish_delivery(
+                &state.db,
+                delivery.id,
+                DeliveryStatus::Processed,
+                Some(outcome.action),
+                None,
+            )
+            .await?
+        }
+        Err(e) => {
+            webhook_delivery_repo::finish_delivery(
+                &state.db,
+                delivery.id,
+                DeliveryStatus::Failed,
+                None,
+                Some(&e.to_string()),
+            )
+            .await?
+        }
+    }
+    result
+}
+
+/// Stores a verified delivery and processes it. Deliveries whose ID was seen
+/// before, such as GitHub's automatic redeliveries, are acknowledged without
+/// being processed again.
+#[tracing::instrument(name = "github_webhook", skip(state, delivery), fields(delivery_id = %delivery.delivery_id))]
+pub async fn receive(
+    state: &AppState,
+    delivery: NewWebhookDelivery,
+) -> Result<WebhookOutcome, AppError> {
+    let Some(stored) = webhook_delivery_repo::create_delivery(&state.db, &delivery).await? else {
+        tracing::info!("Ignoring duplicate webhook delivery");
+        return Ok(WebhookOutcome {
+            event: delivery.event,
+            action: WebhookAction::Duplicate,
+            project_ids: Vec::new(),
+        });
+    };
+
+    process_delivery(state, &stored, None).await
+}
+
+/// Re-dispatches a stored delivery through the same handlers, acting only on
+/// the caller's projects. A failed replay is recorded on the delivery rather
+/// than returned as an error.
+#[tracing::instrument(name = "webhook_replay", skip(state), fields(user_id = %user_id))]
+pub async fn replay(
+    state: &AppState,
+    user_id: Uuid,
+    provider: &str,
+    delivery_id: &str,
+) -> Result<WebhookReplay, AppError> {
+    let not_found = || AppError::NotFound("Webhook delivery not found".into());
+    let delivery = webhook_delivery_repo::get_delivery(&state.db, provider, delivery_id)
+        .await?
+        .ok_or_else(not_found)?;
+    let repository = delivery.repository.as_deref().ok_or_else(not_found)?;
+    if projects_for_repository(state, repository, None, Some(user_id))
+        .await?
+        .is_empty()
+    {
+        return Err(not_found());
+    }
+
+    let delivery = webhook_delivery_repo::start_replay(&state.db, delivery.id).await?;
+    tracing::info!(
+        replay_count = delivery.replay_count,
+        "Replaying webhook delivery"
+    );
+    let outcome = process_delivery(state, &delivery, Some(user_id)).await.ok();
+    let delivery = webhook_delivery_repo::get_delivery(&state.db, provider, delivery_id)
+        .await?
+        .ok_or_else(not_found)?;
+
+    Ok(WebhookReplay { delivery, outcome })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workflow_build_status() {
+        let status = workflow_build_status;
+        assert_eq!(status("completed", Some("success")), BuildStatus::Success);
+        assert_eq!(status("completed", Some("skipped")), BuildStatus::Success);
+        assert_eq!(status("completed", Some("failure")), BuildStatus::Failed);
+        assert_eq!(status("completed", Some("timed_out")), BuildStatus::Failed);
+        assert_eq!(status("completed", None), BuildStatus::Failed);
+        assert_eq!(status("in_progress", None), BuildStatus::Running);
+        assert_eq!(status("queued", None), BuildStatus::Pending);
+    }
+
+    #[test]
+    fn test_repository_location() {
+        let location = |host: &str, path: &str| Some((host.to_string(), path.to_string()));
+        assert_eq!(
+            repository_location("https://github.com/Org/Repo"),
+            location("github.com", "org/repo")
+        );
+        assert_eq!(
+            repository_location("git@github.com:org/repo.git"),
+            location("github.com", "org/repo")
+        );
+        assert_eq!(
+            repository_location("https://gitlab.example.com/group/sub/repo/"),
+            location("gitlab.example.com", "group/sub/repo")
+        );
+        assert_eq!(
+            repository_location("ssh://git@bitbucket.org:22/team/repo.git"),
+            location("bitbucket.org", "team/repo")
+        );
+        assert_eq!(repository_location("https://github.com/org"), None);
+        assert_eq!(repository_location("not a url"), None);
+    }
+}
@@ -0,0 +1,483 @@
+//! Normalization of declared dependency licenses to SPDX expressions and
+//! their evaluation against a project's license policy.
+
+use std::fmt;
+
+use crate::domain::license::LicensePolicy;
+
+/// SPDX identifiers Sentinai recognizes. Anything else is treated as unknown.
+const SPDX_IDS: &[&str] = &[
+    "0BSD",
+    "AFL-3.0",
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "Apache-1.1",
+    "Apache-2.0",
+    "Artistic-2.0",
+    "BlueOak-1.0.0",
+    "BSD-1-Clause",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "BSD-3-Clause-Clear",
+    "BSD-4-Clause",
+    "BSL-1.0",
+    "BUSL-1.1",
+    "CC-BY-3.0",
+    "CC-BY-4.0",
+    "CC-BY-SA-4.0",
+    "CC0-1.0",
+    "CDDL-1.0",
+    "CDDL-1.1",
+    "CPL-1.0",
+    "EPL-1.0",
+    "EPL-2.0",
+    "EUPL-1.1",
+    "EUPL-1.2",
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "HPND",
+    "ISC",
+    "LGPL-2.0-only",
+    "LGPL-2.0-or-later",
+    "LGPL-2.1-only",
+    "LGPL-2.1-or-later",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "MIT",
+    "MIT-0",
+    "MPL-1.1",
+    "MPL-2.0",
+    "MS-PL",
+    "NCSA",
+    "ODbL-1.0",
+    "OFL-1.1",
+    "OpenSSL",
+    "PostgreSQL",
+    "PSF-2.0",
+    "Python-2.0",
+    "SSPL-1.0",
+    "Unicode-3.0",
+    "Unicode-DFS-2016",
+    "Unlicense",
+    "UPL-1.0",
+    "W3C",
+    "WTFPL",
+    "X11",
+    "Zlib",
+];
+
+/// Deprecated SPDX identifiers and common free-form names, lowercased, with
+/// the identifier they stand for.
+const ALIASES: &[(&str, &str)] = &[
+    ("agpl-3.0", "AGPL-3.0-only"),
+    ("agpl-3.0+", "AGPL-3.0-or-later"),
+    ("agplv3", "AGPL-3.0-only"),
+    ("gnu affero general public license v3", "AGPL-3.0-only"),
+    (
+        "gnu affero general public license v3 or later",
+        "AGPL-3.0-or-later",
+    ),
+    ("apache 2.0", "Apache-2.0"),
+    ("apache 2", "Apache-2.0"),
+    ("apache-2", "Apache-2.0"),
+    ("apache2", "Apache-2.0"),
+    ("apache license 2.0", "Apache-2.0"),
+    ("apache license, version 2.0", "Apache-2.0"),
+    ("apache software license", "Apache-2.0"),
+    ("new bsd license", "BSD-3-Clause"),
+    ("3-clause bsd license", "BSD-3-Clause"),
+    ("bsd-3", "BSD-3-Clause"),
+    ("simplified bsd license", "BSD-2-Clause"),
+    ("2-clause bsd license", "BSD-2-Clause"),
+    ("bsd-2", "BSD-2-Clause"),
+    ("boost software license 1.0", "BSL-1.0"),
+    ("cc0", "CC0-1.0"),
+    ("gpl-2.0", "GPL-2.0-only"),
+    ("gpl-2.0+", "GPL-2.0-or-later"),
+    ("gplv2", "GPL-2.0-only"),
+    ("gpl-3.0", "GPL-3.0-only"),
+    ("gpl-3.0+", "GPL-3.0-or-later"),
+    ("gplv3", "GPL-3.0-only"),
+    ("isc license", "ISC"),
+    ("lgpl-2.0", "LGPL-2.0-only"),
+    ("lgpl-2.0+", "LGPL-2.0-or-later"),
+    ("lgpl-2.1", "LGPL-2.1-only"),
+    ("lgpl-2.1+", "LGPL-2.1-or-later"),
+    ("lgpl-3.0", "LGPL-3.0-only"),
+    ("lgpl-3.0+", "LGPL-3.0-or-later"),
+    ("lgplv3", "LGPL-3.0-only"),
+    ("mit license", "MIT"),
+    ("the mit license", "MIT"),
+    ("mozilla public license 2.0", "MPL-2.0"),
+    ("mpl 2.0", "MPL-2.0"),
+    ("python software foundation license", "PSF-2.0"),
+    ("the unlicense", "Unlicense"),
+    ("zlib license", "Zlib"),
+];
+
+/// Maps a single license name to its canonical SPDX identifier.
+pub fn normalize_license_id(name: &str) -> Option<String> {
+    let name = name.trim();
+    if let Some(id) = SPDX_IDS.iter().find(|id| id.eq_ignore_ascii_case(name)) {
+        return Some(id.to_string());
+    }
+    let lowered = name.to_lowercase();
+    ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == lowered)
+        .map(|(_, id)| id.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseExpr {
+    License(String),
+    Unknown(String),
+    With(Box<LicenseExpr>, String),
+    And(Vec<LicenseExpr>),
+    Or(Vec<LicenseExpr>),
+}
+
+impl LicenseExpr {
+    fn is_known(&self) -> bool {
+        match self {
+            LicenseExpr::License(_) => true,
+            LicenseExpr::Unknown(_) => false,
+            LicenseExpr::With(license, _) => license.is_known(),
+            LicenseExpr::And(terms) | LicenseExpr::Or(terms) => terms.iter().all(Self::is_known),
+        }
+    }
+}
+
+impl fmt::Display for LicenseExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, terms: &[LicenseExpr], op: &str| {
+            for (idx, term) in terms.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, " {} ", op)?;
+                }
+                match term {
+                    LicenseExpr::And(_) | LicenseExpr::Or(_) => write!(f, "({})", term)?,
+                    _ => write!(f, "{}", term)?,
+                }
+            }
+            Ok(())
+        };
+        match self {
+            LicenseExpr::License(id) | LicenseExpr::Unknown(id) => write!(f, "{}", id),
+            LicenseExpr::With(license, exception) => write!(f, "{} WITH {}", license, exception),
+            LicenseExpr::And(terms) => join(f, terms, "AND"),
+            LicenseExpr::Or(terms) => join(f, terms, "OR"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    With,
+    Word(String),
+}
+
+fn tokenize(expression: &str) -> Vec<Token> {
+    // Cargo used to accept `MIT/Apache-2.0` as a disjunction.
+    let spaced = expression
+        .replace('/', " OR ")
+        .replace('(', " ( ")
+        .replace(')', " ) ");
+
+    let mut tokens: Vec<Token> = Vec::new();
+    for word in spaced.split_whitespace() {
+        let token = match word {
+            "(" => Token::Open,
+            ")" => Token::Close,
+            _ if word.eq_ignore_ascii_case("and") => Token::And,
+            _ if word.eq_ignore_ascii_case("or") => Token::Or,
+            _ if word.eq_ignore_ascii_case("with") => Token::With,
+            _ => {
+                // Free-form names span several words, e.g. `Apache License 2.0`.
+                if let Some(Token::Word(previous)) = tokens.last_mut() {
+                    previous.push(' ');
+                    previous.push_str(word);
+                    continue;
+                }
+                Token::Word(word.to_string())
+            }
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        self.pos += 1;
+        self.tokens.get(self.pos - 1)
+    }
+
+    fn or_expr(&mut self) -> Option<LicenseExpr> {
+        let mut terms = vec![self.and_expr()?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            terms.push(self.and_expr()?);
+        }
+        Some(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            LicenseExpr::Or(terms)
+        })
+    }
+
+    fn and_expr(&mut self) -> Option<LicenseExpr> {
+        let mut terms = vec![self.with_expr()?];
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            terms.push(self.with_expr()?);
+        }
+        Some(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            LicenseExpr::And(terms)
+        })
+    }
+
+    fn with_expr(&mut self) -> Option<LicenseExpr> {
+        let license = self.atom()?;
+        if self.peek() != Some(&Token::With) {
+            return Some(license);
+        }
+        self.pos += 1;
+        match self.next() {
+            Some(Token::Word(exception)) => {
+                Some(LicenseExpr::With(Box::new(license), exception.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    fn atom(&mut self) -> Option<LicenseExpr> {
+        match self.next()? {
+            Token::Open => {
+                let expr = self.or_expr()?;
+                match self.next() {
+                    Some(Token::Close) => Some(expr),
+                    _ => None,
+                }
+            }
+            Token::Word(word) => Some(match normalize_license_id(word) {
+                Some(id) => LicenseExpr::License(id),
+                None => LicenseExpr::Unknown(word.clone()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a declared license into an expression over SPDX identifiers.
+/// Returns `None` when the text is not an expression at all.
+pub fn parse_license(declared: &str) -> Option<LicenseExpr> {
+    if let Some(id) = normalize_license_id(declared) {
+        return Some(LicenseExpr::License(id));
+    }
+
+    let mut parser = Parser {
+        tokens: tokenize(declared),
+        pos: 0,
+    };
+    let expr = parser.or_expr()?;
+    if parser.pos != parser.tokens.len() {
+        return None;
+    }
+    Some(expr)
+}
+
+/// The canonical SPDX expression for a declared license, if every license in
+/// it is recognized.
+pub fn normalize_license(declared: &str) -> Option<String> {
+    parse_license(declared)
+        .filter(LicenseExpr::is_known)
+        .map(|expr| expr.to_string())
+}
+
+/// Outcome of checking a license against a policy, from best to worst.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseVerdict {
+    Allowed,
+    Unknown,
+    NotAllowed(Vec<String>),
+    Denied(Vec<String>),
+}
+
+impl LicenseVerdict {
+    fn rank(&self) -> u8 {
+        match self {
+            LicenseVerdict::Allowed => 0,
+            LicenseVerdict::Unknown => 1,
+            LicenseVerdict::NotAllowed(_) => 2,
+            LicenseVerdict::Denied(_) => 3,
+        }
+    }
+
+    /// Both terms of a conjunction apply, so the worse one wins and offending
+    /// licenses of the same kind accumulate.
+    fn and(self, other: LicenseVerdict) -> LicenseVerdict {
+        match (self, other) {
+            (LicenseVerdict::Denied(mut a), LicenseVerdict::Denied(b)) => {
+                a.extend(b);
+                LicenseVerdict::Denied(a)
+            }
+            (LicenseVerdict::NotAllowed(mut a), LicenseVerdict::NotAllowed(b)) => {
+                a.extend(b);
+                LicenseVerdict::NotAllowed(a)
+            }
+            (a, b) if a.rank() >= b.rank() => a,
+            (_, b) => b,
+        }
+    }
+
+    /// A disjunction lets the consumer pick, so the better term wins.
+    fn or(self, other: LicenseVerdict) -> LicenseVerdict {
+        if other.rank() < self.rank() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+fn evaluate_expr(expr: &LicenseExpr, policy: &LicensePolicy) -> LicenseVerdict {
+    match expr {
+        LicenseExpr::License(id) => {
+            if policy.denied.iter().any(|denied| denied == id) {
+                LicenseVerdict::Denied(vec![id.clone()])
+            } else if !policy.allowed.is_empty() && !policy.allowed.iter().any(|a| a == id) {
+                LicenseVerdict::NotAllowed(vec![id.clone()])
+            } else {
+                LicenseVerdict::Allowed
+            }
+        }
+        LicenseExpr::Unknown(_) => LicenseVerdict::Unknown,
+        // Exceptions only grant additional permissions.
+        LicenseExpr::With(license, _) => evaluate_expr(license, policy),
+        LicenseExpr::And(terms) => terms
+            .iter()
+            .map(|term| evaluate_expr(term, policy))
+            .reduce(LicenseVerdict::and)
+            .unwrap_or(LicenseVerdict::Unknown),
+        LicenseExpr::Or(terms) => terms
+            .iter()
+            .map(|term| evaluate_expr(term, policy))
+            .reduce(LicenseVerdict::or)
+            .unwrap_or(LicenseVerdict::Unknown),
+    }
+}
+
+/// Checks a package's declared license, if any, against `policy`.
+pub fn evaluate(declared: Option<&str>, policy: &LicensePolicy) -> LicenseVerdict {
+    match declared.and_then(parse_license) {
+        Some(expr) => evaluate_expr(&expr, policy),
+        None => LicenseVerdict::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn policy(allowed: &[&str], denied: &[&str]) -> LicensePolicy {
+        LicensePolicy {
+            project_id: Uuid::nil(),
+            allowed: allowed.iter().map(|s| s.to_string()).collect(),
+            denied: denied.iter().map(|s| s.to_string()).collect(),
+            flag_unknown: true,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_license() {
+        assert_eq!(normalize_license("mit").as_deref(), Some("MIT"));
+        assert_eq!(
+            normalize_license("MIT/Apache-2.0").as_deref(),
+            Some("MIT OR Apache-2.0")
+        );
+        assert_eq!(
+            normalize_license("Apache License 2.0 or MIT License").as_deref(),
+            Some("Apache-2.0 OR MIT")
+        );
+        assert_eq!(
+            normalize_license("GPL-2.0+").as_deref(),
+            Some("GPL-2.0-or-later")
+        );
+        assert_eq!(
+            normalize_license("(MIT OR Apache-2.0) AND Unicode-DFS-2016").as_deref(),
+            Some("(MIT OR Apache-2.0) AND Unicode-DFS-2016")
+        );
+        assert_eq!(
+            normalize_license("GPL-2.0 WITH Classpath-exception-2.0").as_deref(),
+            Some("GPL-2.0-only WITH Classpath-exception-2.0")
+        );
+        assert_eq!(normalize_license("Proprietary"), None);
+        assert_eq!(normalize_license("(MIT"), None);
+    }
+
+    #[test]
+    fn test_evaluate_against_deny_list() {
+        let policy = LicensePolicy::default_for(Uuid::nil());
+
+        assert_eq!(evaluate(Some("MIT"), &policy), LicenseVerdict::Allowed);
+        assert_eq!(
+            evaluate(Some("AGPL-3.0"), &policy),
+            LicenseVerdict::Denied(vec!["AGPL-3.0-only".into()])
+        );
+        assert_eq!(
+            evaluate(Some("AGPL-3.0-or-later OR MIT"), &policy),
+            LicenseVerdict::Allowed
+        );
+        assert_eq!(
+            evaluate(Some("AGPL-3.0-or-later AND MIT"), &policy),
+            LicenseVerdict::Denied(vec!["AGPL-3.0-or-later".into()])
+        );
+        assert_eq!(evaluate(None, &policy), LicenseVerdict::Unknown);
+        assert_eq!(
+            evaluate(Some("see LICENSE.txt"), &policy),
+            LicenseVerdict::Unknown
+        );
+    }
+
+    #[test]
+    fn test_evaluate_against_allow_list() {
+        let policy = policy(&["MIT", "Apache-2.0"], &["GPL-3.0-only"]);
+
+        assert_eq!(
+            evaluate(Some("Apache-2.0 OR MIT"), &policy),
+            LicenseVerdict::Allowed
+        );
+        assert_eq!(
+            evaluate(Some("MPL-2.0 AND BSD-3-Clause"), &policy),
+            LicenseVerdict::NotAllowed(vec!["MPL-2.0".into(), "BSD-3-Clause".into()])
+        );
+        assert_eq!(
+            evaluate(Some("GPL-3.0 OR MPL-2.0"), &policy),
+            LicenseVerdict::NotAllowed(vec!["MPL-2.0".into()])
+        );
+        assert_eq!(
+            evaluate(Some("Custom OR MPL-2.0"), &policy),
+            LicenseVerdict::Unknown
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Language model providers used to diagnose and remediate failures.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use reqwest::Client;
+
+use crate::config::Config;
+use crate::domain::llm::{LlmMessage, LlmRequest, LlmResponse};
+use crate::error::AppError;
+
+mod openai;
+mod stub;
+
+pub use openai::OpenAiProvider;
+pub use stub::StubProvider;
+
+pub type LlmFuture<'a> = Pin<Box<dyn Future<Output = Result<LlmResponse, AppError>> + Send + 'a>>;
+
+/// A chat completion backend.
+pub trait LlmProvider: Send + Sync {
+    /// Recorded with each interaction, e.g. `openai`.
+    fn name(&self) -> &'static str;
+    fn model(&self) -> &str;
+    fn complete<'a>(&'a self, request: &'a LlmRequest) -> LlmFuture<'a>;
+}
+
+/// The provider `LLM_PROVIDER` selects, or `None` when it is unset.
+pub fn from_config(
+    config: &Config,
+    http_client: &Client,
+) -> Result<Option<Arc<dyn LlmProvider>>, AppError> {
+    let Some(provider) = config.llm_provider.as_deref() else {
+        return Ok(None);
+    };
+    let provider: Arc<dyn LlmProvider> = match provider {
+        "openai" => Arc::new(OpenAiProvider::new(
+            http_client.clone(),
+            &config.llm_base_url,
+            config.llm_api_key.clone(),
+            &config.llm_model,
+        )),
+        "stub" => Arc::new(StubProvider),
+        other => {
+            return Err(AppError::InternalServerError(format!(
+                "Unknown LLM_PROVIDER: {}",
+                other
+            )))
+        }
+    };
+    Ok(Some(provider))
+}
+
+/// A rough token count for `messages`, at about four characters a token.
+pub fn estimate_tokens(messages: &[LlmMessage]) -> i64 {
+    messages
+        .iter()
+        .map(|message| estimate_text_tokens(&message.content) + 4)
+        .sum()
+}
+
+/// A rough token count for a bare completion.
+pub fn estimate_text_tokens(text: &str) -> i64 {
+    (text.chars().count() as i64 + 3) / 4
+}
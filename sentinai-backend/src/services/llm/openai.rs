@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::llm::{LlmMessage, LlmRequest, LlmResponse};
+use crate::error::AppError;
+
+use super::{LlmFuture, LlmProvider};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Any server speaking the OpenAI chat completions API.
+pub struct OpenAiProvider {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [LlmMessage],
+    max_tokens: u32,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChatUsage {
+    prompt_tokens: i32,
+    completion_tokens: i32,
+}
+
+impl OpenAiProvider {
+    pub fn new(client: Client, base_url: &str, api_key: Option<String>, model: &str) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model: model.to_string(),
+        }
+    }
+
+    async fn chat(&self, request: &LlmRequest) -> Result<LlmResponse, AppError> {
+        let mut http_request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .timeout(REQUEST_TIMEOUT)
+            .json(&ChatRequest {
+                model: &self.model,
+                messages: &request.messages,
+                max_tokens: request.max_tokens,
+            });
+        if let Some(api_key) = &self.api_key {
+            http_request = http_request.bearer_auth(api_key);
+        }
+
+        let res = http_request
+            .send()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("LLM API error: {}", e)))?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let body = res.text().await.unwrap_or_default();
+            return Err(AppError::InternalServerError(format!(
+                "LLM API returned {}: {}",
+                status,
+                body.chars().take(500).collect::<String>()
+            )));
+        }
+
+        let response: ChatResponse = res.json().await.map_err(|e| {
+            AppError::InternalServerError(format!("Failed to parse LLM response: {}", e))
+        })?;
+        let content = response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| AppError::InternalServerError("LLM returned no answer".into()))?;
+        // Servers that leave out `usage` are charged an estimate instead, so
+        // their calls still count against the budget.
+        let (prompt_tokens, completion_tokens) = match response.usage {
+            Some(usage) => (usage.prompt_tokens, usage.completion_tokens),
+            None => (
+                super::estimate_tokens(&request.messages) as i32,
+                super::estimate_text_tokens(&content) as i32,
+            ),
+        };
+
+        Ok(LlmResponse {
+            content,
+            prompt_tokens,
+            completion_tokens,
+        })
+    }
+}
+
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn complete<'a>(&'a self, request: &'a LlmRequest) -> LlmFuture<'a> {
+        Box::pin(self.chat(request))
+    }
+}
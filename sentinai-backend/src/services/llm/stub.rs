@@ -0,0 +1,75 @@
+use crate::domain::llm::{LlmMessage, LlmRequest, LlmResponse};
+
+use super::{estimate_tokens, LlmFuture, LlmProvider};
+
+/// Answers without a model, for tests and local development. The answer
+/// depends only on the request.
+pub struct StubProvider;
+
+impl StubProvider {
+    fn answer(request: &LlmRequest) -> LlmResponse {
+        let prompt = request
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.role == "user")
+            .map(|message| message.content.as_str())
+            .unwrap_or_default();
+        let first_line = prompt.lines().next().unwrap_or_default();
+        let content: String = format!(
+            "Stub answer to: {}\n\n```diff\n--- a/README.md\n+++ b/README.md\n@@ -1 +1 @@\n-stub\n+stub\n```\n",
+            first_line
+        )
+        .chars()
+        .take(request.max_tokens as usize * 4)
+        .collect();
+        let completion_tokens = estimate_tokens(&[LlmMessage::user(content.as_str())]);
+
+        LlmResponse {
+            prompt_tokens: estimate_tokens(&request.messages) as i32,
+            completion_tokens: completion_tokens as i32,
+            content,
+        }
+    }
+}
+
+impl LlmProvider for StubProvider {
+    fn name(&self) -> &'static str {
+        "stub"
+    }
+
+    fn model(&self) -> &str {
+        "stub"
+    }
+
+    fn complete<'a>(&'a self, request: &'a LlmRequest) -> LlmFuture<'a> {
+        Box::pin(async move { Ok(Self::answer(request)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stub_is_deterministic() {
+        let request = LlmRequest {
+            messages: vec![
+                LlmMessage::system("You are a CI expert."),
+                LlmMessage::user("Summarize build ci\nerror[E0425]"),
+            ],
+            max_tokens: 256,
+        };
+        let first = StubProvider.complete(&request).await.unwrap();
+        let second = StubProvider.complete(&request).await.unwrap();
+        assert_eq!(first, second);
+        assert!(first
+            .content
+            .starts_with("Stub answer to: Summarize build ci\n"));
+        assert_eq!(
+            first.prompt_tokens as i64,
+            estimate_tokens(&request.messages)
+        );
+        assert!(first.completion_tokens > 0);
+    }
+}
@@ -0,0 +1,455 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::db::{llm_repo, project_repo, security_repo};
+use crate::domain::build::{Build, BuildStatus};
+use crate::domain::llm::{
+    LlmAnswer, LlmBudget, LlmCallStatus, LlmInteraction, LlmMessage, LlmPurpose, LlmRequest,
+    NewLlmInteraction,
+};
+use crate::domain::security::SecurityFinding;
+use crate::error::AppError;
+use crate::services::llm::{self, LlmProvider};
+use crate::services::repository::{self, RepoSnapshot};
+use crate::services::{build_log_service, build_service, failure_classifier};
+use crate::state::AppState;
+
+const MAX_LIMIT: i64 = 200;
+/// Completion tokens asked for when the budget allows.
+const MAX_COMPLETION_TOKENS: u32 = 1024;
+/// Calls that could not get at least this many completion tokens are rejected.
+const MIN_COMPLETION_TOKENS: i64 = 64;
+/// Repository files sent along with a fix request at most.
+const MAX_CONTEXT_FILES: usize = 3;
+/// Characters of each repository file sent at most.
+const MAX_FILE_CHARS: usize = 6000;
+
+const SYSTEM_PROMPT: &str = "You are Sentinai, a senior DevOps engineer helping a team keep their CI green and their dependencies secure. Be concise and specific, and only state what the evidence supports.";
+
+/// A project's token budget and what is left of it.
+pub async fn get_budget(state: &AppState, project_id: Uuid) -> Result<LlmBudget, AppError> {
+    let custom = llm_repo::get_budget(&state.db, project_id).await?;
+    let daily_token_limit = custom.unwrap_or(state.config.llm_daily_token_budget);
+    let used_tokens = llm_repo::tokens_used_today(&state.db, project_id).await?;
+
+    Ok(LlmBudget {
+        project_id,
+        daily_token_limit,
+        used_tokens,
+        remaining_tokens: (daily_token_limit - used_tokens).max(0),
+        custom: custom.is_some(),
+    })
+}
+
+#[tracing::instrument(name = "llm_budget", skip(state), fields(project_id = %project_id))]
+pub async fn update_budget(
+    state: &AppState,
+    project_id: Uuid,
+    daily_token_limit: i64,
+) -> Result<LlmBudget, AppError> {
+    if daily_token_limit < 0 {
+        return Err(AppError::BadRequest(
+            "daily_token_limit must not be negative".into(),
+        ));
+    }
+    llm_repo::upsert_budget(&state.db, project_id, daily_token_limit).await?;
+    get_budget(state, project_id).await
+}
+
+pub async fn list_interactions(
+    state: &AppState,
+    project_id: Uuid,
+    limit: i64,
+) -> Result<Vec<LlmInteraction>, AppError> {
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_LIMIT
+        )));
+    }
+    llm_repo::list_interactions(&state.db, project_id, limit).await
+}
+
+/// Explains in plain words why a failed build failed.
+#[tracing::instrument(name = "llm_build_summary", skip(state), fields(build_id = %build_id))]
+pub async fn summarize_build(
+    state: &AppState,
+    user_id: Uuid,
+    build_id: Uuid,
+) -> Result<LlmAnswer, AppError> {
+    let provider = provider(state)?;
+    let build = failed_build(state, user_id, build_id).await?;
+    let (category, excerpt) = failure_context(state, &build).await?;
+
+    let prompt = format!(
+        "Summarize why this CI build failed and what the team should do next.\n\n{}\nFailure category: {}\n{}\nLog excerpt:\n```\n{}\n```",
+        describe_build(&build),
+        category,
+        failing_tests_line(&build.failing_tests),
+        excerpt
+    );
+    let (interaction, content) = complete(
+        state,
+        provider.as_ref(),
+        Call {
+            project_id: build.project_id,
+            user_id,
+            purpose: LlmPurpose::BuildSummary,
+            subject_id: build.id,
+        },
+        vec![LlmMessage::system(SYSTEM_PROMPT), LlmMessage::user(prompt)],
+    )
+    .await?;
+
+    Ok(answer(interaction, content, None))
+}
+
+/// Drafts a patch for a failed build from its log excerpt and the repository
+/// files the excerpt mentions.
+#[tracing::instrument(name = "llm_fix_draft", skip(state), fields(build_id = %build_id))]
+pub async fn draft_fix(
+    state: &AppState,
+    user_id: Uuid,
+    build_id: Uuid,
+) -> Result<LlmAnswer, AppError> {
+    let provider = provider(state)?;
+    let build = failed_build(state, user_id, build_id).await?;
+    let (category, excerpt) = failure_context(state, &build).await?;
+
+    // The fix is drafted without file context when the repository is out of
+    // reach, e.g. for private repositories without GITHUB_TOKEN.
+    let mut files = String::new();
+    if let Some(sha) = build.head_sha.as_deref() {
+        let project = project_repo::get_project(&state.db, build.project_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Build not found".into()))?;
+        match repository::fetch_snapshot(state, &project.repository_url, sha).await {
+            Ok(snapshot) => {
+                for (path, contents) in relevant_files(&snapshot, &excerpt) {
+                    let contents: String = contents.chars().take(MAX_FILE_CHARS).collect();
+                    files.push_str(&format!("\nFile `{}`:\n```\n{}\n```\n", path, contents));
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "Drafting fix without repository files"),
+        }
+    }
+
+    let prompt = format!(
+        "Draft the smallest change that fixes this CI failure. Explain it briefly, then give it as a unified diff in a ```diff block with paths relative to the repository root.\n\n{}\nFailure category: {}\n{}\nLog excerpt:\n```\n{}\n```\n{}",
+        describe_build(&build),
+        category,
+        failing_tests_line(&build.failing_tests),
+        excerpt,
+        files
+    );
+    let (interaction, content) = complete(
+        state,
+        provider.as_ref(),
+        Call {
+            project_id: build.project_id,
+            user_id,
+            purpose: LlmPurpose::FixDraft,
+            subject_id: build.id,
+        },
+        vec![LlmMessage::system(SYSTEM_PROMPT), LlmMessage::user(prompt)],
+    )
+    .await?;
+    let patch = extract_patch(&content);
+
+    Ok(answer(interaction, content, patch))
+}
+
+/// Explains what a security finding means for the project and how to fix it.
+#[tracing::instrument(name = "llm_finding_explanation", skip(state), fields(finding_id = %finding_id))]
+pub async fn explain_finding(
+    state: &AppState,
+    user_id: Uuid,
+    project_id: Uuid,
+    finding_id: Uuid,
+) -> Result<LlmAnswer, AppError> {
+    let provider = provider(state)?;
+    let finding = security_repo::get_finding(&state.db, project_id, finding_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Finding not found".into()))?;
+
+    let (interaction, content) = complete(
+        state,
+        provider.as_ref(),
+        Call {
+            project_id,
+            user_id,
+            purpose: LlmPurpose::FindingExplanation,
+            subject_id: finding.id,
+        },
+        vec![
+            LlmMessage::system(SYSTEM_PROMPT),
+            LlmMessage::user(finding_prompt(&finding)),
+        ],
+    )
+    .await?;
+
+    Ok(answer(interaction, content, None))
+}
+
+struct Call {
+    project_id: Uuid,
+    user_id: Uuid,
+    purpose: LlmPurpose,
+    subject_id: Uuid,
+}
+
+fn provider(state: &AppState) -> Result<Arc<dyn LlmProvider>, AppError> {
+    state.llm.clone().ok_or_else(|| {
+        AppError::BadRequest("No LLM provider is configured; set LLM_PROVIDER".into())
+    })
+}
+
+/// Sends `messages` within the project's remaining token budget and records
+/// the interaction, whether or not it succeeds. The call's estimated tokens
+/// are reserved before it is sent, so concurrent calls cannot overspend.
+async fn complete(
+    state: &AppState,
+    provider: &dyn LlmProvider,
+    call: Call,
+    messages: Vec<LlmMessage>,
+) -> Result<(LlmInteraction, String), AppError> {
+    let daily_token_limit = llm_repo::get_budget(&state.db, call.project_id)
+        .await?
+        .unwrap_or(state.config.llm_daily_token_budget);
+    let record = |status, prompt_tokens, completion_tokens, error| NewLlmInteraction {
+        project_id: call.project_id,
+        user_id: Some(call.user_id),
+        purpose: call.purpose,
+        subject_id: Some(call.subject_id),
+        provider: provider.name(),
+        model: provider.model(),
+        messages: &messages,
+        response: None,
+        status,
+        error,
+        prompt_tokens,
+        completion_tokens,
+    };
+
+    let reserved = llm_repo::reserve_interaction(
+        &state.db,
+        &record(
+            LlmCallStatus::Pending,
+            llm::estimate_tokens(&messages) as i32,
+            MAX_COMPLETION_TOKENS as i32,
+            None,
+        ),
+        daily_token_limit,
+        MIN_COMPLETION_TOKENS,
+    )
+    .await?;
+    let Some(reserved) = reserved else {
+        let used_tokens = llm_repo::tokens_used_today(&state.db, call.project_id).await?;
+        let error = format!(
+            "Daily LLM token budget of {} is spent ({} used)",
+            daily_token_limit, used_tokens
+        );
+        llm_repo::record_interaction(
+            &state.db,
+            &record(LlmCallStatus::Rejected, 0, 0, Some(&error)),
+        )
+        .await?;
+        return Err(AppError::BadRequest(error));
+    };
+
+    let request = LlmRequest {
+        max_tokens: reserved.completion_tokens as u32,
+        messages: messages.clone(),
+    };
+    match provider.complete(&request).await {
+        Ok(response) => {
+            let interaction = llm_repo::finish_interaction(
+                &state.db,
+                reserved.id,
+                LlmCallStatus::Succeeded,
+                Some(&response.content),
+                None,
+                response.prompt_tokens,
+                response.completion_tokens,
+            )
+            .await?;
+            tracing::info!(
+                prompt_tokens = response.prompt_tokens,
+                completion_tokens = response.completion_tokens,
+                "LLM call succeeded"
+            );
+            Ok((interaction, response.content))
+        }
+        Err(e) => {
+            let error = e.to_string();
+            llm_repo::finish_interaction(
+                &state.db,
+                reserved.id,
+                LlmCallStatus::Failed,
+                None,
+                Some(&error),
+                0,
+                0,
+            )
+            .await?;
+            Err(e)
+        }
+    }
+}
+
+fn answer(interaction: LlmInteraction, content: String, patch: Option<String>) -> LlmAnswer {
+    LlmAnswer {
+        interaction_id: interaction.id,
+        purpose: interaction.purpose,
+        subject_id: interaction.subject_id.unwrap_or_default(),
+        content,
+        patch,
+        prompt_tokens: interaction.prompt_tokens,
+        completion_tokens: interaction.completion_tokens,
+    }
+}
+
+async fn failed_build(state: &AppState, user_id: Uuid, build_id: Uuid) -> Result<Build, AppError> {
+    let build = build_service::get_build(state, user_id, build_id).await?;
+    if build.status != BuildStatus::Failed {
+        return Err(AppError::BadRequest(
+            "Only failed builds can be diagnosed".into(),
+        ));
+    }
+    Ok(build)
+}
+
+/// The build's failure category and log excerpt, classifying the log now if
+/// the build has not been classified yet.
+async fn failure_context(state: &AppState, build: &Build) -> Result<(String, String), AppError> {
+    if let (Some(category), Some(excerpt)) = (build.failure_category, &build.failure_excerpt) {
+        return Ok((format!("{:?}", category), excerpt.clone()));
+    }
+    let log = build_log_service::read_log(state, build.id).await?;
+    let classification = failure_classifier::classify(&log, build.conclusion.as_deref());
+    Ok((
+        format!("{:?}", classification.category),
+        classification.excerpt.unwrap_or_default(),
+    ))
+}
+
+fn describe_build(build: &Build) -> String {
+    format!(
+        "Build: {}\nBranch: {}\nCommit: {}\nConclusion: {}",
+        build.name.as_deref().unwrap_or("unnamed"),
+        build.head_branch.as_deref().unwrap_or("unknown"),
+        build.head_sha.as_deref().unwrap_or("unknown"),
+        build.conclusion.as_deref().unwrap_or("failure"),
+    )
+}
+
+fn failing_tests_line(failing_tests: &[String]) -> String {
+    if failing_tests.is_empty() {
+        String::new()
+    } else {
+        format!("Failing tests: {}\n", failing_tests.join(", "))
+    }
+}
+
+fn finding_prompt(finding: &SecurityFinding) -> String {
+    let mut prompt = format!(
+        "Explain this security finding to the developers who own the repository: what the risk is, whether it is likely to matter, and how to fix it.\n\nScanner: {}\nRule: {}\nSeverity: {}\nDescription: {}\n",
+        finding.scanner, finding.rule_id, finding.severity, finding.description
+    );
+    if let Some(location) = &finding.location {
+        prompt.push_str(&format!("Location: {}\n", location));
+    }
+    if let Some(package) = &finding.package {
+        prompt.push_str(&format!("Package: {}\n", package));
+    }
+    if !finding.fixed_versions.is_empty() {
+        prompt.push_str(&format!(
+            "Fixed in: {}\n",
+            finding.fixed_versions.join(", ")
+        ));
+    }
+    if let Some(remediation) = &finding.remediation {
+        prompt.push_str(&format!("Scanner's remediation: {}\n", remediation));
+    }
+    prompt
+}
+
+/// Repository files mentioned in a log excerpt, in the order they are first
+/// mentioned. Runner paths such as `/home/runner/work/app/app/src/main.rs:4:9`
+/// match `src/main.rs`.
+fn relevant_files<'a>(snapshot: &'a RepoSnapshot, excerpt: &str) -> Vec<(&'a str, &'a str)> {
+    let mut files: Vec<(&str, &str)> = Vec::new();
+    let tokens = excerpt.split(|c: char| {
+        c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '\'' | '"' | '`' | ',')
+    });
+    for token in tokens {
+        let path = token.split(':').next().unwrap_or(token);
+        let path = path.trim_start_matches("./");
+        if !path.contains('.') || path.is_empty() {
+            continue;
+        }
+        let found = snapshot.files().find(|(file, _)| {
+            path == *file
+                || path
+                    .strip_suffix(file)
+                    .is_some_and(|prefix| prefix.ends_with('/'))
+        });
+        if let Some(found) = found {
+            if !files.iter().any(|(file, _)| *file == found.0) {
+                files.push(found);
+                if files.len() == MAX_CONTEXT_FILES {
+                    break;
+                }
+            }
+        }
+    }
+    files
+}
+
+/// The contents of the first ```diff (or ```patch) block in an answer.
+fn extract_patch(content: &str) -> Option<String> {
+    let start = ["```diff\n", "```patch\n"]
+        .iter()
+        .filter_map(|fence| content.find(fence).map(|i| i + fence.len()))
+        .min()?;
+    let body = &content[start..];
+    let end = body.find("\n```").map(|i| i + 1).unwrap_or(body.len());
+    let patch = &body[..end];
+    (!patch.trim().is_empty()).then(|| patch.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relevant_files() {
+        let snapshot = RepoSnapshot::from_files([
+            ("src/main.rs", "fn main() {}"),
+            ("src/lib.rs", "pub fn lib() {}"),
+            ("Cargo.toml", "[package]"),
+            ("README.md", "# app"),
+        ]);
+        let excerpt = "error[E0425]: cannot find value `x` in this scope\n  --> /home/runner/work/app/app/src/lib.rs:4:9\n   |\nnote: required by (src/main.rs:2)\nwarning: src/lib.rs:7 again; see Cargo.toml,";
+        let files: Vec<&str> = relevant_files(&snapshot, excerpt)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(files, vec!["src/lib.rs", "src/main.rs", "Cargo.toml"]);
+        assert!(relevant_files(&snapshot, "nothing to see in main.rsx").is_empty());
+    }
+
+    #[test]
+    fn test_extract_patch() {
+        let answer = "Import the missing item.\n\n```diff\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1,2 @@\n+use std::fmt;\n fn lib() {}\n```\nThat should do it.";
+        assert_eq!(
+            extract_patch(answer).as_deref(),
+            Some(
+                "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1,2 @@\n+use std::fmt;\n fn lib() {}\n"
+            )
+        );
+        assert_eq!(extract_patch("No change needed."), None);
+        assert_eq!(extract_patch("```diff\n\n```"), None);
+    }
+}
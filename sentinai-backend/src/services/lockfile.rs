@@ -0,0 +1,552 @@
+//! Resolves the third-party packages pinned by a repository's lockfiles.
+//!
+//! Supported inputs are `Cargo.lock`, npm's `package-lock.json` (lockfile
+//! versions 2 and 3) and pinned pip `requirements*.txt` files.
+
+use base64::Engine;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::domain::sbom::{Ecosystem, HashAlgorithm, Package, PackageHash, SbomContents};
+use crate::services::repository::RepoSnapshot;
+
+/// Packages resolved from a single lockfile plus the packages the project
+/// depends on directly.
+#[derive(Debug, Default)]
+struct Resolved {
+    packages: Vec<Package>,
+    roots: Vec<String>,
+}
+
+/// Resolves every supported lockfile in the snapshot. Lockfiles that fail to
+/// parse are reported in the returned warnings and otherwise skipped.
+pub fn resolve_packages(snapshot: &RepoSnapshot) -> (SbomContents, Vec<String>) {
+    let mut packages: BTreeMap<String, Package> = BTreeMap::new();
+    let mut roots = BTreeSet::new();
+    let mut warnings = Vec::new();
+
+    for (path, contents) in snapshot.files() {
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        let result = if file_name == "Cargo.lock" {
+            parse_cargo_lock(path, contents)
+        } else if file_name == "package-lock.json" {
+            parse_package_lock(path, contents)
+        } else if file_name.starts_with("requirements") && file_name.ends_with(".txt") {
+            parse_requirements(path, contents, &mut warnings)
+        } else {
+            continue;
+        };
+
+        match result {
+            Ok(resolved) => {
+                roots.extend(resolved.roots);
+                for package in resolved.packages {
+                    match packages.entry(package.purl()) {
+                        std::collections::btree_map::Entry::Vacant(entry) => {
+                            entry.insert(package);
+                        }
+                        std::collections::btree_map::Entry::Occupied(mut entry) => {
+                            let existing = entry.get_mut();
+                            for dependency in package.dependencies {
+                                if !existing.dependencies.contains(&dependency) {
+                                    existing.dependencies.push(dependency);
+                                }
+                            }
+                            if existing.license.is_none() {
+                                existing.license = package.license;
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => warnings.push(format!("{}: {}", path, e)),
+        }
+    }
+
+    let contents = SbomContents {
+        packages: packages.into_values().collect(),
+        root_dependencies: roots.into_iter().collect(),
+    };
+    (contents, warnings)
+}
+
+#[derive(Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<CargoLockPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoLockPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    checksum: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+fn parse_cargo_lock(path: &str, contents: &str) -> Result<Resolved, String> {
+    let lock: CargoLock = toml::from_str(contents).map_err(|e| e.to_string())?;
+
+    let purl_of = |package: &CargoLockPackage| {
+        Package {
+            ecosystem: Ecosystem::Cargo,
+            name: package.name.clone(),
+            version: package.version.clone(),
+            license: None,
+            hashes: Vec::new(),
+            dependencies: Vec::new(),
+            source_file: String::new(),
+        }
+        .purl()
+    };
+
+    // Entries look like `name`, `name version` or `name version (source)`,
+    // with the extra parts only present when the name alone is ambiguous.
+    let resolve = |dependency: &str| {
+        let mut parts = dependency.split_whitespace();
+        let name = parts.next()?;
+        let version = parts.next();
+        lock.package
+            .iter()
+            .find(|p| p.name == name && version.is_none_or(|v| p.version == v))
+    };
+
+    let mut resolved = Resolved::default();
+    for package in &lock.package {
+        let dependencies = package
+            .dependencies
+            .iter()
+            .filter_map(|d| resolve(d))
+            .filter(|d| d.source.is_some())
+            .map(purl_of)
+            .collect::<Vec<_>>();
+
+        // Workspace members and path dependencies have no source; they are
+        // the project itself rather than third-party packages.
+        if package.source.is_none() {
+            resolved.roots.extend(dependencies);
+            continue;
+        }
+
+        resolved.packages.push(Package {
+            ecosystem: Ecosystem::Cargo,
+            name: package.name.clone(),
+            version: package.version.clone(),
+            license: None,
+            hashes: package
+                .checksum
+                .iter()
+                .map(|checksum| PackageHash {
+                    algorithm: HashAlgorithm::Sha256,
+                    value: checksum.to_lowercase(),
+                })
+                .collect(),
+            dependencies,
+            source_file: path.to_string(),
+        });
+    }
+
+    Ok(resolved)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageLock {
+    lockfile_version: Option<u32>,
+    packages: Option<HashMap<String, PackageLockEntry>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageLockEntry {
+    name: Option<String>,
+    version: Option<String>,
+    integrity: Option<String>,
+    license: Option<serde_json::Value>,
+    #[serde(default)]
+    link: bool,
+    #[serde(default)]
+    dependencies: BTreeMap<String, String>,
+    #[serde(default)]
+    dev_dependencies: BTreeMap<String, String>,
+    #[serde(default)]
+    optional_dependencies: BTreeMap<String, String>,
+}
+
+impl PackageLockEntry {
+    fn dependency_names(&self) -> impl Iterator<Item = &String> {
+        self.dependencies
+            .keys()
+            .chain(self.dev_dependencies.keys())
+            .chain(self.optional_dependencies.keys())
+    }
+}
+
+/// Finds the installed location of `dependency` as seen from the package at
+/// `from`, following node's module resolution up through parent
+/// `node_modules` directories.
+fn resolve_node_module<'a>(
+    packages: &'a HashMap<String, PackageLockEntry>,
+    from: &str,
+    dependency: &str,
+) -> Option<(&'a str, &'a PackageLockEntry)> {
+    let mut base = from.to_string();
+    loop {
+        let candidate = if base.is_empty() {
+            format!("node_modules/{}", dependency)
+        } else {
+            format!("{}/node_modules/{}", base, dependency)
+        };
+        if let Some((key, entry)) = packages.get_key_value(&candidate) {
+            return Some((key.as_str(), entry));
+        }
+        if base.is_empty() {
+            return None;
+        }
+        base = match base.rfind("node_modules/") {
+            Some(idx) => base[..idx].trim_end_matches('/').to_string(),
+            None => String::new(),
+        };
+    }
+}
+
+fn npm_package(key: &str, entry: &PackageLockEntry, path: &str) -> Option<Package> {
+    let name = match &entry.name {
+        Some(name) => name.clone(),
+        None => key.rsplit_once("node_modules/")?.1.to_string(),
+    };
+    let license = match &entry.license {
+        Some(serde_json::Value::String(license)) => Some(license.clone()),
+        Some(serde_json::Value::Object(license)) => license
+            .get("type")
+            .and_then(|t| t.as_str())
+            .map(str::to_string),
+        _ => None,
+    };
+
+    Some(Package {
+        ecosystem: Ecosystem::Npm,
+        name,
+        version: entry.version.clone()?,
+        license,
+        hashes: entry
+            .integrity
+            .as_deref()
+            .map(parse_integrity)
+            .unwrap_or_default(),
+        dependencies: Vec::new(),
+        source_file: path.to_string(),
+    })
+}
+
+/// Decodes a Subresource Integrity string such as `sha512-<base64>`.
+fn parse_integrity(integrity: &str) -> Vec<PackageHash> {
+    integrity
+        .split_whitespace()
+        .filter_map(|hash| {
+            let (algorithm, digest) = hash.split_once('-')?;
+            let algorithm = match algorithm {
+                "sha256" => HashAlgorithm::Sha256,
+                "sha512" => HashAlgorithm::Sha512,
+                _ => return None,
+            };
+            let digest = base64::engine::general_purpose::STANDARD
+                .decode(digest)
+                .ok()?;
+            Some(PackageHash {
+                algorithm,
+                value: hex::encode(digest),
+            })
+        })
+        .collect()
+}
+
+fn parse_package_lock(path: &str, contents: &str) -> Result<Resolved, String> {
+    let lock: PackageLock = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let packages = match lock.packages {
+        Some(packages) if lock.lockfile_version.unwrap_or(1) >= 2 => packages,
+        _ => return Err("lockfile version 1 is not supported".to_string()),
+    };
+
+    let mut resolved = Resolved::default();
+    let mut keys: Vec<&String> = packages.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        let entry = &packages[key];
+        if entry.link {
+            continue;
+        }
+
+        let dependencies = entry
+            .dependency_names()
+            .filter_map(|dependency| resolve_node_module(&packages, key, dependency))
+            .filter(|(_, dependency)| !dependency.link)
+            .filter_map(|(dependency_key, dependency)| {
+                npm_package(dependency_key, dependency, path)
+            })
+            .map(|dependency| dependency.purl())
+            .collect::<Vec<_>>();
+
+        // The root entry and workspace members live outside node_modules.
+        if !key.contains("node_modules/") {
+            resolved.roots.extend(dependencies);
+            continue;
+        }
+
+        if let Some(mut package) = npm_package(key, entry, path) {
+            package.dependencies = dependencies;
+            resolved.packages.push(package);
+        }
+    }
+
+    Ok(resolved)
+}
+
+fn parse_requirements(
+    path: &str,
+    contents: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Resolved, String> {
+    let mut resolved = Resolved::default();
+    let joined = contents.replace("\\\r\n", " ").replace("\\\n", " ");
+
+    for line in joined.lines() {
+        let line = match line.find(" #") {
+            Some(idx) => &line[..idx],
+            None => line,
+        }
+        .trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+            continue;
+        }
+
+        let requirement = line
+            .split(" --")
+            .next()
+            .unwrap_or(line)
+            .split(';')
+            .next()
+            .unwrap_or(line)
+            .trim();
+        let hashes = line
+            .split_whitespace()
+            .filter_map(|token| token.strip_prefix("--hash=sha256:"))
+            .map(|digest| PackageHash {
+                algorithm: HashAlgorithm::Sha256,
+                value: digest.to_lowercase(),
+            })
+            .collect();
+
+        let Some((name, version)) = requirement
+            .split_once("===")
+            .or_else(|| requirement.split_once("=="))
+        else {
+            warnings.push(format!(
+                "{}: skipping unpinned requirement `{}`",
+                path, requirement
+            ));
+            continue;
+        };
+        let name = name.split('[').next().unwrap_or(name).trim();
+        let version = version.trim();
+        if name.is_empty() || version.is_empty() || version.contains(',') {
+            warnings.push(format!(
+                "{}: skipping unpinned requirement `{}`",
+                path, requirement
+            ));
+            continue;
+        }
+
+        let package = Package {
+            ecosystem: Ecosystem::Pypi,
+            name: name.to_string(),
+            version: version.to_string(),
+            license: None,
+            hashes,
+            dependencies: Vec::new(),
+            source_file: path.to_string(),
+        };
+        resolved.roots.push(package.purl());
+        resolved.packages.push(package);
+    }
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARGO_LOCK: &str = r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "serde",
+ "rand 0.8.5",
+]
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "34AF8D1A0E25924BC5B7C43C079C942339D8F0A8B57C39049BEF581B46327404"
+dependencies = [
+ "rand_core",
+]
+
+[[package]]
+name = "rand"
+version = "0.7.3"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "rand_core"
+version = "0.6.4"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "serde"
+version = "1.0.197"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+    const PACKAGE_LOCK: &str = r#"{
+  "name": "web",
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "name": "web", "dependencies": { "@scope/ui": "^1.0.0" }, "devDependencies": { "left-pad": "^1.3.0" } },
+    "node_modules/@scope/ui": {
+      "version": "1.2.0",
+      "integrity": "sha512-AAEC",
+      "license": "MIT",
+      "dependencies": { "left-pad": "^2.0.0" }
+    },
+    "node_modules/@scope/ui/node_modules/left-pad": { "version": "2.0.0", "license": "WTFPL" },
+    "node_modules/left-pad": { "version": "1.3.0", "license": "WTFPL", "dev": true },
+    "node_modules/local": { "resolved": "packages/local", "link": true }
+  }
+}"#;
+
+    fn purls(packages: &[Package]) -> Vec<String> {
+        packages.iter().map(Package::purl).collect()
+    }
+
+    #[test]
+    fn test_parse_cargo_lock() {
+        let resolved = parse_cargo_lock("Cargo.lock", CARGO_LOCK).unwrap();
+
+        assert_eq!(
+            purls(&resolved.packages),
+            vec![
+                "pkg:cargo/rand@0.8.5",
+                "pkg:cargo/rand@0.7.3",
+                "pkg:cargo/rand_core@0.6.4",
+                "pkg:cargo/serde@1.0.197",
+            ]
+        );
+        assert_eq!(
+            resolved.roots,
+            vec!["pkg:cargo/serde@1.0.197", "pkg:cargo/rand@0.8.5"]
+        );
+        assert_eq!(
+            resolved.packages[0].dependencies,
+            vec!["pkg:cargo/rand_core@0.6.4"]
+        );
+        assert_eq!(
+            resolved.packages[0].hashes,
+            vec![PackageHash {
+                algorithm: HashAlgorithm::Sha256,
+                value: "34af8d1a0e25924bc5b7c43c079c942339d8f0a8b57c39049bef581b46327404"
+                    .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_package_lock_resolves_nested_modules() {
+        let resolved = parse_package_lock("web/package-lock.json", PACKAGE_LOCK).unwrap();
+
+        assert_eq!(
+            purls(&resolved.packages),
+            vec![
+                "pkg:npm/%40scope/ui@1.2.0",
+                "pkg:npm/left-pad@2.0.0",
+                "pkg:npm/left-pad@1.3.0",
+            ]
+        );
+        assert_eq!(
+            resolved.roots,
+            vec!["pkg:npm/%40scope/ui@1.2.0", "pkg:npm/left-pad@1.3.0"]
+        );
+
+        let ui = &resolved.packages[0];
+        assert_eq!(ui.dependencies, vec!["pkg:npm/left-pad@2.0.0"]);
+        assert_eq!(ui.license.as_deref(), Some("MIT"));
+        assert_eq!(
+            ui.hashes,
+            vec![PackageHash {
+                algorithm: HashAlgorithm::Sha512,
+                value: "000102".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_package_lock_rejects_v1() {
+        let err = parse_package_lock(
+            "package-lock.json",
+            r#"{"lockfileVersion": 1, "dependencies": {}}"#,
+        )
+        .unwrap_err();
+        assert!(err.contains("version 1"));
+    }
+
+    #[test]
+    fn test_parse_requirements() {
+        let contents = "\
+# pinned deps
+Django==4.2.1 \\
+    --hash=sha256:ABCD
+requests[socks]==2.31.0 ; python_version >= \"3.8\"  # http
+-r other.txt
+flask>=2.0
+";
+        let mut warnings = Vec::new();
+        let resolved = parse_requirements("requirements.txt", contents, &mut warnings).unwrap();
+
+        assert_eq!(
+            purls(&resolved.packages),
+            vec!["pkg:pypi/django@4.2.1", "pkg:pypi/requests@2.31.0"]
+        );
+        assert_eq!(resolved.roots.len(), 2);
+        assert_eq!(resolved.packages[0].hashes[0].value, "abcd");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("flask>=2.0"));
+    }
+
+    #[test]
+    fn test_resolve_packages_merges_lockfiles() {
+        let snapshot = RepoSnapshot::from_files([
+            ("Cargo.lock", CARGO_LOCK),
+            ("web/package-lock.json", PACKAGE_LOCK),
+            ("requirements-dev.txt", "pytest==8.0.0\n"),
+            ("broken/package-lock.json", "{"),
+            ("README.md", "# readme"),
+        ]);
+
+        let (contents, warnings) = resolve_packages(&snapshot);
+
+        assert_eq!(contents.packages.len(), 8);
+        assert!(contents
+            .root_dependencies
+            .contains(&"pkg:pypi/pytest@8.0.0".to_string()));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("broken/package-lock.json"));
+    }
+}
@@ -1,4 +1,33 @@
+pub mod build_failure_service;
+pub mod build_fix_service;
+pub mod build_log;
+pub mod build_log_service;
+pub mod build_retry_service;
+pub mod build_service;
+pub mod ci_fix;
 pub mod ci_generator;
+pub mod dependency_bump;
+pub mod diff;
+pub mod failure_classifier;
+pub mod flaky_tests;
 pub mod github_service;
+pub mod github_webhook_service;
+pub mod license;
+pub mod llm;
+pub mod llm_service;
+pub mod lockfile;
 pub mod pipeline_service;
+pub mod registry;
+pub mod remediation_service;
+pub mod repository;
+pub mod sarif;
+pub mod sbom;
+pub mod sbom_service;
+pub mod scan_scheduler;
+pub mod scanners;
+pub mod security_gate_service;
+pub mod security_metrics_service;
 pub mod security_service;
+pub mod test_report;
+pub mod test_result_service;
+pub mod webhook;
@@ -0,0 +1,115 @@
+use serde::Deserialize;
+
+use crate::domain::sbom::Ecosystem;
+use crate::error::AppError;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+struct CratesIoVersion {
+    version: CratesIoVersionInfo,
+}
+
+#[derive(Deserialize)]
+struct CratesIoVersionInfo {
+    license: Option<String>,
+    checksum: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NpmVersion {
+    license: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct PypiRelease {
+    info: PypiInfo,
+}
+
+#[derive(Deserialize)]
+struct PypiInfo {
+    license_expression: Option<String>,
+    license: Option<String>,
+}
+
+async fn get_json<T: for<'de> Deserialize<'de>>(
+    state: &AppState,
+    url: &str,
+) -> Result<Option<T>, AppError> {
+    let res = state
+        .http_client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Registry error: {}", e)))?;
+
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !res.status().is_success() {
+        return Err(AppError::InternalServerError(format!(
+            "Registry returned {} for {}",
+            res.status(),
+            url
+        )));
+    }
+
+    let body = res.json().await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to parse registry response: {}", e))
+    })?;
+    Ok(Some(body))
+}
+
+/// Looks up the license a package version declares in its registry.
+#[tracing::instrument(name = "registry", skip(state))]
+pub async fn fetch_license(
+    state: &AppState,
+    ecosystem: Ecosystem,
+    name: &str,
+    version: &str,
+) -> Result<Option<String>, AppError> {
+    let license = match ecosystem {
+        Ecosystem::Cargo => {
+            let url = format!("https://crates.io/api/v1/crates/{}/{}", name, version);
+            get_json::<CratesIoVersion>(state, &url)
+                .await?
+                .and_then(|v| v.version.license)
+        }
+        Ecosystem::Npm => {
+            let url = format!("https://registry.npmjs.org/{}/{}", name, version);
+            get_json::<NpmVersion>(state, &url)
+                .await?
+                .and_then(|v| match v.license {
+                    Some(serde_json::Value::String(license)) => Some(license),
+                    _ => None,
+                })
+        }
+        Ecosystem::Pypi => {
+            let url = format!("https://pypi.org/pypi/{}/{}/json", name, version);
+            get_json::<PypiRelease>(state, &url).await?.and_then(|r| {
+                r.info.license_expression.or(r
+                    .info
+                    .license
+                    // Some projects paste their whole license text here.
+                    .filter(|l| !l.is_empty() && l.len() <= 64 && !l.contains('\n')))
+            })
+        }
+    };
+
+    Ok(license
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty()))
+}
+
+/// The SHA-256 checksum crates.io records for a crate version, as written to
+/// `Cargo.lock`.
+#[tracing::instrument(name = "registry", skip(state))]
+pub async fn fetch_crate_checksum(
+    state: &AppState,
+    name: &str,
+    version: &str,
+) -> Result<Option<String>, AppError> {
+    let url = format!("https://crates.io/api/v1/crates/{}/{}", name, version);
+    Ok(get_json::<CratesIoVersion>(state, &url)
+        .await?
+        .and_then(|v| v.version.checksum))
+}
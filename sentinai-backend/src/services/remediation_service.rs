@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+
+use uuid::Uuid;
+
+use crate::db::security_repo;
+use crate::domain::project::Project;
+use crate::domain::remediation::{
+    DependencyBumpRequest, DependencyBumpResult, FileChange, FileDiff, PackageUpgrade,
+    PullRequestRef, SkippedFinding,
+};
+use crate::domain::sbom::Ecosystem;
+use crate::domain::security::SecurityFinding;
+use crate::error::AppError;
+use crate::services::repository::RepoSnapshot;
+use crate::services::{dependency_bump, diff, github_service, lockfile, registry, repository};
+use crate::state::AppState;
+
+/// Changes Sentinai proposes as a pull request against a project's default
+/// branch.
+pub struct PullRequestDraft<'a> {
+    /// The branch is named `sentinai/<branch_prefix>-<random suffix>`.
+    pub branch_prefix: &'a str,
+    pub base_branch: &'a str,
+    pub base_sha: &'a str,
+    pub title: &'a str,
+    pub body: &'a str,
+    pub files: &'a [FileChange],
+}
+
+/// Rejects requests to open pull requests when no `GITHUB_TOKEN` is
+/// configured, before any work is done for them.
+pub fn require_pull_requests(state: &AppState, dry_run: bool) -> Result<(), AppError> {
+    if !dry_run && state.config.github_token.is_none() {
+        return Err(AppError::BadRequest(
+            "GITHUB_TOKEN must be configured to open pull requests".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Commits the draft's files to a new branch off its base commit and opens a
+/// pull request from that branch.
+#[tracing::instrument(name = "remediation", skip(state, project, draft), fields(project_id = %project.id))]
+pub async fn open_pull_request(
+    state: &AppState,
+    project: &Project,
+    draft: PullRequestDraft<'_>,
+) -> Result<PullRequestRef, AppError> {
+    let branch = format!(
+        "sentinai/{}-{}",
+        draft.branch_prefix,
+        &Uuid::new_v4().simple().to_string()[..8]
+    );
+    let files: Vec<(String, String)> = draft
+        .files
+        .iter()
+        .map(|change| (change.path.clone(), change.after.clone()))
+        .collect();
+    github_service::create_branch_commit(
+        state,
+        &project.repository_url,
+        draft.base_sha,
+        &branch,
+        draft.title,
+        &files,
+    )
+    .await?;
+    let pr = github_service::create_pull_request(
+        state,
+        &project.repository_url,
+        &branch,
+        draft.base_branch,
+        draft.title,
+        draft.body,
+    )
+    .await?;
+    tracing::info!(number = pr.number, branch = %branch, "Opened pull request");
+
+    Ok(PullRequestRef {
+        number: pr.number,
+        url: pr.html_url,
+        branch,
+    })
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn is_requirements_file(path: &str) -> bool {
+    let name = file_name(path);
+    name.starts_with("requirements") && name.ends_with(".txt")
+}
+
+/// Applies each upgrade to every manifest and lockfile of its ecosystem.
+/// Upgrades that change no file are dropped and their findings skipped.
+async fn apply_upgrades(
+    state: &AppState,
+    snapshot: &RepoSnapshot,
+    upgrades: Vec<PackageUpgrade>,
+    skipped: &mut Vec<SkippedFinding>,
+    warnings: &mut Vec<String>,
+) -> (Vec<PackageUpgrade>, BTreeMap<String, String>) {
+    let mut changed: BTreeMap<String, String> = BTreeMap::new();
+    let mut applied = Vec::new();
+
+    for upgrade in upgrades {
+        let checksum = match upgrade.ecosystem {
+            Ecosystem::Cargo => {
+                match registry::fetch_crate_checksum(state, &upgrade.name, &upgrade.to_version)
+                    .await
+                {
+                    Ok(Some(checksum)) => Some(checksum),
+                    Ok(None) | Err(_) => {
+                        warnings.push(format!(
+                            "No checksum for {} {}; run `cargo update -p {}` to record one",
+                            upgrade.name, upgrade.to_version, upgrade.name
+                        ));
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        let mut touched = false;
+        for (path, original) in snapshot.files() {
+            let current = changed.get(path).map(String::as_str).unwrap_or(original);
+            let (name, to) = (upgrade.name.as_str(), upgrade.to_version.as_str());
+            let updated = match (upgrade.ecosystem, file_name(path)) {
+                (Ecosystem::Cargo, "Cargo.toml") => {
+                    dependency_bump::bump_cargo_manifest(current, name, to)
+                }
+                (Ecosystem::Cargo, "Cargo.lock") => dependency_bump::bump_cargo_lock(
+                    current,
+                    name,
+                    &upgrade.from_version,
+                    to,
+                    checksum.as_deref(),
+                ),
+                (Ecosystem::Npm, "package.json") => {
+                    dependency_bump::bump_package_json(current, name, to)
+                }
+                (Ecosystem::Pypi, _) if is_requirements_file(path) => {
+                    dependency_bump::bump_requirements(current, name, to)
+                }
+                _ => None,
+            };
+            if let Some(updated) = updated {
+                touched = true;
+                changed.insert(path.to_string(), updated);
+            }
+        }
+
+        if !touched {
+            skipped.extend(
+                upgrade
+                    .finding_ids
+                    .iter()
+                    .map(|&finding_id| SkippedFinding {
+                        finding_id,
+                        package: upgrade.name.clone(),
+                        reason: "No manifest pins the package directly".into(),
+                    }),
+            );
+            continue;
+        }
+        if upgrade.ecosystem == Ecosystem::Npm {
+            warnings.push(format!(
+                "package-lock.json is not rewritten; run `npm install` to lock {} {}",
+                upgrade.name, upgrade.to_version
+            ));
+        }
+        applied.push(upgrade);
+    }
+
+    (applied, changed)
+}
+
+/// Upgrades the packages behind a project's open findings to their minimal
+/// fixed versions. On a dry run only the patch is returned; otherwise it is
+/// committed to a new branch and opened as a pull request.
+#[tracing::instrument(name = "remediation", skip(state, project, request), fields(project_id = %project.id))]
+pub async fn bump_dependencies(
+    state: &AppState,
+    project: &Project,
+    request: DependencyBumpRequest,
+) -> Result<DependencyBumpResult, AppError> {
+    require_pull_requests(state, request.dry_run)?;
+
+    let findings = security_repo::get_findings_for_project(&state.db, project.id).await?;
+    if let Some(missing) = request
+        .finding_ids
+        .iter()
+        .find(|id| !findings.iter().any(|f| f.id == **id))
+    {
+        return Err(AppError::NotFound(format!("Finding {} not found", missing)));
+    }
+    let findings: Vec<SecurityFinding> = findings
+        .into_iter()
+        .filter(|f| request.finding_ids.is_empty() || request.finding_ids.contains(&f.id))
+        .filter(|f| !f.status.is_closed() && f.package.is_some() && !f.fixed_versions.is_empty())
+        .collect();
+
+    let base_branch = github_service::get_default_branch(state, &project.repository_url).await?;
+    let base_sha =
+        github_service::get_commit_sha(state, &project.repository_url, &base_branch).await?;
+    let snapshot = repository::fetch_snapshot(state, &project.repository_url, &base_sha).await?;
+
+    let (contents, mut warnings) = lockfile::resolve_packages(&snapshot);
+    let (upgrades, mut skipped) = dependency_bump::plan_upgrades(&contents.packages, &findings);
+    let (upgrades, changed) =
+        apply_upgrades(state, &snapshot, upgrades, &mut skipped, &mut warnings).await;
+
+    let originals: BTreeMap<&str, &str> = snapshot.files().collect();
+    let changes: Vec<FileChange> = changed
+        .into_iter()
+        .map(|(path, after)| FileChange {
+            before: originals
+                .get(path.as_str())
+                .copied()
+                .unwrap_or_default()
+                .to_string(),
+            path,
+            after,
+        })
+        .collect();
+    let files: Vec<FileDiff> = changes
+        .iter()
+        .map(|change| FileDiff {
+            path: change.path.clone(),
+            diff: diff::unified_diff(&change.path, &change.before, &change.after),
+        })
+        .collect();
+    let patch: String = files.iter().map(|f| f.diff.as_str()).collect();
+
+    let pull_request = if request.dry_run {
+        None
+    } else {
+        if upgrades.is_empty() {
+            return Err(AppError::BadRequest(
+                "No dependency upgrades to apply".into(),
+            ));
+        }
+        let (title, body) = dependency_bump::describe_upgrades(&upgrades, &findings);
+        let pr = open_pull_request(
+            state,
+            project,
+            PullRequestDraft {
+                branch_prefix: "dependency-bumps",
+                base_branch: &base_branch,
+                base_sha: &base_sha,
+                title: &title,
+                body: &body,
+                files: &changes,
+            },
+        )
+        .await?;
+        Some(pr)
+    };
+
+    Ok(DependencyBumpResult {
+        project_id: project.id,
+        dry_run: request.dry_run,
+        base_branch,
+        base_sha,
+        upgrades,
+        skipped,
+        files,
+        warnings,
+        patch,
+        pull_request,
+    })
+}
@@ -0,0 +1,165 @@
+use flate2::read::GzDecoder;
+use std::collections::BTreeMap;
+use std::io::Read;
+
+use crate::error::AppError;
+use crate::services::github_service;
+use crate::state::AppState;
+
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+const MAX_SNAPSHOT_SIZE: u64 = 64 * 1024 * 1024;
+const SKIPPED_DIRS: [&str; 4] = [".git", "node_modules", "target", "vendor"];
+
+/// The text files of a repository at a given commit, keyed by their path
+/// relative to the repository root.
+#[derive(Debug, Default)]
+pub struct RepoSnapshot {
+    files: BTreeMap<String, String>,
+}
+
+impl RepoSnapshot {
+    #[cfg(test)]
+    pub fn from_files<I, P, C>(files: I) -> Self
+    where
+        I: IntoIterator<Item = (P, C)>,
+        P: Into<String>,
+        C: Into<String>,
+    {
+        Self {
+            files: files
+                .into_iter()
+                .map(|(p, c)| (p.into(), c.into()))
+                .collect(),
+        }
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.files.iter().map(|(p, c)| (p.as_str(), c.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+}
+
+/// Unpacks a gzipped tarball as served by GitHub's tarball endpoint. The
+/// leading `<owner>-<repo>-<sha>/` directory is stripped, and binary, oversized
+/// and vendored files are skipped.
+pub fn extract_tarball(bytes: &[u8]) -> Result<RepoSnapshot, AppError> {
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+    let mut files = BTreeMap::new();
+    let mut total: u64 = 0;
+
+    let entries = archive
+        .entries()
+        .map_err(|e| AppError::InternalServerError(format!("Invalid repository archive: {}", e)))?;
+
+    for entry in entries {
+        let mut entry = entry.map_err(|e| {
+            AppError::InternalServerError(format!("Invalid repository archive: {}", e))
+        })?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let size = entry.header().size().unwrap_or(u64::MAX);
+        if size > MAX_FILE_SIZE {
+            continue;
+        }
+
+        let path = match entry.path() {
+            Ok(path) => path.to_string_lossy().into_owned(),
+            Err(_) => continue,
+        };
+        let Some((_, relative)) = path.split_once('/') else {
+            continue;
+        };
+        if relative
+            .split('/')
+            .any(|segment| SKIPPED_DIRS.contains(&segment))
+        {
+            continue;
+        }
+
+        total += size;
+        if total > MAX_SNAPSHOT_SIZE {
+            return Err(AppError::InternalServerError(
+                "Repository is too large to scan".into(),
+            ));
+        }
+
+        let mut contents = Vec::with_capacity(size as usize);
+        if entry.read_to_end(&mut contents).is_err() {
+            continue;
+        }
+        if let Ok(text) = String::from_utf8(contents) {
+            files.insert(relative.to_string(), text);
+        }
+    }
+
+    Ok(RepoSnapshot { files })
+}
+
+#[tracing::instrument(name = "repository", skip(state))]
+pub async fn fetch_snapshot(
+    state: &AppState,
+    repository_url: &str,
+    git_ref: &str,
+) -> Result<RepoSnapshot, AppError> {
+    let bytes = github_service::download_tarball(state, repository_url, git_ref).await?;
+
+    let snapshot = tokio::task::spawn_blocking(move || extract_tarball(&bytes))
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Archive extraction failed: {}", e))
+        })??;
+
+    tracing::debug!(files = snapshot.len(), "Repository snapshot fetched");
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    fn tarball(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+        for (path, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *contents).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_extract_tarball_strips_prefix_and_skips_vendored_and_binary_files() {
+        let bytes = tarball(&[
+            ("org-repo-abc123/Cargo.lock", b"version = 3"),
+            ("org-repo-abc123/src/main.rs", b"fn main() {}"),
+            (
+                "org-repo-abc123/node_modules/left-pad/index.js",
+                b"module.exports = 1",
+            ),
+            ("org-repo-abc123/logo.png", &[0x89, 0x50, 0xff, 0xfe]),
+        ]);
+
+        let snapshot = extract_tarball(&bytes).unwrap();
+        assert_eq!(
+            snapshot.files().collect::<Vec<_>>(),
+            vec![
+                ("Cargo.lock", "version = 3"),
+                ("src/main.rs", "fn main() {}")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_tarball_rejects_garbage() {
+        assert!(extract_tarball(b"not a tarball").is_err());
+    }
+}
@@ -0,0 +1,662 @@
+//! Conversion between Sentinai findings and SARIF 2.1.0 logs.
+//!
+//! Only the parts of the SARIF object model that carry information Sentinai
+//! stores are modelled; everything else is ignored on import.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::domain::security::{FindingStatus, NewFinding, SecurityFinding};
+use crate::error::AppError;
+
+pub const SARIF_VERSION: &str = "2.1.0";
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const FINGERPRINT_KEY: &str = "sentinai/v1";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    pub version: String,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    #[serde(default)]
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub information_uri: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRule {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short_description: Option<SarifMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help: Option<SarifMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Map<String, Value>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    pub message: SarifMessage,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<SarifLocation>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub partial_fingerprints: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<SarifSuppression>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Map<String, Value>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    pub physical_location: Option<SarifPhysicalLocation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    pub artifact_location: Option<SarifArtifactLocation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SarifArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_column: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SarifSuppression {
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+fn severity_to_level(severity: &str) -> &'static str {
+    match severity {
+        "critical" | "high" => "error",
+        "medium" => "warning",
+        _ => "note",
+    }
+}
+
+/// Scores understood by GitHub code scanning's `security-severity` property.
+fn severity_to_score(severity: &str) -> &'static str {
+    match severity {
+        "critical" => "9.5",
+        "high" => "8.0",
+        "medium" => "5.5",
+        _ => "2.0",
+    }
+}
+
+fn level_to_severity(level: Option<&str>) -> &'static str {
+    match level {
+        Some("error") => "high",
+        Some("warning") => "medium",
+        _ => "low",
+    }
+}
+
+fn score_to_severity(score: f64) -> &'static str {
+    if score >= 9.0 {
+        "critical"
+    } else if score >= 7.0 {
+        "high"
+    } else if score >= 4.0 {
+        "medium"
+    } else {
+        "low"
+    }
+}
+
+fn security_severity(properties: Option<&Map<String, Value>>) -> Option<f64> {
+    match properties?.get("security-severity")? {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.as_f64(),
+        _ => None,
+    }
+}
+
+/// Splits a stored location of the form `path[:line[:column]]`.
+fn split_location(location: &str) -> (&str, Option<u32>, Option<u32>) {
+    let mut parts = location.rsplitn(3, ':');
+    let last = parts.next().unwrap_or(location);
+    let middle = parts.next();
+    let first = parts.next();
+
+    match (first, middle, last.parse::<u32>().ok()) {
+        (Some(path), Some(line), Some(column)) if line.parse::<u32>().is_ok() => {
+            (path, line.parse().ok(), Some(column))
+        }
+        (_, Some(_), Some(line)) => {
+            let path = &location[..location.len() - last.len() - 1];
+            (path, Some(line), None)
+        }
+        _ => (location, None, None),
+    }
+}
+
+/// The file a finding located by item or occurrence is in, e.g. `src/db.rs`
+/// for `src/db.rs#Repo::find[2]` or `app/db.py[2]`.
+fn location_uri(location: &str) -> &str {
+    let uri = location.split('#').next().unwrap_or(location);
+    match uri.strip_suffix(']').and_then(|rest| rest.rsplit_once('[')) {
+        Some((path, n)) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => path,
+        _ => uri,
+    }
+}
+
+/// Builds a SARIF log from a project's findings. Resolved findings are left
+/// out so that consumers see them as fixed; suppressed findings are emitted
+/// with an accepted external suppression.
+pub fn to_sarif(findings: &[SecurityFinding]) -> SarifLog {
+    let mut rules: Vec<SarifRule> = Vec::new();
+    let mut rule_indexes: HashMap<String, usize> = HashMap::new();
+    let mut results = Vec::new();
+
+    for finding in findings
+        .iter()
+        .filter(|f| f.status != FindingStatus::Resolved)
+    {
+        let rule_id = format!("{}/{}", finding.scanner, finding.rule_id);
+        let rule_index = *rule_indexes.entry(rule_id.clone()).or_insert_with(|| {
+            let mut properties = Map::new();
+            properties.insert(
+                "security-severity".into(),
+                Value::String(severity_to_score(&finding.severity).into()),
+            );
+            properties.insert("tags".into(), Value::from(vec!["security"]));
+            rules.push(SarifRule {
+                id: rule_id.clone(),
+                short_description: Some(SarifMessage {
+                    text: finding.description.clone(),
+                }),
+                help: None,
+                properties: Some(properties),
+            });
+            rules.len() - 1
+        });
+
+        let locations = finding
+            .location
+            .as_deref()
+            .map(|location| {
+                let (uri, start_line, start_column) = match finding.start_line {
+                    Some(line) => (
+                        location_uri(location),
+                        u32::try_from(line).ok(),
+                        finding.start_column.and_then(|c| u32::try_from(c).ok()),
+                    ),
+                    None => split_location(location),
+                };
+                vec![SarifLocation {
+                    physical_location: Some(SarifPhysicalLocation {
+                        artifact_location: Some(SarifArtifactLocation {
+                            uri: uri.to_string(),
+                        }),
+                        region: start_line.map(|line| SarifRegion {
+                            start_line: Some(line),
+                            start_column,
+                        }),
+                    }),
+                }]
+            })
+            .unwrap_or_default();
+
+        let suppressions = if finding.status.is_suppression() {
+            vec![SarifSuppression {
+                kind: "external".into(),
+                status: Some("accepted".into()),
+            }]
+        } else {
+            Vec::new()
+        };
+
+        let mut properties = Map::new();
+        properties.insert("severity".into(), Value::String(finding.severity.clone()));
+        properties.insert(
+            "security-severity".into(),
+            Value::String(severity_to_score(&finding.severity).into()),
+        );
+        properties.insert(
+            "status".into(),
+            Value::String(finding.status.as_str().into()),
+        );
+        if let Some(package) = &finding.package {
+            properties.insert("package".into(), Value::String(package.clone()));
+        }
+        if let Some(remediation) = &finding.remediation {
+            properties.insert("remediation".into(), Value::String(remediation.clone()));
+        }
+        if !finding.fixed_versions.is_empty() {
+            properties.insert(
+                "fixed_versions".into(),
+                Value::from(finding.fixed_versions.clone()),
+            );
+        }
+
+        results.push(SarifResult {
+            rule_id: Some(rule_id),
+            rule_index: Some(rule_index),
+            level: Some(severity_to_level(&finding.severity).into()),
+            message: SarifMessage {
+                text: finding.description.clone(),
+            },
+            locations,
+            partial_fingerprints: HashMap::from([(
+                FINGERPRINT_KEY.to_string(),
+                finding.fingerprint.clone(),
+            )]),
+            suppressions,
+            properties: Some(properties),
+        });
+    }
+
+    SarifLog {
+        schema: Some(SARIF_SCHEMA.into()),
+        version: SARIF_VERSION.into(),
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "Sentinai".into(),
+                    version: Some(env!("CARGO_PKG_VERSION").into()),
+                    information_uri: Some("https://github.com/krishpranav/sentinai".into()),
+                    rules,
+                },
+            },
+            results,
+        }],
+    }
+}
+
+/// The scanner that findings imported from the tool `driver_name` are stored
+/// under, e.g. `sarif:semgrep`. The prefix keeps them apart from Sentinai's
+/// own scanners, so an import never resolves their findings or vice versa.
+pub fn imported_scanner(driver_name: &str) -> String {
+    format!("sarif:{}", driver_name.trim().to_lowercase())
+}
+
+/// Converts every result of an external SARIF log into findings. Each tool
+/// gets its own scanner, so results from different tools never deduplicate
+/// against each other. Findings are located by file only, so they survive
+/// edits that move them; the tool's partial fingerprints tell apart results
+/// of one rule in one file, or else a `[n]` suffix on the nth of them.
+pub fn from_sarif(log: &SarifLog) -> Result<Vec<NewFinding>, AppError> {
+    if log.version != SARIF_VERSION {
+        return Err(AppError::BadRequest(format!(
+            "Unsupported SARIF version {}, expected {}",
+            log.version, SARIF_VERSION
+        )));
+    }
+
+    let mut findings = Vec::new();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    for run in &log.runs {
+        if run.tool.driver.name.trim().is_empty() {
+            return Err(AppError::BadRequest(
+                "SARIF run is missing a tool name".into(),
+            ));
+        }
+        let scanner = imported_scanner(&run.tool.driver.name);
+
+        for result in &run.results {
+            let rule = result
+                .rule_index
+                .and_then(|i| run.tool.driver.rules.get(i))
+                .or_else(|| {
+                    let id = result.rule_id.as_deref()?;
+                    run.tool.driver.rules.iter().find(|r| r.id == id)
+                });
+            let rule_id = result
+                .rule_id
+                .clone()
+                .or_else(|| rule.map(|r| r.id.clone()))
+                .unwrap_or_else(|| "unknown".into());
+
+            let severity = security_severity(result.properties.as_ref())
+                .or_else(|| security_severity(rule.and_then(|r| r.properties.as_ref())))
+                .map(score_to_severity)
+                .unwrap_or_else(|| level_to_severity(result.level.as_deref()));
+
+            let physical = result
+                .locations
+                .iter()
+                .filter_map(|l| l.physical_location.as_ref())
+                .find(|p| p.artifact_location.is_some());
+            let location = physical.and_then(|p| Some(p.artifact_location.as_ref()?.uri.clone()));
+            let region = physical.and_then(|p| p.region.as_ref());
+            let start_line = region
+                .and_then(|r| r.start_line)
+                .and_then(|line| i32::try_from(line).ok());
+            let start_column = region
+                .and_then(|r| r.start_column)
+                .and_then(|column| i32::try_from(column).ok());
+
+            let package = result
+                .properties
+                .as_ref()
+                .and_then(|p| p.get("package"))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+
+            let remediation = result
+                .properties
+                .as_ref()
+                .and_then(|p| p.get("remediation"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .or_else(|| rule.and_then(|r| r.help.as_ref()).map(|h| h.text.clone()));
+
+            let fixed_versions = match result
+                .properties
+                .as_ref()
+                .and_then(|p| p.get("fixed_versions").or_else(|| p.get("fixed_version")))
+            {
+                Some(Value::String(version)) => vec![version.clone()],
+                Some(Value::Array(versions)) => versions
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            let partial_fingerprint = (!result.partial_fingerprints.is_empty()).then(|| {
+                let mut pairs: Vec<String> = result
+                    .partial_fingerprints
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .collect();
+                pairs.sort();
+                pairs.join(",")
+            });
+
+            let mut finding = NewFinding {
+                scanner: scanner.clone(),
+                rule_id,
+                severity: severity.to_string(),
+                description: result.message.text.clone(),
+                location,
+                start_line,
+                start_column,
+                package,
+                remediation,
+                fixed_versions,
+                partial_fingerprint,
+            };
+            let seen = occurrences.entry(finding.fingerprint()).or_insert(0);
+            *seen += 1;
+            if *seen > 1 {
+                if let Some(location) = &mut finding.location {
+                    location.push_str(&format!("[{}]", seen));
+                }
+            }
+            findings.push(finding);
+        }
+    }
+
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use insta::assert_json_snapshot;
+    use uuid::Uuid;
+
+    fn stored(rule_id: &str, severity: &str, location: Option<&str>) -> SecurityFinding {
+        let at = Utc.with_ymd_and_hms(2026, 2, 22, 10, 20, 0).unwrap();
+        SecurityFinding {
+            id: Uuid::nil(),
+            project_id: Uuid::nil(),
+            scanner: "dependency-audit".into(),
+            rule_id: rule_id.into(),
+            severity: severity.into(),
+            description: format!("{} finding", rule_id),
+            location: location.map(str::to_string),
+            start_line: None,
+            start_column: None,
+            package: Some("openssl".into()),
+            remediation: None,
+            fixed_versions: Vec::new(),
+            fingerprint: format!("fp-{}", rule_id),
+            status: FindingStatus::Open,
+            status_expires_at: None,
+            created_at: at,
+            last_seen_at: at,
+            fixed_at: None,
+            scan_run_id: None,
+            last_scan_run_id: None,
+        }
+    }
+
+    #[test]
+    fn test_split_location() {
+        assert_eq!(split_location("Cargo.lock"), ("Cargo.lock", None, None));
+        assert_eq!(
+            split_location("src/main.rs:12"),
+            ("src/main.rs", Some(12), None)
+        );
+        assert_eq!(
+            split_location("src/main.rs:12:5"),
+            ("src/main.rs", Some(12), Some(5))
+        );
+        assert_eq!(
+            split_location("C:\\repo\\Dockerfile:3"),
+            ("C:\\repo\\Dockerfile", Some(3), None)
+        );
+    }
+
+    #[test]
+    fn test_to_sarif_export() {
+        let mut suppressed = stored("weak-hash", "medium", Some("src/auth.rs:40:9"));
+        suppressed.status = FindingStatus::FalsePositive;
+        let mut resolved = stored("old-tls", "low", None);
+        resolved.status = FindingStatus::Resolved;
+
+        let log = to_sarif(&[
+            stored("outdated-dependency", "critical", Some("Cargo.lock")),
+            suppressed,
+            resolved,
+        ]);
+        let mut value = serde_json::to_value(&log).unwrap();
+        value["runs"][0]["tool"]["driver"]["version"] = Value::String("[version]".into());
+
+        assert_json_snapshot!("sarif_export", value);
+    }
+
+    #[test]
+    fn test_from_sarif_maps_levels_and_security_severity() {
+        let log: SarifLog = serde_json::from_value(serde_json::json!({
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "Semgrep",
+                        "rules": [{
+                            "id": "python.sql-injection",
+                            "properties": { "security-severity": "9.8" }
+                        }]
+                    }
+                },
+                "results": [
+                    {
+                        "ruleId": "python.sql-injection",
+                        "ruleIndex": 0,
+                        "level": "error",
+                        "message": { "text": "SQL built from user input" },
+                        "locations": [{
+                            "physicalLocation": {
+                                "artifactLocation": { "uri": "app/db.py" },
+                                "region": { "startLine": 17, "startColumn": 4 }
+                            }
+                        }]
+                    },
+                    {
+                        "ruleId": "clippy::unwrap_used",
+                        "level": "warning",
+                        "message": { "text": "used `unwrap()` on a `Result` value" }
+                    },
+                    {
+                        "level": "note",
+                        "message": { "text": "informational" }
+                    }
+                ]
+            }]
+        }))
+        .unwrap();
+
+        let findings = from_sarif(&log).unwrap();
+        assert_eq!(findings.len(), 3);
+
+        assert_eq!(findings[0].scanner, "sarif:semgrep");
+        assert_eq!(findings[0].rule_id, "python.sql-injection");
+        assert_eq!(findings[0].severity, "critical");
+        assert_eq!(findings[0].location.as_deref(), Some("app/db.py"));
+        assert_eq!(findings[0].start_line, Some(17));
+        assert_eq!(findings[0].start_column, Some(4));
+
+        assert_eq!(findings[1].severity, "medium");
+        assert_eq!(findings[1].location, None);
+
+        assert_eq!(findings[2].rule_id, "unknown");
+        assert_eq!(findings[2].severity, "low");
+    }
+
+    #[test]
+    fn test_sarif_round_trip_keeps_identity() {
+        let mut original = stored("outdated-dependency", "high", Some("Cargo.lock:3"));
+        original.fixed_versions = vec!["0.10.66".into()];
+        let exported = serde_json::to_string(&to_sarif(&[original])).unwrap();
+        let imported = from_sarif(&serde_json::from_str(&exported).unwrap()).unwrap();
+
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].scanner, "sarif:sentinai");
+        assert_eq!(imported[0].rule_id, "dependency-audit/outdated-dependency");
+        assert_eq!(imported[0].severity, "high");
+        assert_eq!(imported[0].location.as_deref(), Some("Cargo.lock"));
+        assert_eq!(imported[0].start_line, Some(3));
+        assert_eq!(imported[0].package.as_deref(), Some("openssl"));
+        assert_eq!(imported[0].fixed_versions, vec!["0.10.66".to_string()]);
+    }
+
+    fn results_at(lines: &[u32], partial_fingerprints: &[&str]) -> SarifLog {
+        let results: Vec<Value> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let mut result = serde_json::json!({
+                    "ruleId": "python.sql-injection",
+                    "message": { "text": "SQL built from user input" },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": "app/db.py" },
+                            "region": { "startLine": line }
+                        }
+                    }]
+                });
+                if let Some(hash) = partial_fingerprints.get(i) {
+                    result["partialFingerprints"] =
+                        serde_json::json!({ "primaryLocationLineHash": hash });
+                }
+                result
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "version": "2.1.0",
+            "runs": [{ "tool": { "driver": { "name": "Semgrep" } }, "results": results }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_sarif_identity_survives_line_shifts() {
+        let fingerprints = |log: &SarifLog| -> Vec<String> {
+            from_sarif(log)
+                .unwrap()
+                .iter()
+                .map(NewFinding::fingerprint)
+                .collect()
+        };
+
+        let before = from_sarif(&results_at(&[17, 40], &[])).unwrap();
+        assert_eq!(before[0].location.as_deref(), Some("app/db.py"));
+        assert_eq!(before[1].location.as_deref(), Some("app/db.py[2]"));
+        assert_eq!(
+            fingerprints(&results_at(&[17, 40], &[])),
+            fingerprints(&results_at(&[20, 43], &[]))
+        );
+
+        let hashed = fingerprints(&results_at(&[17, 40], &["a1", "b2"]));
+        assert_eq!(hashed, fingerprints(&results_at(&[20, 43], &["a1", "b2"])));
+        // With the tool's own identity, dropping the first result keeps the
+        // second one's fingerprint.
+        assert_eq!(hashed[1], fingerprints(&results_at(&[43], &["b2"]))[0]);
+    }
+
+    #[test]
+    fn test_location_uri() {
+        assert_eq!(location_uri("app/db.py"), "app/db.py");
+        assert_eq!(location_uri("app/db.py[2]"), "app/db.py");
+        assert_eq!(location_uri("src/db.rs#Repo::find[3]"), "src/db.rs");
+        assert_eq!(location_uri("data[x]"), "data[x]");
+    }
+
+    #[test]
+    fn test_from_sarif_rejects_other_versions() {
+        let log = SarifLog {
+            schema: None,
+            version: "2.0.0".into(),
+            runs: Vec::new(),
+        };
+        assert!(matches!(from_sarif(&log), Err(AppError::BadRequest(_))));
+    }
+}
@@ -0,0 +1,437 @@
+//! Rendering of stored package inventories as CycloneDX 1.5 and SPDX 2.3
+//! JSON documents.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::domain::project::Project;
+use crate::domain::sbom::{HashAlgorithm, PackageHash, Sbom};
+
+pub const CYCLONEDX_SPEC_VERSION: &str = "1.5";
+pub const SPDX_VERSION: &str = "SPDX-2.3";
+const TOOL_NAME: &str = "sentinai";
+const NOASSERTION: &str = "NOASSERTION";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycloneDxBom {
+    pub bom_format: &'static str,
+    pub spec_version: &'static str,
+    pub serial_number: String,
+    pub version: u32,
+    pub metadata: CycloneDxMetadata,
+    pub components: Vec<CycloneDxComponent>,
+    pub dependencies: Vec<CycloneDxDependency>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CycloneDxMetadata {
+    pub timestamp: String,
+    pub tools: CycloneDxTools,
+    pub component: CycloneDxComponent,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CycloneDxTools {
+    pub components: Vec<CycloneDxComponent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycloneDxComponent {
+    #[serde(rename = "type")]
+    pub component_type: &'static str,
+    #[serde(rename = "bom-ref", skip_serializing_if = "Option::is_none")]
+    pub bom_ref: Option<String>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purl: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<CycloneDxLicenseChoice>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hashes: Vec<CycloneDxHash>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub external_references: Vec<CycloneDxExternalReference>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum CycloneDxLicenseChoice {
+    Expression { expression: String },
+    License { license: CycloneDxLicense },
+}
+
+#[derive(Debug, Serialize)]
+pub struct CycloneDxLicense {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CycloneDxHash {
+    pub alg: &'static str,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CycloneDxExternalReference {
+    #[serde(rename = "type")]
+    pub reference_type: &'static str,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycloneDxDependency {
+    #[serde(rename = "ref")]
+    pub reference: String,
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxDocument {
+    pub spdx_version: &'static str,
+    pub data_license: &'static str,
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: &'static str,
+    pub name: String,
+    pub document_namespace: String,
+    pub creation_info: SpdxCreationInfo,
+    pub packages: Vec<SpdxPackage>,
+    pub relationships: Vec<SpdxRelationship>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpdxCreationInfo {
+    pub created: String,
+    pub creators: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxPackage {
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_info: Option<String>,
+    pub download_location: String,
+    pub files_analyzed: bool,
+    pub license_concluded: String,
+    pub license_declared: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub checksums: Vec<SpdxChecksum>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub external_refs: Vec<SpdxExternalRef>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxChecksum {
+    pub algorithm: &'static str,
+    pub checksum_value: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxExternalRef {
+    pub reference_category: &'static str,
+    pub reference_type: &'static str,
+    pub reference_locator: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpdxRelationship {
+    pub spdx_element_id: String,
+    pub relationship_type: &'static str,
+    pub related_spdx_element: String,
+}
+
+/// Whether `license` reads as an SPDX license expression, as opposed to
+/// free-form text copied from a registry.
+fn is_spdx_expression(license: &str) -> bool {
+    !license.is_empty()
+        && license
+            .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .filter(|token| !token.is_empty())
+            .all(|token| {
+                token
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+' | ':'))
+            })
+}
+
+fn cyclonedx_hash(hash: &PackageHash) -> CycloneDxHash {
+    CycloneDxHash {
+        alg: match hash.algorithm {
+            HashAlgorithm::Sha256 => "SHA-256",
+            HashAlgorithm::Sha512 => "SHA-512",
+        },
+        content: hash.value.clone(),
+    }
+}
+
+fn spdx_checksum(hash: &PackageHash) -> SpdxChecksum {
+    SpdxChecksum {
+        algorithm: match hash.algorithm {
+            HashAlgorithm::Sha256 => "SHA256",
+            HashAlgorithm::Sha512 => "SHA512",
+        },
+        checksum_value: hash.value.clone(),
+    }
+}
+
+pub fn to_cyclonedx(project: &Project, sbom: &Sbom) -> CycloneDxBom {
+    let root_ref = format!("project:{}", project.id);
+    let packages = &sbom.contents.packages;
+
+    let components = packages
+        .iter()
+        .map(|package| {
+            let purl = package.purl();
+            CycloneDxComponent {
+                component_type: "library",
+                bom_ref: Some(purl.clone()),
+                name: package.name.clone(),
+                version: Some(package.version.clone()),
+                purl: Some(purl),
+                licenses: package
+                    .license
+                    .iter()
+                    .map(|license| {
+                        if is_spdx_expression(license) {
+                            CycloneDxLicenseChoice::Expression {
+                                expression: license.clone(),
+                            }
+                        } else {
+                            CycloneDxLicenseChoice::License {
+                                license: CycloneDxLicense {
+                                    name: license.clone(),
+                                },
+                            }
+                        }
+                    })
+                    .collect(),
+                hashes: package.hashes.iter().map(cyclonedx_hash).collect(),
+                external_references: Vec::new(),
+            }
+        })
+        .collect();
+
+    let mut dependencies = vec![CycloneDxDependency {
+        reference: root_ref.clone(),
+        depends_on: sbom.contents.root_dependencies.clone(),
+    }];
+    dependencies.extend(packages.iter().map(|package| CycloneDxDependency {
+        reference: package.purl(),
+        depends_on: package.dependencies.clone(),
+    }));
+
+    CycloneDxBom {
+        bom_format: "CycloneDX",
+        spec_version: CYCLONEDX_SPEC_VERSION,
+        serial_number: format!("urn:uuid:{}", sbom.id),
+        version: 1,
+        metadata: CycloneDxMetadata {
+            timestamp: sbom
+                .created_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            tools: CycloneDxTools {
+                components: vec![CycloneDxComponent {
+                    component_type: "application",
+                    bom_ref: None,
+                    name: TOOL_NAME.to_string(),
+                    version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                    purl: None,
+                    licenses: Vec::new(),
+                    hashes: Vec::new(),
+                    external_references: Vec::new(),
+                }],
+            },
+            component: CycloneDxComponent {
+                component_type: "application",
+                bom_ref: Some(root_ref),
+                name: project.name.clone(),
+                version: sbom.commit_sha.clone(),
+                purl: None,
+                licenses: Vec::new(),
+                hashes: Vec::new(),
+                external_references: vec![CycloneDxExternalReference {
+                    reference_type: "vcs",
+                    url: project.repository_url.clone(),
+                }],
+            },
+        },
+        components,
+        dependencies,
+    }
+}
+
+pub fn to_spdx(project: &Project, sbom: &Sbom) -> SpdxDocument {
+    const ROOT_ID: &str = "SPDXRef-Project";
+    let packages = &sbom.contents.packages;
+
+    let ids: HashMap<String, String> = packages
+        .iter()
+        .enumerate()
+        .map(|(idx, package)| (package.purl(), format!("SPDXRef-Package-{}", idx + 1)))
+        .collect();
+
+    let mut spdx_packages = vec![SpdxPackage {
+        spdx_id: ROOT_ID.to_string(),
+        name: project.name.clone(),
+        version_info: sbom.commit_sha.clone(),
+        download_location: project.repository_url.clone(),
+        files_analyzed: false,
+        license_concluded: NOASSERTION.to_string(),
+        license_declared: NOASSERTION.to_string(),
+        checksums: Vec::new(),
+        external_refs: Vec::new(),
+    }];
+    let mut relationships = vec![SpdxRelationship {
+        spdx_element_id: "SPDXRef-DOCUMENT".to_string(),
+        relationship_type: "DESCRIBES",
+        related_spdx_element: ROOT_ID.to_string(),
+    }];
+
+    let depends_on = |from: &str, to: &String| {
+        ids.get(to).map(|related| SpdxRelationship {
+            spdx_element_id: from.to_string(),
+            relationship_type: "DEPENDS_ON",
+            related_spdx_element: related.clone(),
+        })
+    };
+    relationships.extend(
+        sbom.contents
+            .root_dependencies
+            .iter()
+            .filter_map(|purl| depends_on(ROOT_ID, purl)),
+    );
+
+    for package in packages {
+        let purl = package.purl();
+        let id = &ids[&purl];
+        relationships.extend(
+            package
+                .dependencies
+                .iter()
+                .filter_map(|dependency| depends_on(id, dependency)),
+        );
+        spdx_packages.push(SpdxPackage {
+            spdx_id: id.clone(),
+            name: package.name.clone(),
+            version_info: Some(package.version.clone()),
+            download_location: NOASSERTION.to_string(),
+            files_analyzed: false,
+            license_concluded: NOASSERTION.to_string(),
+            license_declared: package
+                .license
+                .clone()
+                .filter(|license| is_spdx_expression(license))
+                .unwrap_or_else(|| NOASSERTION.to_string()),
+            checksums: package.hashes.iter().map(spdx_checksum).collect(),
+            external_refs: vec![SpdxExternalRef {
+                reference_category: "PACKAGE-MANAGER",
+                reference_type: "purl",
+                reference_locator: purl,
+            }],
+        });
+    }
+
+    SpdxDocument {
+        spdx_version: SPDX_VERSION,
+        data_license: "CC0-1.0",
+        spdx_id: "SPDXRef-DOCUMENT",
+        name: project.name.clone(),
+        document_namespace: format!("https://spdx.org/spdxdocs/sentinai-{}", sbom.id),
+        creation_info: SpdxCreationInfo {
+            created: sbom
+                .created_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            creators: vec![format!("Tool: {}-{}", TOOL_NAME, env!("CARGO_PKG_VERSION"))],
+        },
+        packages: spdx_packages,
+        relationships,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::sbom::{Ecosystem, Package, SbomContents};
+    use chrono::{TimeZone, Utc};
+    use insta::assert_json_snapshot;
+    use sqlx::types::Json;
+    use uuid::Uuid;
+
+    fn fixture() -> (Project, Sbom) {
+        let at = Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap();
+        let project = Project {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            name: "api".into(),
+            repository_url: "https://github.com/org/api".into(),
+            created_at: at,
+        };
+        let contents = SbomContents {
+            packages: vec![
+                Package {
+                    ecosystem: Ecosystem::Cargo,
+                    name: "rand".into(),
+                    version: "0.8.5".into(),
+                    license: Some("MIT OR Apache-2.0".into()),
+                    hashes: vec![PackageHash {
+                        algorithm: HashAlgorithm::Sha256,
+                        value: "34af8d1a".into(),
+                    }],
+                    dependencies: vec!["pkg:cargo/rand_core@0.6.4".into()],
+                    source_file: "Cargo.lock".into(),
+                },
+                Package {
+                    ecosystem: Ecosystem::Cargo,
+                    name: "rand_core".into(),
+                    version: "0.6.4".into(),
+                    license: Some("Custom license, see LICENSE file".into()),
+                    hashes: Vec::new(),
+                    dependencies: Vec::new(),
+                    source_file: "Cargo.lock".into(),
+                },
+            ],
+            root_dependencies: vec!["pkg:cargo/rand@0.8.5".into()],
+        };
+        let sbom = Sbom {
+            id: Uuid::nil(),
+            project_id: project.id,
+            scan_run_id: Uuid::nil(),
+            commit_sha: Some("abc123".into()),
+            contents: Json(contents),
+            created_at: at,
+        };
+        (project, sbom)
+    }
+
+    #[test]
+    fn test_is_spdx_expression() {
+        assert!(is_spdx_expression("MIT"));
+        assert!(is_spdx_expression("(MIT OR Apache-2.0) AND BSD-3-Clause"));
+        assert!(is_spdx_expression("GPL-2.0+ WITH Classpath-exception-2.0"));
+        assert!(!is_spdx_expression("MIT/Apache"));
+        assert!(!is_spdx_expression("Custom license, see LICENSE file"));
+        assert!(!is_spdx_expression(""));
+    }
+
+    #[test]
+    fn test_to_cyclonedx() {
+        let (project, sbom) = fixture();
+        assert_json_snapshot!("cyclonedx_export", to_cyclonedx(&project, &sbom));
+    }
+
+    #[test]
+    fn test_to_spdx() {
+        let (project, sbom) = fixture();
+        assert_json_snapshot!("spdx_export", to_spdx(&project, &sbom));
+    }
+}
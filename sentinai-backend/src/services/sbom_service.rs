@@ -0,0 +1,144 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+use crate::db::sbom_repo;
+use crate::domain::project::Project;
+use crate::domain::sbom::{Sbom, SbomContents, SbomFormat};
+use crate::error::AppError;
+use crate::services::license;
+use crate::services::lockfile;
+use crate::services::registry;
+use crate::services::repository::RepoSnapshot;
+use crate::services::sbom::{self, CycloneDxBom, SpdxDocument};
+use crate::state::AppState;
+
+const REGISTRY_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum SbomDocument {
+    CycloneDx(CycloneDxBom),
+    Spdx(SpdxDocument),
+}
+
+/// Fills in licenses the lockfiles did not declare, reusing those recorded
+/// for the project's previous SBOM before asking the package registries.
+async fn resolve_licenses(
+    state: &AppState,
+    project_id: Uuid,
+    contents: &mut SbomContents,
+) -> Result<(), AppError> {
+    let known: HashMap<String, String> = sbom_repo::get_latest_sbom(&state.db, project_id)
+        .await?
+        .map(|previous| {
+            previous
+                .contents
+                .0
+                .packages
+                .into_iter()
+                .filter_map(|p| Some((p.purl(), p.license?)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let semaphore = Arc::new(Semaphore::new(REGISTRY_CONCURRENCY));
+    let mut lookups = JoinSet::new();
+    for (idx, package) in contents.packages.iter_mut().enumerate() {
+        if package.license.is_some() {
+            continue;
+        }
+        if let Some(license) = known.get(&package.purl()) {
+            package.license = Some(license.clone());
+            continue;
+        }
+
+        let state = state.clone();
+        let semaphore = semaphore.clone();
+        let (ecosystem, name, version) = (
+            package.ecosystem,
+            package.name.clone(),
+            package.version.clone(),
+        );
+        lookups.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (
+                idx,
+                registry::fetch_license(&state, ecosystem, &name, &version).await,
+            )
+        });
+    }
+
+    while let Some(result) = lookups.join_next().await {
+        match result {
+            Ok((idx, Ok(license))) => contents.packages[idx].license = license,
+            Ok((idx, Err(e))) => tracing::debug!(
+                package = %contents.packages[idx].purl(),
+                error = %e,
+                "License lookup failed"
+            ),
+            Err(e) => tracing::warn!(error = %e, "License lookup task failed"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the package inventory for a repository snapshot and stores it as
+/// the SBOM of `scan_run_id`.
+#[tracing::instrument(name = "sbom", skip(state, snapshot), fields(project_id = %project_id))]
+pub async fn generate_sbom(
+    state: &AppState,
+    project_id: Uuid,
+    scan_run_id: Uuid,
+    commit_sha: Option<&str>,
+    snapshot: &RepoSnapshot,
+) -> Result<Sbom, AppError> {
+    let (mut contents, warnings) = lockfile::resolve_packages(snapshot);
+    for warning in &warnings {
+        tracing::warn!(warning = %warning, "Lockfile could not be fully resolved");
+    }
+
+    resolve_licenses(state, project_id, &mut contents).await?;
+    for package in &mut contents.packages {
+        if let Some(normalized) = package
+            .license
+            .as_deref()
+            .and_then(license::normalize_license)
+        {
+            package.license = Some(normalized);
+        }
+    }
+
+    let sbom =
+        sbom_repo::create_sbom(&state.db, project_id, scan_run_id, commit_sha, &contents).await?;
+    tracing::info!(packages = sbom.contents.packages.len(), "SBOM generated");
+
+    Ok(sbom)
+}
+
+/// Renders the SBOM of `scan_run_id`, or of the latest scan when none is
+/// given.
+#[tracing::instrument(name = "sbom", skip(state, project), fields(project_id = %project.id))]
+pub async fn get_sbom_document(
+    state: &AppState,
+    project: &Project,
+    scan_run_id: Option<Uuid>,
+    format: SbomFormat,
+) -> Result<SbomDocument, AppError> {
+    let sbom = match scan_run_id {
+        Some(scan_run_id) => {
+            sbom_repo::get_sbom_for_scan_run(&state.db, project.id, scan_run_id).await?
+        }
+        None => sbom_repo::get_latest_sbom(&state.db, project.id).await?,
+    }
+    .ok_or_else(|| AppError::NotFound("SBOM not found".into()))?;
+
+    Ok(match format {
+        SbomFormat::CycloneDxJson => SbomDocument::CycloneDx(sbom::to_cyclonedx(project, &sbom)),
+        SbomFormat::SpdxJson => SbomDocument::Spdx(sbom::to_spdx(project, &sbom)),
+    })
+}
@@ -0,0 +1,209 @@
+use std::str::FromStr;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::db::{project_repo, scan_schedule_repo};
+use crate::domain::scan_schedule::{ScanSchedule, UpdateScanScheduleRequest};
+use crate::error::AppError;
+use crate::services::security_service;
+use crate::state::AppState;
+
+/// How often each instance looks for due schedules.
+const POLL_INTERVAL: StdDuration = StdDuration::from_secs(30);
+/// Upper bound on schedules started by one instance per poll.
+const MAX_DUE_PER_POLL: i64 = 20;
+const MIN_INTERVAL_SECONDS: i32 = 300;
+
+/// When a schedule fires: on a cron expression or at a fixed interval.
+#[derive(Debug)]
+pub enum Cadence {
+    Cron(Box<cron::Schedule>),
+    Interval(Duration),
+}
+
+impl Cadence {
+    /// Accepts standard five-field cron expressions as well as the
+    /// six/seven-field form with seconds (and years).
+    pub fn parse(
+        cron_expression: Option<&str>,
+        interval_seconds: Option<i32>,
+    ) -> Result<Cadence, AppError> {
+        match (cron_expression, interval_seconds) {
+            (Some(expression), None) => {
+                let expression = expression.trim();
+                let normalized = if expression.split_whitespace().count() == 5 {
+                    format!("0 {}", expression)
+                } else {
+                    expression.to_string()
+                };
+                cron::Schedule::from_str(&normalized)
+                    .map(|schedule| Cadence::Cron(Box::new(schedule)))
+                    .map_err(|e| AppError::BadRequest(format!("Invalid cron expression: {}", e)))
+            }
+            (None, Some(seconds)) if seconds >= MIN_INTERVAL_SECONDS => {
+                Ok(Cadence::Interval(Duration::seconds(i64::from(seconds))))
+            }
+            (None, Some(_)) => Err(AppError::BadRequest(format!(
+                "interval_seconds must be at least {}",
+                MIN_INTERVAL_SECONDS
+            ))),
+            _ => Err(AppError::BadRequest(
+                "Exactly one of cron_expression or interval_seconds must be set".into(),
+            )),
+        }
+    }
+
+    pub fn next_after(&self, after: DateTime<Utc>) -> Result<DateTime<Utc>, AppError> {
+        match self {
+            Cadence::Cron(schedule) => schedule
+                .after(&after)
+                .next()
+                .ok_or_else(|| AppError::BadRequest("Cron expression never fires again".into())),
+            Cadence::Interval(interval) => Ok(after + *interval),
+        }
+    }
+}
+
+#[tracing::instrument(name = "scan_scheduler", skip(state), fields(project_id = %project_id))]
+pub async fn get_schedule(state: &AppState, project_id: Uuid) -> Result<ScanSchedule, AppError> {
+    scan_schedule_repo::get_schedule(&state.db, project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Scan schedule not found".into()))
+}
+
+#[tracing::instrument(name = "scan_scheduler", skip(state, request), fields(project_id = %project_id))]
+pub async fn update_schedule(
+    state: &AppState,
+    project_id: Uuid,
+    request: UpdateScanScheduleRequest,
+) -> Result<ScanSchedule, AppError> {
+    let cron_expression = request
+        .cron_expression
+        .as_deref()
+        .map(str::trim)
+        .filter(|expression| !expression.is_empty());
+    let cadence = Cadence::parse(cron_expression, request.interval_seconds)?;
+    let next_run_at = cadence.next_after(Utc::now())?;
+
+    scan_schedule_repo::upsert_schedule(
+        &state.db,
+        project_id,
+        cron_expression,
+        request.interval_seconds,
+        request.enabled,
+        next_run_at,
+    )
+    .await
+}
+
+#[tracing::instrument(name = "scan_scheduler", skip(state), fields(project_id = %project_id))]
+pub async fn delete_schedule(state: &AppState, project_id: Uuid) -> Result<(), AppError> {
+    let rows = scan_schedule_repo::delete_schedule(&state.db, project_id).await?;
+    if rows == 0 {
+        return Err(AppError::NotFound("Scan schedule not found".into()));
+    }
+    Ok(())
+}
+
+/// Starts the background loop that runs due scans. Schedules live in the
+/// database, so runs missed while no instance was up start on the first poll
+/// after a restart.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = start_due_scans(&state).await {
+                tracing::warn!(error = %e, "Could not load due scan schedules");
+            }
+        }
+    });
+}
+
+async fn start_due_scans(state: &AppState) -> Result<(), AppError> {
+    for schedule in scan_schedule_repo::list_due_schedules(&state.db, MAX_DUE_PER_POLL).await? {
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = run_schedule(&state, &schedule).await {
+                tracing::warn!(
+                    project_id = %schedule.project_id,
+                    error = %e,
+                    "Scheduled scan failed"
+                );
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Runs one due schedule. Claiming the run is a single `UPDATE`, so with
+/// several replicas only one of them scans, and no connection is held while
+/// the scan runs.
+#[tracing::instrument(name = "scan_scheduler", skip(state, schedule), fields(project_id = %schedule.project_id))]
+async fn run_schedule(state: &AppState, schedule: &ScanSchedule) -> Result<(), AppError> {
+    let cadence = Cadence::parse(
+        schedule.cron_expression.as_deref(),
+        schedule.interval_seconds,
+    )?;
+    let next_run_at = cadence.next_after(Utc::now())?;
+
+    // Another instance may have claimed this run since it was listed.
+    if scan_schedule_repo::claim_schedule(&state.db, schedule.id, next_run_at)
+        .await?
+        .is_none()
+    {
+        return Ok(());
+    }
+
+    let project = project_repo::get_project_by_id(&state.db, schedule.project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    tracing::info!(%next_run_at, "Starting scheduled scan");
+    let summary = security_service::run_security_scan(state, &project).await?;
+    scan_schedule_repo::set_last_scan_run(&state.db, schedule.id, summary.scan_run.id).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cadence_next_run() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 10, 15, 30).unwrap();
+
+        let daily = Cadence::parse(Some("0 3 * * *"), None).unwrap();
+        assert_eq!(
+            daily.next_after(now).unwrap(),
+            Utc.with_ymd_and_hms(2026, 3, 2, 3, 0, 0).unwrap()
+        );
+
+        let with_seconds = Cadence::parse(Some("0 */30 * * * *"), None).unwrap();
+        assert_eq!(
+            with_seconds.next_after(now).unwrap(),
+            Utc.with_ymd_and_hms(2026, 3, 1, 10, 30, 0).unwrap()
+        );
+
+        let hourly = Cadence::parse(None, Some(3600)).unwrap();
+        assert_eq!(hourly.next_after(now).unwrap(), now + Duration::hours(1));
+    }
+
+    #[test]
+    fn test_cadence_rejects_invalid_schedules() {
+        let rejected = [
+            Cadence::parse(Some("every day"), None),
+            Cadence::parse(None, Some(60)),
+            Cadence::parse(Some("0 3 * * *"), Some(3600)),
+            Cadence::parse(None, None),
+        ];
+        assert!(rejected
+            .iter()
+            .all(|result| matches!(result, Err(AppError::BadRequest(_)))));
+    }
+}
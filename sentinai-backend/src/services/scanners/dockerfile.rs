@@ -0,0 +1,588 @@
+//! Static analysis of Dockerfiles for insecure build and runtime settings.
+
+use std::collections::HashMap;
+
+use crate::domain::security::NewFinding;
+use crate::error::AppError;
+use crate::services::repository::RepoSnapshot;
+
+use super::{ScanContext, Scanner};
+
+const SECRET_MARKERS: [&str; 9] = [
+    "PASSWORD",
+    "PASSWD",
+    "SECRET",
+    "TOKEN",
+    "API_KEY",
+    "APIKEY",
+    "PRIVATE_KEY",
+    "ACCESS_KEY",
+    "CREDENTIAL",
+];
+const SHELLS: [&str; 6] = ["sh", "bash", "zsh", "ash", "dash", "ksh"];
+
+/// A logical Dockerfile instruction with continuation lines joined.
+#[derive(Debug, PartialEq, Eq)]
+struct Instruction {
+    /// 1-based line the instruction starts on.
+    line: usize,
+    keyword: String,
+    args: String,
+}
+
+pub fn is_dockerfile(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    file_name == "Dockerfile"
+        || file_name == "Containerfile"
+        || file_name.starts_with("Dockerfile.")
+        || file_name.ends_with(".Dockerfile")
+        || file_name.ends_with(".dockerfile")
+}
+
+fn parse(contents: &str) -> Vec<Instruction> {
+    let mut escape = '\\';
+    let mut instructions = Vec::new();
+    let mut current: Option<Instruction> = None;
+    let mut in_preamble = true;
+
+    for (idx, raw) in contents.lines().enumerate() {
+        let trimmed = raw.trim();
+
+        if in_preamble {
+            if let Some(directive) = trimmed.strip_prefix('#') {
+                if let Some((key, value)) = directive.split_once('=') {
+                    if key.trim().eq_ignore_ascii_case("escape") {
+                        escape = value.trim().chars().next().unwrap_or('\\');
+                    }
+                }
+                continue;
+            }
+            in_preamble = false;
+        }
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let (text, continues) = match trimmed.strip_suffix(escape) {
+            Some(text) => (text.trim_end(), true),
+            None => (trimmed, false),
+        };
+
+        match current.as_mut() {
+            Some(instruction) => {
+                instruction.args.push(' ');
+                instruction.args.push_str(text);
+            }
+            None => {
+                let (keyword, args) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+                current = Some(Instruction {
+                    line: idx + 1,
+                    keyword: keyword.to_uppercase(),
+                    args: args.trim().to_string(),
+                });
+            }
+        }
+
+        if !continues {
+            instructions.extend(current.take());
+        }
+    }
+    instructions.extend(current);
+    instructions
+}
+
+/// Splits arguments on whitespace, keeping quoted strings together and
+/// dropping the quotes.
+fn split_words(args: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote: Option<char> = None;
+    let mut has_word = false;
+
+    for c in args.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                has_word = true;
+            }
+            None if c.is_whitespace() => {
+                if has_word || !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                    has_word = false;
+                }
+            }
+            None => word.push(c),
+        }
+    }
+    if has_word || !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn without_flags(words: &[String]) -> impl Iterator<Item = &String> {
+    words.iter().filter(|word| !word.starts_with("--"))
+}
+
+struct Check<'a> {
+    path: &'a str,
+    findings: Vec<NewFinding>,
+    /// The `AS` name of the stage being checked, or `stage-<n>` for unnamed
+    /// stages. `None` before the first `FROM`.
+    stage: Option<String>,
+    /// Findings reported so far per location and rule.
+    occurrences: HashMap<(String, String), usize>,
+}
+
+impl Check<'_> {
+    /// Reports a finding located by stage and `subject`, e.g. the variable
+    /// holding a secret, rather than by line, so that edits elsewhere in the
+    /// file keep its fingerprint. Repeats of a rule at one location are
+    /// numbered in file order.
+    fn report(
+        &mut self,
+        line: usize,
+        subject: Option<&str>,
+        rule_id: &str,
+        severity: &str,
+        description: String,
+        remediation: String,
+    ) {
+        let scope = [self.stage.as_deref(), subject]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("/");
+        let base = if scope.is_empty() {
+            self.path.to_string()
+        } else {
+            format!("{}#{}", self.path, scope)
+        };
+        let count = self
+            .occurrences
+            .entry((base.clone(), rule_id.to_string()))
+            .or_default();
+        *count += 1;
+        let location = match *count {
+            1 => base,
+            n => format!("{}[{}]", base, n),
+        };
+
+        self.findings.push(NewFinding {
+            scanner: Scanner::Dockerfile.name().to_string(),
+            rule_id: rule_id.to_string(),
+            severity: severity.to_string(),
+            description,
+            location: Some(location),
+            start_line: i32::try_from(line).ok(),
+            start_column: None,
+            package: None,
+            remediation: Some(remediation),
+            fixed_versions: Vec::new(),
+            partial_fingerprint: None,
+        });
+    }
+}
+
+fn check_from(check: &mut Check, instruction: &Instruction, stages: &[String]) {
+    let words = split_words(&instruction.args);
+    let Some(image) = without_flags(&words).next() else {
+        return;
+    };
+    if image.eq_ignore_ascii_case("scratch")
+        || image.contains('$')
+        || image.contains('@')
+        || stages.iter().any(|stage| stage.eq_ignore_ascii_case(image))
+    {
+        return;
+    }
+
+    let name_end = image.rfind('/').map_or(0, |idx| idx + 1);
+    let tag = image[name_end..].split_once(':').map(|(_, tag)| tag);
+    let description = match tag {
+        None => format!("Base image `{}` is not pinned to a version", image),
+        Some("latest") => format!("Base image `{}` uses the mutable `latest` tag", image),
+        Some(_) => return,
+    };
+    let repository = image.split_once(':').map_or(image.as_str(), |(r, _)| r);
+    check.report(
+        instruction.line,
+        None,
+        "unpinned-base-image",
+        "medium",
+        description,
+        format!(
+            "Pin the base image to a specific version or digest, e.g. `FROM {}:<version>` or `FROM {}@sha256:<digest>`",
+            repository, repository
+        ),
+    );
+}
+
+fn check_run(check: &mut Check, instruction: &Instruction) {
+    let command = &instruction.args;
+
+    for segment in command.split(['&', ';', '|']) {
+        let words: Vec<&str> = segment.split_whitespace().collect();
+        let installs = words
+            .iter()
+            .position(|w| *w == "apt-get" || *w == "apt")
+            .is_some_and(|idx| words[idx..].contains(&"install"));
+        if installs
+            && !words.contains(&"--no-install-recommends")
+            && !segment.contains("Install-Recommends=false")
+        {
+            check.report(
+                instruction.line,
+                None,
+                "apt-install-recommends",
+                "low",
+                "apt installs recommended packages, growing the image and its attack surface"
+                    .to_string(),
+                "Add `--no-install-recommends` to `apt-get install`".to_string(),
+            );
+            break;
+        }
+    }
+
+    let pipes: Vec<&str> = command.split('|').collect();
+    for pair in pipes.windows(2) {
+        let fetches = pair[0]
+            .split_whitespace()
+            .any(|w| w == "curl" || w == "wget");
+        let shell = pair[1]
+            .split_whitespace()
+            .find(|w| *w != "sudo" && !w.starts_with('-'))
+            .map(|w| w.rsplit('/').next().unwrap_or(w));
+        if fetches && shell.is_some_and(|s| SHELLS.contains(&s)) {
+            check.report(
+                instruction.line,
+                None,
+                "curl-pipe-shell",
+                "high",
+                "Remote script is piped straight into a shell without verification".to_string(),
+                "Download the script to a file, verify its checksum or signature, then run it"
+                    .to_string(),
+            );
+            break;
+        }
+    }
+}
+
+fn check_add(check: &mut Check, instruction: &Instruction) {
+    let words = split_words(&instruction.args);
+    if words.iter().any(|w| w.starts_with("--checksum")) {
+        return;
+    }
+    let paths: Vec<&String> = without_flags(&words).collect();
+    let sources = &paths[..paths.len().saturating_sub(1)];
+    if let Some(url) = sources
+        .iter()
+        .find(|s| s.starts_with("http://") || s.starts_with("https://"))
+    {
+        check.report(
+            instruction.line,
+            Some(url.as_str()),
+            "add-remote-url",
+            "medium",
+            format!("`ADD` fetches `{}` without verifying its contents", url),
+            "Use `ADD --checksum=sha256:<digest>` or download with `RUN curl` and verify the checksum"
+                .to_string(),
+        );
+    }
+}
+
+fn check_secrets(check: &mut Check, instruction: &Instruction) {
+    let words = split_words(&instruction.args);
+    // `ENV KEY value` is the legacy single-variable form.
+    let pairs: Vec<(String, String)> =
+        if instruction.keyword == "ENV" && !words.first().is_some_and(|w| w.contains('=')) {
+            match words.split_first() {
+                Some((key, rest)) => vec![(key.clone(), rest.join(" "))],
+                None => Vec::new(),
+            }
+        } else {
+            words
+                .iter()
+                .filter_map(|w| w.split_once('='))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+
+    for (key, value) in pairs {
+        let upper = key.to_uppercase();
+        if value.is_empty()
+            || value.starts_with('$')
+            || !SECRET_MARKERS.iter().any(|marker| upper.contains(marker))
+        {
+            continue;
+        }
+        check.report(
+            instruction.line,
+            Some(&key),
+            "secret-in-env",
+            "high",
+            format!(
+                "`{} {}` bakes a secret into the image where it is visible in its history",
+                instruction.keyword, key
+            ),
+            "Pass secrets at build time with `RUN --mount=type=secret` or at runtime through the environment"
+                .to_string(),
+        );
+    }
+}
+
+/// Runs every rule against one Dockerfile.
+pub fn analyze(path: &str, contents: &str) -> Vec<NewFinding> {
+    let instructions = parse(contents);
+    let mut check = Check {
+        path,
+        findings: Vec::new(),
+        stage: None,
+        occurrences: HashMap::new(),
+    };
+    let mut stages: Vec<String> = Vec::new();
+    // The `USER` in effect at the end of each named stage, keyed by
+    // lowercase name, so that stages built `FROM` another inherit it.
+    let mut stage_users: HashMap<String, Option<(usize, String)>> = HashMap::new();
+    let mut stage_count = 0;
+    let mut alias: Option<String> = None;
+    let mut final_stage_line = None;
+    let mut final_user: Option<(usize, String)> = None;
+
+    for instruction in &instructions {
+        match instruction.keyword.as_str() {
+            "FROM" => {
+                stage_count += 1;
+                let words = split_words(&instruction.args);
+                alias = words
+                    .iter()
+                    .position(|w| w.eq_ignore_ascii_case("as"))
+                    .and_then(|idx| words.get(idx + 1))
+                    .cloned();
+                check.stage = Some(
+                    alias
+                        .clone()
+                        .unwrap_or_else(|| format!("stage-{}", stage_count)),
+                );
+                check_from(&mut check, instruction, &stages);
+
+                final_user = without_flags(&words)
+                    .next()
+                    .and_then(|image| stage_users.get(&image.to_lowercase()))
+                    .cloned()
+                    .flatten();
+                if let Some(alias) = &alias {
+                    stage_users.insert(alias.to_lowercase(), final_user.clone());
+                    stages.push(alias.clone());
+                }
+                final_stage_line = Some(instruction.line);
+            }
+            "USER" => {
+                final_user = Some((instruction.line, instruction.args.clone()));
+                if let Some(user) = alias
+                    .as_ref()
+                    .and_then(|alias| stage_users.get_mut(&alias.to_lowercase()))
+                {
+                    *user = final_user.clone();
+                }
+            }
+            "RUN" => check_run(&mut check, instruction),
+            "ADD" => check_add(&mut check, instruction),
+            "ENV" | "ARG" => check_secrets(&mut check, instruction),
+            _ => {}
+        }
+    }
+
+    match (final_stage_line, final_user) {
+        (Some(line), None) => check.report(
+            line,
+            None,
+            "missing-user",
+            "medium",
+            "The final stage has no `USER` instruction, so the container runs as root".to_string(),
+            "Create an unprivileged user and switch to it with `USER`".to_string(),
+        ),
+        (_, Some((line, user))) => {
+            let name = user.split(':').next().unwrap_or_default().trim();
+            if name == "root" || name == "0" {
+                check.report(
+                    line,
+                    None,
+                    "root-user",
+                    "medium",
+                    "The container is configured to run as root".to_string(),
+                    "Switch to an unprivileged user with `USER`".to_string(),
+                );
+            }
+        }
+        (None, None) => {}
+    }
+
+    check.findings
+}
+
+pub async fn scan(ctx: &ScanContext<'_>) -> Result<Vec<NewFinding>, AppError> {
+    let snapshot: &RepoSnapshot = ctx.snapshot.ok_or_else(|| {
+        AppError::InternalServerError(
+            "No repository snapshot available for Dockerfile checks".into(),
+        )
+    })?;
+
+    Ok(snapshot
+        .files()
+        .filter(|(path, _)| is_dockerfile(path))
+        .flat_map(|(path, contents)| analyze(path, contents))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(findings: &[NewFinding]) -> Vec<(&str, &str, i32)> {
+        findings
+            .iter()
+            .map(|f| {
+                (
+                    f.rule_id.as_str(),
+                    f.location.as_deref().unwrap_or_default(),
+                    f.start_line.unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_joins_continuations_and_skips_comments() {
+        let instructions = parse(
+            "# syntax=docker/dockerfile:1\nFROM rust:1.77 AS build\n\n# build it\nrun cargo build \\\n    --release \\\n    # inline comment\n    --locked\n",
+        );
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction {
+                    line: 2,
+                    keyword: "FROM".into(),
+                    args: "rust:1.77 AS build".into(),
+                },
+                Instruction {
+                    line: 5,
+                    keyword: "RUN".into(),
+                    args: "cargo build --release --locked".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_honours_escape_directive() {
+        let instructions =
+            parse("# escape=`\nFROM mcr.microsoft.com/windows:ltsc2022\nRUN dir `\n  c:\\\n");
+        assert_eq!(instructions[1].args, "dir c:\\");
+    }
+
+    #[test]
+    fn test_analyze_reports_insecure_dockerfile() {
+        let dockerfile = r#"FROM node
+FROM ubuntu:latest AS base
+ENV API_TOKEN=abc123 NODE_ENV=production
+ENV DB_PASSWORD hunter2
+ARG GITHUB_TOKEN
+RUN apt-get update && apt-get install -y curl
+RUN curl -fsSL https://example.com/install.sh | sudo bash
+ADD https://example.com/tool.tar.gz /opt/
+USER root
+"#;
+        let findings = analyze("docker/Dockerfile", dockerfile);
+
+        assert_eq!(
+            rules(&findings),
+            vec![
+                ("unpinned-base-image", "docker/Dockerfile#stage-1", 1),
+                ("unpinned-base-image", "docker/Dockerfile#base", 2),
+                ("secret-in-env", "docker/Dockerfile#base/API_TOKEN", 3),
+                ("secret-in-env", "docker/Dockerfile#base/DB_PASSWORD", 4),
+                ("apt-install-recommends", "docker/Dockerfile#base", 6),
+                ("curl-pipe-shell", "docker/Dockerfile#base", 7),
+                (
+                    "add-remote-url",
+                    "docker/Dockerfile#base/https://example.com/tool.tar.gz",
+                    8
+                ),
+                ("root-user", "docker/Dockerfile#base", 9),
+            ]
+        );
+        assert!(!findings[2].description.contains("abc123"));
+        assert!(findings.iter().all(|f| f.remediation.is_some()));
+    }
+
+    #[test]
+    fn test_analyze_accepts_hardened_dockerfile() {
+        let dockerfile = r#"FROM rust:1.77-slim AS build
+RUN apt-get update \
+    && apt-get install -y --no-install-recommends pkg-config
+RUN curl -fsSL https://sh.rustup.rs -o rustup.sh && sh rustup.sh -y
+FROM gcr.io/distroless/cc@sha256:abc
+COPY --from=build /app /app
+ADD --checksum=sha256:def https://example.com/ca.pem /etc/ssl/
+ENV TOKEN_PATH=$HOME/.token
+USER 10001:10001
+"#;
+        assert!(analyze("Dockerfile", dockerfile).is_empty());
+    }
+
+    #[test]
+    fn test_analyze_flags_missing_user_in_final_stage_only() {
+        let dockerfile = "FROM alpine:3.19 AS build\nRUN make\nFROM alpine:3.19\nRUN echo done\n";
+        assert_eq!(
+            rules(&analyze("Dockerfile", dockerfile)),
+            vec![("missing-user", "Dockerfile#stage-2", 3)]
+        );
+    }
+
+    #[test]
+    fn test_analyze_inherits_user_from_earlier_stage() {
+        let dockerfile = "FROM alpine:3.19 AS base\nUSER app\nFROM base AS app\nRUN echo done\nFROM app\nCOPY . .\n";
+        assert!(analyze("Dockerfile", dockerfile).is_empty());
+
+        let root = "FROM alpine:3.19 AS base\nUSER root\nFROM base\n";
+        assert_eq!(
+            rules(&analyze("Dockerfile", root)),
+            vec![("root-user", "Dockerfile#stage-2", 2)]
+        );
+    }
+
+    #[test]
+    fn test_analyze_locates_findings_by_stage_and_variable() {
+        let dockerfile = "FROM alpine:3.19\nENV API_TOKEN=a DB_PASSWORD=b\nUSER app\n";
+        let shifted = format!("# comment\n\n{}", dockerfile);
+        let findings = analyze("Dockerfile", dockerfile);
+        assert_eq!(
+            rules(&findings),
+            vec![
+                ("secret-in-env", "Dockerfile#stage-1/API_TOKEN", 2),
+                ("secret-in-env", "Dockerfile#stage-1/DB_PASSWORD", 2),
+            ]
+        );
+        let fingerprints = |findings: &[NewFinding]| -> Vec<String> {
+            findings.iter().map(NewFinding::fingerprint).collect()
+        };
+        assert_eq!(
+            fingerprints(&findings),
+            fingerprints(&analyze("Dockerfile", &shifted))
+        );
+    }
+
+    #[test]
+    fn test_is_dockerfile() {
+        assert!(is_dockerfile("Dockerfile"));
+        assert!(is_dockerfile("services/api/Dockerfile.prod"));
+        assert!(is_dockerfile("build/api.Dockerfile"));
+        assert!(is_dockerfile("Containerfile"));
+        assert!(!is_dockerfile("docs/Dockerfile.md.txt/readme"));
+        assert!(!is_dockerfile("dockerfile-lint.json"));
+    }
+}
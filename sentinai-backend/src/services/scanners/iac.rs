@@ -0,0 +1,521 @@
+//! Misconfiguration checks for Kubernetes manifests and Terraform files.
+
+use hcl::{Block, Body, Expression};
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use crate::domain::security::NewFinding;
+use crate::error::AppError;
+
+use super::{ScanContext, Scanner};
+
+const PUBLIC_S3_ACLS: [&str; 3] = ["public-read", "public-read-write", "authenticated-read"];
+const OPEN_CIDRS: [&str; 2] = ["0.0.0.0/0", "::/0"];
+
+struct Check<'a> {
+    path: &'a str,
+    findings: Vec<NewFinding>,
+}
+
+impl Check<'_> {
+    /// Records a finding located at `path#resource`, where `resource`
+    /// identifies the Kubernetes object or Terraform resource.
+    fn report(
+        &mut self,
+        resource: &str,
+        rule_id: &str,
+        severity: &str,
+        description: String,
+        remediation: &str,
+    ) {
+        self.findings.push(NewFinding {
+            scanner: Scanner::Iac.name().to_string(),
+            rule_id: rule_id.to_string(),
+            severity: severity.to_string(),
+            description,
+            location: Some(format!("{}#{}", self.path, resource)),
+            start_line: None,
+            start_column: None,
+            package: None,
+            remediation: Some(remediation.to_string()),
+            fixed_versions: Vec::new(),
+            partial_fingerprint: None,
+        });
+    }
+}
+
+fn lookup<'v>(value: &'v Value, path: &[&str]) -> Option<&'v Value> {
+    path.iter().try_fold(value, |value, key| value.get(*key))
+}
+
+fn sequence<'v>(value: &'v Value, key: &str) -> &'v [Value] {
+    value
+        .get(key)
+        .and_then(Value::as_sequence)
+        .map_or(&[], Vec::as_slice)
+}
+
+/// The pod spec embedded in a workload, wherever its kind keeps it.
+fn pod_spec<'v>(kind: &str, manifest: &'v Value) -> Option<&'v Value> {
+    match kind {
+        "Pod" => manifest.get("spec"),
+        "Deployment"
+        | "StatefulSet"
+        | "DaemonSet"
+        | "ReplicaSet"
+        | "ReplicationController"
+        | "Job" => lookup(manifest, &["spec", "template", "spec"]),
+        "CronJob" => lookup(
+            manifest,
+            &["spec", "jobTemplate", "spec", "template", "spec"],
+        ),
+        _ => None,
+    }
+}
+
+fn check_manifest(check: &mut Check, manifest: &Value) {
+    let (Some(kind), Some(name)) = (
+        manifest.get("kind").and_then(Value::as_str),
+        lookup(manifest, &["metadata", "name"]).and_then(Value::as_str),
+    ) else {
+        return;
+    };
+    let Some(spec) = pod_spec(kind, manifest) else {
+        return;
+    };
+    let resource = match lookup(manifest, &["metadata", "namespace"]).and_then(Value::as_str) {
+        Some(namespace) => format!("{}/{}/{}", kind, namespace, name),
+        None => format!("{}/{}", kind, name),
+    };
+
+    for volume in sequence(spec, "volumes") {
+        if volume.get("hostPath").is_none() {
+            continue;
+        }
+        let volume_name = volume.get("name").and_then(Value::as_str).unwrap_or("?");
+        check.report(
+            &format!("{}/volumes/{}", resource, volume_name),
+            "host-path-volume",
+            "high",
+            format!(
+                "{} mounts the host filesystem through hostPath volume `{}`",
+                resource, volume_name
+            ),
+            "Replace the hostPath volume with a persistent volume claim, configMap or emptyDir",
+        );
+    }
+
+    let pod_non_root = lookup(spec, &["securityContext", "runAsNonRoot"]).and_then(Value::as_bool);
+
+    for container in sequence(spec, "initContainers")
+        .iter()
+        .chain(sequence(spec, "containers"))
+    {
+        let container_name = container.get("name").and_then(Value::as_str).unwrap_or("?");
+        let target = format!("{}/containers/{}", resource, container_name);
+        let security = container.get("securityContext");
+
+        if security
+            .and_then(|s| s.get("privileged"))
+            .and_then(Value::as_bool)
+            == Some(true)
+        {
+            check.report(
+                &target,
+                "privileged-container",
+                "high",
+                format!(
+                    "Container `{}` in {} runs privileged",
+                    container_name, resource
+                ),
+                "Remove `privileged: true` and grant only the capabilities the container needs",
+            );
+        }
+
+        let limits = lookup(container, &["resources", "limits"]);
+        let missing: Vec<&str> = ["cpu", "memory"]
+            .into_iter()
+            .filter(|key| limits.and_then(|l| l.get(*key)).is_none())
+            .collect();
+        if !missing.is_empty() {
+            check.report(
+                &target,
+                "missing-resource-limits",
+                "medium",
+                format!(
+                    "Container `{}` in {} has no {} limit",
+                    container_name,
+                    resource,
+                    missing.join(" or ")
+                ),
+                "Set `resources.limits.cpu` and `resources.limits.memory`",
+            );
+        }
+
+        let non_root = security
+            .and_then(|s| s.get("runAsNonRoot"))
+            .and_then(Value::as_bool)
+            .or(pod_non_root);
+        if non_root != Some(true) {
+            check.report(
+                &target,
+                "run-as-non-root",
+                "medium",
+                format!(
+                    "Container `{}` in {} does not set `runAsNonRoot: true`",
+                    container_name, resource
+                ),
+                "Set `securityContext.runAsNonRoot: true` on the pod or container",
+            );
+        }
+    }
+}
+
+/// Runs the Kubernetes rules against every document of a YAML file. Files
+/// that are not valid YAML, such as Helm templates, are skipped.
+pub fn analyze_kubernetes(path: &str, contents: &str) -> Vec<NewFinding> {
+    let mut check = Check {
+        path,
+        findings: Vec::new(),
+    };
+    for document in serde_yaml::Deserializer::from_str(contents) {
+        match Value::deserialize(document) {
+            Ok(manifest) => check_manifest(&mut check, &manifest),
+            Err(e) => {
+                tracing::debug!(path, error = %e, "Skipping unparseable YAML");
+                break;
+            }
+        }
+    }
+    check.findings
+}
+
+fn attribute<'b>(body: &'b Body, key: &str) -> Option<&'b Expression> {
+    body.attributes()
+        .find(|attribute| attribute.key() == key)
+        .map(|attribute| attribute.expr())
+}
+
+fn is_open_cidr(expr: Option<&Expression>) -> bool {
+    match expr {
+        Some(Expression::String(cidr)) => OPEN_CIDRS.contains(&cidr.as_str()),
+        Some(Expression::Array(items)) => items.iter().any(|item| is_open_cidr(Some(item))),
+        _ => false,
+    }
+}
+
+/// Encryption is flagged only when it is absent or literally `false`;
+/// values computed from variables are given the benefit of the doubt.
+fn is_unencrypted(expr: Option<&Expression>) -> bool {
+    matches!(expr, None | Some(Expression::Bool(false)))
+}
+
+fn check_resource(check: &mut Check, block: &Block) {
+    let [kind, name] = block.labels() else {
+        return;
+    };
+    let kind = kind.as_str();
+    let resource = format!("{}.{}", kind, name.as_str());
+    let body = block.body();
+
+    match kind {
+        "aws_s3_bucket" | "aws_s3_bucket_acl" => {
+            if let Some(Expression::String(acl)) = attribute(body, "acl") {
+                if PUBLIC_S3_ACLS.contains(&acl.as_str()) {
+                    check.report(
+                        &resource,
+                        "s3-public-acl",
+                        "high",
+                        format!("{} grants the public `{}` ACL", resource, acl),
+                        "Use the `private` ACL and enable an `aws_s3_bucket_public_access_block`",
+                    );
+                }
+            }
+        }
+        "aws_security_group"
+        | "aws_security_group_rule"
+        | "aws_vpc_security_group_ingress_rule" => {
+            let open = match kind {
+                "aws_security_group" => body
+                    .blocks()
+                    .filter(|rule| rule.identifier() == "ingress")
+                    .any(|rule| {
+                        is_open_cidr(attribute(rule.body(), "cidr_blocks"))
+                            || is_open_cidr(attribute(rule.body(), "ipv6_cidr_blocks"))
+                    }),
+                "aws_security_group_rule" => {
+                    matches!(attribute(body, "type"), Some(Expression::String(t)) if t == "ingress")
+                        && (is_open_cidr(attribute(body, "cidr_blocks"))
+                            || is_open_cidr(attribute(body, "ipv6_cidr_blocks")))
+                }
+                _ => {
+                    is_open_cidr(attribute(body, "cidr_ipv4"))
+                        || is_open_cidr(attribute(body, "cidr_ipv6"))
+                }
+            };
+            if open {
+                check.report(
+                    &resource,
+                    "open-security-group",
+                    "high",
+                    format!("{} allows ingress from the whole internet", resource),
+                    "Restrict ingress CIDR blocks to the networks that need access",
+                );
+            }
+        }
+        "aws_ebs_volume" | "aws_efs_file_system" | "aws_db_instance" | "aws_rds_cluster" => {
+            let key = match kind {
+                "aws_ebs_volume" | "aws_efs_file_system" => "encrypted",
+                _ => "storage_encrypted",
+            };
+            if is_unencrypted(attribute(body, key)) {
+                check.report(
+                    &resource,
+                    "unencrypted-storage",
+                    "medium",
+                    format!("{} does not encrypt its storage at rest", resource),
+                    if key == "encrypted" {
+                        "Set `encrypted = true`"
+                    } else {
+                        "Set `storage_encrypted = true`"
+                    },
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Runs the Terraform rules against every `resource` block of a file.
+pub fn analyze_terraform(path: &str, contents: &str) -> Vec<NewFinding> {
+    let mut check = Check {
+        path,
+        findings: Vec::new(),
+    };
+    match hcl::from_str::<Body>(contents) {
+        Ok(body) => body
+            .blocks()
+            .filter(|block| block.identifier() == "resource")
+            .for_each(|block| check_resource(&mut check, block)),
+        Err(e) => tracing::debug!(path, error = %e, "Skipping unparseable Terraform"),
+    }
+    check.findings
+}
+
+pub async fn scan(ctx: &ScanContext<'_>) -> Result<Vec<NewFinding>, AppError> {
+    let snapshot = ctx.snapshot.ok_or_else(|| {
+        AppError::InternalServerError(
+            "No repository snapshot available for infrastructure checks".into(),
+        )
+    })?;
+
+    let mut findings = Vec::new();
+    for (path, contents) in snapshot.files() {
+        if path.ends_with(".tf") {
+            findings.extend(analyze_terraform(path, contents));
+        } else if path.ends_with(".yaml") || path.ends_with(".yml") {
+            findings.extend(analyze_kubernetes(path, contents));
+        }
+    }
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(findings: &[NewFinding]) -> Vec<(&str, &str)> {
+        findings
+            .iter()
+            .map(|f| {
+                (
+                    f.rule_id.as_str(),
+                    f.location.as_deref().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_kubernetes_rules() {
+        let manifests = r#"
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: settings
+data:
+  key: value
+---
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: api
+  namespace: prod
+spec:
+  template:
+    spec:
+      volumes:
+        - name: docker
+          hostPath:
+            path: /var/run/docker.sock
+        - name: cache
+          emptyDir: {}
+      containers:
+        - name: web
+          securityContext:
+            privileged: true
+          resources:
+            limits:
+              memory: 256Mi
+---
+apiVersion: batch/v1
+kind: CronJob
+metadata:
+  name: cleanup
+spec:
+  jobTemplate:
+    spec:
+      template:
+        spec:
+          securityContext:
+            runAsNonRoot: true
+          containers:
+            - name: job
+              resources:
+                limits:
+                  cpu: 100m
+                  memory: 64Mi
+"#;
+        assert_eq!(
+            rules(&analyze_kubernetes("k8s/app.yaml", manifests)),
+            vec![
+                (
+                    "host-path-volume",
+                    "k8s/app.yaml#Deployment/prod/api/volumes/docker"
+                ),
+                (
+                    "privileged-container",
+                    "k8s/app.yaml#Deployment/prod/api/containers/web"
+                ),
+                (
+                    "missing-resource-limits",
+                    "k8s/app.yaml#Deployment/prod/api/containers/web"
+                ),
+                (
+                    "run-as-non-root",
+                    "k8s/app.yaml#Deployment/prod/api/containers/web"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kubernetes_container_overrides_pod_security_context() {
+        let manifest = r#"
+apiVersion: v1
+kind: Pod
+metadata:
+  name: debug
+spec:
+  securityContext:
+    runAsNonRoot: true
+  initContainers:
+    - name: setup
+      securityContext:
+        runAsNonRoot: false
+      resources:
+        limits: {cpu: 1, memory: 1Gi}
+"#;
+        let findings = analyze_kubernetes("pod.yml", manifest);
+        assert_eq!(
+            rules(&findings),
+            vec![("run-as-non-root", "pod.yml#Pod/debug/containers/setup")]
+        );
+    }
+
+    #[test]
+    fn test_kubernetes_skips_non_manifests() {
+        assert!(analyze_kubernetes(".github/workflows/ci.yml", "on: push\njobs: {}\n").is_empty());
+        assert!(
+            analyze_kubernetes("chart/templates/deploy.yaml", "{{- if .Values.x }}\n").is_empty()
+        );
+    }
+
+    #[test]
+    fn test_terraform_rules() {
+        let terraform = r#"
+variable "encrypt" {}
+
+resource "aws_s3_bucket" "logs" {
+  bucket = "logs"
+  acl    = "public-read"
+}
+
+resource "aws_s3_bucket" "private" {
+  acl = "private"
+}
+
+resource "aws_security_group" "web" {
+  ingress {
+    from_port   = 443
+    to_port     = 443
+    protocol    = "tcp"
+    cidr_blocks = ["10.0.0.0/8", "0.0.0.0/0"]
+  }
+  egress {
+    cidr_blocks = ["0.0.0.0/0"]
+  }
+}
+
+resource "aws_security_group_rule" "egress" {
+  type        = "egress"
+  cidr_blocks = ["0.0.0.0/0"]
+}
+
+resource "aws_vpc_security_group_ingress_rule" "ssh" {
+  cidr_ipv6 = "::/0"
+}
+
+resource "aws_ebs_volume" "data" {
+  size = 10
+}
+
+resource "aws_db_instance" "main" {
+  storage_encrypted = var.encrypt
+}
+
+resource "aws_rds_cluster" "analytics" {
+  storage_encrypted = false
+}
+"#;
+        let findings = analyze_terraform("infra/main.tf", terraform);
+        assert_eq!(
+            rules(&findings),
+            vec![
+                ("s3-public-acl", "infra/main.tf#aws_s3_bucket.logs"),
+                (
+                    "open-security-group",
+                    "infra/main.tf#aws_security_group.web"
+                ),
+                (
+                    "open-security-group",
+                    "infra/main.tf#aws_vpc_security_group_ingress_rule.ssh"
+                ),
+                ("unencrypted-storage", "infra/main.tf#aws_ebs_volume.data"),
+                (
+                    "unencrypted-storage",
+                    "infra/main.tf#aws_rds_cluster.analytics"
+                ),
+            ]
+        );
+        assert_eq!(
+            findings[3].remediation.as_deref(),
+            Some("Set `encrypted = true`")
+        );
+    }
+
+    #[test]
+    fn test_terraform_skips_invalid_files() {
+        assert!(analyze_terraform("broken.tf", "resource \"aws_s3_bucket\" {").is_empty());
+    }
+}
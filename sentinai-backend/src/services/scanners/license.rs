@@ -0,0 +1,156 @@
+use crate::db::license_policy_repo;
+use crate::domain::license::LicensePolicy;
+use crate::domain::sbom::SbomContents;
+use crate::domain::security::NewFinding;
+use crate::error::AppError;
+use crate::services::license::{self, LicenseVerdict};
+use crate::state::AppState;
+
+use super::{ScanContext, Scanner};
+
+/// One finding per package whose license violates `policy`. The package is
+/// recorded as `name@version`, so each version of a package keeps its own
+/// finding.
+fn license_findings(contents: &SbomContents, policy: &LicensePolicy) -> Vec<NewFinding> {
+    contents
+        .packages
+        .iter()
+        .filter_map(|package| {
+            let declared = package.license.as_deref();
+            let (rule_id, severity, description) =
+                match license::evaluate(declared, policy) {
+                    LicenseVerdict::Allowed => return None,
+                    LicenseVerdict::Unknown if !policy.flag_unknown => return None,
+                    LicenseVerdict::Unknown => (
+                        "unknown-license",
+                        "low",
+                        format!(
+                            "{} {} has an unrecognized license: {}",
+                            package.name,
+                            package.version,
+                            declared.unwrap_or("none declared")
+                        ),
+                    ),
+                    LicenseVerdict::NotAllowed(ids) => (
+                        "license-not-allowed",
+                        "medium",
+                        format!(
+                            "{} {} is licensed under {}, which is not on the project's allow list",
+                            package.name,
+                            package.version,
+                            ids.join(", ")
+                        ),
+                    ),
+                    LicenseVerdict::Denied(ids) => (
+                        "denied-license",
+                        "high",
+                        format!(
+                            "{} {} is licensed under {}, which is denied by the project's license policy",
+                            package.name,
+                            package.version,
+                            ids.join(", ")
+                        ),
+                    ),
+                };
+
+            let remediation = match rule_id {
+                "unknown-license" => format!(
+                    "Confirm the license of {} and add its SPDX identifier to the license policy",
+                    package.name
+                ),
+                _ => format!(
+                    "Replace {} with an alternative under an allowed license",
+                    package.name
+                ),
+            };
+
+            Some(NewFinding {
+                scanner: Scanner::License.name().to_string(),
+                rule_id: rule_id.to_string(),
+                severity: severity.to_string(),
+                description,
+                location: Some(package.source_file.clone()),
+                start_line: None,
+                start_column: None,
+                package: Some(format!("{}@{}", package.name, package.version)),
+                remediation: Some(remediation),
+                fixed_versions: Vec::new(),
+                partial_fingerprint: None,
+            })
+        })
+        .collect()
+}
+
+pub async fn scan(state: &AppState, ctx: &ScanContext<'_>) -> Result<Vec<NewFinding>, AppError> {
+    let contents = ctx.sbom.ok_or_else(|| {
+        AppError::InternalServerError("No package inventory available for license checks".into())
+    })?;
+
+    let policy = license_policy_repo::get_license_policy(&state.db, ctx.project.id)
+        .await?
+        .unwrap_or_else(|| LicensePolicy::default_for(ctx.project.id));
+
+    Ok(license_findings(contents, &policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::sbom::{Ecosystem, Package};
+    use uuid::Uuid;
+
+    fn package(name: &str, license: Option<&str>) -> Package {
+        Package {
+            ecosystem: Ecosystem::Npm,
+            name: name.into(),
+            version: "1.0.0".into(),
+            license: license.map(str::to_string),
+            hashes: Vec::new(),
+            dependencies: Vec::new(),
+            source_file: "package-lock.json".into(),
+        }
+    }
+
+    #[test]
+    fn test_license_findings() {
+        let contents = SbomContents {
+            packages: vec![
+                package("ok", Some("MIT")),
+                package("copyleft", Some("AGPL-3.0")),
+                package("mystery", None),
+            ],
+            root_dependencies: Vec::new(),
+        };
+        let mut policy = LicensePolicy::default_for(Uuid::nil());
+
+        let findings = license_findings(&contents, &policy);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].rule_id, "denied-license");
+        assert_eq!(findings[0].severity, "high");
+        assert_eq!(findings[0].package.as_deref(), Some("copyleft@1.0.0"));
+        assert_eq!(findings[0].location.as_deref(), Some("package-lock.json"));
+        assert!(findings[0].description.contains("AGPL-3.0-only"));
+        assert_eq!(findings[1].rule_id, "unknown-license");
+
+        policy.flag_unknown = false;
+        policy.allowed = vec!["Apache-2.0".into()];
+        let findings = license_findings(&contents, &policy);
+        let rules: Vec<&str> = findings.iter().map(|f| f.rule_id.as_str()).collect();
+        assert_eq!(rules, vec!["license-not-allowed", "denied-license"]);
+    }
+
+    #[test]
+    fn test_license_findings_tell_versions_apart() {
+        let mut newer = package("copyleft", Some("AGPL-3.0"));
+        newer.version = "2.0.0".into();
+        let contents = SbomContents {
+            packages: vec![package("copyleft", Some("AGPL-3.0")), newer],
+            root_dependencies: Vec::new(),
+        };
+
+        let findings = license_findings(&contents, &LicensePolicy::default_for(Uuid::nil()));
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[1].package.as_deref(), Some("copyleft@2.0.0"));
+        assert_ne!(findings[0].fingerprint(), findings[1].fingerprint());
+    }
+}
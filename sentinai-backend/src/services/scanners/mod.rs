@@ -0,0 +1,102 @@
+use crate::domain::project::Project;
+use crate::domain::sbom::SbomContents;
+use crate::domain::security::NewFinding;
+use crate::error::AppError;
+use crate::services::repository::RepoSnapshot;
+use crate::state::AppState;
+
+mod dockerfile;
+mod iac;
+mod license;
+mod rust_sast;
+
+/// What a scanner gets to look at during a scan run.
+pub struct ScanContext<'a> {
+    pub project: &'a Project,
+    pub commit_sha: Option<&'a str>,
+    /// Packages resolved for this run, if the repository could be fetched.
+    pub sbom: Option<&'a SbomContents>,
+    /// Repository files at the scanned commit.
+    pub snapshot: Option<&'a RepoSnapshot>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scanner {
+    DependencyAudit,
+    HttpHeaders,
+    License,
+    Dockerfile,
+    Iac,
+    RustSast,
+}
+
+impl Scanner {
+    pub const ALL: [Scanner; 6] = [
+        Scanner::DependencyAudit,
+        Scanner::HttpHeaders,
+        Scanner::License,
+        Scanner::Dockerfile,
+        Scanner::Iac,
+        Scanner::RustSast,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scanner::DependencyAudit => "dependency-audit",
+            Scanner::HttpHeaders => "http-headers",
+            Scanner::License => "license",
+            Scanner::Dockerfile => "dockerfile",
+            Scanner::Iac => "iac",
+            Scanner::RustSast => "rust-sast",
+        }
+    }
+
+    pub async fn scan(
+        self,
+        state: &AppState,
+        ctx: &ScanContext<'_>,
+    ) -> Result<Vec<NewFinding>, AppError> {
+        tracing::debug!(
+            scanner = self.name(),
+            repository = %ctx.project.repository_url,
+            commit_sha = ?ctx.commit_sha,
+            "Running scanner"
+        );
+
+        let findings = match self {
+            Scanner::DependencyAudit => vec![NewFinding {
+                scanner: self.name().to_string(),
+                rule_id: "outdated-dependency".to_string(),
+                severity: "high".to_string(),
+                description: "Detected outdated OpenSSL dependency in Cargo.lock".to_string(),
+                location: Some("Cargo.lock".to_string()),
+                start_line: None,
+                start_column: None,
+                package: Some("openssl".to_string()),
+                // A placeholder without fixed versions, so dependency bumps never act on it.
+                remediation: None,
+                fixed_versions: Vec::new(),
+                partial_fingerprint: None,
+            }],
+            Scanner::HttpHeaders => vec![NewFinding {
+                scanner: self.name().to_string(),
+                rule_id: "missing-security-headers".to_string(),
+                severity: "medium".to_string(),
+                description: "Missing security headers in API response".to_string(),
+                location: None,
+                start_line: None,
+                start_column: None,
+                package: None,
+                remediation: None,
+                fixed_versions: Vec::new(),
+                partial_fingerprint: None,
+            }],
+            Scanner::License => license::scan(state, ctx).await?,
+            Scanner::Dockerfile => dockerfile::scan(ctx).await?,
+            Scanner::Iac => iac::scan(ctx).await?,
+            Scanner::RustSast => rust_sast::scan(ctx).await?,
+        };
+
+        Ok(findings)
+    }
+}
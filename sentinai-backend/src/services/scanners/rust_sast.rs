@@ -0,0 +1,623 @@
+//! Source-level checks for Rust code, run over the syntax tree of every `.rs`
+//! file in the repository.
+
+use std::collections::{HashMap, HashSet};
+
+use proc_macro2::Span;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Attribute, Expr, FnArg, Pat, ReturnType, Signature, Type};
+
+use crate::domain::security::NewFinding;
+use crate::error::AppError;
+
+use super::{ScanContext, Scanner};
+
+const SQLX_QUERY_FNS: [&str; 5] = [
+    "query",
+    "query_as",
+    "query_scalar",
+    "query_with",
+    "query_as_with",
+];
+const PANICKING_METHODS: [&str; 2] = ["unwrap", "expect"];
+const PANICKING_MACROS: [&str; 4] = ["panic", "todo", "unimplemented", "unreachable"];
+/// Types whose presence in an async fn's signature marks it as an HTTP
+/// handler or middleware.
+const HANDLER_TYPES: [&str; 13] = [
+    "State",
+    "Path",
+    "Query",
+    "Json",
+    "Form",
+    "Extension",
+    "Multipart",
+    "HeaderMap",
+    "Request",
+    "Next",
+    "Response",
+    "IntoResponse",
+    "Html",
+];
+
+#[derive(Default)]
+struct TypeIdents(HashSet<String>);
+
+impl Visit<'_> for TypeIdents {
+    fn visit_path_segment(&mut self, segment: &syn::PathSegment) {
+        self.0.insert(segment.ident.to_string());
+        visit::visit_path_segment(self, segment);
+    }
+}
+
+/// Identifiers referenced anywhere in an expression.
+#[derive(Default)]
+struct ExprIdents(HashSet<String>);
+
+impl Visit<'_> for ExprIdents {
+    fn visit_expr_path(&mut self, expr: &syn::ExprPath) {
+        if let Some(ident) = expr.path.get_ident() {
+            self.0.insert(ident.to_string());
+        }
+        visit::visit_expr_path(self, expr);
+    }
+}
+
+fn is_handler(sig: &Signature) -> bool {
+    if sig.asyncness.is_none() {
+        return false;
+    }
+    let mut idents = TypeIdents::default();
+    for input in &sig.inputs {
+        if let FnArg::Typed(arg) = input {
+            idents.visit_type(&arg.ty);
+        }
+    }
+    if let ReturnType::Type(_, ty) = &sig.output {
+        idents.visit_type(ty);
+    }
+    HANDLER_TYPES.iter().any(|name| idents.0.contains(*name))
+}
+
+fn param_names(sig: &Signature) -> HashSet<String> {
+    struct Bindings(HashSet<String>);
+    impl Visit<'_> for Bindings {
+        fn visit_pat_ident(&mut self, pat: &syn::PatIdent) {
+            self.0.insert(pat.ident.to_string());
+            visit::visit_pat_ident(self, pat);
+        }
+    }
+
+    let mut bindings = Bindings(HashSet::new());
+    for input in &sig.inputs {
+        if let FnArg::Typed(arg) = input {
+            bindings.visit_pat(&arg.pat);
+        }
+    }
+    bindings.0
+}
+
+fn is_test_only(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let path = attr.path();
+        path.is_ident("test")
+            || path.segments.last().is_some_and(|s| s.ident == "test")
+            || (path.is_ident("cfg")
+                && attr
+                    .parse_args::<syn::Ident>()
+                    .is_ok_and(|ident| ident == "test"))
+    })
+}
+
+fn is_format_macro(expr: &Expr) -> bool {
+    matches!(expr, Expr::Macro(m) if m.mac.path.is_ident("format"))
+}
+
+fn local_name(pat: &Pat) -> Option<String> {
+    match pat {
+        Pat::Ident(pat) => Some(pat.ident.to_string()),
+        Pat::Type(pat) => local_name(&pat.pat),
+        _ => None,
+    }
+}
+
+fn is_command_new(expr: &Expr) -> bool {
+    matches!(expr, Expr::Call(call) if is_command_new_call(call))
+}
+
+fn is_command_new_call(call: &syn::ExprCall) -> bool {
+    let Expr::Path(func) = &*call.func else {
+        return false;
+    };
+    let segments: Vec<String> = func
+        .path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect();
+    segments.ends_with(&["Command".to_string(), "new".to_string()])
+}
+
+struct FnContext {
+    name: String,
+    handler: bool,
+    params: HashSet<String>,
+    /// Locals bound to a `format!` result.
+    formatted: HashSet<String>,
+    /// Locals bound to a `Command::new(..)` builder.
+    commands: HashSet<String>,
+}
+
+struct Analyzer<'a> {
+    path: &'a str,
+    findings: Vec<NewFinding>,
+    functions: Vec<FnContext>,
+    /// Names of the modules, impl blocks and functions being visited.
+    scopes: Vec<String>,
+    /// Findings reported so far per location and rule.
+    occurrences: HashMap<(String, String), usize>,
+}
+
+impl Analyzer<'_> {
+    fn report(
+        &mut self,
+        span: Span,
+        rule_id: &str,
+        severity: &str,
+        description: String,
+        remediation: &str,
+    ) {
+        // Locations name the enclosing item rather than the line, so that
+        // edits elsewhere in the file keep the fingerprint. Repeats of a rule
+        // within one item are numbered in source order.
+        let item = if self.scopes.is_empty() {
+            self.path.to_string()
+        } else {
+            format!("{}#{}", self.path, self.scopes.join("::"))
+        };
+        let count = self
+            .occurrences
+            .entry((item.clone(), rule_id.to_string()))
+            .or_default();
+        *count += 1;
+        let location = match *count {
+            1 => item,
+            n => format!("{}[{}]", item, n),
+        };
+
+        let start = span.start();
+        self.findings.push(NewFinding {
+            scanner: Scanner::RustSast.name().to_string(),
+            rule_id: rule_id.to_string(),
+            severity: severity.to_string(),
+            description,
+            location: Some(location),
+            start_line: i32::try_from(start.line).ok(),
+            start_column: i32::try_from(start.column + 1).ok(),
+            package: None,
+            remediation: Some(remediation.to_string()),
+            fixed_versions: Vec::new(),
+            partial_fingerprint: None,
+        });
+    }
+
+    fn enter_fn(&mut self, sig: &Signature, visit_body: impl FnOnce(&mut Self)) {
+        self.functions.push(FnContext {
+            name: sig.ident.to_string(),
+            handler: is_handler(sig),
+            params: param_names(sig),
+            formatted: HashSet::new(),
+            commands: HashSet::new(),
+        });
+        self.scopes.push(sig.ident.to_string());
+        visit_body(self);
+        self.scopes.pop();
+        self.functions.pop();
+    }
+
+    fn handler_name(&self) -> Option<&str> {
+        self.functions
+            .last()
+            .filter(|f| f.handler)
+            .map(|f| f.name.as_str())
+    }
+
+    fn is_dynamic_sql(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Reference(r) => self.is_dynamic_sql(&r.expr),
+            Expr::Paren(p) => self.is_dynamic_sql(&p.expr),
+            Expr::MethodCall(call) if call.method == "as_str" || call.method == "as_ref" => {
+                self.is_dynamic_sql(&call.receiver)
+            }
+            Expr::Path(path) => path.path.get_ident().is_some_and(|ident| {
+                self.functions
+                    .last()
+                    .is_some_and(|f| f.formatted.contains(&ident.to_string()))
+            }),
+            _ => is_format_macro(expr),
+        }
+    }
+
+    fn is_command(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::MethodCall(call) => self.is_command(&call.receiver),
+            Expr::Reference(r) => self.is_command(&r.expr),
+            Expr::Path(path) => path.path.get_ident().is_some_and(|ident| {
+                self.functions
+                    .last()
+                    .is_some_and(|f| f.commands.contains(&ident.to_string()))
+            }),
+            _ => is_command_new(expr),
+        }
+    }
+
+    /// The first parameter of the enclosing function an expression reads.
+    fn tainted_by(&self, expr: &Expr) -> Option<String> {
+        let function = self.functions.last()?;
+        let mut idents = ExprIdents::default();
+        idents.visit_expr(expr);
+        let mut params: Vec<&String> = idents.0.intersection(&function.params).collect();
+        params.sort();
+        params.first().map(|p| p.to_string())
+    }
+
+    fn report_command(&mut self, span: Span, arg: &Expr) {
+        let Some(param) = self.tainted_by(arg) else {
+            return;
+        };
+        let function = self.functions.last().map_or("", |f| f.name.as_str());
+        self.report(
+            span,
+            "command-user-input",
+            "high",
+            format!(
+                "`std::process::Command` is built from parameter `{}` of `{}`",
+                param, function
+            ),
+            "Validate the input against an allow-list and pass it as a separate argument, never through a shell",
+        );
+    }
+}
+
+impl<'ast> Visit<'ast> for Analyzer<'_> {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        if !is_test_only(&item.attrs) {
+            self.scopes.push(item.ident.to_string());
+            visit::visit_item_mod(self, item);
+            self.scopes.pop();
+        }
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let name = match &*item.self_ty {
+            Type::Path(ty) => ty.path.segments.last().map(|s| s.ident.to_string()),
+            _ => None,
+        };
+        self.scopes.push(name.unwrap_or_else(|| "impl".to_string()));
+        visit::visit_item_impl(self, item);
+        self.scopes.pop();
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        if !is_test_only(&item.attrs) {
+            self.enter_fn(&item.sig, |this| visit::visit_item_fn(this, item));
+        }
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        if !is_test_only(&item.attrs) {
+            self.enter_fn(&item.sig, |this| visit::visit_impl_item_fn(this, item));
+        }
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        if let (Some(name), Some(init)) = (local_name(&local.pat), &local.init) {
+            if let Some(function) = self.functions.last_mut() {
+                if is_format_macro(&init.expr) {
+                    function.formatted.insert(name);
+                } else if is_command_new(&init.expr) {
+                    function.commands.insert(name);
+                }
+            }
+        }
+        visit::visit_local(self, local);
+    }
+
+    fn visit_expr_unsafe(&mut self, expr: &'ast syn::ExprUnsafe) {
+        self.report(
+            expr.unsafe_token.span,
+            "unsafe-block",
+            "medium",
+            "`unsafe` block bypasses the compiler's memory safety checks".to_string(),
+            "Replace it with a safe abstraction, or document the invariants it relies on in a `// SAFETY:` comment",
+        );
+        visit::visit_expr_unsafe(self, expr);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let Expr::Path(func) = &*call.func {
+            let segments = &func.path.segments;
+            let is_sqlx_query = segments.len() > 1
+                && segments.first().is_some_and(|s| s.ident == "sqlx")
+                && segments
+                    .last()
+                    .is_some_and(|s| SQLX_QUERY_FNS.contains(&s.ident.to_string().as_str()));
+            if is_sqlx_query
+                && call
+                    .args
+                    .first()
+                    .is_some_and(|sql| self.is_dynamic_sql(sql))
+            {
+                let function = segments.last().map(|s| s.ident.to_string());
+                self.report(
+                    call.span(),
+                    "sql-string-building",
+                    "high",
+                    format!(
+                        "SQL passed to `sqlx::{}` is built with `format!`",
+                        function.unwrap_or_default()
+                    ),
+                    "Use a static query string and pass values with `.bind()`",
+                );
+            }
+        }
+        if is_command_new_call(call) {
+            if let Some(program) = call.args.first() {
+                self.report_command(call.span(), program);
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        let method = call.method.to_string();
+        if PANICKING_METHODS.contains(&method.as_str()) {
+            if let Some(handler) = self.handler_name() {
+                self.report(
+                    call.method.span(),
+                    "panic-in-handler",
+                    "medium",
+                    format!(
+                        "`.{}()` can panic inside async handler `{}`",
+                        method, handler
+                    ),
+                    "Propagate the error with `?` and map it to an error response",
+                );
+            }
+        }
+        if (method == "arg" || method == "args") && self.is_command(&call.receiver) {
+            if let Some(arg) = call.args.first() {
+                self.report_command(call.method.span(), arg);
+            }
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let name = mac.path.segments.last().map(|s| s.ident.to_string());
+        if let (Some(name), Some(handler)) = (name, self.handler_name()) {
+            if PANICKING_MACROS.contains(&name.as_str()) {
+                let description = format!("`{}!` panics inside async handler `{}`", name, handler);
+                self.report(
+                    mac.path.span(),
+                    "panic-in-handler",
+                    "medium",
+                    description,
+                    "Return an error response instead of panicking",
+                );
+            }
+        }
+        visit::visit_macro(self, mac);
+    }
+}
+
+/// Parses one Rust source file and runs every rule over it. Files that fail
+/// to parse are skipped.
+pub fn analyze(path: &str, contents: &str) -> Vec<NewFinding> {
+    let file = match syn::parse_file(contents) {
+        Ok(file) => file,
+        Err(e) => {
+            tracing::debug!(path, error = %e, "Skipping unparseable Rust file");
+            return Vec::new();
+        }
+    };
+    let mut analyzer = Analyzer {
+        path,
+        findings: Vec::new(),
+        functions: Vec::new(),
+        scopes: Vec::new(),
+        occurrences: HashMap::new(),
+    };
+    analyzer.visit_file(&file);
+    analyzer.findings
+}
+
+fn is_production_source(path: &str) -> bool {
+    path.ends_with(".rs")
+        && !path
+            .split('/')
+            .any(|dir| matches!(dir, "tests" | "benches" | "examples"))
+}
+
+pub async fn scan(ctx: &ScanContext<'_>) -> Result<Vec<NewFinding>, AppError> {
+    let snapshot = ctx.snapshot.ok_or_else(|| {
+        AppError::InternalServerError("No repository snapshot available for Rust checks".into())
+    })?;
+
+    Ok(snapshot
+        .files()
+        .filter(|(path, _)| is_production_source(path))
+        .flat_map(|(path, contents)| analyze(path, contents))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(findings: &[NewFinding]) -> Vec<(&str, &str, i32, i32)> {
+        findings
+            .iter()
+            .map(|f| {
+                (
+                    f.rule_id.as_str(),
+                    f.location.as_deref().unwrap_or_default(),
+                    f.start_line.unwrap_or_default(),
+                    f.start_column.unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_panics_in_handlers_and_middleware() {
+        let source = r#"
+pub async fn propagate_request_id(mut req: Request, next: Next) -> Response {
+    let request_id = Uuid::new_v4().to_string();
+    req.headers_mut().insert("x-request-id", request_id.parse().unwrap());
+    next.run(req).await
+}
+
+async fn get_project(State(state): State<AppState>, Path(id): Path<Uuid>) -> Result<Json<Project>, AppError> {
+    let project = find(&state, id).await.expect("project exists");
+    if project.archived {
+        todo!()
+    }
+    Ok(Json(project))
+}
+
+async fn background(pool: PgPool) {
+    pool.close().await.unwrap();
+}
+
+fn parse(value: &str) -> u32 {
+    value.parse().unwrap()
+}
+"#;
+        assert_eq!(
+            rules(&analyze("src/utils/middleware.rs", source)),
+            vec![
+                (
+                    "panic-in-handler",
+                    "src/utils/middleware.rs#propagate_request_id",
+                    4,
+                    65
+                ),
+                (
+                    "panic-in-handler",
+                    "src/utils/middleware.rs#get_project",
+                    9,
+                    42
+                ),
+                (
+                    "panic-in-handler",
+                    "src/utils/middleware.rs#get_project[2]",
+                    11,
+                    9
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sql_built_with_format() {
+        let source = r#"
+async fn list(pool: &PgPool, order: &str) -> Vec<Row> {
+    let sql = format!("SELECT * FROM t ORDER BY {}", order);
+    let a = sqlx::query_as::<_, Row>(&sql).fetch_all(pool).await;
+    let b = sqlx::query(&format!("DELETE FROM {}", order)).execute(pool).await;
+    let c = sqlx::query("SELECT 1").execute(pool).await;
+    let d = other::query(&sql);
+    a
+}
+"#;
+        let findings = analyze("src/db/repo.rs", source);
+        assert_eq!(
+            rules(&findings),
+            vec![
+                ("sql-string-building", "src/db/repo.rs#list", 4, 13),
+                ("sql-string-building", "src/db/repo.rs#list[2]", 5, 13),
+            ]
+        );
+        assert_eq!(
+            findings[0].description,
+            "SQL passed to `sqlx::query_as` is built with `format!`"
+        );
+    }
+
+    #[test]
+    fn test_unsafe_blocks_and_commands() {
+        let source = r#"
+fn raw(ptr: *const u8) -> u8 {
+    unsafe { *ptr }
+}
+
+fn clone_repo(url: &str) {
+    let mut cmd = std::process::Command::new("git");
+    cmd.arg("clone").arg(url).status().unwrap();
+    Command::new("ls").arg("-la").status().unwrap();
+}
+
+fn run(program: String) {
+    Command::new(program).spawn().unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    fn helper() { unsafe { std::hint::unreachable_unchecked() } }
+}
+"#;
+        let findings = analyze("src/lib.rs", source);
+        assert_eq!(
+            rules(&findings),
+            vec![
+                ("unsafe-block", "src/lib.rs#raw", 3, 5),
+                ("command-user-input", "src/lib.rs#clone_repo", 8, 22),
+                ("command-user-input", "src/lib.rs#run", 13, 5),
+            ]
+        );
+        assert_eq!(
+            findings[1].description,
+            "`std::process::Command` is built from parameter `url` of `clone_repo`"
+        );
+    }
+
+    #[test]
+    fn test_locations_survive_line_shifts() {
+        let source = r#"
+impl Store {
+    fn read(&self) -> u8 {
+        unsafe { *self.ptr }
+    }
+}
+
+mod ffi {
+    fn read(ptr: *const u8) -> u8 {
+        unsafe { *ptr }
+    }
+}
+"#;
+        let before = analyze("src/store.rs", source);
+        let after = analyze("src/store.rs", &format!("use std::ptr;\n\n{}", source));
+        assert_eq!(
+            rules(&before),
+            vec![
+                ("unsafe-block", "src/store.rs#Store::read", 4, 9),
+                ("unsafe-block", "src/store.rs#ffi::read", 10, 9),
+            ]
+        );
+        assert_eq!(after[0].start_line, Some(6));
+        let fingerprints = |findings: &[NewFinding]| -> Vec<String> {
+            findings.iter().map(NewFinding::fingerprint).collect()
+        };
+        assert_eq!(fingerprints(&before), fingerprints(&after));
+    }
+
+    #[test]
+    fn test_skips_unparseable_and_test_sources() {
+        assert!(analyze("src/broken.rs", "fn {").is_empty());
+        assert!(is_production_source("src/main.rs"));
+        assert!(!is_production_source("tests/api.rs"));
+        assert!(!is_production_source("src/main.ts"));
+    }
+}
@@ -0,0 +1,326 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::db::security_gate_repo;
+use crate::domain::project::Project;
+use crate::domain::security::SecurityFinding;
+use crate::domain::security_gate::{
+    default_gate_rules, GateEvaluation, GatePolicy, GatePolicyScope, GateRule, GateRuleResult,
+    Severity, UpdateGatePolicyRequest,
+};
+use crate::error::AppError;
+use crate::services::{github_service, security_service};
+use crate::state::AppState;
+
+pub const COMMIT_STATUS_CONTEXT: &str = "sentinai/security-gate";
+
+fn violates(rule: &GateRule, finding: &SecurityFinding, now: DateTime<Utc>) -> bool {
+    if finding.status.is_closed() {
+        return false;
+    }
+    let severity = Severity::from_finding(&finding.severity);
+    match rule {
+        GateRule::NoOpenFindings { min_severity } => severity >= *min_severity,
+        GateRule::MaxFindingAge {
+            min_severity,
+            max_age_days,
+        } => {
+            severity >= *min_severity
+                && now - finding.created_at > Duration::days(i64::from(*max_age_days))
+        }
+        GateRule::NoDeniedLicenses => {
+            finding.scanner == "license" && finding.rule_id == "denied-license"
+        }
+    }
+}
+
+pub fn evaluate_rules(
+    rules: &[GateRule],
+    findings: &[SecurityFinding],
+    now: DateTime<Utc>,
+) -> Vec<GateRuleResult> {
+    rules
+        .iter()
+        .map(|rule| {
+            let violations: Vec<SecurityFinding> = findings
+                .iter()
+                .filter(|finding| violates(rule, finding, now))
+                .cloned()
+                .collect();
+            GateRuleResult {
+                rule: rule.clone(),
+                description: rule.describe(),
+                passed: violations.is_empty(),
+                violations,
+            }
+        })
+        .collect()
+}
+
+/// The rules that apply to a project: its own policy, else its owner's
+/// organization-wide policy, else the built-in defaults.
+async fn effective_rules(
+    state: &AppState,
+    project: &Project,
+) -> Result<(Vec<GateRule>, GatePolicyScope), AppError> {
+    if let Some(policy) = security_gate_repo::get_project_policy(&state.db, project.id).await? {
+        return Ok((policy.rules.0, GatePolicyScope::Project));
+    }
+    if let Some(policy) =
+        security_gate_repo::get_organization_policy(&state.db, project.user_id).await?
+    {
+        return Ok((policy.rules.0, GatePolicyScope::Organization));
+    }
+    Ok((default_gate_rules(), GatePolicyScope::Default))
+}
+
+#[tracing::instrument(name = "security_gate", skip(state, project), fields(project_id = %project.id))]
+pub async fn evaluate_gate(
+    state: &AppState,
+    project: &Project,
+) -> Result<GateEvaluation, AppError> {
+    let (rules, policy_scope) = effective_rules(state, project).await?;
+    let findings = security_service::get_security_findings(state, project.id).await?;
+
+    let now = Utc::now();
+    let results = evaluate_rules(&rules, &findings, now);
+    let passed = results.iter().all(|result| result.passed);
+
+    tracing::info!(passed, scope = ?policy_scope, "Security gate evaluated");
+
+    Ok(GateEvaluation {
+        project_id: project.id,
+        passed,
+        policy_scope,
+        rules: results,
+        evaluated_at: now,
+    })
+}
+
+/// Whether gate results for `project` can be published as commit statuses,
+/// which needs a `GITHUB_TOKEN` and a repository hosted on GitHub.
+pub fn publishes_commit_status(state: &AppState, project: &Project) -> bool {
+    state.config.github_token.is_some()
+        && github_service::parse_repository_url(&project.repository_url).is_some()
+}
+
+/// Reports the gate result as a commit status so branch protection can block
+/// merges on it.
+#[tracing::instrument(name = "security_gate", skip(state, project), fields(project_id = %project.id))]
+pub async fn publish_commit_status(
+    state: &AppState,
+    project: &Project,
+    commit_sha: &str,
+) -> Result<GateEvaluation, AppError> {
+    let evaluation = evaluate_gate(state, project).await?;
+
+    let failed: Vec<&str> = evaluation
+        .rules
+        .iter()
+        .filter(|result| !result.passed)
+        .map(|result| result.description.as_str())
+        .collect();
+    let (status, description) = if failed.is_empty() {
+        ("success", "All security policies passed".to_string())
+    } else {
+        ("failure", format!("Failed: {}", failed.join("; ")))
+    };
+    // GitHub rejects descriptions longer than 140 characters.
+    let description: String = description.chars().take(140).collect();
+
+    github_service::create_commit_status(
+        state,
+        &project.repository_url,
+        commit_sha,
+        status,
+        COMMIT_STATUS_CONTEXT,
+        &description,
+    )
+    .await?;
+
+    Ok(evaluation)
+}
+
+fn validate_rules(rules: &[GateRule]) -> Result<(), AppError> {
+    if rules.iter().any(|rule| {
+        matches!(
+            rule,
+            GateRule::MaxFindingAge {
+                max_age_days: 0,
+                ..
+            }
+        )
+    }) {
+        return Err(AppError::BadRequest(
+            "max_age_days must be at least 1".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[tracing::instrument(name = "security_gate", skip(state), fields(project_id = %project_id))]
+pub async fn get_project_policy(
+    state: &AppState,
+    project_id: Uuid,
+) -> Result<GatePolicy, AppError> {
+    security_gate_repo::get_project_policy(&state.db, project_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Security gate policy not found".into()))
+}
+
+#[tracing::instrument(name = "security_gate", skip(state, request), fields(project_id = %project_id))]
+pub async fn update_project_policy(
+    state: &AppState,
+    project_id: Uuid,
+    request: UpdateGatePolicyRequest,
+) -> Result<GatePolicy, AppError> {
+    validate_rules(&request.rules)?;
+    security_gate_repo::upsert_project_policy(&state.db, project_id, &request.rules).await
+}
+
+#[tracing::instrument(name = "security_gate", skip(state), fields(project_id = %project_id))]
+pub async fn delete_project_policy(state: &AppState, project_id: Uuid) -> Result<(), AppError> {
+    let rows = security_gate_repo::delete_project_policy(&state.db, project_id).await?;
+    if rows == 0 {
+        return Err(AppError::NotFound("Security gate policy not found".into()));
+    }
+    Ok(())
+}
+
+#[tracing::instrument(name = "security_gate", skip(state), fields(user_id = %user_id))]
+pub async fn get_organization_policy(
+    state: &AppState,
+    user_id: Uuid,
+) -> Result<GatePolicy, AppError> {
+    security_gate_repo::get_organization_policy(&state.db, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Security gate policy not found".into()))
+}
+
+#[tracing::instrument(name = "security_gate", skip(state, request), fields(user_id = %user_id))]
+pub async fn update_organization_policy(
+    state: &AppState,
+    user_id: Uuid,
+    request: UpdateGatePolicyRequest,
+) -> Result<GatePolicy, AppError> {
+    validate_rules(&request.rules)?;
+    security_gate_repo::upsert_organization_policy(&state.db, user_id, &request.rules).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::security::FindingStatus;
+    use chrono::TimeZone;
+
+    fn finding(
+        scanner: &str,
+        rule_id: &str,
+        severity: &str,
+        age_days: i64,
+        now: DateTime<Utc>,
+    ) -> SecurityFinding {
+        let created_at = now - Duration::days(age_days);
+        SecurityFinding {
+            id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            scanner: scanner.into(),
+            rule_id: rule_id.into(),
+            severity: severity.into(),
+            description: format!("{} finding", rule_id),
+            location: None,
+            start_line: None,
+            start_column: None,
+            package: None,
+            remediation: None,
+            fixed_versions: Vec::new(),
+            fingerprint: format!("fp-{}-{}", rule_id, severity),
+            status: FindingStatus::Open,
+            status_expires_at: None,
+            created_at,
+            last_seen_at: created_at,
+            fixed_at: None,
+            scan_run_id: None,
+            last_scan_run_id: None,
+        }
+    }
+
+    #[test]
+    fn test_default_rules() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+        let mut suppressed = finding("sast", "unsafe-block", "critical", 1, now);
+        suppressed.status = FindingStatus::AcceptedRisk;
+        let findings = vec![
+            suppressed,
+            finding("dependency-audit", "outdated-dependency", "high", 3, now),
+            finding(
+                "dependency-audit",
+                "vulnerable-dependency",
+                "critical",
+                20,
+                now,
+            ),
+            finding("license", "denied-license", "high", 1, now),
+            finding("license", "unknown-license", "low", 1, now),
+        ];
+
+        let results = evaluate_rules(&default_gate_rules(), &findings, now);
+
+        let violations = |idx: usize| -> Vec<&str> {
+            results[idx]
+                .violations
+                .iter()
+                .map(|f| f.rule_id.as_str())
+                .collect()
+        };
+        assert!(!results[0].passed);
+        assert_eq!(violations(0), vec!["vulnerable-dependency"]);
+        assert_eq!(violations(1), vec!["vulnerable-dependency"]);
+        assert_eq!(violations(2), vec!["denied-license"]);
+        assert_eq!(
+            results[1].description,
+            "No open high or higher findings older than 14 days"
+        );
+    }
+
+    #[test]
+    fn test_rules_pass_without_violations() {
+        let now = Utc::now();
+        let findings = vec![finding(
+            "http-headers",
+            "missing-headers",
+            "medium",
+            30,
+            now,
+        )];
+        let rules = vec![
+            GateRule::NoOpenFindings {
+                min_severity: Severity::High,
+            },
+            GateRule::MaxFindingAge {
+                min_severity: Severity::High,
+                max_age_days: 7,
+            },
+        ];
+
+        let results = evaluate_rules(&rules, &findings, now);
+        assert!(results.iter().all(|r| r.passed && r.violations.is_empty()));
+
+        let rules = vec![GateRule::MaxFindingAge {
+            min_severity: Severity::Medium,
+            max_age_days: 7,
+        }];
+        assert!(!evaluate_rules(&rules, &findings, now)[0].passed);
+    }
+
+    #[test]
+    fn test_rules_round_trip_as_json() {
+        let json = serde_json::to_value(default_gate_rules()).unwrap();
+        assert_eq!(
+            json[1],
+            serde_json::json!({"type": "max_finding_age", "min_severity": "high", "max_age_days": 14})
+        );
+        let parsed: Vec<GateRule> = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, default_gate_rules());
+    }
+}
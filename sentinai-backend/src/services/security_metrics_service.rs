@@ -0,0 +1,105 @@
+use uuid::Uuid;
+
+use crate::db::{project_repo, security_metrics_repo};
+use crate::domain::security_metrics::{RemediationTime, SecurityMetrics};
+use crate::error::AppError;
+use crate::state::AppState;
+
+const MAX_DAYS: i32 = 365;
+/// Scan runs listed in the new-vs-fixed trend.
+const MAX_SCAN_RUNS: i64 = 100;
+
+/// Mean over every resolved finding, weighting each severity's mean by how
+/// many findings it covers.
+fn overall_mean_seconds(times: &[RemediationTime]) -> Option<f64> {
+    let resolved: i64 = times.iter().map(|t| t.resolved_count).sum();
+    if resolved == 0 {
+        return None;
+    }
+    let total: f64 = times
+        .iter()
+        .map(|t| t.mean_seconds * t.resolved_count as f64)
+        .sum();
+    Some(total / resolved as f64)
+}
+
+async fn compute_metrics(
+    state: &AppState,
+    project_id: Option<Uuid>,
+    project_ids: &[Uuid],
+    days: i32,
+) -> Result<SecurityMetrics, AppError> {
+    if !(1..=MAX_DAYS).contains(&days) {
+        return Err(AppError::BadRequest(format!(
+            "days must be between 1 and {}",
+            MAX_DAYS
+        )));
+    }
+
+    let open_over_time =
+        security_metrics_repo::open_findings_over_time(&state.db, project_ids, days).await?;
+    let remediation =
+        security_metrics_repo::remediation_times(&state.db, project_ids, days).await?;
+    let age_distribution = security_metrics_repo::open_finding_ages(&state.db, project_ids).await?;
+    let scan_runs =
+        security_metrics_repo::scan_run_trends(&state.db, project_ids, days, MAX_SCAN_RUNS).await?;
+
+    Ok(SecurityMetrics {
+        project_id,
+        project_count: project_ids.len(),
+        days,
+        open_over_time,
+        mean_time_to_remediate_seconds: overall_mean_seconds(&remediation),
+        mean_time_to_remediate_by_severity: remediation,
+        age_distribution,
+        scan_runs,
+    })
+}
+
+#[tracing::instrument(name = "security_metrics", skip(state), fields(project_id = %project_id))]
+pub async fn get_project_metrics(
+    state: &AppState,
+    project_id: Uuid,
+    days: i32,
+) -> Result<SecurityMetrics, AppError> {
+    compute_metrics(state, Some(project_id), &[project_id], days).await
+}
+
+/// Rolls the metrics up across every project the user owns.
+#[tracing::instrument(name = "security_metrics", skip(state), fields(user_id = %user_id))]
+pub async fn get_organization_metrics(
+    state: &AppState,
+    user_id: Uuid,
+    days: i32,
+) -> Result<SecurityMetrics, AppError> {
+    let project_ids: Vec<Uuid> = project_repo::list_projects(&state.db, user_id)
+        .await?
+        .into_iter()
+        .map(|project| project.id)
+        .collect();
+
+    compute_metrics(state, None, &project_ids, days).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overall_mean_weights_by_resolved_count() {
+        let times = [
+            RemediationTime {
+                severity: "critical".into(),
+                resolved_count: 1,
+                mean_seconds: 3600.0,
+            },
+            RemediationTime {
+                severity: "low".into(),
+                resolved_count: 3,
+                mean_seconds: 7200.0,
+            },
+        ];
+        assert_eq!(overall_mean_seconds(&times), Some(6300.0));
+        assert_eq!(overall_mean_seconds(&[]), None);
+    }
+}
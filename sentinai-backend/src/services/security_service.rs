@@ -1,30 +1,102 @@
+use chrono::Utc;
 use uuid::Uuid;
 
-use crate::db::security_repo;
+use crate::db::{license_policy_repo, scan_run_repo, security_repo};
+use crate::domain::license::{LicensePolicy, UpdateLicensePolicyRequest};
+use crate::domain::project::Project;
 use crate::domain::realtime::{RealtimeEvent, SecurityEvent};
-use crate::domain::security::SecurityFinding;
+use crate::domain::scan_run::{ScanRun, ScanRunCounts, ScanRunStatus};
+use crate::domain::security::{
+    FindingDetail, FindingStatus, NewFinding, ScanSummary, SecurityFinding,
+    UpdateFindingStatusRequest,
+};
 use crate::error::AppError;
+use crate::services::sarif::{self, SarifLog};
+use crate::services::scanners::{ScanContext, Scanner};
+use crate::services::{github_service, license, repository, sbom_service, security_gate_service};
 use crate::state::AppState;
 
-#[tracing::instrument(name = "security", skip(state), fields(project_id = %project_id))]
-pub async fn run_security_scan(
+/// Records a history entry for a status change made by Sentinai itself rather
+/// than by a user.
+async fn record_system_transition(
+    state: &AppState,
+    finding: &SecurityFinding,
+    previous: FindingStatus,
+    justification: &str,
+) -> Result<(), AppError> {
+    security_repo::create_finding_event(
+        &state.db,
+        finding.id,
+        None,
+        previous,
+        finding.status,
+        justification,
+        None,
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn reopen_expired_findings(state: &AppState, project_id: Uuid) -> Result<(), AppError> {
+    let reopened = security_repo::reopen_expired_findings(&state.db, project_id).await?;
+    if !reopened.is_empty() {
+        tracing::info!(
+            count = reopened.len(),
+            "Reopened findings with expired suppressions"
+        );
+    }
+
+    for (finding, previous) in &reopened {
+        record_system_transition(state, finding, *previous, "Suppression expired").await?;
+    }
+
+    Ok(())
+}
+
+/// Findings and totals gathered while a scan run is in progress.
+#[derive(Default)]
+struct ScanOutcome {
+    new: Vec<SecurityFinding>,
+    existing: Vec<SecurityFinding>,
+    fixed: Vec<SecurityFinding>,
+    counts: ScanRunCounts,
+    scanner_errors: Vec<String>,
+}
+
+/// Upserts the candidates reported by `completed_scanners` and closes any of
+/// their previously open findings that were not reported this time. Findings
+/// from before fingerprinting are closed too when `close_legacy` is set.
+async fn persist_findings(
     state: &AppState,
     project_id: Uuid,
-) -> Result<Vec<SecurityFinding>, AppError> {
-    let mock_findings = vec![
-        ("high", "Detected outdated OpenSSL dependency in Cargo.lock"),
-        ("medium", "Missing security headers in API response"),
-    ];
+    scan_run_id: Uuid,
+    candidates: Vec<NewFinding>,
+    completed_scanners: &[String],
+    close_legacy: bool,
+) -> Result<ScanOutcome, AppError> {
+    let mut outcome = ScanOutcome::default();
+    let mut seen = Vec::new();
+    for candidate in candidates {
+        let (finding, previous) =
+            security_repo::upsert_finding(&state.db, project_id, scan_run_id, &candidate).await?;
+        seen.push(finding.fingerprint.clone());
+        outcome.counts.record_severity(&finding.severity);
 
-    let mut results = Vec::new();
+        if let Some(previous) = previous {
+            if previous != finding.status {
+                record_system_transition(state, &finding, previous, "Reported again by scanner")
+                    .await?;
+            }
+            outcome.existing.push(finding);
+            continue;
+        }
 
-    for (severity, desc) in mock_findings {
         tracing::warn!(
-            severity = severity,
-            finding = desc,
-            "1 vulnerable dependency found"
+            severity = %finding.severity,
+            finding = %finding.description,
+            "New security finding"
         );
-        let finding = security_repo::create_finding(&state.db, project_id, severity, desc).await?;
 
         let _ = state
             .tx
@@ -35,10 +107,229 @@ pub async fn run_security_scan(
                 description: finding.description.clone(),
             }));
 
-        results.push(finding);
+        outcome.new.push(finding);
+    }
+
+    let fixed = security_repo::close_missing_findings(
+        &state.db,
+        project_id,
+        completed_scanners,
+        &seen,
+        close_legacy,
+    )
+    .await?;
+    for (finding, previous) in fixed {
+        record_system_transition(state, &finding, previous, "No longer reported by scanner")
+            .await?;
+        outcome.fixed.push(finding);
+    }
+
+    outcome.counts.new = outcome.new.len() as i32;
+    outcome.counts.existing = outcome.existing.len() as i32;
+    outcome.counts.fixed = outcome.fixed.len() as i32;
+
+    Ok(outcome)
+}
+
+async fn execute_scan(
+    state: &AppState,
+    project: &Project,
+    scan_run_id: Uuid,
+) -> Result<ScanOutcome, AppError> {
+    reopen_expired_findings(state, project.id).await?;
+
+    let commit_sha = match github_service::get_head_commit_sha(state, &project.repository_url).await
+    {
+        Ok(sha) => Some(sha),
+        Err(e) => {
+            tracing::warn!(error = %e, "Could not resolve commit SHA for scan");
+            None
+        }
+    };
+
+    let scanner_names: Vec<String> = Scanner::ALL.iter().map(|s| s.name().to_string()).collect();
+    scan_run_repo::mark_running(
+        &state.db,
+        scan_run_id,
+        &scanner_names,
+        commit_sha.as_deref(),
+    )
+    .await?;
+
+    let mut outcome = ScanOutcome::default();
+
+    let git_ref = commit_sha.as_deref().unwrap_or("HEAD");
+    let snapshot = match repository::fetch_snapshot(state, &project.repository_url, git_ref).await {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            tracing::warn!(error = %e, "Could not fetch repository snapshot for scan");
+            outcome.scanner_errors.push(format!("repository: {}", e));
+            None
+        }
+    };
+    let sbom = match &snapshot {
+        Some(snapshot) => Some(
+            sbom_service::generate_sbom(
+                state,
+                project.id,
+                scan_run_id,
+                commit_sha.as_deref(),
+                snapshot,
+            )
+            .await?,
+        ),
+        None => None,
+    };
+
+    let ctx = ScanContext {
+        project,
+        commit_sha: commit_sha.as_deref(),
+        sbom: sbom.as_ref().map(|sbom| &sbom.contents.0),
+        snapshot: snapshot.as_ref(),
+    };
+    let mut succeeded = Vec::new();
+    let mut candidates = Vec::new();
+
+    // Only scanners that completed can vouch for a finding being gone.
+    for scanner in Scanner::ALL {
+        match scanner.scan(state, &ctx).await {
+            Ok(findings) => {
+                succeeded.push(scanner.name().to_string());
+                candidates.extend(findings);
+            }
+            Err(e) => {
+                tracing::warn!(scanner = scanner.name(), error = %e, "Scanner failed");
+                outcome
+                    .scanner_errors
+                    .push(format!("{}: {}", scanner.name(), e));
+            }
+        }
     }
 
-    Ok(results)
+    let persisted =
+        persist_findings(state, project.id, scan_run_id, candidates, &succeeded, true).await?;
+    outcome.new = persisted.new;
+    outcome.existing = persisted.existing;
+    outcome.fixed = persisted.fixed;
+    outcome.counts = persisted.counts;
+
+    Ok(outcome)
+}
+
+#[tracing::instrument(name = "security", skip(state, project), fields(project_id = %project.id))]
+pub async fn run_security_scan(
+    state: &AppState,
+    project: &Project,
+) -> Result<ScanSummary, AppError> {
+    let run = scan_run_repo::create_scan_run(&state.db, project.id).await?;
+
+    let outcome = execute_scan(state, project, run.id).await;
+    let summary = finish_scan_run(state, run.id, outcome).await?;
+
+    if let Some(commit_sha) = &summary.scan_run.commit_sha {
+        if security_gate_service::publishes_commit_status(state, project) {
+            if let Err(e) =
+                security_gate_service::publish_commit_status(state, project, commit_sha).await
+            {
+                tracing::warn!(error = %e, "Could not publish security gate commit status");
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Records the result of a scan run, whether it came from Sentinai's own
+/// scanners or from an imported report.
+async fn finish_scan_run(
+    state: &AppState,
+    scan_run_id: Uuid,
+    outcome: Result<ScanOutcome, AppError>,
+) -> Result<ScanSummary, AppError> {
+    let outcome = match outcome {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            scan_run_repo::complete_scan_run(
+                &state.db,
+                scan_run_id,
+                ScanRunStatus::Failed,
+                &ScanRunCounts::default(),
+                Some(&e.to_string()),
+            )
+            .await?;
+            return Err(e);
+        }
+    };
+
+    let (status, error) = if outcome.scanner_errors.is_empty() {
+        (ScanRunStatus::Succeeded, None)
+    } else {
+        (
+            ScanRunStatus::Failed,
+            Some(outcome.scanner_errors.join("; ")),
+        )
+    };
+    let scan_run = scan_run_repo::complete_scan_run(
+        &state.db,
+        scan_run_id,
+        status,
+        &outcome.counts,
+        error.as_deref(),
+    )
+    .await?;
+
+    tracing::info!(
+        scan_run_id = %scan_run.id,
+        new = outcome.new.len(),
+        existing = outcome.existing.len(),
+        fixed = outcome.fixed.len(),
+        duration_ms = scan_run.duration_ms,
+        "Security scan completed"
+    );
+
+    Ok(ScanSummary {
+        scan_run,
+        new: outcome.new,
+        existing: outcome.existing,
+        fixed: outcome.fixed,
+    })
+}
+
+/// Ingests a SARIF report produced by an external tool as a scan run of its
+/// own. Each tool in the report is treated as a scanner that has completed,
+/// so findings it no longer reports are closed as fixed.
+#[tracing::instrument(name = "security", skip(state, project, log), fields(project_id = %project.id))]
+pub async fn import_sarif(
+    state: &AppState,
+    project: &Project,
+    log: SarifLog,
+) -> Result<ScanSummary, AppError> {
+    let candidates = sarif::from_sarif(&log)?;
+
+    let mut tools: Vec<String> = log
+        .runs
+        .iter()
+        .map(|run| sarif::imported_scanner(&run.tool.driver.name))
+        .collect();
+    tools.sort();
+    tools.dedup();
+
+    let run = scan_run_repo::create_scan_run(&state.db, project.id).await?;
+    scan_run_repo::mark_running(&state.db, run.id, &tools, None).await?;
+
+    let outcome = persist_findings(state, project.id, run.id, candidates, &tools, false).await;
+    finish_scan_run(state, run.id, outcome).await
+}
+
+#[tracing::instrument(name = "security", skip(state), fields(project_id = %project_id))]
+pub async fn export_sarif(state: &AppState, project_id: Uuid) -> Result<SarifLog, AppError> {
+    let findings = get_security_findings(state, project_id).await?;
+    Ok(sarif::to_sarif(&findings))
+}
+
+#[tracing::instrument(name = "security", skip(state), fields(project_id = %project_id))]
+pub async fn list_scan_runs(state: &AppState, project_id: Uuid) -> Result<Vec<ScanRun>, AppError> {
+    scan_run_repo::list_scan_runs(&state.db, project_id).await
 }
 
 #[tracing::instrument(name = "security", skip(state), fields(project_id = %project_id))]
@@ -46,5 +337,155 @@ pub async fn get_security_findings(
     state: &AppState,
     project_id: Uuid,
 ) -> Result<Vec<SecurityFinding>, AppError> {
+    reopen_expired_findings(state, project_id).await?;
     security_repo::get_findings_for_project(&state.db, project_id).await
 }
+
+#[tracing::instrument(name = "security", skip(state), fields(project_id = %project_id))]
+pub async fn get_finding_detail(
+    state: &AppState,
+    project_id: Uuid,
+    finding_id: Uuid,
+) -> Result<FindingDetail, AppError> {
+    reopen_expired_findings(state, project_id).await?;
+
+    let finding = security_repo::get_finding(&state.db, project_id, finding_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Finding not found".into()))?;
+    let events = security_repo::get_events_for_finding(&state.db, finding.id).await?;
+
+    Ok(FindingDetail { finding, events })
+}
+
+#[tracing::instrument(
+    name = "security",
+    skip(state, request),
+    fields(project_id = %project_id, status = request.status.as_str())
+)]
+pub async fn update_finding_status(
+    state: &AppState,
+    project_id: Uuid,
+    finding_id: Uuid,
+    actor_id: Uuid,
+    request: UpdateFindingStatusRequest,
+) -> Result<FindingDetail, AppError> {
+    let justification = request.justification.trim();
+    if justification.is_empty() {
+        return Err(AppError::BadRequest(
+            "A justification is required to change a finding's status".into(),
+        ));
+    }
+
+    if let Some(expires_at) = request.expires_at {
+        if !request.status.is_suppression() {
+            return Err(AppError::BadRequest(
+                "Only false_positive and accepted_risk findings can expire".into(),
+            ));
+        }
+        if expires_at <= Utc::now() {
+            return Err(AppError::BadRequest(
+                "Expiry date must be in the future".into(),
+            ));
+        }
+    }
+
+    let current = get_finding_detail(state, project_id, finding_id)
+        .await?
+        .finding;
+    if !current.status.can_transition_to(request.status) {
+        return Err(AppError::BadRequest(format!(
+            "Cannot move a finding from {} to {}",
+            current.status.as_str(),
+            request.status.as_str()
+        )));
+    }
+
+    let finding = security_repo::update_finding_status(
+        &state.db,
+        current.id,
+        current.status,
+        request.status,
+        request.expires_at,
+    )
+    .await?
+    .ok_or_else(|| AppError::BadRequest("Finding status changed concurrently".into()))?;
+
+    security_repo::create_finding_event(
+        &state.db,
+        finding.id,
+        Some(actor_id),
+        current.status,
+        finding.status,
+        justification,
+        request.expires_at,
+    )
+    .await?;
+
+    tracing::info!(
+        from = current.status.as_str(),
+        to = finding.status.as_str(),
+        "Finding status updated"
+    );
+
+    let events = security_repo::get_events_for_finding(&state.db, finding.id).await?;
+    Ok(FindingDetail { finding, events })
+}
+
+/// Canonicalizes a policy's license list, rejecting names that are not SPDX
+/// identifiers.
+fn normalize_policy_licenses(licenses: &[String]) -> Result<Vec<String>, AppError> {
+    let mut normalized = Vec::new();
+    for name in licenses {
+        let id = license::normalize_license_id(name).ok_or_else(|| {
+            AppError::BadRequest(format!("Unknown SPDX license identifier: {}", name))
+        })?;
+        if !normalized.contains(&id) {
+            normalized.push(id);
+        }
+    }
+    Ok(normalized)
+}
+
+#[tracing::instrument(name = "security", skip(state), fields(project_id = %project_id))]
+pub async fn get_license_policy(
+    state: &AppState,
+    project_id: Uuid,
+) -> Result<LicensePolicy, AppError> {
+    Ok(
+        license_policy_repo::get_license_policy(&state.db, project_id)
+            .await?
+            .unwrap_or_else(|| LicensePolicy::default_for(project_id)),
+    )
+}
+
+#[tracing::instrument(name = "security", skip(state, request), fields(project_id = %project_id))]
+pub async fn update_license_policy(
+    state: &AppState,
+    project_id: Uuid,
+    request: UpdateLicensePolicyRequest,
+) -> Result<LicensePolicy, AppError> {
+    let allowed = normalize_policy_licenses(&request.allowed)?;
+    let denied = normalize_policy_licenses(&request.denied)?;
+    if let Some(id) = allowed.iter().find(|id| denied.contains(id)) {
+        return Err(AppError::BadRequest(format!(
+            "{} cannot be both allowed and denied",
+            id
+        )));
+    }
+
+    let policy = license_policy_repo::upsert_license_policy(
+        &state.db,
+        project_id,
+        &allowed,
+        &denied,
+        request.flag_unknown,
+    )
+    .await?;
+
+    tracing::info!(
+        allowed = policy.allowed.len(),
+        denied = policy.denied.len(),
+        "License policy updated"
+    );
+    Ok(policy)
+}
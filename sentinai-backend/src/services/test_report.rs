@@ -0,0 +1,272 @@
+//! Parsing of JUnit XML and libtest JSON test reports.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::domain::test_result::{ParsedTest, TestOutcome, TestReportFormat};
+use crate::error::AppError;
+
+/// Characters of a failure message kept per test.
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// The format of a report: XML is JUnit, JSON lines naming tests as
+/// `binary$test` come from nextest, other JSON lines from `cargo test`.
+pub fn detect_format(report: &str) -> TestReportFormat {
+    if report.trim_start().starts_with('<') {
+        return TestReportFormat::Junit;
+    }
+    let nextest = libtest_events(report).any(|event| event.name.contains('$'));
+    if nextest {
+        TestReportFormat::Nextest
+    } else {
+        TestReportFormat::Cargo
+    }
+}
+
+/// The tests in a report. A test reported more than once keeps its worst
+/// outcome, so a retried test that failed once counts as failed.
+pub fn parse_report(report: &str, format: TestReportFormat) -> Result<Vec<ParsedTest>, AppError> {
+    let tests = match format {
+        TestReportFormat::Junit => parse_junit(report)?,
+        TestReportFormat::Cargo | TestReportFormat::Nextest => parse_libtest(report),
+    };
+    if tests.is_empty() {
+        return Err(AppError::BadRequest(
+            "No test results found in the report".into(),
+        ));
+    }
+    Ok(dedupe(tests))
+}
+
+fn parse_junit(report: &str) -> Result<Vec<ParsedTest>, AppError> {
+    let document = roxmltree::Document::parse(report)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JUnit XML: {}", e)))?;
+
+    let tests = document
+        .descendants()
+        .filter(|node| node.has_tag_name("testcase"))
+        .filter_map(|case| {
+            let name = case.attribute("name").filter(|n| !n.is_empty())?;
+            let test_id = match case.attribute("classname").filter(|c| !c.is_empty()) {
+                Some(class) if class != name => format!("{}::{}", class, name),
+                _ => name.to_string(),
+            };
+            let failure = case
+                .children()
+                .find(|child| child.has_tag_name("failure") || child.has_tag_name("error"));
+            let skipped = case.children().any(|child| child.has_tag_name("skipped"));
+            let (outcome, message) = match failure {
+                Some(failure) => (
+                    TestOutcome::Failed,
+                    failure
+                        .attribute("message")
+                        .filter(|m| !m.trim().is_empty())
+                        .or_else(|| failure.text())
+                        .map(truncate_message),
+                ),
+                None if skipped => (TestOutcome::Skipped, None),
+                None => (TestOutcome::Passed, None),
+            };
+            let duration_ms = case
+                .attribute("time")
+                .and_then(|time| time.parse::<f64>().ok())
+                .map(|seconds| (seconds * 1000.0).round() as i32);
+
+            Some(ParsedTest {
+                test_id,
+                file: case
+                    .attribute("file")
+                    .map(|f| f.trim_start_matches("./").to_string()),
+                outcome,
+                duration_ms,
+                message,
+            })
+        })
+        .collect();
+
+    Ok(tests)
+}
+
+/// A libtest JSON event, as printed by
+/// `cargo test -- -Z unstable-options --format json` and
+/// `cargo nextest run --message-format libtest-json`.
+#[derive(Deserialize)]
+struct LibtestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: String,
+    exec_time: Option<f64>,
+    stdout: Option<String>,
+}
+
+/// Test events of a report, skipping lines that are not JSON, e.g. compiler
+/// output mixed into the same stream.
+fn libtest_events(report: &str) -> impl Iterator<Item = LibtestEvent> + '_ {
+    report
+        .lines()
+        .filter(|line| line.trim_start().starts_with('{'))
+        .filter_map(|line| serde_json::from_str::<LibtestEvent>(line).ok())
+        .filter(|event| event.kind == "test")
+}
+
+fn parse_libtest(report: &str) -> Vec<ParsedTest> {
+    libtest_events(report)
+        .filter_map(|event| {
+            let outcome = match event.event.as_str() {
+                "ok" => TestOutcome::Passed,
+                "failed" | "timeout" => TestOutcome::Failed,
+                "ignored" => TestOutcome::Skipped,
+                _ => return None,
+            };
+            let message = match outcome {
+                TestOutcome::Failed => event
+                    .stdout
+                    .as_deref()
+                    .filter(|out| !out.trim().is_empty())
+                    .map(truncate_message),
+                _ => None,
+            };
+            Some(ParsedTest {
+                // nextest prefixes the test binary: `crate::bin$module::test`.
+                test_id: event.name.replace('$', "::"),
+                file: None,
+                outcome,
+                duration_ms: event
+                    .exec_time
+                    .map(|seconds| (seconds * 1000.0).round() as i32),
+                message,
+            })
+        })
+        .collect()
+}
+
+fn truncate_message(message: &str) -> String {
+    message.trim().chars().take(MAX_MESSAGE_CHARS).collect()
+}
+
+fn severity(outcome: TestOutcome) -> u8 {
+    match outcome {
+        TestOutcome::Skipped => 0,
+        TestOutcome::Passed => 1,
+        TestOutcome::Failed => 2,
+    }
+}
+
+fn dedupe(tests: Vec<ParsedTest>) -> Vec<ParsedTest> {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut unique: Vec<ParsedTest> = Vec::with_capacity(tests.len());
+    for test in tests {
+        match index.get(&test.test_id) {
+            Some(&i) => {
+                if severity(test.outcome) > severity(unique[i].outcome) {
+                    unique[i] = test;
+                }
+            }
+            None => {
+                index.insert(test.test_id.clone(), unique.len());
+                unique.push(test);
+            }
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_junit() {
+        let report = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="sentinai-backend" tests="4">
+    <testcase classname="sentinai-backend" name="services::diff::tests::test_unified_diff" time="0.012"/>
+    <testcase classname="tests.test_api.TestLogin" name="test_login" file="./tests/test_api.py" time="1.5">
+      <failure message="AssertionError: 401 != 200">Traceback ...</failure>
+    </testcase>
+    <testcase classname="Checkout" name="Checkout"><error>TypeError: cart is undefined</error></testcase>
+    <testcase classname="Checkout" name="applies coupons"><skipped/></testcase>
+  </testsuite>
+</testsuites>"#;
+        assert_eq!(detect_format(report), TestReportFormat::Junit);
+        let tests = parse_report(report, TestReportFormat::Junit).unwrap();
+        assert_eq!(
+            tests,
+            vec![
+                ParsedTest {
+                    test_id: "sentinai-backend::services::diff::tests::test_unified_diff".into(),
+                    file: None,
+                    outcome: TestOutcome::Passed,
+                    duration_ms: Some(12),
+                    message: None,
+                },
+                ParsedTest {
+                    test_id: "tests.test_api.TestLogin::test_login".into(),
+                    file: Some("tests/test_api.py".into()),
+                    outcome: TestOutcome::Failed,
+                    duration_ms: Some(1500),
+                    message: Some("AssertionError: 401 != 200".into()),
+                },
+                ParsedTest {
+                    test_id: "Checkout".into(),
+                    file: None,
+                    outcome: TestOutcome::Failed,
+                    duration_ms: None,
+                    message: Some("TypeError: cart is undefined".into()),
+                },
+                ParsedTest {
+                    test_id: "Checkout::applies coupons".into(),
+                    file: None,
+                    outcome: TestOutcome::Skipped,
+                    duration_ms: None,
+                    message: None,
+                },
+            ]
+        );
+        assert!(parse_report("<testsuites", TestReportFormat::Junit).is_err());
+        assert!(parse_report("<testsuites/>", TestReportFormat::Junit).is_err());
+    }
+
+    #[test]
+    fn test_parse_libtest_json() {
+        let cargo = r#"   Compiling app v0.1.0
+{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::a" }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.004 }
+{ "type": "test", "name": "tests::b", "event": "failed", "stdout": "thread 'tests::b' panicked at src/lib.rs:9:5\n" }
+{ "type": "test", "name": "tests::c", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1 }"#;
+        assert_eq!(detect_format(cargo), TestReportFormat::Cargo);
+        let tests = parse_report(cargo, TestReportFormat::Cargo).unwrap();
+        let summary: Vec<(&str, TestOutcome)> = tests
+            .iter()
+            .map(|t| (t.test_id.as_str(), t.outcome))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("tests::a", TestOutcome::Passed),
+                ("tests::b", TestOutcome::Failed),
+                ("tests::c", TestOutcome::Skipped),
+            ]
+        );
+        assert_eq!(tests[0].duration_ms, Some(4));
+        assert_eq!(
+            tests[1].message.as_deref(),
+            Some("thread 'tests::b' panicked at src/lib.rs:9:5")
+        );
+
+        // nextest reports each retry; a failed attempt wins.
+        let nextest = r#"{"type":"test","event":"failed","name":"app::bin/cli$tests::flaky"}
+{"type":"test","event":"ok","name":"app::bin/cli$tests::flaky"}"#;
+        assert_eq!(detect_format(nextest), TestReportFormat::Nextest);
+        let tests = parse_report(nextest, TestReportFormat::Nextest).unwrap();
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].test_id, "app::bin/cli::tests::flaky");
+        assert_eq!(tests[0].outcome, TestOutcome::Failed);
+
+        assert!(parse_report("no json here", TestReportFormat::Cargo).is_err());
+    }
+}
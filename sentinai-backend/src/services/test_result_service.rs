@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::db::{project_repo, test_result_repo};
+use crate::domain::remediation::FileDiff;
+use crate::domain::test_result::{
+    FlakyTest, FlakyTestQuery, QuarantineRequest, QuarantineResult, TestIngestResult, TestOutcome,
+    TestReportFormat,
+};
+use crate::error::AppError;
+use crate::services::remediation_service::{self, PullRequestDraft};
+use crate::services::{build_service, diff, flaky_tests, github_service, repository, test_report};
+use crate::state::AppState;
+
+const MAX_DAYS: i32 = 90;
+const MAX_LIMIT: usize = 200;
+/// Days of history scored when choosing which tests to quarantine.
+const QUARANTINE_DAYS: i32 = 14;
+const QUARANTINE_MIN_SCORE: f64 = 0.3;
+
+/// Stores the results of a JUnit XML or libtest JSON report for a build.
+#[tracing::instrument(name = "test_result_service", skip(state, report), fields(build_id = %build_id))]
+pub async fn ingest_report(
+    state: &AppState,
+    user_id: Uuid,
+    build_id: Uuid,
+    report: &str,
+    format: Option<TestReportFormat>,
+) -> Result<TestIngestResult, AppError> {
+    let build = build_service::get_build(state, user_id, build_id).await?;
+    let format = format.unwrap_or_else(|| test_report::detect_format(report));
+    let tests = test_report::parse_report(report, format)?;
+    test_result_repo::upsert_results(&state.db, build.project_id, build.id, &tests).await?;
+
+    let count = |outcome| tests.iter().filter(|t| t.outcome == outcome).count();
+    let result = TestIngestResult {
+        build_id: build.id,
+        format,
+        total: tests.len(),
+        passed: count(TestOutcome::Passed),
+        failed: count(TestOutcome::Failed),
+        skipped: count(TestOutcome::Skipped),
+    };
+    tracing::info!(
+        total = result.total,
+        failed = result.failed,
+        "Ingested test report"
+    );
+    Ok(result)
+}
+
+/// Tests whose history across the project's builds makes them look flaky,
+/// most flaky first.
+pub async fn list_flaky_tests(
+    state: &AppState,
+    project_id: Uuid,
+    query: &FlakyTestQuery,
+) -> Result<Vec<FlakyTest>, AppError> {
+    if !(1..=MAX_DAYS).contains(&query.days) {
+        return Err(AppError::BadRequest(format!(
+            "days must be between 1 and {}",
+            MAX_DAYS
+        )));
+    }
+    if !(0.0..=1.0).contains(&query.min_score) {
+        return Err(AppError::BadRequest(
+            "min_score must be between 0 and 1".into(),
+        ));
+    }
+    if !(1..=MAX_LIMIT).contains(&query.limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_LIMIT
+        )));
+    }
+
+    let runs =
+        test_result_repo::list_runs(&state.db, project_id, query.branch.as_deref(), query.days)
+            .await?;
+    let mut flaky = flaky_tests::score_tests(&runs);
+    flaky.retain(|test| test.score >= query.min_score);
+    flaky.truncate(query.limit);
+    Ok(flaky)
+}
+
+/// Proposes skipping flaky tests where they are defined. On a dry run only
+/// the patch is returned; otherwise it is committed to a new branch and
+/// opened as a pull request.
+#[tracing::instrument(name = "test_quarantine", skip(state, request), fields(project_id = %project_id))]
+pub async fn propose_quarantine(
+    state: &AppState,
+    user_id: Uuid,
+    project_id: Uuid,
+    request: QuarantineRequest,
+) -> Result<QuarantineResult, AppError> {
+    remediation_service::require_pull_requests(state, request.dry_run)?;
+    let project = project_repo::get_project(&state.db, project_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Project not found".into()))?;
+
+    let runs = test_result_repo::list_runs(&state.db, project_id, None, QUARANTINE_DAYS).await?;
+    let scored = flaky_tests::score_tests(&runs);
+    let tests: Vec<(String, Option<String>)> = if request.test_ids.is_empty() {
+        scored
+            .iter()
+            .filter(|test| test.score >= QUARANTINE_MIN_SCORE)
+            .map(|test| (test.test_id.clone(), test.file.clone()))
+            .collect()
+    } else {
+        let files: HashMap<&str, Option<String>> = runs
+            .iter()
+            .map(|run| (run.test_id.as_str(), run.file.clone()))
+            .collect();
+        request
+            .test_ids
+            .iter()
+            .map(|id| (id.clone(), files.get(id.as_str()).cloned().flatten()))
+            .collect()
+    };
+    if tests.is_empty() {
+        return Err(AppError::BadRequest("No flaky tests to quarantine".into()));
+    }
+
+    let base_branch = github_service::get_default_branch(state, &project.repository_url).await?;
+    let base_sha =
+        github_service::get_commit_sha(state, &project.repository_url, &base_branch).await?;
+    let snapshot = repository::fetch_snapshot(state, &project.repository_url, &base_sha).await?;
+    let plan = flaky_tests::plan_quarantine(&snapshot, &tests);
+
+    let files: Vec<FileDiff> = plan
+        .files
+        .iter()
+        .map(|change| FileDiff {
+            path: change.path.clone(),
+            diff: diff::unified_diff(&change.path, &change.before, &change.after),
+        })
+        .collect();
+    let patch: String = files.iter().map(|f| f.diff.as_str()).collect();
+
+    let pull_request = if request.dry_run {
+        None
+    } else {
+        if plan.quarantined.is_empty() {
+            return Err(AppError::BadRequest(
+                "None of the tests could be found to quarantine".into(),
+            ));
+        }
+        let scores: HashMap<&str, &FlakyTest> =
+            scored.iter().map(|t| (t.test_id.as_str(), t)).collect();
+        let title = match plan.quarantined.len() {
+            1 => "Quarantine 1 flaky test".to_string(),
+            n => format!("Quarantine {} flaky tests", n),
+        };
+        let mut body = String::from(
+            "Sentinai found these tests both passing and failing across recent builds and marked them as skipped until they are fixed.\n\n| Test | File | Score | Failed runs |\n| --- | --- | --- | --- |\n",
+        );
+        for test in &plan.quarantined {
+            let (score, failures) = scores
+                .get(test.test_id.as_str())
+                .map(|t| {
+                    (
+                        format!("{:.2}", t.score),
+                        format!("{}/{}", t.failures, t.runs),
+                    )
+                })
+                .unwrap_or_else(|| ("-".into(), "-".into()));
+            body.push_str(&format!(
+                "| `{}` | `{}` | {} | {} |\n",
+                test.test_id, test.path, score, failures
+            ));
+        }
+        let pr = remediation_service::open_pull_request(
+            state,
+            &project,
+            PullRequestDraft {
+                branch_prefix: "quarantine",
+                base_branch: &base_branch,
+                base_sha: &base_sha,
+                title: &title,
+                body: &body,
+                files: &plan.files,
+            },
+        )
+        .await?;
+        Some(pr)
+    };
+
+    Ok(QuarantineResult {
+        project_id,
+        dry_run: request.dry_run,
+        base_branch,
+        base_sha,
+        quarantined: plan.quarantined,
+        unresolved: plan.unresolved,
+        files,
+        patch,
+        pull_request,
+    })
+}
@@ -0,0 +1,72 @@
+//! Authentication of incoming repository webhooks.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+/// Checks a `sha256=<hex>` signature header against the HMAC-SHA256 of the
+/// raw request body. The digest comparison runs in constant time.
+pub fn verify_signature(secret: &str, body: &[u8], signature: Option<&str>) -> bool {
+    let Some(expected) = signature
+        .and_then(|s| s.trim().strip_prefix("sha256="))
+        .and_then(|hex_digest| hex::decode(hex_digest).ok())
+    else {
+        return false;
+    };
+
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Checks a shared-secret token header, such as GitLab's `X-Gitlab-Token`,
+/// in constant time.
+pub fn verify_token(expected: &str, token: Option<&str>) -> bool {
+    token.is_some_and(|token| bool::from(token.as_bytes().ct_eq(expected.as_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The example delivery from GitHub's webhook validation docs.
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+    const SIGNATURE: &str =
+        "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    #[test]
+    fn test_verify_signature_accepts_known_good_signature() {
+        assert!(verify_signature(SECRET, BODY, Some(SIGNATURE)));
+        assert!(verify_signature(
+            "webhook-secret",
+            br#"{"zen":"Keep it logically awesome."}"#,
+            Some("sha256=839047ab01aba15d4db87571078a5610d88aa6892c0b3b034b44b7ae527e0830"),
+        ));
+    }
+
+    #[test]
+    fn test_verify_signature_rejects_mismatches() {
+        assert!(!verify_signature("wrong secret", BODY, Some(SIGNATURE)));
+        assert!(!verify_signature(SECRET, b"Hello, World?", Some(SIGNATURE)));
+        assert!(!verify_signature(
+            SECRET,
+            BODY,
+            Some(SIGNATURE.trim_start_matches("sha256="))
+        ));
+        assert!(!verify_signature(SECRET, BODY, Some("sha256=not-hex")));
+        assert!(!verify_signature(SECRET, BODY, Some("sha256=7571")));
+        assert!(!verify_signature(SECRET, BODY, None));
+    }
+
+    #[test]
+    fn test_verify_token() {
+        assert!(verify_token("gitlab-token", Some("gitlab-token")));
+        assert!(!verify_token("gitlab-token", Some("gitlab-tokem")));
+        assert!(!verify_token("gitlab-token", Some("gitlab")));
+        assert!(!verify_token("gitlab-token", Some("")));
+        assert!(!verify_token("gitlab-token", None));
+    }
+}
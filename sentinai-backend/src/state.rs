@@ -9,6 +9,7 @@ use std::time::Duration;
 
 use crate::config::Config;
 use crate::error::AppError;
+use crate::services::llm::{self, LlmProvider};
 
 use crate::domain::realtime::RealtimeEvent;
 use tokio::sync::broadcast;
@@ -19,6 +20,7 @@ pub struct AppState {
     pub config: Arc<Config>,
     pub http_client: Client,
     pub tx: broadcast::Sender<RealtimeEvent>,
+    pub llm: Option<Arc<dyn LlmProvider>>,
 }
 
 impl AppState {
@@ -53,6 +55,8 @@ impl AppState {
             .build()
             .map_err(|e| AppError::InternalServerError(e.to_string()))?;
 
+        let llm = llm::from_config(&config, &http_client)?;
+
         let (tx, _rx) = broadcast::channel(100);
 
         Ok(Self {
@@ -60,6 +64,7 @@ impl AppState {
             config: Arc::new(config),
             http_client,
             tx,
+            llm,
         })
     }
 }
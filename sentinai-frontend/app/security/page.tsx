@@ -93,8 +93,8 @@ export default function SecurityPage() {
                                                         {proj?.name || "Unknown"}
                                                     </td>
                                                     <td className="px-6 py-4">
-                                                        {finding.resolved ? (
-                                                            <span className="text-green-500 font-medium tracking-tight">Resolved</span>
+                                                        {finding.status !== "open" && finding.status !== "acknowledged" ? (
+                                                            <span className="text-green-500 font-medium tracking-tight capitalize">{finding.status.replace("_", " ")}</span>
                                                         ) : (
                                                             <span className="text-red-400 font-medium tracking-tight">Action Required</span>
                                                         )}
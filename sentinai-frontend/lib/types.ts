@@ -29,8 +29,18 @@ export interface Pipeline {
 export interface SecurityFinding {
   id: string;
   project_id: string;
+  scanner: string;
+  rule_id: string;
   severity: "low" | "medium" | "high" | "critical";
   description: string;
-  resolved: boolean;
+  location: string | null;
+  package: string | null;
+  remediation: string | null;
+  fixed_versions: string[];
+  fingerprint: string;
+  status: "open" | "acknowledged" | "resolved" | "false_positive" | "accepted_risk";
+  status_expires_at: string | null;
   created_at: string;
+  last_seen_at: string;
+  fixed_at: string | null;
 }